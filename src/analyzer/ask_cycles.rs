@@ -0,0 +1,235 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::parser::symbols::{ParsedSymbols, SendMethod};
+use crate::parser::type_inference::base_type_name;
+
+/// One `ask` call made by an actor
+#[derive(Debug, Clone, Serialize)]
+pub struct AskSite {
+    pub message_type: String,
+    /// Function the ask is made from, e.g. `orders::OrderBook::handle`
+    pub function: Option<String>,
+    pub crate_name: String,
+    pub file_path: String,
+    /// 1-based line of the ask
+    pub line: usize,
+}
+
+/// Asks from one actor to another within a cycle
+#[derive(Debug, Clone, Serialize)]
+pub struct AskHop {
+    pub from: String,
+    pub to: String,
+    pub sends: Vec<AskSite>,
+}
+
+/// Actors that ask each other in a loop; each waits for a reply the next cannot give
+/// until it is done waiting itself
+#[derive(Debug, Clone, Serialize)]
+pub struct AskCycle {
+    /// Actors in ask order, starting from the alphabetically first
+    pub actors: Vec<String>,
+    pub hops: Vec<AskHop>,
+}
+
+impl AskCycle {
+    /// `A -ask(Ping)-> B -ask(Pong)-> A`
+    pub fn describe(&self) -> String {
+        let mut text = self.actors.first().cloned().unwrap_or_default();
+        for hop in &self.hops {
+            let messages: BTreeSet<&str> = hop.sends.iter().map(|s| s.message_type.as_str()).collect();
+            let messages: Vec<&str> = messages.into_iter().collect();
+            text.push_str(&format!(" -ask({})-> {}", messages.join("|"), hop.to));
+        }
+        text
+    }
+}
+
+/// Directed graph of which actor asks which, built from `ask` sends and the actors
+/// handling the sent message types
+#[derive(Debug, Default)]
+pub struct AskGraph {
+    edges: BTreeMap<String, BTreeMap<String, Vec<AskSite>>>,
+}
+
+impl AskGraph {
+    /// Only asks made from an actor's own code count: a send inside a detached task does not
+    /// block the actor's mailbox. The receiver is the actor behind the `ActorRef` when the
+    /// parser could infer it, otherwise every actor that handles the message type.
+    pub fn from_symbols(symbols: &ParsedSymbols) -> Self {
+        let mut actors: BTreeSet<&str> = symbols.actors.iter().map(|a| a.name.as_str()).collect();
+        let mut handlers: HashMap<String, BTreeSet<&str>> = HashMap::new();
+        for handler in &symbols.message_handlers {
+            actors.insert(handler.actor_name.as_str());
+            let message = base_type_name(&handler.message_type).unwrap_or_else(|| handler.message_type.clone());
+            handlers.entry(message).or_default().insert(handler.actor_name.as_str());
+        }
+
+        let mut graph = AskGraph::default();
+        for send in &symbols.message_sends {
+            if send.send_method != SendMethod::Ask || !actors.contains(send.sender_actor.as_str()) {
+                continue;
+            }
+
+            let receivers: Vec<&str> = if actors.contains(send.receiver_actor.as_str()) {
+                vec![send.receiver_actor.as_str()]
+            } else {
+                handlers.get(&send.message_type).into_iter().flatten().copied().collect()
+            };
+
+            for receiver in receivers {
+                graph.edges
+                    .entry(send.sender_actor.clone())
+                    .or_default()
+                    .entry(receiver.to_string())
+                    .or_default()
+                    .push(AskSite {
+                        message_type: send.message_type.clone(),
+                        function: send.sender_qualified.clone(),
+                        crate_name: send.from_crate.clone(),
+                        file_path: send.file_path.clone(),
+                        line: send.line + 1,
+                    });
+            }
+        }
+        graph
+    }
+
+    /// Number of distinct asking actor pairs
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(|targets| targets.len()).sum()
+    }
+
+    /// Elementary ask cycles that `keep` accepts, shortest first, at most `limit` of them. Each
+    /// cycle is found once, starting from its alphabetically first actor; an actor asking itself
+    /// is a cycle of one. Cycles are searched one length at a time, so the limit never cuts off
+    /// a shorter cycle in favour of a longer one.
+    pub fn cycles(&self, limit: usize, keep: impl Fn(&AskCycle) -> bool) -> Vec<AskCycle> {
+        let mut cycles = Vec::new();
+        // Every actor on a cycle asks someone, so no cycle is longer than the number of askers
+        for length in 1..=self.edges.len() {
+            if cycles.len() >= limit {
+                break;
+            }
+            let mut found = Vec::new();
+            for start in self.edges.keys() {
+                let mut path = vec![start.as_str()];
+                self.extend_cycles(start, &mut path, length, &mut found);
+            }
+            // Starts and targets are visited in order, so each length comes out sorted by actors
+            cycles.extend(found.into_iter().map(|actors| self.cycle(actors)).filter(|cycle| keep(cycle)));
+        }
+        cycles.truncate(limit);
+        cycles
+    }
+
+    fn extend_cycles<'a>(&'a self, start: &str, path: &mut Vec<&'a str>, length: usize, found: &mut Vec<Vec<String>>) {
+        let Some(last) = path.last().copied() else {
+            return;
+        };
+        for next in self.edges.get(last).into_iter().flat_map(|targets| targets.keys()) {
+            if path.len() == length {
+                if next == start {
+                    found.push(path.iter().map(|a| a.to_string()).collect());
+                }
+            } else if next.as_str() > start && !path.contains(&next.as_str()) {
+                path.push(next);
+                self.extend_cycles(start, path, length, found);
+                path.pop();
+            }
+        }
+    }
+
+    fn cycle(&self, actors: Vec<String>) -> AskCycle {
+        let hops = actors.iter()
+            .zip(actors.iter().cycle().skip(1))
+            .map(|(from, to)| AskHop {
+                from: from.clone(),
+                to: to.clone(),
+                sends: self.edges[from][to].clone(),
+            })
+            .collect();
+        AskCycle { actors, hops }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+    use std::path::Path;
+
+    const ORDER_FLOW: &str = r#"
+pub struct OrderBook { risk: ActorRef<RiskEngine> }
+pub struct RiskEngine { book: ActorRef<OrderBook> }
+pub struct Auditor;
+
+impl Message<PlaceOrder> for OrderBook {
+    type Reply = bool;
+    async fn handle(&mut self, msg: PlaceOrder, _ctx: &mut Context<Self, Self::Reply>) -> bool {
+        self.risk.ask(CheckLimits { qty: msg.qty }).await.unwrap()
+    }
+}
+
+impl Message<CheckLimits> for RiskEngine {
+    type Reply = bool;
+    async fn handle(&mut self, msg: CheckLimits, _ctx: &mut Context<Self, Self::Reply>) -> bool {
+        let position = self.book.ask(GetPosition).await.unwrap();
+        let book = self.book.clone();
+        tokio::spawn(async move { book.ask(GetPosition).await });
+        position < msg.qty
+    }
+}
+
+impl Message<GetPosition> for OrderBook {
+    type Reply = u64;
+    async fn handle(&mut self, _: GetPosition, _ctx: &mut Context<Self, Self::Reply>) -> u64 { 0 }
+}
+
+impl Message<Audit> for Auditor {
+    type Reply = ();
+    async fn handle(&mut self, msg: Audit, ctx: &mut Context<Self, Self::Reply>) {
+        ctx.actor_ref().ask(Audit).await;
+        self.book.tell(GetPosition).await;
+    }
+}
+"#;
+
+    #[test]
+    fn test_ask_cycles_with_send_sites() {
+        let mut parser = RustParser::new().unwrap();
+        let symbols = parser.parse_source(ORDER_FLOW, Path::new("src/orders.rs"), "orders").unwrap();
+        let graph = AskGraph::from_symbols(&symbols);
+        let cycles = graph.cycles(10, |_| true);
+
+        let described: Vec<_> = cycles.iter().map(|c| c.describe()).collect();
+        assert_eq!(described, vec![
+            "Auditor -ask(Audit)-> Auditor",
+            "OrderBook -ask(CheckLimits)-> RiskEngine -ask(GetPosition)-> OrderBook",
+        ]);
+
+        // The ask inside tokio::spawn runs on its own task and cannot block RiskEngine
+        let back = &cycles[1].hops[1];
+        assert_eq!(back.sends.len(), 1);
+        assert_eq!(back.sends[0].function.as_deref(), Some("RiskEngine::handle"));
+        let expected_row = ORDER_FLOW.lines().position(|l| l.contains("self.book.ask(GetPosition)")).unwrap() + 1;
+        assert_eq!(back.sends[0].line, expected_row);
+        assert_eq!(back.sends[0].file_path, "src/orders.rs");
+    }
+
+    #[test]
+    fn test_cycle_limit_and_tells_ignored() {
+        let mut parser = RustParser::new().unwrap();
+        let symbols = parser.parse_source(ORDER_FLOW, Path::new("src/orders.rs"), "orders").unwrap();
+        let graph = AskGraph::from_symbols(&symbols);
+
+        assert_eq!(graph.cycles(1, |_| true).len(), 1);
+        // Filtering happens before the limit, so a rejected shorter cycle leaves room for a longer one
+        let with_risk = graph.cycles(1, |cycle| cycle.actors.iter().any(|a| a == "RiskEngine"));
+        assert_eq!(with_risk.len(), 1);
+        assert_eq!(with_risk[0].actors, vec!["OrderBook", "RiskEngine"]);
+        // Auditor only tells OrderBook, so there is no Auditor -> OrderBook edge
+        assert_eq!(graph.edge_count(), 3);
+    }
+}
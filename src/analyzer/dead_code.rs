@@ -0,0 +1,1021 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Component, Path};
+
+use crate::analyzer::FrameworkPatterns;
+use crate::config::DeadCodeConfig;
+use crate::parser::symbols::{CallType, FunctionContext, ParsedSymbols, RustFunction, RustType, TypeKind};
+use crate::parser::references::ReferenceResolver;
+use crate::parser::type_inference::base_type_name;
+use crate::workspace::CrateMetadata;
+
+/// Longest reason chain reported for one unreachable item
+const MAX_CHAIN: usize = 8;
+
+/// A function or type in the reachability graph, indexing into `ParsedSymbols`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Item {
+    Function(usize),
+    Type(usize),
+}
+
+/// Why one item keeps another alive
+#[derive(Debug, Clone)]
+enum Link {
+    Call,
+    Use,
+    /// Function passed or stored as a value, e.g. `map(Self::convert)`
+    Reference,
+    /// Trait impl method kept alive by its implementing type
+    Implements { trait_name: String, framework: Option<String> },
+    /// Impl method reached through the trait method it implements
+    Dispatch,
+    /// Method started by a framework entry point such as an actor spawn
+    Trigger { pattern: String },
+}
+
+impl Link {
+    fn describe(&self, only: bool) -> String {
+        let only = if only { "only " } else { "" };
+        match self {
+            Link::Call => format!("is {}called from", only),
+            Link::Use => format!("is {}used by", only),
+            Link::Reference => format!("is {}passed as a value in", only),
+            Link::Implements { trait_name, framework: Some(framework) } => {
+                format!("implements `{}` ({} dispatch) for", trait_name, framework)
+            }
+            Link::Implements { trait_name, framework: None } => format!("implements `{}` for", trait_name),
+            Link::Dispatch => format!("is {}dispatched through", only),
+            Link::Trigger { pattern } => format!("is {}started by `{}` in", only, pattern),
+        }
+    }
+}
+
+/// Impl method of a trait that is live once its type is live and, for workspace traits,
+/// once the trait method it implements is reachable
+struct TraitImplMethod {
+    method: usize,
+    trait_name: String,
+    framework: Option<String>,
+    /// Workspace types named like the implementing type; empty for foreign types
+    types: Vec<usize>,
+    /// Workspace trait methods this implements; empty for foreign traits
+    declarations: Vec<usize>,
+}
+
+/// How an item was first reached
+#[derive(Debug, Clone)]
+enum Visit {
+    Root(String),
+    Via(Item, Link),
+}
+
+/// An unreachable function or type with the chain of reasons nothing live reaches it
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadItem {
+    pub kind: String,
+    pub name: String,
+    pub qualified_name: String,
+    pub crate_name: String,
+    pub file_path: String,
+    /// 1-based, as editors and the other reports print it
+    pub line: usize,
+    pub visibility: String,
+    pub reason: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadCodeReport {
+    /// Number of roots per entry point kind
+    pub entry_points: BTreeMap<String, usize>,
+    pub total_functions: usize,
+    pub reachable_functions: usize,
+    pub total_types: usize,
+    pub reachable_types: usize,
+    pub dead_functions: Vec<DeadItem>,
+    pub dead_types: Vec<DeadItem>,
+}
+
+/// Reachability of every function and type from the configured entry points.
+///
+/// Edges are resolved calls (falling back to the callee name when the resolver found
+/// nothing and the receiver type is unknown), types named in a function's source or in
+/// a type's fields, and trait dispatch: an impl method of a foreign trait (`Display`,
+/// kameo's `Message`, ...) is live when its type is, an impl method of a workspace trait
+/// additionally needs the trait method itself to be reachable.
+pub struct Reachability<'a> {
+    symbols: &'a ParsedSymbols,
+    /// Functions and types that are test, bench or example code; never reported
+    support: HashSet<Item>,
+    /// Items outside the analysed workspace members; never reported
+    foreign: HashSet<Item>,
+    live: HashMap<Item, Visit>,
+    users: HashMap<Item, Vec<(Item, Link)>>,
+    entry_points: BTreeMap<String, usize>,
+}
+
+impl<'a> Reachability<'a> {
+    pub fn analyze(
+        symbols: &'a ParsedSymbols,
+        crates: &[CrateMetadata],
+        config: &DeadCodeConfig,
+        patterns: &FrameworkPatterns,
+    ) -> Self {
+        let graph = GraphBuilder::new(symbols, crates, config, patterns);
+        let mut reachability = Self {
+            symbols,
+            support: HashSet::new(),
+            foreign: HashSet::new(),
+            live: HashMap::new(),
+            users: HashMap::new(),
+            entry_points: BTreeMap::new(),
+        };
+
+        let members: HashSet<&str> = crates
+            .iter()
+            .filter(|c| c.is_workspace_member)
+            .map(|c| c.name.as_str())
+            .collect();
+        let is_foreign = |crate_name: &str, file_path: &str| {
+            file_path == "<synthetic>" || (!members.is_empty() && !members.contains(crate_name))
+        };
+        for (idx, function) in symbols.functions.iter().enumerate() {
+            if is_foreign(&function.crate_name, &function.file_path) {
+                reachability.foreign.insert(Item::Function(idx));
+            }
+            if graph.support_scope(idx).is_some() {
+                reachability.support.insert(Item::Function(idx));
+            }
+        }
+        for (idx, rust_type) in symbols.types.iter().enumerate() {
+            if is_foreign(&rust_type.crate_name, &rust_type.file_path) {
+                reachability.foreign.insert(Item::Type(idx));
+            }
+            if rust_type.is_test || graph.support_dir(&rust_type.crate_name, &rust_type.file_path).is_some() {
+                reachability.support.insert(Item::Type(idx));
+            }
+        }
+
+        for (from, edges) in &graph.edges {
+            for (to, link) in edges {
+                reachability.users.entry(*to).or_default().push((*from, link.clone()));
+            }
+        }
+        for pending in &graph.trait_impls {
+            let method = Item::Function(pending.method);
+            let users = reachability.users.entry(method).or_default();
+            for &type_idx in &pending.types {
+                users.push((
+                    Item::Type(type_idx),
+                    Link::Implements { trait_name: pending.trait_name.clone(), framework: pending.framework.clone() },
+                ));
+            }
+            for &declaration in &pending.declarations {
+                users.push((Item::Function(declaration), Link::Dispatch));
+            }
+        }
+
+        let mut queue = VecDeque::new();
+        for (item, kind) in graph.roots(crates) {
+            if !reachability.live.contains_key(&item) {
+                *reachability.entry_points.entry(kind.clone()).or_default() += 1;
+                reachability.live.insert(item, Visit::Root(kind));
+                queue.push_back(item);
+            }
+        }
+
+        loop {
+            while let Some(item) = queue.pop_front() {
+                for (next, link) in graph.edges.get(&item).into_iter().flatten() {
+                    if !reachability.live.contains_key(next) {
+                        reachability.live.insert(*next, Visit::Via(item, link.clone()));
+                        queue.push_back(*next);
+                    }
+                }
+            }
+
+            for pending in &graph.trait_impls {
+                let method = Item::Function(pending.method);
+                if reachability.live.contains_key(&method) {
+                    continue;
+                }
+                if let Some(visit) = reachability.trait_impl_visit(pending) {
+                    reachability.live.insert(method, visit);
+                    queue.push_back(method);
+                }
+            }
+            if queue.is_empty() {
+                break;
+            }
+        }
+
+        reachability
+    }
+
+    /// How a trait impl method becomes live, if its conditions are met
+    fn trait_impl_visit(&self, pending: &TraitImplMethod) -> Option<Visit> {
+        let live_type = pending.types.iter().copied().map(Item::Type).find(|t| self.live.contains_key(t));
+        if !pending.types.is_empty() && live_type.is_none() {
+            return None;
+        }
+        if pending.declarations.is_empty() {
+            let link = Link::Implements { trait_name: pending.trait_name.clone(), framework: pending.framework.clone() };
+            return Some(match live_type {
+                Some(rust_type) => Visit::Via(rust_type, link),
+                None => Visit::Root(format!("impl of foreign trait `{}`", pending.trait_name)),
+            });
+        }
+        pending
+            .declarations
+            .iter()
+            .copied()
+            .map(Item::Function)
+            .find(|d| self.live.contains_key(d))
+            .map(|declaration| Visit::Via(declaration, Link::Dispatch))
+    }
+
+    /// Chain from `qualified_name` back to the entry point that keeps it alive, or `None`
+    /// when no function or type of that name is reachable
+    pub fn why_live(&self, qualified_name: &str) -> Option<Vec<String>> {
+        let start = self
+            .symbols
+            .functions
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.qualified_name == qualified_name || f.name == qualified_name)
+            .map(|(idx, _)| Item::Function(idx))
+            .chain(
+                self.symbols
+                    .types
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, t)| t.qualified_name == qualified_name || t.name == qualified_name)
+                    .map(|(idx, _)| Item::Type(idx)),
+            )
+            .find(|item| self.live.contains_key(item))?;
+
+        let mut chain = Vec::new();
+        let mut current = start;
+        while let Some(visit) = self.live.get(&current) {
+            match visit {
+                Visit::Root(kind) => {
+                    chain.push(format!("`{}` is an entry point: {}", self.name(current), kind));
+                    break;
+                }
+                Visit::Via(parent, link) => {
+                    chain.push(format!("`{}` {} `{}`", self.name(current), link.describe(false), self.name(*parent)));
+                    current = *parent;
+                }
+            }
+        }
+        Some(chain)
+    }
+
+    /// Unreachable functions and types, optionally limited to one crate
+    pub fn report(&self, crate_filter: Option<&str>) -> DeadCodeReport {
+        let reportable = |item: &Item| !self.support.contains(item) && !self.foreign.contains(item);
+
+        let functions: Vec<Item> = (0..self.symbols.functions.len()).map(Item::Function).filter(reportable).collect();
+        let types: Vec<Item> = (0..self.symbols.types.len()).map(Item::Type).filter(reportable).collect();
+        let in_crate = |item: &&Item| crate_filter.is_none_or(|c| self.crate_of(**item) == c);
+
+        let dead = |items: &[Item]| -> Vec<DeadItem> {
+            items
+                .iter()
+                .filter(in_crate)
+                .filter(|item| !self.live.contains_key(item))
+                .map(|&item| self.dead_item(item))
+                .collect()
+        };
+
+        DeadCodeReport {
+            entry_points: self.entry_points.clone(),
+            total_functions: functions.iter().filter(in_crate).count(),
+            reachable_functions: functions.iter().filter(in_crate).filter(|i| self.live.contains_key(i)).count(),
+            total_types: types.iter().filter(in_crate).count(),
+            reachable_types: types.iter().filter(in_crate).filter(|i| self.live.contains_key(i)).count(),
+            dead_functions: dead(&functions),
+            dead_types: dead(&types),
+        }
+    }
+
+    fn dead_item(&self, item: Item) -> DeadItem {
+        let reason = self.dead_chain(item);
+        match item {
+            Item::Function(idx) => {
+                let f = &self.symbols.functions[idx];
+                DeadItem {
+                    kind: "function".to_string(),
+                    name: f.name.clone(),
+                    qualified_name: f.qualified_name.clone(),
+                    crate_name: f.crate_name.clone(),
+                    file_path: f.file_path.clone(),
+                    line: f.line_start + 1,
+                    visibility: f.visibility.clone(),
+                    reason,
+                }
+            }
+            Item::Type(idx) => {
+                let t = &self.symbols.types[idx];
+                DeadItem {
+                    kind: format!("{:?}", t.kind).to_lowercase(),
+                    name: t.name.clone(),
+                    qualified_name: t.qualified_name.clone(),
+                    crate_name: t.crate_name.clone(),
+                    file_path: t.file_path.clone(),
+                    line: t.line_start + 1,
+                    visibility: t.visibility.clone(),
+                    reason,
+                }
+            }
+        }
+    }
+
+    /// Follow unreachable users back until an item nothing refers to, or a cycle
+    fn dead_chain(&self, start: Item) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::from([start]);
+        let mut current = start;
+
+        while chain.len() < MAX_CHAIN {
+            let users: Vec<&(Item, Link)> = self
+                .users
+                .get(&current)
+                .into_iter()
+                .flatten()
+                .filter(|(user, _)| !self.live.contains_key(user) && !self.is_method_of(*user, current))
+                .collect();
+
+            let Some((user, link)) = users.first() else {
+                let verb = match current {
+                    Item::Function(_) => "has no callers",
+                    Item::Type(_) => "is never used",
+                };
+                chain.push(format!("`{}` {}", self.name(current), verb));
+                break;
+            };
+
+            let others = match users.len() {
+                1 => String::new(),
+                n => format!(" (and {} more)", n - 1),
+            };
+            chain.push(format!(
+                "`{}` {} unreachable `{}`{}",
+                self.name(current),
+                link.describe(true),
+                self.name(*user),
+                others
+            ));
+
+            if !seen.insert(*user) {
+                chain.push(format!("`{}` is only reachable through this cycle", self.name(*user)));
+                break;
+            }
+            current = *user;
+        }
+        chain
+    }
+
+    /// Whether `user` is a method of the type `item`; a type is not "used" by its own impls
+    fn is_method_of(&self, user: Item, item: Item) -> bool {
+        let (Item::Function(function), Item::Type(rust_type)) = (user, item) else {
+            return false;
+        };
+        let type_name = match &self.symbols.functions[function].function_context {
+            FunctionContext::RegularImpl { type_name } | FunctionContext::TraitImpl { type_name, .. } => type_name,
+            _ => return false,
+        };
+        base_type_name(type_name).as_deref() == Some(self.symbols.types[rust_type].name.as_str())
+    }
+
+    fn name(&self, item: Item) -> &str {
+        match item {
+            Item::Function(idx) => &self.symbols.functions[idx].qualified_name,
+            Item::Type(idx) => &self.symbols.types[idx].qualified_name,
+        }
+    }
+
+    fn crate_of(&self, item: Item) -> &str {
+        match item {
+            Item::Function(idx) => &self.symbols.functions[idx].crate_name,
+            Item::Type(idx) => &self.symbols.types[idx].crate_name,
+        }
+    }
+}
+
+/// Test, bench or example directory a path relative to its crate lies in
+fn support_dir(path: &Path) -> Option<&'static str> {
+    path.components().find_map(|component| match component {
+        Component::Normal(name) if name == "tests" => Some("test"),
+        Component::Normal(name) if name == "benches" => Some("bench"),
+        Component::Normal(name) if name == "examples" => Some("example"),
+        _ => None,
+    })
+}
+
+/// Bare name of the type whose impl or trait declares `function`
+pub(crate) fn impl_type(function: &RustFunction) -> Option<&str> {
+    let type_name = match &function.function_context {
+        FunctionContext::RegularImpl { type_name } | FunctionContext::TraitImpl { type_name, .. } => type_name,
+        FunctionContext::TraitDeclaration { trait_name } => trait_name,
+        _ => return None,
+    };
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    path.rsplit("::").next().map(str::trim)
+}
+
+/// Names called as `name(..)` or `.name(..)` inside the arguments of macro invocations
+fn macro_call_names(source: &str) -> HashSet<&str> {
+    let bytes = source.as_bytes();
+    let mut names = HashSet::new();
+    let mut depth = 0usize;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'"' => {
+                idx += 1;
+                while idx < bytes.len() && bytes[idx] != b'"' {
+                    idx += if bytes[idx] == b'\\' { 2 } else { 1 };
+                }
+            }
+            // Char literals such as '"', but not lifetimes
+            b'\'' if bytes.get(idx + 1) == Some(&b'\\') => {
+                idx += 2;
+                while idx < bytes.len() && bytes[idx] != b'\'' {
+                    idx += 1;
+                }
+            }
+            b'\'' if bytes.get(idx + 2) == Some(&b'\'') => idx += 2,
+            b'!' if depth == 0 && matches!(bytes.get(idx + 1), Some(b'(' | b'[' | b'{')) => {
+                depth = 1;
+                idx += 1;
+            }
+            b'(' | b'[' | b'{' if depth > 0 => depth += 1,
+            b')' | b']' | b'}' if depth > 0 => depth -= 1,
+            c if depth > 0 && (c.is_ascii_alphabetic() || c == b'_') => {
+                let start = idx;
+                while idx + 1 < bytes.len() && (bytes[idx + 1].is_ascii_alphanumeric() || bytes[idx + 1] == b'_') {
+                    idx += 1;
+                }
+                if bytes.get(idx + 1) == Some(&b'(') {
+                    names.insert(&source[start..=idx]);
+                }
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+    names
+}
+
+/// Identifier tokens of a piece of source, used to find the types a function mentions
+fn identifiers(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|token| token.starts_with(|c: char| c.is_alphabetic()))
+}
+
+struct GraphBuilder<'a> {
+    symbols: &'a ParsedSymbols,
+    config: &'a DeadCodeConfig,
+    patterns: &'a FrameworkPatterns,
+    edges: HashMap<Item, Vec<(Item, Link)>>,
+    trait_impls: Vec<TraitImplMethod>,
+    types_by_name: HashMap<&'a str, Vec<usize>>,
+    /// Root directory of each crate, so only the paths inside it decide support code
+    crate_roots: HashMap<&'a str, &'a Path>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    fn new(
+        symbols: &'a ParsedSymbols,
+        crates: &'a [CrateMetadata],
+        config: &'a DeadCodeConfig,
+        patterns: &'a FrameworkPatterns,
+    ) -> Self {
+        let mut types_by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (idx, rust_type) in symbols.types.iter().enumerate() {
+            types_by_name.entry(rust_type.name.as_str()).or_default().push(idx);
+        }
+
+        let mut builder = Self {
+            symbols,
+            config,
+            patterns,
+            edges: HashMap::new(),
+            trait_impls: Vec::new(),
+            types_by_name,
+            crate_roots: crates.iter().map(|c| (c.name.as_str(), c.path.as_path())).collect(),
+        };
+        builder.add_call_edges();
+        builder.add_type_edges();
+        builder.add_trait_dispatch();
+        builder.add_framework_triggers();
+        builder
+    }
+
+    fn edge(&mut self, from: Item, to: Item, link: Link) {
+        if from != to {
+            self.edges.entry(from).or_default().push((to, link));
+        }
+    }
+
+    fn types_named(&self, type_text: &str) -> Vec<usize> {
+        base_type_name(type_text)
+            .and_then(|name| self.types_by_name.get(name.as_str()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn add_call_edges(&mut self) {
+        let symbols = self.symbols;
+        let mut by_qualified: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (idx, function) in symbols.functions.iter().enumerate() {
+            by_qualified.entry(function.qualified_name.as_str()).or_default().push(idx);
+            by_name.entry(function.name.as_str()).or_default().push(idx);
+        }
+        let mut caller_tokens: HashMap<usize, HashSet<&str>> = HashMap::new();
+        let mut resolver = ReferenceResolver::new();
+        if resolver.build_symbol_table(symbols).is_err() {
+            resolver = ReferenceResolver::new();
+        }
+
+        for call in &symbols.calls {
+            // `caller_id` holds the caller's qualified name
+            let Some(callers) = by_qualified.get(call.caller_id.as_str()) else {
+                continue;
+            };
+            let targets: Vec<usize> = call
+                .qualified_callee
+                .as_deref()
+                .and_then(|q| by_qualified.get(q))
+                .cloned()
+                .unwrap_or_default();
+            let same_name = by_name.get(call.callee_name.as_str()).map(Vec::as_slice).unwrap_or_default();
+
+            for &caller in callers {
+                let mut caller_targets = targets.clone();
+                if matches!(call.call_type, CallType::Associated) {
+                    // The parser drops the path of `Type::func()`, so the resolver may have picked
+                    // another type's `func`; keep every candidate whose type the caller names
+                    let tokens = caller_tokens.entry(caller).or_insert_with(|| {
+                        let caller_fn = &symbols.functions[caller];
+                        let mut tokens: HashSet<&str> = identifiers(&caller_fn.signature).collect();
+                        tokens.extend(impl_type(caller_fn));
+                        tokens
+                    });
+                    caller_targets.extend(same_name.iter().copied().filter(|&candidate| {
+                        impl_type(&symbols.functions[candidate]).is_none_or(|owner| tokens.contains(owner))
+                    }));
+                } else if targets.is_empty() || matches!(call.call_type, CallType::Method) {
+                    // Without a receiver type any method of that name may be the target, but a
+                    // receiver of a known foreign type (`Vec`, `String`, ...) cannot reach ours
+                    let foreign_receiver = call
+                        .receiver_type
+                        .as_ref()
+                        .and_then(|receiver| resolver.receiver_type_name(receiver))
+                        .is_some_and(|name| !self.types_by_name.contains_key(name.as_str()));
+                    if !foreign_receiver {
+                        caller_targets.extend(same_name);
+                    }
+                }
+                for &target in &caller_targets {
+                    self.edge(Item::Function(caller), Item::Function(target), Link::Call);
+                }
+            }
+        }
+
+        for reference in &symbols.fn_references {
+            let Some(target) = reference.qualified_target.as_deref() else { continue };
+            let callers = by_qualified.get(reference.caller_id.as_str()).cloned().unwrap_or_default();
+            let targets = by_qualified.get(target).cloned().unwrap_or_default();
+            for &caller in &callers {
+                for &target in &targets {
+                    self.edge(Item::Function(caller), Item::Function(target), Link::Reference);
+                }
+            }
+        }
+
+        // Calls inside macro arguments (`format!("{}", self.name())`) are not parsed as calls
+        for (caller, function) in symbols.functions.iter().enumerate() {
+            for name in macro_call_names(&function.signature) {
+                for &target in by_name.get(name).into_iter().flatten() {
+                    self.edge(Item::Function(caller), Item::Function(target), Link::Call);
+                }
+            }
+        }
+    }
+
+    fn add_type_edges(&mut self) {
+        let symbols = self.symbols;
+        for (idx, function) in symbols.functions.iter().enumerate() {
+            let mut mentioned: HashSet<usize> = identifiers(&function.signature)
+                .filter_map(|token| self.types_by_name.get(token))
+                .flatten()
+                .copied()
+                .collect();
+
+            // A live method keeps its type (and the trait it implements or declares) alive
+            match &function.function_context {
+                FunctionContext::RegularImpl { type_name } => mentioned.extend(self.types_named(type_name)),
+                FunctionContext::TraitImpl { trait_name, type_name } => {
+                    mentioned.extend(self.types_named(type_name));
+                    mentioned.extend(self.types_named(trait_name));
+                }
+                FunctionContext::TraitDeclaration { trait_name } => mentioned.extend(self.types_named(trait_name)),
+                _ => {}
+            }
+
+            for type_idx in mentioned {
+                self.edge(Item::Function(idx), Item::Type(type_idx), Link::Use);
+            }
+        }
+
+        for (idx, rust_type) in symbols.types.iter().enumerate() {
+            let field_types = rust_type
+                .fields
+                .iter()
+                .chain(rust_type.variants.iter().flat_map(|v| v.fields.iter()))
+                .map(|field| field.field_type.as_str());
+            let mentioned: HashSet<usize> = field_types
+                .flat_map(identifiers)
+                .filter_map(|token| self.types_by_name.get(token))
+                .flatten()
+                .copied()
+                .collect();
+            for type_idx in mentioned {
+                self.edge(Item::Type(idx), Item::Type(type_idx), Link::Use);
+            }
+        }
+    }
+
+    fn add_trait_dispatch(&mut self) {
+        let symbols = self.symbols;
+        let mut declarations: HashMap<(String, &str), Vec<usize>> = HashMap::new();
+        for (idx, function) in symbols.functions.iter().enumerate() {
+            if let FunctionContext::TraitDeclaration { trait_name } = &function.function_context {
+                if let Some(trait_name) = base_type_name(trait_name) {
+                    declarations.entry((trait_name, function.name.as_str())).or_default().push(idx);
+                }
+            }
+        }
+
+        for (idx, function) in symbols.functions.iter().enumerate() {
+            let FunctionContext::TraitImpl { trait_name, type_name } = &function.function_context else {
+                continue;
+            };
+            let trait_name = base_type_name(trait_name).unwrap_or_else(|| trait_name.clone());
+            let framework = self
+                .patterns
+                .trait_dispatch
+                .iter()
+                .find(|p| p.trait_name == trait_name && p.target_methods.contains(&function.name))
+                .map(|p| p.framework.clone());
+
+            self.trait_impls.push(TraitImplMethod {
+                method: idx,
+                declarations: declarations
+                    .get(&(trait_name.clone(), function.name.as_str()))
+                    .cloned()
+                    .unwrap_or_default(),
+                trait_name,
+                framework,
+                types: self.types_named(type_name),
+            });
+        }
+    }
+
+    /// Framework entry points that start other methods, e.g. an actor spawn running `on_start`
+    fn add_framework_triggers(&mut self) {
+        if !self.config.framework_entry_points {
+            return;
+        }
+        let symbols = self.symbols;
+        for (idx, function) in symbols.functions.iter().enumerate() {
+            for pattern in self.matching_entry_points(idx) {
+                if pattern.triggers_methods.contains(&function.name) {
+                    continue;
+                }
+                for (target, candidate) in symbols.functions.iter().enumerate() {
+                    let is_impl_method = matches!(candidate.function_context, FunctionContext::TraitImpl { .. });
+                    if is_impl_method && pattern.triggers_methods.contains(&candidate.name) {
+                        let link = Link::Trigger { pattern: pattern.name.clone() };
+                        self.edge(Item::Function(idx), Item::Function(target), link);
+                    }
+                }
+            }
+        }
+    }
+
+    fn matching_entry_points(&self, idx: usize) -> Vec<&'a crate::analyzer::EntryPointPattern> {
+        let function = &self.symbols.functions[idx];
+        let mut code: String = function.attributes.iter().map(|a| format!("{}\n", a)).collect();
+        code.push_str(&function.signature);
+        self.patterns.find_entry_points(&code)
+    }
+
+    /// Test, bench or example code the function at `idx` belongs to, if any
+    fn support_scope(&self, idx: usize) -> Option<&'static str> {
+        let function = &self.symbols.functions[idx];
+        if function.is_test {
+            return Some("test");
+        }
+        if function.attributes.iter().any(|a| a == "#[bench]") {
+            return Some("bench");
+        }
+        self.support_dir(&function.crate_name, &function.file_path)
+    }
+
+    /// Test, bench or example directory of its crate a file lives in
+    fn support_dir(&self, crate_name: &str, file_path: &str) -> Option<&'static str> {
+        let path = Path::new(file_path);
+        let within_crate = self.crate_roots.get(crate_name)
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        support_dir(within_crate)
+    }
+
+    /// Every root item with the kind of entry point it is, in priority order
+    fn roots(&self, crates: &[CrateMetadata]) -> Vec<(Item, String)> {
+        let symbols = self.symbols;
+        let config = self.config;
+        let mut roots = Vec::new();
+
+        let library_crates: HashSet<&str> = symbols
+            .functions
+            .iter()
+            .map(|f| (f.crate_name.as_str(), f.file_path.as_str()))
+            .chain(symbols.types.iter().map(|t| (t.crate_name.as_str(), t.file_path.as_str())))
+            .filter(|(_, file)| file.ends_with("src/lib.rs"))
+            .map(|(crate_name, _)| crate_name)
+            .chain(
+                crates
+                    .iter()
+                    .filter(|c| c.path.join("src").join("lib.rs").exists())
+                    .map(|c| c.name.as_str()),
+            )
+            .collect();
+
+        for (idx, function) in symbols.functions.iter().enumerate() {
+            let item = Item::Function(idx);
+            let is_main = function.name == "main" && matches!(function.function_context, FunctionContext::Free);
+            let path = function.file_path.as_str();
+
+            if is_main && (path.ends_with("src/main.rs") || path.contains("src/bin/") || path.ends_with("build.rs")) {
+                roots.push((item, "binary main".to_string()));
+            }
+            if config.framework_entry_points {
+                for pattern in self.matching_entry_points(idx) {
+                    if pattern.triggers_methods.contains(&function.name) {
+                        roots.push((item, format!("framework entry point `{}`", pattern.name)));
+                    }
+                }
+            }
+            match self.support_scope(idx) {
+                Some("test") if config.tests => roots.push((item, "test".to_string())),
+                Some("bench") if config.benches => roots.push((item, "bench".to_string())),
+                Some("example") if config.examples && is_main => roots.push((item, "example main".to_string())),
+                _ => {}
+            }
+            if config.public_api && library_crates.contains(function.crate_name.as_str()) && self.is_exported(function) {
+                roots.push((item, "public API of a library crate".to_string()));
+            }
+            if config.entry_points.iter().any(|e| *e == function.name || *e == function.qualified_name) {
+                roots.push((item, "configured entry point".to_string()));
+            }
+        }
+
+        if config.public_api {
+            for (idx, rust_type) in symbols.types.iter().enumerate() {
+                if rust_type.visibility == "pub" && library_crates.contains(rust_type.crate_name.as_str()) {
+                    roots.push((Item::Type(idx), "public API of a library crate".to_string()));
+                }
+            }
+        }
+
+        roots
+    }
+
+    /// `pub` free functions and inherent methods, and methods of `pub` traits.
+    ///
+    /// Module privacy is not tracked, so a `pub fn` in a private module still counts.
+    fn is_exported(&self, function: &RustFunction) -> bool {
+        match &function.function_context {
+            FunctionContext::Free | FunctionContext::RegularImpl { .. } | FunctionContext::MacroExpansion { .. } => {
+                function.visibility == "pub"
+            }
+            FunctionContext::TraitDeclaration { trait_name } => self
+                .types_named(trait_name)
+                .into_iter()
+                .any(|idx| is_pub_trait(&self.symbols.types[idx])),
+            _ => false,
+        }
+    }
+}
+
+fn is_pub_trait(rust_type: &RustType) -> bool {
+    rust_type.kind == TypeKind::Trait && rust_type.visibility == "pub"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+    use tempfile::TempDir;
+
+    const LIB: &str = r#"
+pub fn api() -> Config { helper(); Config { inner: Inner } }
+
+fn helper() {}
+
+fn legacy_entry() { legacy_parse(); }
+
+fn legacy_parse() {}
+
+pub struct Config { inner: Inner }
+
+struct Inner;
+
+struct Unused;
+
+impl std::fmt::Display for Unused {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { write!(f, "unused") }
+}
+
+fn tested_only() {}
+
+#[test]
+fn test_tested_only() { tested_only(); }
+"#;
+
+    const MAIN: &str = r#"
+use kameo::Actor;
+
+struct Worker;
+struct Idle;
+struct Ping;
+
+impl Actor for Worker {
+    async fn on_start(&mut self) { warm_up(); }
+}
+
+impl Message<Ping> for Worker {
+    async fn handle(&mut self, msg: Ping) { process(); }
+}
+
+impl Message<Ping> for Idle {
+    async fn handle(&mut self, msg: Ping) { idle_work(); }
+}
+
+fn warm_up() {}
+fn process() {}
+fn idle_work() {}
+
+#[tokio::main]
+async fn main() {
+    let worker = kameo::spawn(Worker);
+    app::api();
+}
+"#;
+
+    fn parse_fixture() -> (TempDir, ParsedSymbols) {
+        let dir = TempDir::new().unwrap();
+        let symbols = parse_fixture_in(dir.path());
+        (dir, symbols)
+    }
+
+    fn parse_fixture_in(root: &Path) -> ParsedSymbols {
+        let src = root.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+
+        let mut parser = RustParser::new().unwrap();
+        let mut symbols = ParsedSymbols::new();
+        for (file, source) in [("lib.rs", LIB), ("main.rs", MAIN)] {
+            let path = src.join(file);
+            std::fs::write(&path, source).unwrap();
+            symbols.merge(parser.parse_source(source, &path, "app").unwrap());
+        }
+        crate::parser::references::resolve_all_references(&mut symbols).unwrap();
+        symbols
+    }
+
+    fn dead_names(report: &DeadCodeReport) -> Vec<&str> {
+        report.dead_functions.iter().chain(&report.dead_types).map(|d| d.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_entry_points_and_trait_dispatch_are_live() {
+        let config = DeadCodeConfig::default();
+        let (_dir, symbols) = parse_fixture();
+        let patterns = FrameworkPatterns::with_default_patterns();
+        let reachability = Reachability::analyze(&symbols, &[], &config, &patterns);
+        let report = reachability.report(None);
+
+        let mut dead = dead_names(&report);
+        dead.sort();
+        assert_eq!(dead, vec!["Idle", "Unused", "fmt", "handle", "idle_work", "legacy_entry", "legacy_parse"]);
+
+        assert_eq!(report.entry_points.get("binary main"), Some(&1));
+        assert_eq!(report.entry_points.get("test"), Some(&1));
+
+        let why = reachability.why_live("warm_up").unwrap();
+        assert_eq!(why.last().unwrap(), "`main` is an entry point: binary main");
+        assert!(why.iter().any(|step| step.contains("started by `kameo_actor_spawn`")), "{:?}", why);
+        let why = reachability.why_live("process").unwrap();
+        assert!(why.iter().any(|step| step.contains("implements `Message` (kameo dispatch) for")), "{:?}", why);
+    }
+
+    #[test]
+    fn test_dead_items_carry_reason_chains() {
+        let config = DeadCodeConfig::default();
+        let (_dir, symbols) = parse_fixture();
+        let patterns = FrameworkPatterns::with_default_patterns();
+        let report = Reachability::analyze(&symbols, &[], &config, &patterns).report(None);
+
+        let reason = |name: &str| {
+            report.dead_functions.iter().chain(&report.dead_types).find(|d| d.name == name).unwrap().reason.clone()
+        };
+        assert_eq!(
+            reason("legacy_parse"),
+            vec!["`legacy_parse` is only called from unreachable `legacy_entry`", "`legacy_entry` has no callers"]
+        );
+        assert_eq!(
+            reason("idle_work"),
+            vec![
+                "`idle_work` is only called from unreachable `Idle::handle`",
+                "`Idle::handle` implements `Message` (kameo dispatch) for unreachable `Idle`",
+                "`Idle` is never used",
+            ]
+        );
+
+        let legacy = report.dead_functions.iter().find(|d| d.name == "legacy_parse").unwrap();
+        let expected_line = LIB.lines().position(|l| l == "fn legacy_parse() {}").unwrap() + 1;
+        assert_eq!(legacy.line, expected_line, "lines are reported 1-based");
+    }
+
+    #[test]
+    fn test_support_dirs_above_the_crate_root_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("examples").join("ws");
+        let symbols = parse_fixture_in(&root);
+        let crates = [CrateMetadata {
+            name: "app".to_string(),
+            version: "0.1.0".to_string(),
+            path: root.clone(),
+            layer: None,
+            depth: 0,
+            dependencies: Vec::new(),
+            is_workspace_member: true,
+            is_external: false,
+            features: Vec::new(),
+            targets: Vec::new(),
+        }];
+        let patterns = FrameworkPatterns::with_default_patterns();
+        let report = Reachability::analyze(&symbols, &crates, &DeadCodeConfig::default(), &patterns).report(None);
+
+        assert_eq!(report.entry_points.get("binary main"), Some(&1));
+        assert!(dead_names(&report).contains(&"legacy_parse"));
+    }
+
+    #[test]
+    fn test_attributes_come_from_the_parsed_snapshot() {
+        let source = "#[bench]\nfn bench_parse(b: &mut Bencher) {}\n\n#[tokio::main]\nasync fn main() {}\n\nfn unused() {}\n";
+        let mut parser = RustParser::new().unwrap();
+        // The file is never written, so attributes can only come from the parse
+        let symbols = parser.parse_source(source, Path::new("/nonexistent/src/lib.rs"), "app").unwrap();
+        let patterns = FrameworkPatterns::with_default_patterns();
+        let reachability = Reachability::analyze(&symbols, &[], &DeadCodeConfig::default(), &patterns);
+
+        assert_eq!(dead_names(&reachability.report(None)), vec!["unused"]);
+        assert_eq!(reachability.why_live("main").unwrap().last().unwrap(), "`main` is an entry point: framework entry point `tokio_main`");
+    }
+
+    #[test]
+    fn test_macro_call_names() {
+        let source = r#"fn f() { let c = '"'; println!("{} (x)", self.name(a), describe(b)); run(); vec![build(1)]; }"#;
+        let mut names: Vec<&str> = macro_call_names(source).into_iter().collect();
+        names.sort();
+        assert_eq!(names, vec!["build", "describe", "name"]);
+    }
+
+    #[test]
+    fn test_roots_are_configurable() {
+        let config = DeadCodeConfig {
+            tests: false,
+            public_api: false,
+            entry_points: vec!["legacy_entry".to_string()],
+            ..DeadCodeConfig::default()
+        };
+        let (_dir, symbols) = parse_fixture();
+        let patterns = FrameworkPatterns::with_default_patterns();
+        let report = Reachability::analyze(&symbols, &[], &config, &patterns).report(None);
+
+        let dead = dead_names(&report);
+        assert!(dead.contains(&"tested_only"));
+        assert!(!dead.contains(&"test_tested_only"), "test code itself is never reported");
+        assert!(!dead.contains(&"legacy_parse"));
+        // `api` is still called from `main`
+        assert!(!dead.contains(&"api"));
+    }
+}
@@ -126,6 +126,8 @@ impl FrameworkPatterns {
         let mut patterns = Self::new();
         patterns.add_tokio_patterns();
         patterns.add_kameo_patterns();
+        patterns.add_actix_patterns();
+        patterns.add_ractor_patterns();
         patterns.add_async_std_patterns();
         patterns.add_websocket_patterns();
         patterns.compile_patterns().unwrap_or_else(|e| {
@@ -219,6 +221,62 @@ impl FrameworkPatterns {
         });
     }
 
+    /// Add actix actor framework patterns
+    pub fn add_actix_patterns(&mut self) {
+        // Handler trait dispatch
+        self.trait_dispatch.push(TraitDispatchPattern {
+            name: "actix_handler_trait".to_string(),
+            description: "actix Handler trait methods".to_string(),
+            framework: "actix".to_string(),
+            trait_name: "Handler".to_string(),
+            dispatch_method: "handle".to_string(),
+            target_methods: vec!["handle".to_string()],
+            dispatch_conditions: vec!["impl.*Handler".to_string()],
+        });
+
+        // Actor pattern
+        self.actor_patterns.push(ActorPattern {
+            name: "actix_actor".to_string(),
+            description: "actix Actor system".to_string(),
+            framework: "actix".to_string(),
+            actor_trait: "Actor".to_string(),
+            lifecycle_methods: vec!["started".to_string(), "stopping".to_string(), "stopped".to_string()],
+            message_handlers: vec!["handle".to_string()],
+            spawn_patterns: vec![r"\.start\(\)".to_string(), r"Actor::create".to_string()],
+        });
+    }
+
+    /// Add ractor actor framework patterns
+    pub fn add_ractor_patterns(&mut self) {
+        // Actor trait dispatch: ractor handles messages on the Actor trait itself
+        self.trait_dispatch.push(TraitDispatchPattern {
+            name: "ractor_actor_trait".to_string(),
+            description: "ractor Actor trait methods".to_string(),
+            framework: "ractor".to_string(),
+            trait_name: "Actor".to_string(),
+            dispatch_method: "pre_start|post_start|post_stop|handle|handle_supervisor_evt".to_string(),
+            target_methods: vec![
+                "pre_start".to_string(),
+                "post_start".to_string(),
+                "post_stop".to_string(),
+                "handle".to_string(),
+                "handle_supervisor_evt".to_string(),
+            ],
+            dispatch_conditions: vec!["impl.*Actor".to_string()],
+        });
+
+        // Actor pattern
+        self.actor_patterns.push(ActorPattern {
+            name: "ractor_actor".to_string(),
+            description: "ractor Actor system".to_string(),
+            framework: "ractor".to_string(),
+            actor_trait: "Actor".to_string(),
+            lifecycle_methods: vec!["pre_start".to_string(), "post_start".to_string(), "post_stop".to_string()],
+            message_handlers: vec!["handle".to_string()],
+            spawn_patterns: vec![r"Actor::spawn".to_string(), r"spawn_linked".to_string()],
+        });
+    }
+
     /// Add async-std framework patterns
     pub fn add_async_std_patterns(&mut self) {
         self.runtime_calls.push(RuntimeCallPattern {
@@ -0,0 +1,633 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use crate::config::Config;
+use crate::parser::symbols::{FunctionContext, ParsedSymbols, TypeKind, TypeUsage, TypeUsageKind};
+use crate::parser::type_inference::{base_type_name, named_types};
+
+/// What kind of item an impact report is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    /// A variant of a workspace enum
+    Variant,
+    /// Unions and type aliases
+    Type,
+}
+
+impl TargetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TargetKind::Function => "function",
+            TargetKind::Struct => "struct",
+            TargetKind::Enum => "enum",
+            TargetKind::Trait => "trait",
+            TargetKind::Variant => "variant",
+            TargetKind::Type => "type",
+        }
+    }
+
+    /// The target kind of a workspace type
+    pub fn of_type(kind: TypeKind) -> Self {
+        match kind {
+            TypeKind::Struct => TargetKind::Struct,
+            TypeKind::Enum => TargetKind::Enum,
+            TypeKind::Trait => TargetKind::Trait,
+            TypeKind::TypeAlias | TypeKind::Union => TargetKind::Type,
+        }
+    }
+
+    /// Sections reported for this kind of target, in display order
+    fn sections(&self) -> &'static [&'static str] {
+        match self {
+            TargetKind::Function => &["callers", "references", "dispatched_from"],
+            TargetKind::Struct | TargetKind::Type => {
+                &["constructors", "field_accesses", "signatures", "fields", "matches", "associated_items"]
+            }
+            TargetKind::Enum => &["constructions", "matches", "signatures", "fields", "associated_items"],
+            TargetKind::Trait => &["implementations", "bounds", "dyn_uses", "associated_items"],
+            TargetKind::Variant => &["constructions", "matches"],
+        }
+    }
+}
+
+/// The function, type or variant a report is about
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactTarget {
+    pub kind: TargetKind,
+    /// Qualified name; `Enum::Variant` for variants
+    pub qualified_name: String,
+    pub crate_name: String,
+    pub layer: Option<String>,
+    pub file_path: String,
+    pub line: usize,
+}
+
+/// A place in the workspace that uses the target directly
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactSite {
+    /// Function or type the site is in; the implementing type for trait impls
+    pub user: String,
+    /// Variant, associated function or field named through the target
+    pub member: Option<String>,
+    pub crate_name: String,
+    pub layer: Option<String>,
+    pub file_path: String,
+    pub line: usize,
+}
+
+/// Direct uses of one kind, e.g. the constructors of a struct or the impls of a trait
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactSection {
+    pub name: &'static str,
+    pub sites: Vec<ImpactSite>,
+}
+
+/// A function or type affected by a change to the target, `distance` hops away
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedItem {
+    pub name: String,
+    /// `"function"` or `"type"`
+    pub kind: &'static str,
+    pub crate_name: String,
+    pub layer: Option<String>,
+    pub distance: usize,
+    pub is_test: bool,
+}
+
+/// Who uses a symbol, and what else a change to it may reach
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolImpact {
+    pub target: ImpactTarget,
+    pub sections: Vec<ImpactSection>,
+    /// Users of the direct users, and so on, up to `depth` hops from the target
+    pub blast_radius: Vec<AffectedItem>,
+    pub depth: usize,
+}
+
+impl SymbolImpact {
+    /// Impact reports for every workspace item `symbol` names.
+    ///
+    /// `symbol` matches a bare name, a qualified name or a qualified suffix, and `Enum::Variant`
+    /// (or a bare variant name) matches enum variants. `symbol_type` keeps only targets of that
+    /// kind. Direct uses come from resolved calls, function references, trait dispatches and
+    /// type usages; the blast radius follows the same edges backwards from the direct users.
+    pub fn analyze(symbols: &ParsedSymbols, config: &Config, symbol: &str, symbol_type: Option<&str>, depth: usize) -> Vec<Self> {
+        let layer_of = layer_lookup(config);
+        let users = UserGraph::build(symbols);
+
+        find_targets(symbols, symbol, &layer_of)
+            .into_iter()
+            .filter(|target| symbol_type.is_none_or(|t| t.eq_ignore_ascii_case(target.kind.as_str())))
+            .map(|target| Self::for_target(symbols, &users, &layer_of, target, depth))
+            .collect()
+    }
+
+    fn for_target(
+        symbols: &ParsedSymbols,
+        users: &UserGraph,
+        layer_of: &dyn Fn(&str) -> Option<String>,
+        target: ImpactTarget,
+        depth: usize,
+    ) -> Self {
+        let mut sites: BTreeMap<&'static str, Vec<ImpactSite>> = target.kind.sections()
+            .iter()
+            .map(|name| (*name, Vec::new()))
+            .collect();
+        collect_sites(symbols, &target, layer_of, &mut sites);
+
+        let seeds: Vec<(String, String)> = sites.values()
+            .flatten()
+            .map(|site| (site.user.clone(), site.crate_name.clone()))
+            .collect();
+        let blast_radius = users.reach(&target, seeds, depth)
+            .into_iter()
+            .map(|((name, crate_name), distance)| {
+                let is_test = users.functions.get(&(name.as_str(), crate_name.as_str())).copied();
+                AffectedItem {
+                    kind: if is_test.is_some() { "function" } else { "type" },
+                    is_test: is_test.unwrap_or(false),
+                    layer: layer_of(&crate_name),
+                    name,
+                    crate_name,
+                    distance,
+                }
+            })
+            .collect();
+
+        let sections = target.kind.sections()
+            .iter()
+            .map(|name| {
+                let mut sites = sites.remove(name).unwrap_or_default();
+                sites.sort_by(|a, b| (&a.file_path, a.line, &a.user).cmp(&(&b.file_path, b.line, &b.user)));
+                sites.dedup_by(|a, b| (&a.file_path, a.line, &a.user, &a.member) == (&b.file_path, b.line, &b.user, &b.member));
+                ImpactSection { name, sites }
+            })
+            .collect();
+        SymbolImpact { target, sections, blast_radius, depth }
+    }
+
+    /// Number of direct use sites across all sections
+    pub fn direct_count(&self) -> usize {
+        self.sections.iter().map(|section| section.sites.len()).sum()
+    }
+}
+
+/// Impact of several targets changing together, such as everything a change set touches
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchImpact {
+    pub reports: Vec<SymbolImpact>,
+    /// Items reached from any target at their shortest distance, without the targets themselves
+    pub affected: Vec<AffectedItem>,
+    pub depth: usize,
+}
+
+impl BatchImpact {
+    pub fn analyze(symbols: &ParsedSymbols, config: &Config, targets: Vec<ImpactTarget>, depth: usize) -> Self {
+        let layer_of = layer_lookup(config);
+        let users = UserGraph::build(symbols);
+        let changed: HashSet<(String, String)> = targets.iter()
+            .map(|t| (t.qualified_name.clone(), t.crate_name.clone()))
+            .collect();
+        let reports: Vec<SymbolImpact> = targets.into_iter()
+            .map(|target| SymbolImpact::for_target(symbols, &users, &layer_of, target, depth))
+            .collect();
+
+        let mut affected: HashMap<(String, String), AffectedItem> = HashMap::new();
+        for item in reports.iter().flat_map(|report| &report.blast_radius) {
+            let key = (item.name.clone(), item.crate_name.clone());
+            if changed.contains(&key) || affected.get(&key).is_some_and(|known| known.distance <= item.distance) {
+                continue;
+            }
+            affected.insert(key, item.clone());
+        }
+        let mut affected: Vec<AffectedItem> = affected.into_values().collect();
+        affected.sort_by(|a, b| (a.distance, &a.crate_name, &a.name).cmp(&(b.distance, &b.crate_name, &b.name)));
+        BatchImpact { reports, affected, depth }
+    }
+
+    /// Tests that reach a changed item, directly or through other functions
+    pub fn affected_tests(&self) -> impl Iterator<Item = &AffectedItem> {
+        self.affected.iter().filter(|item| item.is_test)
+    }
+
+    /// Crates with a changed or affected item, sorted
+    pub fn affected_crates(&self) -> Vec<&str> {
+        let mut crates: Vec<&str> = self.reports.iter().map(|r| r.target.crate_name.as_str())
+            .chain(self.affected.iter().map(|item| item.crate_name.as_str()))
+            .collect();
+        crates.sort();
+        crates.dedup();
+        crates
+    }
+}
+
+/// Layer name of a crate, from the configured architecture
+pub(crate) fn layer_lookup(config: &Config) -> impl Fn(&str) -> Option<String> + '_ {
+    |crate_name: &str| {
+        config.get_layer_index(crate_name)
+            .and_then(|idx| config.get_layer_name(idx))
+            .map(String::from)
+    }
+}
+
+/// Functions, types and enum variants `symbol` names, without duplicates
+fn find_targets(symbols: &ParsedSymbols, symbol: &str, layer_of: &dyn Fn(&str) -> Option<String>) -> Vec<ImpactTarget> {
+    let names = |name: &str, qualified: &str| {
+        name == symbol || qualified == symbol || qualified.ends_with(&format!("::{}", symbol))
+    };
+    let mut targets = Vec::new();
+    let mut seen = HashSet::new();
+    let mut push = |target: ImpactTarget| {
+        if seen.insert((target.qualified_name.clone(), target.crate_name.clone())) {
+            targets.push(target);
+        }
+    };
+
+    for function in symbols.functions.iter().filter(|f| names(&f.name, &f.qualified_name)) {
+        push(ImpactTarget {
+            kind: TargetKind::Function,
+            qualified_name: function.qualified_name.clone(),
+            crate_name: function.crate_name.clone(),
+            layer: layer_of(&function.crate_name),
+            file_path: function.file_path.clone(),
+            line: function.line_start,
+        });
+    }
+    for rust_type in &symbols.types {
+        let target = |kind, qualified_name| ImpactTarget {
+            kind,
+            qualified_name,
+            crate_name: rust_type.crate_name.clone(),
+            layer: layer_of(&rust_type.crate_name),
+            file_path: rust_type.file_path.clone(),
+            line: rust_type.line_start,
+        };
+        if names(&rust_type.name, &rust_type.qualified_name) {
+            push(target(TargetKind::of_type(rust_type.kind), rust_type.qualified_name.clone()));
+        }
+        for variant in &rust_type.variants {
+            let qualified = format!("{}::{}", rust_type.qualified_name, variant.name);
+            if names(&variant.name, &qualified) || symbol == format!("{}::{}", rust_type.name, variant.name) {
+                push(target(TargetKind::Variant, qualified));
+            }
+        }
+    }
+    targets
+}
+
+/// Sort the direct uses of `target` into its sections
+fn collect_sites(
+    symbols: &ParsedSymbols,
+    target: &ImpactTarget,
+    layer_of: &dyn Fn(&str) -> Option<String>,
+    sites: &mut BTreeMap<&'static str, Vec<ImpactSite>>,
+) {
+    let mut add = |section: &'static str, user: &str, member: Option<&str>, crate_name: &str, file_path: &str, line: usize| {
+        if let Some(sites) = sites.get_mut(section) {
+            sites.push(ImpactSite {
+                user: user.to_string(),
+                member: member.map(String::from),
+                crate_name: crate_name.to_string(),
+                layer: layer_of(crate_name),
+                file_path: file_path.to_string(),
+                line,
+            });
+        }
+    };
+    let is_target = |name: Option<&String>, crate_name: Option<&String>| {
+        name == Some(&target.qualified_name) && crate_name == Some(&target.crate_name)
+    };
+
+    if target.kind == TargetKind::Function {
+        for call in symbols.calls.iter().filter(|c| is_target(c.qualified_callee.as_ref(), c.to_crate.as_ref())) {
+            add("callers", &call.caller_id, None, &call.from_crate, &call.file_path, call.line);
+        }
+        for reference in symbols.fn_references.iter().filter(|r| is_target(r.qualified_target.as_ref(), r.to_crate.as_ref())) {
+            add("references", &reference.caller_id, None, &reference.from_crate, &reference.file_path, reference.line);
+        }
+        for dispatch in symbols.trait_dispatches.iter()
+            .filter(|d| d.target == target.qualified_name && d.target_crate == target.crate_name)
+        {
+            add("dispatched_from", &dispatch.caller_id, None, &dispatch.from_crate, &dispatch.file_path, dispatch.line);
+        }
+        return;
+    }
+
+    // A variant is used through its enum, under its own name
+    let (type_name, variant) = match target.kind {
+        TargetKind::Variant => match target.qualified_name.rsplit_once("::") {
+            Some((enum_name, variant)) => (enum_name.to_string(), Some(variant)),
+            None => return,
+        },
+        _ => (target.qualified_name.clone(), None),
+    };
+    let rust_type = symbols.types.iter()
+        .find(|t| t.qualified_name == type_name && t.crate_name == target.crate_name);
+    let is_variant = |member: &str| rust_type.is_some_and(|t| t.variants.iter().any(|v| v.name == member));
+    // Associated functions taking no `self` and returning the type
+    let is_constructor = |member: &str| {
+        let qualified = format!("{}::{}", type_name, member);
+        let simple_name = type_name.rsplit("::").next().unwrap_or(&type_name);
+        symbols.functions.iter().any(|f| {
+            f.qualified_name == qualified
+                && f.crate_name == target.crate_name
+                && !f.is_method
+                && f.return_type.as_deref().is_some_and(|ret| {
+                    named_types(ret).iter().any(|name| name == "Self" || name.rsplit("::").next() == Some(simple_name))
+                })
+        })
+    };
+
+    let section_of = |usage: &TypeUsage| -> Option<&'static str> {
+        let member = usage.member.as_deref();
+        if let Some(variant) = variant {
+            return match usage.kind {
+                _ if member != Some(variant) => None,
+                TypeUsageKind::Path | TypeUsageKind::StructLiteral => Some("constructions"),
+                TypeUsageKind::Pattern => Some("matches"),
+                _ => None,
+            };
+        }
+        Some(match (usage.kind, target.kind) {
+            (TypeUsageKind::Parameter | TypeUsageKind::Return | TypeUsageKind::Field, TargetKind::Trait) if usage.is_dyn => "dyn_uses",
+            // `impl Trait` arguments and return types are bounds in all but name
+            (TypeUsageKind::Parameter | TypeUsageKind::Return | TypeUsageKind::Bound, TargetKind::Trait) => "bounds",
+            (TypeUsageKind::Field, TargetKind::Trait) => "dyn_uses",
+            (TypeUsageKind::Parameter | TypeUsageKind::Return, _) => "signatures",
+            (TypeUsageKind::Field, _) => "fields",
+            (TypeUsageKind::Bound, _) => "bounds",
+            (TypeUsageKind::FieldAccess, _) => "field_accesses",
+            (TypeUsageKind::Pattern, _) => "matches",
+            (TypeUsageKind::StructLiteral, TargetKind::Enum) => "constructions",
+            (TypeUsageKind::StructLiteral, _) => "constructors",
+            (TypeUsageKind::Path, TargetKind::Enum) if member.is_some_and(is_variant) => "constructions",
+            (TypeUsageKind::Path, _) if member.is_some_and(is_constructor) => "constructors",
+            (TypeUsageKind::Path, _) => "associated_items",
+        })
+    };
+    let usages = symbols.type_usages.iter()
+        .filter(|u| u.qualified_type.as_ref() == Some(&type_name) && u.to_crate.as_ref() == Some(&target.crate_name))
+        // A type naming itself in its own fields is not a use
+        .filter(|u| !(u.user_is_type && u.user == type_name));
+    for usage in usages {
+        if let Some(section) = section_of(usage) {
+            add(section, &usage.user, usage.member.as_deref(), &usage.from_crate, &usage.file_path, usage.line);
+        }
+    }
+
+    if target.kind == TargetKind::Trait {
+        let trait_name = type_name.rsplit("::").next().unwrap_or(&type_name);
+        let file_crates: HashMap<&str, &str> = symbols.types.iter()
+            .map(|t| (t.file_path.as_str(), t.crate_name.as_str()))
+            .chain(symbols.functions.iter().map(|f| (f.file_path.as_str(), f.crate_name.as_str())))
+            .collect();
+        let implements = |name: &str| base_type_name(name).as_deref() == Some(trait_name);
+        let impls = symbols.impls.iter()
+            .filter(|i| i.trait_name.as_deref().is_some_and(implements))
+            .map(|i| (i.type_name.as_str(), file_crates.get(i.file_path.as_str()).copied().unwrap_or_default(), i.file_path.as_str(), i.line_start));
+        // Impls found by the AST walker are only known through their methods
+        let mut impl_methods: Vec<_> = symbols.functions.iter()
+            .filter_map(|f| match &f.function_context {
+                FunctionContext::TraitImpl { trait_name, type_name } if implements(trait_name) => {
+                    Some((type_name.as_str(), f.crate_name.as_str(), f.file_path.as_str(), f.line_start))
+                }
+                _ => None,
+            })
+            .collect();
+        impl_methods.sort();
+        let mut seen = HashSet::new();
+        for (type_name, crate_name, file_path, line) in impls.chain(impl_methods) {
+            if seen.insert((type_name, file_path)) {
+                add("implementations", type_name, None, crate_name, file_path, line);
+            }
+        }
+    }
+}
+
+type Item = (String, String);
+
+/// Who uses each function and type: callers, referrers, dispatching callers and type users,
+/// keyed by (qualified name, crate)
+struct UserGraph<'s> {
+    users: HashMap<(&'s str, &'s str), Vec<(&'s str, &'s str)>>,
+    /// Whether each function is a test
+    functions: HashMap<(&'s str, &'s str), bool>,
+}
+
+impl<'s> UserGraph<'s> {
+    fn build(symbols: &'s ParsedSymbols) -> Self {
+        let mut users: HashMap<(&str, &str), Vec<(&str, &str)>> = HashMap::new();
+        let mut add = |used: (&'s str, &'s str), user: (&'s str, &'s str)| {
+            users.entry(used).or_default().push(user);
+        };
+        for call in &symbols.calls {
+            if let (Some(callee), Some(to_crate)) = (&call.qualified_callee, &call.to_crate) {
+                add((callee, to_crate), (&call.caller_id, &call.from_crate));
+            }
+        }
+        for reference in &symbols.fn_references {
+            if let (Some(target), Some(to_crate)) = (&reference.qualified_target, &reference.to_crate) {
+                add((target, to_crate), (&reference.caller_id, &reference.from_crate));
+            }
+        }
+        for dispatch in &symbols.trait_dispatches {
+            add((&dispatch.target, &dispatch.target_crate), (&dispatch.caller_id, &dispatch.from_crate));
+        }
+        for usage in &symbols.type_usages {
+            if let (Some(type_name), Some(to_crate)) = (&usage.qualified_type, &usage.to_crate) {
+                if !(usage.user_is_type && usage.user == *type_name) {
+                    add((type_name, to_crate), (&usage.user, &usage.from_crate));
+                }
+            }
+        }
+        let functions = symbols.functions.iter()
+            .map(|f| ((f.qualified_name.as_str(), f.crate_name.as_str()), f.is_test))
+            .collect();
+        UserGraph { users, functions }
+    }
+
+    /// Items within `depth` hops of the target, starting from its direct users at distance 1,
+    /// sorted by distance
+    fn reach(&self, target: &ImpactTarget, seeds: Vec<Item>, depth: usize) -> Vec<(Item, usize)> {
+        let mut distances: HashMap<Item, usize> = HashMap::new();
+        let mut queue: VecDeque<(Item, usize)> = VecDeque::new();
+        // Users of a variant's enum are not necessarily users of the variant
+        let origin = (target.qualified_name.clone(), target.crate_name.clone());
+        for seed in seeds {
+            if depth > 0 && seed != origin && !distances.contains_key(&seed) {
+                distances.insert(seed.clone(), 1);
+                queue.push_back((seed, 1));
+            }
+        }
+        while let Some(((name, crate_name), distance)) = queue.pop_front() {
+            if distance >= depth {
+                continue;
+            }
+            let Some(users) = self.users.get(&(name.as_str(), crate_name.as_str())) else { continue };
+            for (user, user_crate) in users {
+                let item = (user.to_string(), user_crate.to_string());
+                if item != origin && !distances.contains_key(&item) {
+                    distances.insert(item.clone(), distance + 1);
+                    queue.push_back((item, distance + 1));
+                }
+            }
+        }
+        let mut reached: Vec<(Item, usize)> = distances.into_iter().collect();
+        reached.sort_by(|(a, da), (b, db)| (da, &a.1, &a.0).cmp(&(db, &b.1, &b.0)));
+        reached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::references::resolve_all_references;
+    use crate::parser::RustParser;
+    use std::path::Path;
+
+    const ORDERS: &str = r#"
+pub struct Order {
+    pub id: u32,
+    pub status: Status,
+}
+
+pub enum Status {
+    Open,
+    Filled { qty: u32 },
+}
+
+pub trait Store {
+    fn save(&self, order: &Order);
+}
+
+pub struct MemoryStore;
+
+impl Store for MemoryStore {
+    fn save(&self, order: &Order) {}
+}
+
+impl Order {
+    pub fn new(id: u32) -> Self {
+        Order { id, status: Status::Open }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+"#;
+
+    const SERVICE: &str = r#"
+use orders::{Order, Status, Store};
+
+pub fn place(id: u32) -> Order {
+    Order::new(id)
+}
+
+pub fn fill(order: &mut Order) {
+    order.status = Status::Filled { qty: 1 };
+}
+
+pub fn describe(order: &Order) -> u32 {
+    match &order.status {
+        Status::Open => order.id,
+        Status::Filled { qty } => *qty,
+    }
+}
+
+pub fn persist(store: &dyn Store, order: &Order) {
+    store.save(order);
+}
+
+pub fn persist_all<S: Store>(store: &S) {}
+
+pub fn handle(id: u32) {
+    let order = place(id);
+    describe(&order);
+}
+
+pub fn serve() {
+    handle(1);
+}
+"#;
+
+    fn symbols() -> ParsedSymbols {
+        let mut parser = RustParser::new().unwrap();
+        let mut symbols = parser.parse_source(ORDERS, Path::new("orders/src/lib.rs"), "orders").unwrap();
+        symbols.merge(parser.parse_source(SERVICE, Path::new("service/src/lib.rs"), "service").unwrap());
+        resolve_all_references(&mut symbols).unwrap();
+        symbols
+    }
+
+    fn impact(symbol: &str, symbol_type: Option<&str>, depth: usize) -> SymbolImpact {
+        let mut reports = SymbolImpact::analyze(&symbols(), &Config::default(), symbol, symbol_type, depth);
+        assert_eq!(reports.len(), 1, "{:?}", reports.iter().map(|r| &r.target).collect::<Vec<_>>());
+        reports.remove(0)
+    }
+
+    fn users<'r>(report: &'r SymbolImpact, section: &str) -> Vec<(&'r str, Option<&'r str>)> {
+        let section = report.sections.iter().find(|s| s.name == section).unwrap();
+        section.sites.iter().map(|s| (s.user.as_str(), s.member.as_deref())).collect()
+    }
+
+    #[test]
+    fn test_struct_constructors_fields_and_signatures() {
+        let report = impact("Order", Some("struct"), 1);
+        assert_eq!(report.target.crate_name, "orders");
+
+        assert_eq!(users(&report, "constructors"), vec![("Order::new", None), ("place", Some("new"))]);
+        assert_eq!(
+            users(&report, "field_accesses"),
+            vec![("Order::id", Some("id")), ("fill", Some("status")), ("describe", Some("status")), ("describe", Some("id"))]
+        );
+        let signatures = users(&report, "signatures");
+        for user in ["Order::new", "place", "fill", "describe", "persist", "Store::save", "MemoryStore::save"] {
+            assert!(signatures.contains(&(user, None)), "{} missing from {:?}", user, signatures);
+        }
+        let site = &report.sections[0].sites[1];
+        assert_eq!((site.crate_name.as_str(), site.file_path.as_str(), site.line), ("service", "service/src/lib.rs", 4));
+    }
+
+    #[test]
+    fn test_trait_impls_bounds_and_dyn_uses() {
+        let report = impact("Store", None, 1);
+        assert_eq!(report.target.kind, TargetKind::Trait);
+        assert_eq!(users(&report, "implementations"), vec![("MemoryStore", None)]);
+        assert_eq!(users(&report, "bounds"), vec![("persist_all", None)]);
+        assert_eq!(users(&report, "dyn_uses"), vec![("persist", None)]);
+    }
+
+    #[test]
+    fn test_variant_constructions_and_matches() {
+        let report = impact("Status::Filled", None, 1);
+        assert_eq!(report.target.kind, TargetKind::Variant);
+        assert_eq!(report.target.qualified_name, "Status::Filled");
+        assert_eq!(users(&report, "constructions"), vec![("fill", Some("Filled"))]);
+        assert_eq!(users(&report, "matches"), vec![("describe", Some("Filled"))]);
+
+        let open = impact("Status::Open", None, 1);
+        assert_eq!(users(&open, "constructions"), vec![("Order::new", Some("Open"))]);
+        assert_eq!(users(&open, "matches"), vec![("describe", Some("Open"))]);
+    }
+
+    #[test]
+    fn test_blast_radius_is_bounded_by_depth() {
+        let names = |report: &SymbolImpact| -> Vec<(String, usize)> {
+            report.blast_radius.iter().map(|item| (item.name.clone(), item.distance)).collect()
+        };
+
+        let shallow = impact("place", None, 1);
+        assert_eq!(users(&shallow, "callers"), vec![("handle", None)]);
+        assert_eq!(names(&shallow), vec![("handle".to_string(), 1)]);
+
+        let deep = impact("place", None, 3);
+        assert_eq!(names(&deep), vec![("handle".to_string(), 1), ("serve".to_string(), 2)]);
+        assert_eq!(deep.blast_radius[0].kind, "function");
+        assert_eq!(deep.blast_radius[0].crate_name, "service");
+    }
+}
@@ -0,0 +1,241 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::parser::symbols::{MessageSend, ParsedSymbols, SendMethod};
+use crate::parser::type_inference::base_type_name;
+
+/// A `tell`/`ask` whose message no receiving actor handles
+#[derive(Debug, Clone, Serialize)]
+pub struct UnhandledSend {
+    pub message_type: String,
+    /// Receiving actor when the parser could resolve it from the `ActorRef`
+    pub receiver: Option<String>,
+    pub sender: String,
+    pub method: String,
+    pub crate_name: String,
+    pub file_path: String,
+    /// 1-based
+    pub line: usize,
+    /// Actors that do handle the message, if any
+    pub handled_by: Vec<String>,
+}
+
+/// An `impl Message<M> for Actor` that no send in the workspace reaches
+#[derive(Debug, Clone, Serialize)]
+pub struct UnusedHandler {
+    pub actor: String,
+    pub message_type: String,
+    pub reply_type: String,
+    pub crate_name: String,
+    pub file_path: String,
+    /// 1-based
+    pub line: usize,
+}
+
+/// A message type that is defined but neither sent nor handled
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanedMessage {
+    pub name: String,
+    pub qualified_name: String,
+    pub crate_name: String,
+    pub file_path: String,
+    /// 1-based
+    pub line: usize,
+}
+
+/// Messages, handlers and sends that do not line up
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MessageAudit {
+    pub unhandled_sends: Vec<UnhandledSend>,
+    pub unused_handlers: Vec<UnusedHandler>,
+    pub orphaned_messages: Vec<OrphanedMessage>,
+}
+
+impl MessageAudit {
+    /// Correlate `MessageType`, `MessageHandler` and `MessageSend` records by message name.
+    ///
+    /// Sends only count when they target a known actor or carry a known message type, which
+    /// keeps channel `send` calls out of the report. A send whose receiver could not be
+    /// resolved is matched against every handler of its message type.
+    pub fn analyze(symbols: &ParsedSymbols, crate_filter: Option<&str>) -> Self {
+        let message_name = |text: &str| base_type_name(text).unwrap_or_else(|| text.to_string());
+        let in_scope = |crate_name: &str| crate_filter.is_none_or(|c| c == crate_name);
+
+        let mut handlers: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+        for handler in &symbols.message_handlers {
+            handlers.entry(message_name(&handler.message_type)).or_default().insert(handler.actor_name.as_str());
+        }
+        let defined: HashSet<&str> = symbols.message_types.iter().map(|m| m.name.as_str()).collect();
+        let actors: HashSet<&str> = symbols.actors.iter().map(|a| a.name.as_str())
+            .chain(symbols.message_handlers.iter().map(|h| h.actor_name.as_str()))
+            .collect();
+
+        let receiver_of = |send: &MessageSend| {
+            actors.contains(send.receiver_actor.as_str()).then(|| send.receiver_actor.clone())
+        };
+        let sends: Vec<&MessageSend> = symbols.message_sends.iter()
+            .filter(|send| {
+                actors.contains(send.receiver_actor.as_str())
+                    || defined.contains(send.message_type.as_str())
+                    || handlers.contains_key(&send.message_type)
+            })
+            .collect();
+
+        let mut audit = MessageAudit::default();
+        // (message, receiver) pairs reached by some send; `None` receivers reach every handler
+        let mut reached: HashSet<(String, Option<String>)> = HashSet::new();
+        for send in &sends {
+            let receiver = receiver_of(send);
+            reached.insert((send.message_type.clone(), receiver.clone()));
+
+            let handled_by: Vec<String> = handlers.get(&send.message_type)
+                .map(|actors| actors.iter().map(|a| a.to_string()).collect())
+                .unwrap_or_default();
+            let handled = match &receiver {
+                Some(receiver) => handled_by.contains(receiver),
+                None => !handled_by.is_empty(),
+            };
+            if handled || !in_scope(&send.from_crate) {
+                continue;
+            }
+            audit.unhandled_sends.push(UnhandledSend {
+                message_type: send.message_type.clone(),
+                receiver,
+                sender: send.sender_actor.clone(),
+                method: match send.send_method {
+                    SendMethod::Tell => "tell".to_string(),
+                    SendMethod::Ask => "ask".to_string(),
+                },
+                crate_name: send.from_crate.clone(),
+                file_path: send.file_path.clone(),
+                line: send.line + 1,
+                handled_by,
+            });
+        }
+
+        for handler in &symbols.message_handlers {
+            let message = message_name(&handler.message_type);
+            let is_sent = reached.contains(&(message.clone(), None))
+                || reached.contains(&(message.clone(), Some(handler.actor_name.clone())));
+            if is_sent || !in_scope(&handler.crate_name) {
+                continue;
+            }
+            audit.unused_handlers.push(UnusedHandler {
+                actor: handler.actor_name.clone(),
+                message_type: handler.message_type.clone(),
+                reply_type: handler.reply_type.clone(),
+                crate_name: handler.crate_name.clone(),
+                file_path: handler.file_path.clone(),
+                line: handler.line + 1,
+            });
+        }
+
+        let sent: HashSet<&str> = sends.iter().map(|s| s.message_type.as_str()).collect();
+        let mut reported = HashSet::new();
+        for message in &symbols.message_types {
+            if sent.contains(message.name.as_str())
+                || handlers.contains_key(&message.name)
+                || !in_scope(&message.crate_name)
+                || !reported.insert((message.name.as_str(), message.crate_name.as_str()))
+            {
+                continue;
+            }
+            audit.orphaned_messages.push(OrphanedMessage {
+                name: message.name.clone(),
+                qualified_name: message.qualified_name.clone(),
+                crate_name: message.crate_name.clone(),
+                file_path: message.file_path.clone(),
+                line: message.line_start + 1,
+            });
+        }
+
+        audit.unhandled_sends.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+        audit.unused_handlers.sort_by(|a, b| (&a.actor, &a.message_type).cmp(&(&b.actor, &b.message_type)));
+        audit.orphaned_messages.sort_by(|a, b| (&a.crate_name, &a.name).cmp(&(&b.crate_name, &b.name)));
+        audit
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unhandled_sends.is_empty() && self.unused_handlers.is_empty() && self.orphaned_messages.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+    use std::path::Path;
+
+    const ORDERS: &str = r#"
+pub struct OrderBook;
+pub struct Ledger;
+pub struct PlaceOrder;
+pub struct CancelOrder;
+pub struct SettleMessage;
+pub struct ArchiveQuery;
+pub struct Tick;
+
+impl Actor for OrderBook {}
+impl Actor for Ledger {}
+
+impl Message<PlaceOrder> for OrderBook {
+    type Reply = ();
+    async fn handle(&mut self, _: PlaceOrder, _: &mut Context<Self, ()>) {}
+}
+
+impl Message<CancelOrder> for OrderBook {
+    type Reply = bool;
+    async fn handle(&mut self, _: CancelOrder, _: &mut Context<Self, bool>) -> bool { true }
+}
+
+impl Message<Tick> for Ledger {
+    type Reply = ();
+    async fn handle(&mut self, _: Tick, _: &mut Context<Self, ()>) {}
+}
+
+pub async fn run(tx: Sender<u8>) {
+    let book = OrderBook::spawn(OrderBook);
+    let ledger: ActorRef<Ledger> = Ledger::spawn(Ledger);
+    book.tell(PlaceOrder).await;
+    ledger.tell(PlaceOrder).await;
+    book.ask(SettleMessage).await;
+    tx.send(1).await;
+}
+"#;
+
+    fn audit(crate_filter: Option<&str>) -> MessageAudit {
+        let mut parser = RustParser::new().unwrap();
+        let symbols = parser.parse_source(ORDERS, Path::new("src/orders.rs"), "orders").unwrap();
+        MessageAudit::analyze(&symbols, crate_filter)
+    }
+
+    #[test]
+    fn test_unhandled_unused_and_orphaned_messages() {
+        let audit = audit(None);
+
+        let unhandled: Vec<_> = audit.unhandled_sends.iter()
+            .map(|s| (s.message_type.as_str(), s.receiver.as_deref()))
+            .collect();
+        assert_eq!(unhandled, vec![("PlaceOrder", Some("Ledger")), ("SettleMessage", Some("OrderBook"))]);
+        assert_eq!(audit.unhandled_sends[0].handled_by, vec!["OrderBook"]);
+
+        let unused: Vec<_> = audit.unused_handlers.iter()
+            .map(|h| (h.actor.as_str(), h.message_type.as_str()))
+            .collect();
+        assert_eq!(unused, vec![("Ledger", "Tick"), ("OrderBook", "CancelOrder")]);
+
+        let orphaned: Vec<_> = audit.orphaned_messages.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(orphaned, vec!["ArchiveQuery"]);
+
+        let row = |text: &str| ORDERS.lines().position(|l| l.contains(text)).unwrap() + 1;
+        assert_eq!(audit.unhandled_sends[0].line, row("ledger.tell(PlaceOrder)"));
+        assert_eq!(audit.unused_handlers[0].line, row("impl Message<Tick> for Ledger"));
+        assert_eq!(audit.orphaned_messages[0].line, row("pub struct ArchiveQuery"));
+    }
+
+    #[test]
+    fn test_crate_filter() {
+        assert!(audit(Some("other")).is_empty());
+        assert!(!audit(Some("orders")).is_empty());
+    }
+}
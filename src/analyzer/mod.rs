@@ -1,5 +1,12 @@
 pub mod global_index;
 pub mod framework_patterns;
+pub mod dead_code;
+pub mod supervision;
+pub mod ask_cycles;
+pub mod message_audit;
+pub mod impact;
+pub mod sequence;
+pub mod registry;
 pub mod workspace_analyzer;
 
 pub use global_index::{
@@ -25,6 +32,55 @@ pub use framework_patterns::{
     PatternStats,
 };
 
+pub use dead_code::{
+    Reachability,
+    DeadCodeReport,
+    DeadItem,
+};
+
+pub use supervision::{
+    SupervisionTree,
+    SupervisionNode,
+    FailureOutcome,
+};
+
+pub use ask_cycles::{
+    AskGraph,
+    AskCycle,
+    AskHop,
+    AskSite,
+};
+
+pub use message_audit::{
+    MessageAudit,
+    UnhandledSend,
+    UnusedHandler,
+    OrphanedMessage,
+};
+
+pub use impact::{
+    SymbolImpact,
+    BatchImpact,
+    ImpactTarget,
+    TargetKind,
+    ImpactSection,
+    ImpactSite,
+    AffectedItem,
+};
+
+pub use sequence::{
+    SequenceDiagram,
+    SequenceFormat,
+    SequenceStep,
+    StepKind,
+    Participant,
+};
+
+pub use registry::{
+    ActorRegistry,
+    RegisteredName,
+};
+
 pub use workspace_analyzer::{
     WorkspaceAnalyzer,
     WorkspaceSnapshot,
@@ -0,0 +1,122 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::parser::symbols::{ActorRegistration, ParsedSymbols, RemoteLookup};
+
+/// Everything registered under one swarm name
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredName {
+    pub registered_name: String,
+    /// Actors registered under the name; more than one makes lookups ambiguous
+    pub actors: Vec<String>,
+    pub registrations: Vec<ActorRegistration>,
+    pub lookups: Vec<RemoteLookup>,
+}
+
+/// Names actors are registered under on the kameo swarm, and the lookups made against them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ActorRegistry {
+    pub names: Vec<RegisteredName>,
+    /// Lookups of names no code in the workspace registers
+    pub unregistered_lookups: Vec<RemoteLookup>,
+}
+
+impl ActorRegistry {
+    pub fn from_symbols(symbols: &ParsedSymbols) -> Self {
+        let mut names: BTreeMap<&str, RegisteredName> = BTreeMap::new();
+        for registration in &symbols.actor_registrations {
+            names
+                .entry(registration.registered_name.as_str())
+                .or_insert_with(|| RegisteredName {
+                    registered_name: registration.registered_name.clone(),
+                    actors: Vec::new(),
+                    registrations: Vec::new(),
+                    lookups: Vec::new(),
+                })
+                .registrations
+                .push(registration.clone());
+        }
+        for entry in names.values_mut() {
+            let actors: BTreeSet<&str> = entry.registrations.iter().filter_map(|r| r.actor_name.as_deref()).collect();
+            entry.actors = actors.into_iter().map(str::to_string).collect();
+        }
+
+        let mut unregistered_lookups = Vec::new();
+        for lookup in &symbols.remote_lookups {
+            match names.get_mut(lookup.registered_name.as_str()) {
+                Some(entry) => entry.lookups.push(lookup.clone()),
+                None => unregistered_lookups.push(lookup.clone()),
+            }
+        }
+        unregistered_lookups.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+
+        ActorRegistry {
+            names: names.into_values().collect(),
+            unregistered_lookups,
+        }
+    }
+
+    /// Whether anything in the workspace registers `name`
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.names.iter().any(|entry| entry.registered_name == name)
+    }
+
+    /// The one actor registered under `name`, if it is known and unambiguous
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        match self.names.iter().find(|entry| entry.registered_name == name)?.actors.as_slice() {
+            [actor] => Some(actor),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+    use std::path::Path;
+
+    const NODES: &str = r#"
+pub struct PriceFeed;
+pub struct Backup;
+
+pub async fn start() {
+    let feed = PriceFeed::spawn(PriceFeed);
+    feed.register("prices").await.unwrap();
+    let backup: ActorRef<Backup> = Backup::spawn(Backup);
+    backup.register("prices").await.unwrap();
+    let other = Backup::spawn(Backup);
+    other.register("backup").await.unwrap();
+}
+
+pub async fn connect() {
+    let prices = RemoteActorRef::<PriceFeed>::lookup("prices").await.unwrap();
+    let orders = RemoteActorRef::<OrderBook>::lookup("orders").await.unwrap();
+    let dynamic = RemoteActorRef::<PriceFeed>::lookup(name).await.unwrap();
+}
+"#;
+
+    #[test]
+    fn test_registered_names_and_unregistered_lookups() {
+        let mut parser = RustParser::new().unwrap();
+        let symbols = parser.parse_source(NODES, Path::new("src/nodes.rs"), "nodes").unwrap();
+        let registry = ActorRegistry::from_symbols(&symbols);
+
+        let names: Vec<_> = registry.names.iter()
+            .map(|n| (n.registered_name.as_str(), n.actors.clone(), n.lookups.len()))
+            .collect();
+        assert_eq!(names, vec![
+            ("backup", vec!["Backup".to_string()], 0),
+            ("prices", vec!["Backup".to_string(), "PriceFeed".to_string()], 1),
+        ]);
+        assert_eq!(registry.resolve("backup"), Some("Backup"));
+        assert_eq!(registry.resolve("prices"), None, "two actors share the name");
+
+        // Only literal names are tracked, so `lookup(name)` is not reported
+        let unregistered: Vec<_> = registry.unregistered_lookups.iter()
+            .map(|l| (l.registered_name.as_str(), l.actor_name.as_deref()))
+            .collect();
+        assert_eq!(unregistered, vec![("orders", Some("OrderBook"))]);
+        assert!(!registry.is_registered("orders"));
+    }
+}
@@ -0,0 +1,503 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::analyzer::dead_code::impl_type;
+use crate::parser::symbols::{FunctionCall, FunctionContext, MessageHandler, MessageSend, ParsedSymbols, RustFunction, SendMethod};
+use crate::parser::type_inference::base_type_name;
+
+/// Output syntax for a rendered sequence diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceFormat {
+    Mermaid,
+    PlantUml,
+}
+
+impl SequenceFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "mermaid" => Some(SequenceFormat::Mermaid),
+            "plantuml" | "puml" => Some(SequenceFormat::PlantUml),
+            _ => None,
+        }
+    }
+}
+
+/// Kind of arrow between two participants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    /// Direct function call into another type
+    Call,
+    /// Fire-and-forget `tell`
+    Tell,
+    /// `ask` awaiting a reply
+    Ask,
+    /// Reply to an `ask`, labelled with the handler's `Reply` type
+    Reply,
+}
+
+/// An actor, type or free function taking part in the flow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Participant {
+    pub name: String,
+    pub crate_name: String,
+}
+
+/// One arrow of the diagram, in the order it happens
+#[derive(Debug, Clone, Serialize)]
+pub struct SequenceStep {
+    pub from: usize,
+    pub to: usize,
+    pub kind: StepKind,
+    pub label: String,
+    /// The arrow leaves one crate and enters another
+    pub crosses_crate: bool,
+    pub file_path: String,
+    pub line: usize,
+}
+
+/// Ordered message flow starting from an entry function or message type
+#[derive(Debug, Clone, Serialize)]
+pub struct SequenceDiagram {
+    pub entry: String,
+    pub participants: Vec<Participant>,
+    pub steps: Vec<SequenceStep>,
+    /// Some branches were cut off by the depth or step limit
+    pub truncated: bool,
+}
+
+/// A call or send inside a function body
+#[derive(Clone, Copy)]
+enum Event<'a> {
+    Call(&'a FunctionCall),
+    Send(&'a MessageSend),
+}
+
+impl<'a> Event<'a> {
+    fn line(&self) -> usize {
+        match self {
+            Event::Call(call) => call.line,
+            Event::Send(send) => send.line,
+        }
+    }
+}
+
+/// Participant that receives the first message when tracing from a message type
+const CALLER: &str = "Caller";
+const MAX_STEPS: usize = 200;
+
+struct Tracer<'a> {
+    symbols: &'a ParsedSymbols,
+    max_depth: usize,
+    /// Calls and sends per file, ordered by line
+    events: HashMap<&'a str, Vec<Event<'a>>>,
+    by_qualified: HashMap<&'a str, Vec<usize>>,
+    handlers: HashMap<String, Vec<&'a MessageHandler>>,
+    diagram: SequenceDiagram,
+    stack: Vec<usize>,
+}
+
+impl<'a> Tracer<'a> {
+    fn new(symbols: &'a ParsedSymbols, entry: &str, max_depth: usize) -> Self {
+        let mut events: HashMap<&str, Vec<Event>> = HashMap::new();
+        for call in symbols.calls.iter().filter(|c| !c.is_synthetic) {
+            events.entry(call.file_path.as_str()).or_default().push(Event::Call(call));
+        }
+        for send in &symbols.message_sends {
+            events.entry(send.file_path.as_str()).or_default().push(Event::Send(send));
+        }
+        for file_events in events.values_mut() {
+            file_events.sort_by_key(Event::line);
+        }
+
+        let mut by_qualified: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (idx, function) in symbols.functions.iter().enumerate() {
+            by_qualified.entry(function.qualified_name.as_str()).or_default().push(idx);
+        }
+        let mut handlers: HashMap<String, Vec<&MessageHandler>> = HashMap::new();
+        for handler in &symbols.message_handlers {
+            handlers.entry(message_name(&handler.message_type)).or_default().push(handler);
+        }
+
+        Tracer {
+            symbols,
+            max_depth,
+            events,
+            by_qualified,
+            handlers,
+            diagram: SequenceDiagram {
+                entry: entry.to_string(),
+                participants: Vec::new(),
+                steps: Vec::new(),
+                truncated: false,
+            },
+            stack: Vec::new(),
+        }
+    }
+
+    fn participant(&mut self, name: &str, crate_name: &str) -> usize {
+        let participants = &mut self.diagram.participants;
+        if let Some(idx) = participants.iter().position(|p| p.name == name && p.crate_name == crate_name) {
+            return idx;
+        }
+        participants.push(Participant { name: name.to_string(), crate_name: crate_name.to_string() });
+        participants.len() - 1
+    }
+
+    fn participant_of(&mut self, function: usize) -> usize {
+        let symbols = self.symbols;
+        let function = &symbols.functions[function];
+        let name = impl_type(function).unwrap_or(&function.name);
+        self.participant(name, &function.crate_name)
+    }
+
+    fn step(&mut self, from: usize, to: usize, kind: StepKind, label: String, file_path: &str, line: usize) -> bool {
+        if self.diagram.steps.len() >= MAX_STEPS {
+            self.diagram.truncated = true;
+            return false;
+        }
+        let participants = &self.diagram.participants;
+        let crosses_crate = participants[from].crate_name != participants[to].crate_name;
+        self.diagram.steps.push(SequenceStep { from, to, kind, label, crosses_crate, file_path: file_path.to_string(), line });
+        true
+    }
+
+    /// The `handle` method of `impl Message<M> for Actor`
+    fn handler_function(&self, handler: &MessageHandler) -> Option<usize> {
+        let message = message_name(&handler.message_type);
+        self.symbols.functions.iter().position(|function| {
+            let FunctionContext::TraitImpl { trait_name, .. } = &function.function_context else {
+                return false;
+            };
+            function.name == "handle"
+                && function.file_path == handler.file_path
+                && impl_type(function) == Some(handler.actor_name.as_str())
+                && message_argument(trait_name).is_some_and(|m| m == message)
+        })
+    }
+
+    /// Walk a function body in line order, drawing arrows for sends and calls into other types
+    fn visit(&mut self, function: usize, depth: usize) {
+        if self.stack.contains(&function) {
+            return;
+        }
+        if depth > self.max_depth {
+            self.diagram.truncated = true;
+            return;
+        }
+        self.stack.push(function);
+
+        let symbols = self.symbols;
+        let body: &RustFunction = &symbols.functions[function];
+        let from = self.participant_of(function);
+        let events: Vec<Event> = self.events.get(body.file_path.as_str())
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|event| event.line() > body.line_start && event.line() <= body.line_end)
+            .filter(|event| match event {
+                // Skip events of closures or items nested in another traced function
+                Event::Call(call) => call.caller_id == body.qualified_name,
+                Event::Send(send) => send.sender_qualified.as_deref().is_none_or(|q| q == body.qualified_name),
+            })
+            .collect();
+
+        for event in events {
+            match event {
+                Event::Call(call) => self.follow_call(call, from, depth),
+                Event::Send(send) => self.follow_send(send, from, depth),
+            }
+        }
+        self.stack.pop();
+    }
+
+    fn follow_call(&mut self, call: &FunctionCall, from: usize, depth: usize) {
+        // Only follow calls resolved to a single workspace function; guessing by name
+        // would put arrows in the diagram that never happen
+        let Some(&[target]) = call.qualified_callee.as_deref()
+            .and_then(|q| self.by_qualified.get(q))
+            .map(Vec::as_slice)
+        else {
+            return;
+        };
+        let to = self.participant_of(target);
+        if to != from && !self.step(from, to, StepKind::Call, format!("{}()", call.callee_name), &call.file_path, call.line) {
+            return;
+        }
+        self.visit(target, depth + 1);
+    }
+
+    fn follow_send(&mut self, send: &MessageSend, from: usize, depth: usize) {
+        let message = message_name(&send.message_type);
+        let handlers: Vec<&MessageHandler> = self.handlers.get(&message).cloned().unwrap_or_default();
+        let targeted: Vec<&MessageHandler> = handlers.iter().copied()
+            .filter(|h| h.actor_name == send.receiver_actor)
+            .collect();
+        // An unresolved receiver may be any actor handling the message
+        let receivers = if targeted.is_empty() { handlers } else { targeted };
+
+        let kind = match send.send_method {
+            SendMethod::Tell => StepKind::Tell,
+            SendMethod::Ask => StepKind::Ask,
+        };
+        if receivers.is_empty() {
+            let crate_name = send.to_crate.clone().unwrap_or_else(|| send.from_crate.clone());
+            let to = self.participant(&send.receiver_actor, &crate_name);
+            self.step(from, to, kind, send_label(kind, &send.message_type), &send.file_path, send.line);
+            return;
+        }
+
+        for handler in receivers {
+            let to = self.participant(&handler.actor_name, &handler.crate_name);
+            if !self.step(from, to, kind, send_label(kind, &send.message_type), &send.file_path, send.line) {
+                return;
+            }
+            self.enter_handler(handler, depth);
+            if kind == StepKind::Ask {
+                self.step(to, from, StepKind::Reply, handler.reply_type.clone(), &handler.file_path, handler.line);
+            }
+        }
+    }
+
+    fn enter_handler(&mut self, handler: &MessageHandler, depth: usize) {
+        if let Some(function) = self.handler_function(handler) {
+            self.visit(function, depth + 1);
+        }
+    }
+}
+
+impl SequenceDiagram {
+    /// Trace the flow starting at `entry`, a function (bare, `Type::method` or fully qualified
+    /// name) or a message type whose handlers start the flow. Calls are followed only when
+    /// resolved to one workspace function; sends go to the actor behind the `ActorRef`, or to
+    /// every handler of the message when the receiver is unknown. Returns `None` when nothing
+    /// matches `entry`.
+    pub fn trace(symbols: &ParsedSymbols, entry: &str, max_depth: usize) -> Option<Self> {
+        let mut tracer = Tracer::new(symbols, entry, max_depth);
+
+        let suffix = format!("::{}", entry);
+        let mut functions: Vec<usize> = symbols.functions.iter().enumerate()
+            .filter(|(_, f)| f.qualified_name == entry || f.name == entry || f.qualified_name.ends_with(&suffix))
+            .map(|(idx, _)| idx)
+            .collect();
+        functions.sort_by_key(|&idx| (symbols.functions[idx].is_test, idx));
+        if let Some(&function) = functions.first() {
+            tracer.visit(function, 0);
+            return Some(tracer.diagram);
+        }
+
+        let handlers = tracer.handlers.get(&message_name(entry)).cloned()?;
+        for handler in handlers {
+            let caller = tracer.participant(CALLER, &handler.crate_name);
+            let to = tracer.participant(&handler.actor_name, &handler.crate_name);
+            let kind = if handler.reply_type == "()" { StepKind::Tell } else { StepKind::Ask };
+            tracer.step(caller, to, kind, send_label(kind, &handler.message_type), &handler.file_path, handler.line);
+            if let Some(function) = tracer.handler_function(handler) {
+                tracer.visit(function, 0);
+            }
+            if kind == StepKind::Ask {
+                tracer.step(to, caller, StepKind::Reply, handler.reply_type.clone(), &handler.file_path, handler.line);
+            }
+        }
+        Some(tracer.diagram)
+    }
+
+    pub fn render(&self, format: SequenceFormat) -> String {
+        match format {
+            SequenceFormat::Mermaid => self.to_mermaid(),
+            SequenceFormat::PlantUml => self.to_plantuml(),
+        }
+    }
+
+    /// Participants grouped by crate, in order of first appearance
+    fn crate_groups(&self) -> Vec<(&str, Vec<usize>)> {
+        let mut order: Vec<&str> = Vec::new();
+        let mut groups: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (idx, participant) in self.participants.iter().enumerate() {
+            if !groups.contains_key(participant.crate_name.as_str()) {
+                order.push(&participant.crate_name);
+            }
+            groups.entry(&participant.crate_name).or_default().push(idx);
+        }
+        order.into_iter().map(|name| (name, groups.remove(name).unwrap_or_default())).collect()
+    }
+
+    /// Mermaid `sequenceDiagram`; crates are drawn as boxes when the flow spans several
+    pub fn to_mermaid(&self) -> String {
+        let mut lines = vec!["sequenceDiagram".to_string()];
+        let groups = self.crate_groups();
+        for (crate_name, members) in &groups {
+            if groups.len() > 1 {
+                lines.push(format!("    box {}", crate_name));
+            }
+            for &idx in members {
+                lines.push(format!("    participant P{} as {}", idx, self.participants[idx].name));
+            }
+            if groups.len() > 1 {
+                lines.push("    end".to_string());
+            }
+        }
+        for step in &self.steps {
+            let arrow = match step.kind {
+                StepKind::Call | StepKind::Ask => "->>",
+                StepKind::Tell => "-)",
+                StepKind::Reply => "-->>",
+            };
+            lines.push(format!("    P{}{}P{}: {}", step.from, arrow, step.to, self.step_label(step)));
+        }
+        lines.join("\n")
+    }
+
+    /// PlantUML sequence diagram; crates are drawn as boxes when the flow spans several
+    pub fn to_plantuml(&self) -> String {
+        let mut lines = vec!["@startuml".to_string()];
+        let groups = self.crate_groups();
+        for (crate_name, members) in &groups {
+            if groups.len() > 1 {
+                lines.push(format!("box \"{}\"", crate_name));
+            }
+            for &idx in members {
+                lines.push(format!("participant \"{}\" as P{}", self.participants[idx].name, idx));
+            }
+            if groups.len() > 1 {
+                lines.push("end box".to_string());
+            }
+        }
+        for step in &self.steps {
+            let arrow = match step.kind {
+                StepKind::Call | StepKind::Ask => "->",
+                StepKind::Tell => "->>",
+                StepKind::Reply => "-->",
+            };
+            lines.push(format!("P{} {} P{} : {}", step.from, arrow, step.to, self.step_label(step)));
+        }
+        lines.push("@enduml".to_string());
+        lines.join("\n")
+    }
+
+    fn step_label(&self, step: &SequenceStep) -> String {
+        if step.crosses_crate {
+            let from = &self.participants[step.from].crate_name;
+            let to = &self.participants[step.to].crate_name;
+            format!("{} [{} → {}]", step.label, from, to)
+        } else {
+            step.label.clone()
+        }
+    }
+}
+
+fn message_name(text: &str) -> String {
+    base_type_name(text).unwrap_or_else(|| text.to_string())
+}
+
+/// `PlaceOrder` from a `Message<PlaceOrder>` trait name
+fn message_argument(trait_name: &str) -> Option<String> {
+    let (_, args) = trait_name.split_once('<')?;
+    let args = args.strip_suffix('>')?;
+    base_type_name(args)
+}
+
+fn send_label(kind: StepKind, message: &str) -> String {
+    match kind {
+        StepKind::Tell => format!("tell({})", message),
+        _ => format!("ask({})", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+    use std::path::Path;
+
+    const GATEWAY: &str = r#"
+pub struct Gateway { book: ActorRef<OrderBook> }
+
+impl Gateway {
+    pub async fn submit(&self, qty: u64) -> bool {
+        validate(qty);
+        self.book.ask(PlaceOrder { qty }).await.unwrap()
+    }
+}
+
+fn validate(qty: u64) {}
+"#;
+
+    const ENGINE: &str = r#"
+pub struct OrderBook { risk: ActorRef<RiskEngine>, audit: ActorRef<Auditor> }
+pub struct RiskEngine;
+pub struct Auditor;
+
+impl Message<PlaceOrder> for OrderBook {
+    type Reply = bool;
+    async fn handle(&mut self, msg: PlaceOrder, _ctx: &mut Context<Self, Self::Reply>) -> bool {
+        let ok = self.risk.ask(CheckLimits { qty: msg.qty }).await.unwrap();
+        self.audit.tell(Record).await;
+        ok
+    }
+}
+
+impl Message<CheckLimits> for RiskEngine {
+    type Reply = bool;
+    async fn handle(&mut self, msg: CheckLimits, _ctx: &mut Context<Self, Self::Reply>) -> bool { true }
+}
+
+impl Message<Record> for Auditor {
+    type Reply = ();
+    async fn handle(&mut self, _: Record, _ctx: &mut Context<Self, Self::Reply>) {}
+}
+"#;
+
+    fn symbols() -> ParsedSymbols {
+        let mut parser = RustParser::new().unwrap();
+        let mut symbols = parser.parse_source(GATEWAY, Path::new("gateway/src/lib.rs"), "gateway").unwrap();
+        symbols.merge(parser.parse_source(ENGINE, Path::new("engine/src/lib.rs"), "engine").unwrap());
+        crate::parser::references::resolve_all_references(&mut symbols).unwrap();
+        symbols
+    }
+
+    fn arrows(diagram: &SequenceDiagram) -> Vec<String> {
+        diagram.steps.iter()
+            .map(|s| format!("{} {:?} {}: {}", diagram.participants[s.from].name, s.kind, diagram.participants[s.to].name, s.label))
+            .collect()
+    }
+
+    #[test]
+    fn test_trace_from_function_across_crates() {
+        let diagram = SequenceDiagram::trace(&symbols(), "Gateway::submit", 8).unwrap();
+        assert_eq!(arrows(&diagram), vec![
+            "Gateway Call validate: validate()",
+            "Gateway Ask OrderBook: ask(PlaceOrder)",
+            "OrderBook Ask RiskEngine: ask(CheckLimits)",
+            "RiskEngine Reply OrderBook: bool",
+            "OrderBook Tell Auditor: tell(Record)",
+            "OrderBook Reply Gateway: bool",
+        ]);
+        assert!(diagram.steps[1].crosses_crate);
+        assert!(!diagram.steps[2].crosses_crate);
+
+        let mermaid = diagram.render(SequenceFormat::Mermaid);
+        assert!(mermaid.contains("    box gateway"));
+        assert!(mermaid.contains("P0->>P2: ask(PlaceOrder) [gateway → engine]"));
+        assert!(mermaid.contains("-)"));
+        let plantuml = diagram.render(SequenceFormat::PlantUml);
+        assert!(plantuml.starts_with("@startuml") && plantuml.ends_with("@enduml"));
+        assert!(plantuml.contains("box \"engine\""));
+    }
+
+    #[test]
+    fn test_trace_from_message_and_depth_limit() {
+        let symbols = symbols();
+        let diagram = SequenceDiagram::trace(&symbols, "PlaceOrder", 0).unwrap();
+        assert_eq!(arrows(&diagram), vec![
+            "Caller Ask OrderBook: ask(PlaceOrder)",
+            "OrderBook Ask RiskEngine: ask(CheckLimits)",
+            "RiskEngine Reply OrderBook: bool",
+            "OrderBook Tell Auditor: tell(Record)",
+            "OrderBook Reply Caller: bool",
+        ]);
+        assert!(diagram.truncated, "handlers asked from the entry handler are not entered");
+
+        assert!(SequenceDiagram::trace(&symbols, "NoSuchThing", 8).is_none());
+    }
+}
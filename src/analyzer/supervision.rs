@@ -0,0 +1,329 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::graph::SupervisionEdge;
+
+/// What a supervisor does when a linked child dies abnormally
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureOutcome {
+    /// `on_link_died` spawns a replacement
+    Restart,
+    /// `on_link_died` breaks (or is kameo's default), so the supervisor stops too
+    CascadingStop,
+    /// `on_link_died` continues; the supervisor keeps running without the child
+    Ignored,
+    /// `on_link_died` decides at runtime, e.g. by matching on the stop reason
+    Conditional,
+}
+
+impl FailureOutcome {
+    /// Outcome for a supervisor whose `on_link_died` behaves as `hook` (`None` = not overridden)
+    fn from_hook(hook: Option<&str>) -> Self {
+        match hook {
+            Some("restart") => FailureOutcome::Restart,
+            Some("continue") => FailureOutcome::Ignored,
+            Some("conditional") => FailureOutcome::Conditional,
+            // Kameo's default `on_link_died` stops on any abnormal death
+            _ => FailureOutcome::CascadingStop,
+        }
+    }
+
+    fn describe(&self, supervisor: &str) -> String {
+        match self {
+            FailureOutcome::Restart => "↻ restarted".to_string(),
+            FailureOutcome::CascadingStop => format!("✖ failure stops {}", supervisor),
+            FailureOutcome::Ignored => "∅ failure ignored".to_string(),
+            FailureOutcome::Conditional => "? depends on stop reason".to_string(),
+        }
+    }
+}
+
+/// One actor in the supervision tree
+#[derive(Debug, Clone, Serialize)]
+pub struct SupervisionNode {
+    pub actor: String,
+    pub crate_name: String,
+    /// `spawn_link` or `link`; empty for roots
+    pub link: String,
+    /// This actor's `on_link_died` behaviour, `"default"` when not overridden
+    pub on_link_died: String,
+    /// This actor's `on_panic` behaviour when known, `"default"` when not overridden
+    pub on_panic: Option<String>,
+    /// What the parent does when this actor fails; `None` for roots
+    pub on_failure: Option<FailureOutcome>,
+    /// Ancestors that stop, nearest first, when this actor fails
+    pub cascades_to: Vec<String>,
+    /// Whether this actor stops when its parent stops abnormally
+    pub stops_with_parent: bool,
+    /// Children that are restarted when they fail
+    pub restarted_children: Vec<String>,
+    /// Children whose failure stops this actor
+    pub cascading_children: Vec<String>,
+    /// Set when this actor already appears higher up the same branch
+    pub cycle: bool,
+    pub line: usize,
+    pub file_path: String,
+    pub children: Vec<SupervisionNode>,
+}
+
+/// Supervision hierarchy rebuilt from SUPERVISES edges
+#[derive(Debug, Clone, Serialize)]
+pub struct SupervisionTree {
+    pub roots: Vec<SupervisionNode>,
+    pub actor_count: usize,
+    pub link_count: usize,
+}
+
+type ActorKey = (String, String);
+
+impl SupervisionTree {
+    /// Build the forest of supervision trees. Actors that never appear as a child are roots;
+    /// actors only reachable through cycles are rooted at their alphabetically first member.
+    pub fn from_edges(edges: &[SupervisionEdge]) -> Self {
+        let mut children: BTreeMap<ActorKey, Vec<&SupervisionEdge>> = BTreeMap::new();
+        let mut on_link_died: BTreeMap<ActorKey, Option<String>> = BTreeMap::new();
+        let mut on_panic: BTreeMap<ActorKey, Option<String>> = BTreeMap::new();
+        let mut seen_links = HashSet::new();
+        let mut child_keys = BTreeSet::new();
+
+        for edge in edges {
+            let supervisor = (edge.supervisor.clone(), edge.supervisor_crate.clone());
+            let child = (edge.child.clone(), edge.child_crate.clone());
+            on_link_died.entry(supervisor.clone()).or_insert_with(|| edge.supervisor_on_link_died.clone());
+            on_link_died.entry(child.clone()).or_insert_with(|| edge.child_on_link_died.clone());
+            on_panic.entry(child.clone()).or_insert_with(|| edge.child_on_panic.clone());
+            if supervisor == child || !seen_links.insert((supervisor.clone(), child.clone())) {
+                continue;
+            }
+            child_keys.insert(child);
+            children.entry(supervisor).or_default().push(edge);
+        }
+
+        let builder = TreeBuilder { children: &children, on_link_died: &on_link_died, on_panic: &on_panic };
+        let mut placed = HashSet::new();
+        let mut roots = Vec::new();
+        let root_keys = children.keys()
+            .filter(|key| !child_keys.contains(*key))
+            .chain(children.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in root_keys {
+            if placed.contains(&key) {
+                continue;
+            }
+            let mut path = Vec::new();
+            let root = builder.node(&key, None, &mut path, &mut placed);
+            roots.push(root);
+        }
+
+        SupervisionTree {
+            roots,
+            actor_count: on_link_died.len(),
+            link_count: seen_links.len(),
+        }
+    }
+
+    /// Keep only the trees containing `actor`, re-rooted at it
+    pub fn focus(mut self, actor: &str) -> Self {
+        fn find(node: SupervisionNode, actor: &str) -> Option<SupervisionNode> {
+            if node.actor == actor {
+                return Some(node);
+            }
+            node.children.into_iter().find_map(|child| find(child, actor))
+        }
+        self.roots = self.roots.into_iter().filter_map(|root| find(root, actor)).collect();
+        self
+    }
+
+    /// Drop trees that contain no actor from `crate_name`
+    pub fn retain_crate(mut self, crate_name: &str) -> Self {
+        fn mentions(node: &SupervisionNode, crate_name: &str) -> bool {
+            node.crate_name == crate_name || node.children.iter().any(|c| mentions(c, crate_name))
+        }
+        self.roots.retain(|root| mentions(root, crate_name));
+        self
+    }
+
+    /// Text rendering with box-drawing branches, one actor per line
+    pub fn render(&self) -> String {
+        fn render_node(node: &SupervisionNode, prefix: &str, last: bool, parent: Option<&str>, out: &mut Vec<String>) {
+            let mut line = match parent {
+                Some(_) => format!("{}{}{} [{}]", prefix, if last { "└── " } else { "├── " }, node.actor, node.link),
+                None => format!("{} ({})", node.actor, node.crate_name),
+            };
+            if node.on_link_died != "default" {
+                line.push_str(&format!(" on_link_died={}", node.on_link_died));
+            }
+            if let (Some(outcome), Some(parent)) = (node.on_failure, parent) {
+                line.push_str(&format!(" {}", outcome.describe(parent)));
+                if node.cascades_to.len() > 1 {
+                    line.push_str(&format!(" → {}", node.cascades_to[1..].join(" → ")));
+                }
+            }
+            if node.on_panic.as_deref() == Some("continue") {
+                line.push_str(" (survives panics)");
+            }
+            if node.cycle {
+                line.push_str(" (cycle)");
+            }
+            out.push(line);
+
+            let child_prefix = match parent {
+                Some(_) => format!("{}{}", prefix, if last { "    " } else { "│   " }),
+                None => prefix.to_string(),
+            };
+            for (idx, child) in node.children.iter().enumerate() {
+                render_node(child, &child_prefix, idx + 1 == node.children.len(), Some(&node.actor), out);
+            }
+        }
+
+        let mut out = Vec::new();
+        for root in &self.roots {
+            render_node(root, "", true, None, &mut out);
+        }
+        out.join("\n")
+    }
+}
+
+struct TreeBuilder<'a> {
+    children: &'a BTreeMap<ActorKey, Vec<&'a SupervisionEdge>>,
+    on_link_died: &'a BTreeMap<ActorKey, Option<String>>,
+    on_panic: &'a BTreeMap<ActorKey, Option<String>>,
+}
+
+impl TreeBuilder<'_> {
+    /// `path` holds the ancestors of `key` with the outcome of each one's failure for its own parent
+    fn node(
+        &self,
+        key: &ActorKey,
+        via: Option<&SupervisionEdge>,
+        path: &mut Vec<(String, Option<FailureOutcome>)>,
+        placed: &mut HashSet<ActorKey>,
+    ) -> SupervisionNode {
+        let hook = |map: &BTreeMap<ActorKey, Option<String>>| {
+            map.get(key).cloned().flatten().unwrap_or_else(|| "default".to_string())
+        };
+        let on_failure = via.map(|edge| FailureOutcome::from_hook(edge.supervisor_on_link_died.as_deref()));
+
+        // A failure climbs the branch for as long as each supervisor stops in turn
+        let mut cascades_to = Vec::new();
+        if on_failure == Some(FailureOutcome::CascadingStop) {
+            for (ancestor, outcome) in path.iter().rev() {
+                cascades_to.push(ancestor.clone());
+                if *outcome != Some(FailureOutcome::CascadingStop) {
+                    break;
+                }
+            }
+        }
+
+        let cycle = via.is_some() && path.iter().any(|(ancestor, _)| *ancestor == key.0);
+        let mut node = SupervisionNode {
+            actor: key.0.clone(),
+            crate_name: key.1.clone(),
+            link: via.map(|e| e.kind.clone()).unwrap_or_default(),
+            on_link_died: hook(self.on_link_died),
+            on_panic: via.map(|_| hook(self.on_panic)),
+            on_failure,
+            cascades_to,
+            stops_with_parent: via.is_some_and(|e| {
+                FailureOutcome::from_hook(e.child_on_link_died.as_deref()) == FailureOutcome::CascadingStop
+            }),
+            restarted_children: Vec::new(),
+            cascading_children: Vec::new(),
+            cycle,
+            line: via.map(|e| e.line).unwrap_or(0),
+            file_path: via.map(|e| e.file_path.clone()).unwrap_or_default(),
+            children: Vec::new(),
+        };
+        // Actors already expanded under another supervisor are listed without their subtree
+        if cycle || !placed.insert(key.clone()) {
+            return node;
+        }
+
+        path.push((key.0.clone(), on_failure));
+        for edge in self.children.get(key).into_iter().flatten() {
+            let child_key = (edge.child.clone(), edge.child_crate.clone());
+            let child = self.node(&child_key, Some(edge), path, placed);
+            match child.on_failure {
+                Some(FailureOutcome::Restart) => node.restarted_children.push(child.actor.clone()),
+                Some(FailureOutcome::CascadingStop) => node.cascading_children.push(child.actor.clone()),
+                _ => {}
+            }
+            node.children.push(child);
+        }
+        path.pop();
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Edge between two actors of crate `app`; `on_link_died` lists the overridden hooks
+    fn edge(supervisor: &str, child: &str, on_link_died: &[(&str, &str)]) -> SupervisionEdge {
+        let hook = |actor: &str| on_link_died.iter().find(|(a, _)| *a == actor).map(|(_, h)| h.to_string());
+        SupervisionEdge {
+            supervisor: supervisor.to_string(),
+            supervisor_crate: "app".to_string(),
+            child: child.to_string(),
+            child_crate: "app".to_string(),
+            kind: "spawn_link".to_string(),
+            supervisor_on_link_died: hook(supervisor),
+            child_on_link_died: hook(child),
+            child_on_panic: None,
+            line: 10,
+            file_path: "app/src/lib.rs".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_restarts_and_cascading_stops() {
+        let hooks = [("Supervisor", "restart")];
+        let edges = vec![
+            edge("Root", "Supervisor", &hooks),
+            edge("Supervisor", "Worker", &hooks),
+            edge("Supervisor", "Cache", &hooks),
+            edge("Cache", "Loader", &hooks),
+        ];
+        let tree = SupervisionTree::from_edges(&edges);
+
+        assert_eq!(tree.roots.len(), 1);
+        assert_eq!(tree.actor_count, 5);
+        let root = &tree.roots[0];
+        assert_eq!(root.actor, "Root");
+        assert_eq!(root.cascading_children, vec!["Supervisor"]);
+
+        let supervisor = &root.children[0];
+        assert_eq!(supervisor.on_link_died, "restart");
+        assert_eq!(supervisor.restarted_children, vec!["Worker", "Cache"]);
+
+        // Loader's failure stops Cache, which the supervisor then restarts
+        let loader = &supervisor.children[1].children[0];
+        assert_eq!(loader.on_failure, Some(FailureOutcome::CascadingStop));
+        assert_eq!(loader.cascades_to, vec!["Cache"]);
+        assert_eq!(supervisor.cascades_to, vec!["Root"]);
+        assert!(loader.stops_with_parent);
+
+        let rendered = tree.render();
+        assert!(rendered.contains("├── Worker [spawn_link] ↻ restarted"));
+        assert!(rendered.contains("└── Loader [spawn_link] ✖ failure stops Cache"));
+    }
+
+    #[test]
+    fn test_cycles_and_focus() {
+        let hooks = [("A", "continue")];
+        let edges = vec![edge("A", "B", &hooks), edge("B", "A", &hooks)];
+        let tree = SupervisionTree::from_edges(&edges);
+
+        assert_eq!(tree.roots.len(), 1);
+        let b = &tree.roots[0].children[0];
+        assert_eq!(b.on_failure, Some(FailureOutcome::Ignored));
+        assert!(b.children[0].cycle);
+        assert!(b.children[0].children.is_empty());
+
+        let focused = SupervisionTree::from_edges(&edges).focus("B");
+        assert_eq!(focused.roots[0].actor, "B");
+    }
+}
@@ -1,13 +1,23 @@
 use anyhow::Result;
+use rayon::prelude::*;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-use crate::config::Config;
-use crate::parser::{RustParser, ParsedSymbols};
+use crate::config::{CfgMode, Config};
+use crate::parser::cfg::{filter_inactive, ActiveCfg};
+use crate::parser::{expand_crate_source, expand_declarative_macros, merge_expanded_symbols, ActorFrameworks, MacroExpander, ModuleTree, RustParser, ParsedSymbols};
 pub use crate::parser::{RustFunction, RustType};
 use crate::workspace::{WorkspaceDiscovery, CrateMetadata};
 use crate::analyzer::{GlobalSymbolIndex, CrateFunctionInfo, CrateTypeInfo, CrateTraitInfo, CrateExports, Visibility, TypeKind, TraitMethodInfo};
 
+thread_local! {
+    // One RustParser per parsing worker; tree-sitter parsers cannot be shared across threads
+    // and building one compiles every query, so each worker keeps its own.
+    static THREAD_PARSER: RefCell<Option<RustParser>> = const { RefCell::new(None) };
+}
+
 #[derive(Debug, Clone)]
 pub struct WorkspaceSnapshot {
     pub crates: Vec<CrateMetadata>,
@@ -27,6 +37,8 @@ pub struct WorkspaceAnalyzer {
     parser: RustParser,
     workspace_discovery: WorkspaceDiscovery,
     global_index: Option<GlobalSymbolIndex>,
+    /// Built on first use and kept, so parsing crate by crate reuses the workers and their parsers
+    parse_pool: OnceLock<rayon::ThreadPool>,
 }
 
 impl WorkspaceAnalyzer {
@@ -40,6 +52,7 @@ impl WorkspaceAnalyzer {
             parser,
             workspace_discovery,
             global_index: None,
+            parse_pool: OnceLock::new(),
         })
     }
 
@@ -52,6 +65,7 @@ impl WorkspaceAnalyzer {
             parser,
             workspace_discovery,
             global_index: None,
+            parse_pool: OnceLock::new(),
         })
     }
 
@@ -73,22 +87,29 @@ impl WorkspaceAnalyzer {
         let mut all_distributed_actors = Vec::new();
         let mut all_distributed_message_flows = Vec::new();
 
-        for crate_meta in &crates_to_analyze {
-            if let Ok(mut parsed) = self.parse_crate_files_internal(&crate_meta.path, &crate_meta.name) {
-                // Resolve references and generate synthetic trait method calls before merging
+        let pool = self.parse_pool()?;
+        let mut parsed_crates = self.parse_crates_parallel(pool, &crates_to_analyze);
+        let expander = Self::macro_expander(&parsed_crates);
+
+        // Expand macros, resolve references and generate synthetic trait method calls before merging
+        pool.install(|| {
+            parsed_crates.par_iter_mut().zip(crates_to_analyze.par_iter()).for_each(|(parsed, crate_meta)| {
+                expander.expand_into(parsed);
                 eprintln!("  🔗 Resolving references for crate: {}", crate_meta.name);
-                crate::parser::references::resolve_all_references(&mut parsed).unwrap_or_else(|e| {
+                crate::parser::references::resolve_all_references(parsed).unwrap_or_else(|e| {
                     eprintln!("  ⚠️ WARNING: Reference resolution failed for {}: {}", crate_meta.name, e);
                 });
-                
-                all_functions.extend(parsed.functions.clone());
-                all_types.extend(parsed.types.clone());
-                all_actors.extend(parsed.actors.clone());
-                all_actor_spawns.extend(parsed.actor_spawns.clone());
-                all_distributed_actors.extend(parsed.distributed_actors.clone());
-                all_distributed_message_flows.extend(parsed.distributed_message_flows.clone());
-                symbols.insert(crate_meta.name.clone(), parsed);
-            }
+            });
+        });
+
+        for (crate_meta, parsed) in crates_to_analyze.iter().zip(parsed_crates) {
+            all_functions.extend(parsed.functions.clone());
+            all_types.extend(parsed.types.clone());
+            all_actors.extend(parsed.actors.clone());
+            all_actor_spawns.extend(parsed.actor_spawns.clone());
+            all_distributed_actors.extend(parsed.distributed_actors.clone());
+            all_distributed_message_flows.extend(parsed.distributed_message_flows.clone());
+            symbols.insert(crate_meta.name.clone(), parsed);
         }
 
         Ok(WorkspaceSnapshot {
@@ -105,6 +126,13 @@ impl WorkspaceAnalyzer {
         })
     }
 
+    /// Macro expander over the `macro_rules!` definitions of every parsed crate, so that
+    /// exported macros expand in the crates using them
+    fn macro_expander(parsed_crates: &[ParsedSymbols]) -> MacroExpander {
+        let definitions: Vec<_> = parsed_crates.iter().flat_map(|parsed| parsed.macro_definitions.iter().cloned()).collect();
+        MacroExpander::new(&definitions)
+    }
+
     pub fn analyze_workspace(&mut self) -> Result<WorkspaceSnapshot> {
         // For synchronous version, we'll use a simple blocking approach
         // This is a placeholder - in a real implementation you might want to use tokio::task::block_in_place
@@ -114,7 +142,7 @@ impl WorkspaceAnalyzer {
     // New method to parse and populate graph (for MCP use)
     pub async fn analyze_and_populate_graph(
         &mut self,
-        graph: Option<&crate::graph::MemgraphClient>,
+        graph: Option<&dyn crate::graph::GraphBackend>,
         embedding_gen: Option<&crate::embeddings::EmbeddingGenerator>,
         architecture: Option<&crate::architecture::ArchitectureAnalyzer>,
         semantic_search: Option<&mut crate::embeddings::SemanticSearch>,
@@ -138,27 +166,28 @@ impl WorkspaceAnalyzer {
             eprintln!("  ✅ Graph client available, creating crate nodes");
             graph.create_crate_nodes(&crates_to_analyze).await?;
         } else {
-            eprintln!("  ⚠️ WARNING: Graph client is None - no data will be written to Memgraph!");
+            eprintln!("  ⚠️ WARNING: Graph client is None - no data will be written to the graph!");
         }
         eprintln!("  ⏱️ Graph node creation: {:?}", graph_timer.elapsed());
 
         // 3. Parse all files using existing parser
         let parse_timer = std::time::Instant::now();
         let mut all_symbols = ParsedSymbols::new();
-        let total_files = crates_to_analyze.len();
-        for (i, crate_meta) in crates_to_analyze.iter().enumerate() {
-            eprintln!("  📦 Parsing crate {}/{}: {}", i+1, total_files, crate_meta.name);
-            let crate_timer = std::time::Instant::now();
-            if let Ok(parsed) = self.parse_crate_files_internal(&crate_meta.path, &crate_meta.name) {
-                eprintln!("    ⏱️ {} parsed in {:?} ({} files)", 
-                    crate_meta.name, 
-                    crate_timer.elapsed(),
-                    parsed.functions.len());
-                all_symbols.merge(parsed);
-            }
+        let pool = self.parse_pool()?;
+        eprintln!("  📦 Parsing {} crates on {} threads", crates_to_analyze.len(), pool.current_num_threads());
+        let parsed_crates = self.parse_crates_parallel(pool, &crates_to_analyze);
+        for (crate_meta, parsed) in crates_to_analyze.iter().zip(parsed_crates) {
+            eprintln!("    📦 {}: {} functions", crate_meta.name, parsed.functions.len());
+            all_symbols.merge(parsed);
         }
         eprintln!("  ⏱️ Total parsing time: {:?}", parse_timer.elapsed());
 
+        // 3b. Expand macro_rules! invocations so the code they generate is resolved like any other
+        expand_declarative_macros(&mut all_symbols);
+        if let Some(updater) = incremental_updater {
+            updater.set_macro_definitions(all_symbols.macro_definitions.clone());
+        }
+
         // 4. Resolve references and generate synthetic calls (including trait methods)
         let resolve_timer = std::time::Instant::now();
         eprintln!("  🔗 Resolving references and generating synthetic calls...");
@@ -170,7 +199,7 @@ impl WorkspaceAnalyzer {
         // synthetic calls to ALL functions in the same crate with the extracted method name.
         // This removes the need for per-indicator edge cases (from_ohlcv, new, na, nz, etc.).
         {
-            use crate::parser::symbols::{FunctionCall, CallType, MacroContext};
+            use crate::parser::symbols::{FunctionCall, CallType, CallProvenance, MacroContext};
 
             // Build name -> functions map per crate
             let mut functions_by_crate_and_name: HashMap<(String, String), Vec<&crate::parser::symbols::RustFunction>> = HashMap::new();
@@ -247,6 +276,9 @@ impl WorkspaceAnalyzer {
                                     kind: "expansion".to_string(),
                                 }),
                                 synthetic_confidence: 0.9,
+                                provenance: CallProvenance::TreeSitter,
+                                receiver_type: None,
+                                cfg: None,
                             });
                         }
                     }
@@ -276,11 +308,14 @@ impl WorkspaceAnalyzer {
             graph.verify_population().await?;
             eprintln!("  ⏱️ Graph population: {:?}", populate_timer.elapsed());
         } else {
-            eprintln!("  ⚠️ WARNING: Graph client is None - symbols not written to Memgraph!");
+            eprintln!("  ⚠️ WARNING: Graph client is None - symbols not written to the graph!");
         }
 
-        // 7. Index for semantic search (handled separately as it requires embeddings, not raw symbols)
-        // Note: Semantic search indexing should be done after embeddings are generated
+        // 7. Index the generated vectors for semantic search
+        if let (Some(embedding_gen), Some(semantic_search)) = (embedding_gen, semantic_search) {
+            semantic_search.index_function_embeddings(&embedding_gen.extract_function_embeddings(&all_symbols.functions));
+            semantic_search.index_type_embeddings(&embedding_gen.extract_type_embeddings(&all_symbols.types));
+        }
 
         // 8. Run architecture analysis if provided
         let arch_timer = std::time::Instant::now();
@@ -306,14 +341,143 @@ impl WorkspaceAnalyzer {
     }
 
     fn parse_crate_files_internal(&mut self, crate_path: &Path, crate_name: &str) -> Result<ParsedSymbols> {
-        let mut symbols = ParsedSymbols::new();
+        let pool = self.parse_pool()?;
+        let files: Vec<(usize, PathBuf)> = Self::collect_rust_files(crate_path)
+            .into_iter()
+            .map(|file| (0, file))
+            .collect();
+
+        let crate_meta = self.workspace_discovery.get_discovered_crates().get(crate_name);
+        let frameworks = crate_meta.map(ActorFrameworks::for_crate).unwrap_or_default();
+        let default_features = crate_meta.map(|crate_meta| crate_meta.features.clone()).unwrap_or_default();
+
+        let tree = ModuleTree::build(crate_name, crate_path, crate_meta.map(|crate_meta| crate_meta.targets.as_slice()).unwrap_or_default());
+
+        let mut symbols = Self::parse_files_parallel(pool, &files, &[crate_name], std::slice::from_ref(&frameworks), std::slice::from_ref(&tree)).pop().unwrap_or_default();
+        if self.config.cfg.mode == CfgMode::Filter {
+            filter_inactive(&mut symbols, &ActiveCfg::for_crate_name(&self.config.cfg, crate_name, &default_features));
+        }
+        self.merge_expanded_source(crate_path, crate_name, &frameworks, &mut symbols);
+        Ok(symbols)
+    }
+
+    /// Thread pool for parsing, sized by `[performance] max_threads` (0 = one thread per core)
+    fn parse_pool(&self) -> Result<&rayon::ThreadPool> {
+        if let Some(pool) = self.parse_pool.get() {
+            return Ok(pool);
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.config.performance.max_threads)
+            .thread_name(|i| format!("rust-parser-{}", i))
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build parser thread pool: {}", e))?;
+        Ok(self.parse_pool.get_or_init(|| pool))
+    }
+
+    /// Parse every source file of `crates` in parallel and return one `ParsedSymbols` per crate,
+    /// in the same order as `crates`.
+    ///
+    /// Files are spread across the whole pool (not one crate per thread) so a single large crate
+    /// does not serialize the run. Each crate's files are merged in sorted path order, which keeps
+    /// the result independent of thread scheduling.
+    fn parse_crates_parallel(&self, pool: &rayon::ThreadPool, crates: &[CrateMetadata]) -> Vec<ParsedSymbols> {
+        let files: Vec<(usize, PathBuf)> = crates
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, crate_meta)| {
+                Self::collect_rust_files(&crate_meta.path)
+                    .into_iter()
+                    .map(move |file| (idx, file))
+            })
+            .collect();
+        let crate_names: Vec<&str> = crates.iter().map(|c| c.name.as_str()).collect();
+        let frameworks: Vec<ActorFrameworks> = crates.iter().map(ActorFrameworks::for_crate).collect();
+        let trees: Vec<ModuleTree> = pool.install(|| crates.par_iter().map(ModuleTree::for_crate).collect());
+
+        let mut parsed = Self::parse_files_parallel(pool, &files, &crate_names, &frameworks, &trees);
+        if self.config.cfg.mode == CfgMode::Filter {
+            for (symbols, crate_meta) in parsed.iter_mut().zip(crates) {
+                filter_inactive(symbols, &ActiveCfg::for_crate(&self.config.cfg, crate_meta));
+            }
+        }
+        // Expansion builds the crate, so crates are expanded one at a time rather than on the pool
+        for ((symbols, crate_meta), frameworks) in parsed.iter_mut().zip(crates).zip(&frameworks) {
+            self.merge_expanded_source(&crate_meta.path, &crate_meta.name, frameworks, symbols);
+        }
+        parsed
+    }
+
+    /// For crates listed under `[expand]`, parse the macro-expanded source and fold the impls
+    /// and calls generated by proc macros into `symbols`. Any failure keeps the normal parse.
+    fn merge_expanded_source(&self, crate_path: &Path, crate_name: &str, frameworks: &ActorFrameworks, symbols: &mut ParsedSymbols) {
+        if !self.config.expand.crates.iter().any(|name| name == crate_name) {
+            return;
+        }
+        let expanded = expand_crate_source(crate_path, self.config.expand.command).and_then(|source| {
+            let mut parser = RustParser::new()?;
+            parser.parse_source_with_frameworks(&source, &crate_path.join("src/lib.rs"), crate_name, frameworks)
+        });
+        match expanded {
+            Ok(expanded) => {
+                let added = merge_expanded_symbols(symbols, expanded);
+                eprintln!("    🧩 {}: {} functions generated by macros", crate_name, added);
+            }
+            Err(e) => {
+                eprintln!("  ⚠️ WARNING: Macro expansion failed for {}, using the unexpanded source: {}", crate_name, e);
+            }
+        }
+    }
+
+    fn parse_files_parallel(
+        pool: &rayon::ThreadPool,
+        files: &[(usize, PathBuf)],
+        crate_names: &[&str],
+        frameworks: &[ActorFrameworks],
+        trees: &[ModuleTree],
+    ) -> Vec<ParsedSymbols> {
+        let parsed_files: Vec<(usize, Option<ParsedSymbols>)> = pool.install(|| {
+            files
+                .par_iter()
+                .map(|(idx, file_path)| {
+                    // Files that fail to parse are skipped; the rest of the crate still counts
+                    let symbols = THREAD_PARSER.with(|cell| {
+                        let mut slot = cell.borrow_mut();
+                        if slot.is_none() {
+                            *slot = RustParser::new().ok();
+                        }
+                        let module_path = trees[*idx].module_path(file_path);
+                        slot.as_mut()?.parse_file_in_module(file_path, crate_names[*idx], &frameworks[*idx], &module_path).ok()
+                    });
+                    (*idx, symbols)
+                })
+                .collect()
+        });
+
+        // `collect` preserves input order, so merging here is deterministic
+        let mut per_crate: Vec<ParsedSymbols> = trees.iter()
+            .map(|tree| {
+                let mut symbols = ParsedSymbols::new();
+                symbols.modules.extend_from_slice(tree.modules());
+                symbols.reexports.extend_from_slice(tree.reexports());
+                symbols
+            })
+            .collect();
+        for (idx, symbols) in parsed_files {
+            if let Some(symbols) = symbols {
+                per_crate[idx].merge(symbols);
+            }
+        }
+        per_crate
+    }
+
+    /// All `.rs` files under `<crate>/src`, sorted by path
+    fn collect_rust_files(crate_path: &Path) -> Vec<PathBuf> {
         let src_dir = crate_path.join("src");
-        
         if !src_dir.exists() {
-            return Ok(symbols);
+            return Vec::new();
         }
 
-        let walker = walkdir::WalkDir::new(&src_dir)
+        let mut files: Vec<PathBuf> = walkdir::WalkDir::new(&src_dir)
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|entry| {
@@ -321,30 +485,11 @@ impl WorkspaceAnalyzer {
                     .and_then(|ext| ext.to_str())
                     .map(|ext| ext == "rs")
                     .unwrap_or(false)
-            });
-
-        for entry in walker {
-            let file_path = entry.path();
-            
-            // Skip known problematic files that cause segfaults
-            let file_name = file_path.file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("");
-            
-            // Note: Previously problematic files now handled by robust error handling in parser
-            // No longer need to skip entire files - the parser will handle crashes gracefully
-            
-            match self.parser.parse_file(file_path, crate_name) {
-                Ok(file_symbols) => {
-                    symbols.merge(file_symbols)
-                },
-                Err(_e) => {
-                    // Continue parsing other files
-                }
-            }
-        }
-
-        Ok(symbols)
+            })
+            .map(|entry| entry.into_path())
+            .collect();
+        files.sort();
+        files
     }
 
     /// Build global symbol index for cross-crate resolution
@@ -380,35 +525,35 @@ impl WorkspaceAnalyzer {
 
         eprintln!("  📦 Indexing {} crates...", crates_to_analyze.len());
 
-        for (i, crate_meta) in crates_to_analyze.iter().enumerate() {
+        let pool = self.parse_pool()?;
+        let parsed_crates = self.parse_crates_parallel(pool, &crates_to_analyze);
+
+        for (i, (crate_meta, parsed)) in crates_to_analyze.iter().zip(parsed_crates).enumerate() {
             eprintln!("    {}/{}: {}", i + 1, crates_to_analyze.len(), crate_meta.name);
             
-            // Parse crate symbols
-            if let Ok(parsed) = self.parse_crate_files_internal(&crate_meta.path, &crate_meta.name) {
-                // Extract function information
-                for function in &parsed.functions {
-                    let func_info = self.convert_to_crate_function_info(function, &crate_meta.name)?;
-                    index.add_function(func_info);
-                }
+            // Extract function information
+            for function in &parsed.functions {
+                let func_info = self.convert_to_crate_function_info(function, &crate_meta.name)?;
+                index.add_function(func_info);
+            }
 
-                // Extract type information
-                for rust_type in &parsed.types {
-                    let type_info = self.convert_to_crate_type_info(rust_type, &crate_meta.name, &parsed)?;
-                    index.add_type(type_info);
-                }
+            // Extract type information
+            for rust_type in &parsed.types {
+                let type_info = self.convert_to_crate_type_info(rust_type, &crate_meta.name, &parsed)?;
+                index.add_type(type_info);
+            }
 
-                // Extract trait information from types with TypeKind::Trait
-                for rust_type in &parsed.types {
-                    if matches!(rust_type.kind, crate::parser::symbols::TypeKind::Trait) {
-                        let trait_info = self.convert_to_crate_trait_info(rust_type, &crate_meta.name, &parsed)?;
-                        index.add_trait(trait_info);
-                    }
+            // Extract trait information from types with TypeKind::Trait
+            for rust_type in &parsed.types {
+                if matches!(rust_type.kind, crate::parser::symbols::TypeKind::Trait) {
+                    let trait_info = self.convert_to_crate_trait_info(rust_type, &crate_meta.name, &parsed)?;
+                    index.add_trait(trait_info);
                 }
-
-                // Build crate exports
-                let exports = self.build_crate_exports(&crate_meta.name, &parsed)?;
-                index.add_crate_exports(exports);
             }
+
+            // Build crate exports
+            let exports = self.build_crate_exports(&crate_meta.name, &parsed)?;
+            index.add_crate_exports(exports);
         }
 
         let stats = index.stats();
@@ -622,28 +767,32 @@ impl WorkspaceAnalyzer {
         let mut all_distributed_actors = Vec::new();
         let mut all_distributed_message_flows = Vec::new();
 
-        for crate_meta in &crates_to_analyze {
-            if let Ok(mut parsed) = self.parse_crate_files_internal(&crate_meta.path, &crate_meta.name) {
-                // Enhanced reference resolution with global context
-                eprintln!("  🔗 Resolving references for crate: {} (with global context)", crate_meta.name);
-                
-                // First run standard reference resolution
-                crate::parser::references::resolve_all_references(&mut parsed).unwrap_or_else(|e| {
-                    eprintln!("  ⚠️ WARNING: Reference resolution failed for {}: {}", crate_meta.name, e);
-                });
-                
-                // Then apply framework knowledge and global context resolution
-                self.apply_framework_knowledge(&mut parsed).await?;
-                self.apply_cross_crate_resolution(&mut parsed).await?;
-                
-                all_functions.extend(parsed.functions.clone());
-                all_types.extend(parsed.types.clone());
-                all_actors.extend(parsed.actors.clone());
-                all_actor_spawns.extend(parsed.actor_spawns.clone());
-                all_distributed_actors.extend(parsed.distributed_actors.clone());
-                all_distributed_message_flows.extend(parsed.distributed_message_flows.clone());
-                symbols.insert(crate_meta.name.clone(), parsed);
-            }
+        let pool = self.parse_pool()?;
+        let parsed_crates = self.parse_crates_parallel(pool, &crates_to_analyze);
+        let expander = Self::macro_expander(&parsed_crates);
+
+        for (crate_meta, mut parsed) in crates_to_analyze.iter().zip(parsed_crates) {
+            expander.expand_into(&mut parsed);
+
+            // Enhanced reference resolution with global context
+            eprintln!("  🔗 Resolving references for crate: {} (with global context)", crate_meta.name);
+            
+            // First run standard reference resolution
+            crate::parser::references::resolve_all_references(&mut parsed).unwrap_or_else(|e| {
+                eprintln!("  ⚠️ WARNING: Reference resolution failed for {}: {}", crate_meta.name, e);
+            });
+            
+            // Then apply framework knowledge and global context resolution
+            self.apply_framework_knowledge(&mut parsed).await?;
+            self.apply_cross_crate_resolution(&mut parsed).await?;
+            
+            all_functions.extend(parsed.functions.clone());
+            all_types.extend(parsed.types.clone());
+            all_actors.extend(parsed.actors.clone());
+            all_actor_spawns.extend(parsed.actor_spawns.clone());
+            all_distributed_actors.extend(parsed.distributed_actors.clone());
+            all_distributed_message_flows.extend(parsed.distributed_message_flows.clone());
+            symbols.insert(crate_meta.name.clone(), parsed);
         }
 
         Ok(WorkspaceSnapshot {
@@ -700,6 +849,8 @@ impl WorkspaceAnalyzer {
             module: "websocket_framework".to_string(),
             function_context: crate::parser::symbols::FunctionContext::Free,
             is_method: false,
+            cfg: None,
+            attributes: Vec::new(),
         };
         framework_functions.push(websocket_dispatch_function);
 
@@ -727,6 +878,8 @@ impl WorkspaceAnalyzer {
             return_type: Some("()".to_string()),
             embedding_text: None,
             module: "actix_framework".to_string(),
+            cfg: None,
+            attributes: Vec::new(),
         };
         framework_functions.push(actix_lifecycle_function);
 
@@ -754,6 +907,9 @@ impl WorkspaceAnalyzer {
                             is_synthetic: true,
                             macro_context: None,
                             synthetic_confidence: 0.95,
+                            provenance: crate::parser::symbols::CallProvenance::TreeSitter,
+                            receiver_type: None,
+                            cfg: None,
                         });
                     }
                 }
@@ -778,6 +934,9 @@ impl WorkspaceAnalyzer {
                             is_synthetic: true,
                             macro_context: None,
                             synthetic_confidence: 0.90,
+                            provenance: crate::parser::symbols::CallProvenance::TreeSitter,
+                            receiver_type: None,
+                            cfg: None,
                         });
                     }
                 }
@@ -858,28 +1017,196 @@ impl WorkspaceAnalyzer {
     }
 }
 
+/// Tree-sitter analysis whose call edges are checked against an LSP server (rust-analyzer by default)
 pub struct HybridWorkspaceAnalyzer {
     workspace_analyzer: WorkspaceAnalyzer,
+    workspace_root: PathBuf,
+    lsp_config: crate::lsp::LspConfig,
+    last_snapshot: Option<WorkspaceSnapshot>,
 }
 
 impl HybridWorkspaceAnalyzer {
-    pub async fn new<P: AsRef<Path>>(workspace_root: P, _lsp_config: Option<crate::config::Config>) -> Result<Self> {
-        let workspace_analyzer = WorkspaceAnalyzer::new(workspace_root)?;
-        
+    pub async fn new<P: AsRef<Path>>(workspace_root: P, config: Option<crate::config::Config>) -> Result<Self> {
+        let workspace_root = workspace_root.as_ref().to_path_buf();
+        let workspace_analyzer = match config {
+            Some(config) => WorkspaceAnalyzer::new_with_config(config)?,
+            None => WorkspaceAnalyzer::new(&workspace_root)?,
+        };
+        let lsp_config = crate::lsp::LspConfig::load_for_workspace(&workspace_root)?;
+
         Ok(Self {
             workspace_analyzer,
+            workspace_root,
+            lsp_config,
+            last_snapshot: None,
         })
     }
 
+    pub fn with_lsp_config(mut self, lsp_config: crate::lsp::LspConfig) -> Self {
+        self.lsp_config = lsp_config;
+        self
+    }
+
     pub async fn create_snapshot(&mut self) -> Result<WorkspaceSnapshot> {
         self.workspace_analyzer.create_snapshot().await
     }
 
+    /// The snapshot from the last `analyze_workspace`, with LSP-verified calls
+    pub fn last_snapshot(&self) -> Option<&WorkspaceSnapshot> {
+        self.last_snapshot.as_ref()
+    }
+
+    /// Build a tree-sitter snapshot and verify its call edges with the LSP server.
+    ///
+    /// If the server cannot be started and graceful fallback is enabled, the snapshot is kept
+    /// as-is and the result's `analysis_type` is `"tree_sitter"`.
     pub async fn analyze_workspace(&mut self) -> Result<crate::lsp::models::HybridAnalysisResult> {
-        let snapshot = self.create_snapshot().await?;
+        let mut snapshot = self.create_snapshot().await?;
+
+        let client = match crate::lsp::LspClient::start(&self.lsp_config.server, &self.workspace_root).await {
+            Ok(client) => client,
+            Err(e) if self.lsp_config.fallback.enable_graceful_fallback => {
+                if self.lsp_config.fallback.show_warnings {
+                    eprintln!("⚠️ LSP unavailable, using tree-sitter call edges only: {:#}", e);
+                }
+                self.last_snapshot = Some(snapshot);
+                return Ok(crate::lsp::models::HybridAnalysisResult {
+                    analysis_type: "tree_sitter".to_string(),
+                    results: vec![],
+                });
+            }
+            Err(e) => return Err(e),
+        };
+
+        if !client.wait_until_quiescent(self.lsp_config.server.init_timeout()).await {
+            eprintln!("⚠️ LSP server did not finish indexing; results may be incomplete");
+        }
+        let report = crate::lsp::CallVerifier::new(&client, &self.lsp_config.features)
+            .verify(&mut snapshot.symbols)
+            .await;
+        client.shutdown().await?;
+        let report = report?;
+
+        eprintln!(
+            "🔍 LSP verification: {} confirmed, {} corrected, {} discovered, {} unresolved",
+            report.confirmed,
+            report.corrected.len(),
+            report.discovered.len(),
+            report.unresolved
+        );
+
+        let results = report
+            .corrected
+            .iter()
+            .chain(report.discovered.iter())
+            .map(|call| crate::lsp::models::AnalysisItem {
+                name: format!("{} -> {}", call.caller_id, call.qualified_callee.as_deref().unwrap_or(&call.callee_name)),
+                location: format!("{}:{}", call.file_path, call.line + 1),
+                details: call.provenance.as_str().to_string(),
+            })
+            .collect();
+
+        self.last_snapshot = Some(snapshot);
         Ok(crate::lsp::models::HybridAnalysisResult {
             analysis_type: "hybrid".to_string(),
-            results: vec![],
+            results,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_crate(root: &Path, name: &str, files: &[(&str, &str)]) -> CrateMetadata {
+        let crate_path = root.join(name);
+        for (relative, source) in files {
+            let file_path = crate_path.join("src").join(relative);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, source).unwrap();
+        }
+        CrateMetadata {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            path: crate_path,
+            layer: None,
+            depth: 0,
+            dependencies: vec![],
+            is_workspace_member: true,
+            is_external: false,
+            features: Vec::new(),
+            targets: Vec::new(),
+        }
+    }
+
+    fn analyzer_with_threads(root: &Path, max_threads: usize) -> WorkspaceAnalyzer {
+        let mut config = Config::default();
+        config.workspace.root = root.to_path_buf();
+        config.performance.max_threads = max_threads;
+        WorkspaceAnalyzer::new_with_config(config).unwrap()
+    }
+
+    fn function_names(parsed: &[ParsedSymbols]) -> Vec<Vec<String>> {
+        parsed
+            .iter()
+            .map(|symbols| symbols.functions.iter().map(|f| f.qualified_name.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_parse_is_deterministic() {
+        let temp_dir = TempDir::new().unwrap();
+        let module_sources: Vec<(String, String)> = (0..12)
+            .map(|i| (format!("module_{:02}.rs", i), format!("pub fn handler_{}() {{ helper_{}(); }}\nfn helper_{}() {{}}\n", i, i, i)))
+            .collect();
+        let mut files: Vec<(&str, &str)> = module_sources.iter().map(|(p, s)| (p.as_str(), s.as_str())).collect();
+        files.push(("lib.rs", "pub fn entry() {}\n"));
+
+        let crates = vec![
+            write_crate(temp_dir.path(), "alpha", &files),
+            write_crate(temp_dir.path(), "beta", &[("lib.rs", "pub fn beta_entry() {}\n"), ("nested/mod.rs", "fn nested() {}\n")]),
+            write_crate(temp_dir.path(), "no_src", &[]),
+        ];
+
+        let sequential = analyzer_with_threads(temp_dir.path(), 1);
+        let parallel = analyzer_with_threads(temp_dir.path(), 4);
+        let sequential_parse = sequential.parse_crates_parallel(sequential.parse_pool().unwrap(), &crates);
+        let pool = parallel.parse_pool().unwrap();
+        assert_eq!(pool.current_num_threads(), 4);
+        assert!(std::ptr::eq(pool, parallel.parse_pool().unwrap()), "the pool is built once");
+
+        let expected = function_names(&sequential_parse);
+        for _ in 0..3 {
+            let parsed = parallel.parse_crates_parallel(pool, &crates);
+            assert_eq!(function_names(&parsed), expected);
+        }
+
+        // One entry per crate, in input order, with files merged in sorted path order
+        assert_eq!(expected.len(), 3);
+        assert_eq!(expected[0].len(), 25);
+        assert!(expected[0][0].ends_with("entry"), "lib.rs sorts before module_*.rs: {:?}", expected[0]);
+        assert!(expected[0][1].ends_with("handler_0"));
+        assert!(expected[2].is_empty());
+    }
+
+    #[test]
+    fn test_qualified_names_follow_module_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let crates = vec![write_crate(temp_dir.path(), "shop", &[
+            ("lib.rs", "mod orders;\npub use orders::book::place;\npub fn entry() { place(); }\n"),
+            ("orders/mod.rs", "pub mod book;\n"),
+            ("orders/book.rs", "pub fn place() {}\nmod audit {\n    pub fn record() {}\n}\n"),
+        ])];
+
+        let analyzer = analyzer_with_threads(temp_dir.path(), 1);
+        let parsed = analyzer.parse_crates_parallel(analyzer.parse_pool().unwrap(), &crates);
+        assert_eq!(function_names(&parsed), vec![vec!["entry", "orders::book::place", "orders::book::audit::record"]]);
+
+        let reexports: Vec<_> = parsed[0].reexports.iter().map(|r| (r.path.as_str(), r.target.as_str())).collect();
+        assert_eq!(reexports, vec![("place", "orders::book::place")]);
+        let modules: Vec<_> = parsed[0].modules.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(modules, vec!["orders", "orders::book", "orders::book::audit"]);
+    }
+}
@@ -1,9 +1,10 @@
 use anyhow::Result;
-use neo4rs::{Query, Row};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
 
 use crate::config::Config;
-use crate::graph::MemgraphClient;
+use crate::graph::{CallEdge, CrateNode, GraphBackend};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArchitectureViolation {
@@ -44,13 +45,13 @@ pub struct ArchitectureSummary {
 }
 
 pub struct ArchitectureAnalyzer {
-    graph: MemgraphClient,
+    graph: Arc<dyn GraphBackend>,
     config: Config,
 }
 
 impl ArchitectureAnalyzer {
-    pub fn new(graph: std::sync::Arc<MemgraphClient>, config: Config) -> Self {
-        Self { graph: (*graph).clone(), config }
+    pub fn new(graph: Arc<dyn GraphBackend>, config: Config) -> Self {
+        Self { graph, config }
     }
 
     pub async fn analyze_architecture(&self) -> Result<ArchitectureReport> {
@@ -69,25 +70,28 @@ impl ArchitectureAnalyzer {
         })
     }
 
+    /// Layer of each crate: the Crate node's layer, falling back to the configured layers
+    async fn crate_layers(&self) -> Result<HashMap<String, String>> {
+        let mut layers = HashMap::new();
+        for layer in &self.config.architecture.layers {
+            for crate_name in &layer.crates {
+                layers.insert(crate_name.clone(), layer.name.clone());
+            }
+        }
+        for crate_node in self.graph.get_crates().await? {
+            if let Some(layer) = crate_node.layer {
+                layers.insert(crate_node.name, layer);
+            }
+        }
+        Ok(layers)
+    }
+
     async fn check_layer_violations(&self) -> Result<Vec<ArchitectureViolation>> {
-        let query = r#"
-            MATCH (caller:Function)-[call:CALLS]->(callee:Function)
-            WHERE caller.crate <> callee.crate
-            WITH caller, callee, call,
-                 caller.crate as from_crate,
-                 callee.crate as to_crate
-            MATCH (fc:Crate {name: from_crate})
-            MATCH (tc:Crate {name: to_crate})
-            WHERE fc.layer IS NOT NULL AND tc.layer IS NOT NULL
-            RETURN caller, callee, call, fc.layer as from_layer, tc.layer as to_layer,
-                   from_crate, to_crate
-        "#;
-
-        let result = self.graph.execute_query(Query::new(query.to_string())).await?;
+        let layers = self.crate_layers().await?;
         let mut violations = Vec::new();
 
-        for row in result {
-            if let Some(violation) = self.process_layer_violation_row(row).await? {
+        for edge in self.graph.cross_crate_calls().await? {
+            if let Some(violation) = self.process_layer_violation_edge(&edge, &layers) {
                 violations.push(violation);
             }
         }
@@ -96,61 +100,42 @@ impl ArchitectureAnalyzer {
         Ok(violations)
     }
 
-    async fn process_layer_violation_row(&self, row: Row) -> Result<Option<ArchitectureViolation>> {
-        let from_layer: String = row.get("from_layer").unwrap_or_default();
-        let to_layer: String = row.get("to_layer").unwrap_or_default();
-        let from_crate: String = row.get("from_crate").unwrap_or_default();
-        let to_crate: String = row.get("to_crate").unwrap_or_default();
-
-        let from_layer_idx = self.config.get_layer_index(&from_layer);
-        let to_layer_idx = self.config.get_layer_index(&to_layer);
-
-        if let (Some(from_idx), Some(to_idx)) = (from_layer_idx, to_layer_idx) {
-            if from_idx < to_idx {
-                let caller_node: neo4rs::Node = row.get("caller").unwrap();
-                let callee_node: neo4rs::Node = row.get("callee").unwrap();
-                let call_rel: neo4rs::Relation = row.get("call").unwrap();
-                
-                let caller_name: String = caller_node.get("qualified_name").unwrap_or_default();
-                let callee_name: String = callee_node.get("qualified_name").unwrap_or_default();
-                let file: String = caller_node.get("file").unwrap_or_default();
-                let line: i64 = call_rel.get("line").unwrap_or(0);
-
-                return Ok(Some(ArchitectureViolation {
-                    kind: "layer_violation".to_string(),
-                    from: caller_name.clone(),
-                    to: callee_name.clone(),
-                    from_layer: from_layer.clone(),
-                    to_layer: to_layer.clone(),
-                    from_crate: from_crate.clone(),
-                    to_crate: to_crate.clone(),
-                    file: file.clone(),
-                    line: line as usize,
-                    severity: ViolationSeverity::Error,
-                    message: format!(
-                        "Layer violation: {} layer '{}' should not call {} layer '{}'. Function '{}' calls '{}'",
-                        from_layer, from_crate, to_layer, to_crate, caller_name, callee_name
-                    ),
-                }));
-            }
+    fn process_layer_violation_edge(&self, edge: &CallEdge, layers: &HashMap<String, String>) -> Option<ArchitectureViolation> {
+        let from_crate = &edge.caller.crate_name;
+        let to_crate = &edge.callee.crate_name;
+        let from_layer = layers.get(from_crate)?;
+        let to_layer = layers.get(to_crate)?;
+
+        if !self.config.is_layer_violation(from_crate, to_crate) {
+            return None;
         }
 
-        Ok(None)
+        let caller_name = &edge.caller.qualified_name;
+        let callee_name = &edge.callee.qualified_name;
+
+        Some(ArchitectureViolation {
+            kind: "layer_violation".to_string(),
+            from: caller_name.clone(),
+            to: callee_name.clone(),
+            from_layer: from_layer.clone(),
+            to_layer: to_layer.clone(),
+            from_crate: from_crate.clone(),
+            to_crate: to_crate.clone(),
+            file: edge.caller.file.clone(),
+            line: edge.line,
+            severity: ViolationSeverity::Error,
+            message: format!(
+                "Layer violation: {} layer '{}' should not call {} layer '{}'. Function '{}' calls '{}'",
+                from_layer, from_crate, to_layer, to_crate, caller_name, callee_name
+            ),
+        })
     }
 
     async fn check_circular_dependencies(&self) -> Result<Vec<ArchitectureViolation>> {
-        let crate_cycle_query = r#"
-            MATCH (c1:Crate)-[:DEPENDS_ON*2..]->(c2:Crate)-[:DEPENDS_ON*1..]->(c1)
-            RETURN DISTINCT c1.name as crate1, c2.name as crate2
-        "#;
-
-        let result = self.graph.execute_query(Query::new(crate_cycle_query.to_string())).await?;
+        let crates = self.graph.get_crates().await?;
         let mut violations = Vec::new();
 
-        for row in result {
-            let crate1: String = row.get("crate1").unwrap_or_default();
-            let crate2: String = row.get("crate2").unwrap_or_default();
-
+        for (crate1, crate2) in find_dependency_cycles(&crates) {
             violations.push(ArchitectureViolation {
                 kind: "circular_dependency".to_string(),
                 from: crate1.clone(),
@@ -171,39 +156,34 @@ impl ArchitectureAnalyzer {
     }
 
     async fn check_dependency_direction(&self) -> Result<Vec<ArchitectureViolation>> {
-        let reverse_dep_query = r#"
-            MATCH (lower:Crate)-[:DEPENDS_ON]->(higher:Crate)
-            WHERE lower.layer IS NOT NULL AND higher.layer IS NOT NULL
-            RETURN lower.name as lower_crate, lower.layer as lower_layer,
-                   higher.name as higher_crate, higher.layer as higher_layer
-        "#;
-
-        let result = self.graph.execute_query(Query::new(reverse_dep_query.to_string())).await?;
+        let layers = self.crate_layers().await?;
         let mut violations = Vec::new();
 
-        for row in result {
-            let lower_layer: String = row.get("lower_layer").unwrap_or_default();
-            let higher_layer: String = row.get("higher_layer").unwrap_or_default();
-            let lower_crate: String = row.get("lower_crate").unwrap_or_default();
-            let higher_crate: String = row.get("higher_crate").unwrap_or_default();
-
-            if self.config.is_layer_violation(&lower_crate, &higher_crate) {
-                violations.push(ArchitectureViolation {
-                    kind: "reverse_dependency".to_string(),
-                    from: lower_crate.clone(),
-                    to: higher_crate.clone(),
-                    from_layer: lower_layer.clone(),
-                    to_layer: higher_layer.clone(),
-                    from_crate: lower_crate.clone(),
-                    to_crate: higher_crate.clone(),
-                    file: "Cargo.toml".to_string(),
-                    line: 0,
-                    severity: ViolationSeverity::Error,
-                    message: format!(
-                        "Reverse dependency: Lower layer '{}' ({}) should not depend on higher layer '{}' ({})",
-                        lower_layer, lower_crate, higher_layer, higher_crate
-                    ),
-                });
+        for crate_node in self.graph.get_crates().await? {
+            let lower_crate = &crate_node.name;
+            let Some(lower_layer) = layers.get(lower_crate) else { continue };
+
+            for higher_crate in &crate_node.dependencies {
+                let Some(higher_layer) = layers.get(higher_crate) else { continue };
+
+                if self.config.is_layer_violation(lower_crate, higher_crate) {
+                    violations.push(ArchitectureViolation {
+                        kind: "reverse_dependency".to_string(),
+                        from: lower_crate.clone(),
+                        to: higher_crate.clone(),
+                        from_layer: lower_layer.clone(),
+                        to_layer: higher_layer.clone(),
+                        from_crate: lower_crate.clone(),
+                        to_crate: higher_crate.clone(),
+                        file: "Cargo.toml".to_string(),
+                        line: 0,
+                        severity: ViolationSeverity::Error,
+                        message: format!(
+                            "Reverse dependency: Lower layer '{}' ({}) should not depend on higher layer '{}' ({})",
+                            lower_layer, lower_crate, higher_layer, higher_crate
+                        ),
+                    });
+                }
             }
         }
 
@@ -212,26 +192,19 @@ impl ArchitectureAnalyzer {
     }
 
     async fn check_public_api_violations(&self) -> Result<Vec<ArchitectureViolation>> {
-        let private_api_query = r#"
-            MATCH (caller:Function)-[:CALLS]->(callee:Function)
-            WHERE caller.crate <> callee.crate 
-              AND callee.visibility <> "pub"
-              AND NOT callee.visibility STARTS WITH "pub("
-            RETURN caller.qualified_name as caller, caller.crate as caller_crate,
-                   callee.qualified_name as callee, callee.crate as callee_crate,
-                   callee.file as file, callee.line_start as line
-        "#;
-
-        let result = self.graph.execute_query(Query::new(private_api_query.to_string())).await?;
         let mut violations = Vec::new();
 
-        for row in result {
-            let caller: String = row.get("caller").unwrap_or_default();
-            let caller_crate: String = row.get("caller_crate").unwrap_or_default();
-            let callee: String = row.get("callee").unwrap_or_default();
-            let callee_crate: String = row.get("callee_crate").unwrap_or_default();
-            let file: String = row.get("file").unwrap_or_default();
-            let line: i64 = row.get("line").unwrap_or(0);
+        for edge in self.graph.cross_crate_calls().await? {
+            let visibility = &edge.callee.visibility;
+            // Synthetic callee nodes carry no visibility and are not private API
+            if visibility.is_empty() || visibility == "pub" || visibility.starts_with("pub(") {
+                continue;
+            }
+
+            let caller = &edge.caller.qualified_name;
+            let caller_crate = &edge.caller.crate_name;
+            let callee = &edge.callee.qualified_name;
+            let callee_crate = &edge.callee.crate_name;
 
             violations.push(ArchitectureViolation {
                 kind: "private_api_access".to_string(),
@@ -241,8 +214,8 @@ impl ArchitectureAnalyzer {
                 to_layer: "unknown".to_string(),
                 from_crate: caller_crate.clone(),
                 to_crate: callee_crate.clone(),
-                file: file.clone(),
-                line: line as usize,
+                file: edge.callee.file.clone(),
+                line: edge.callee.line_start,
                 severity: ViolationSeverity::Warning,
                 message: format!(
                     "Private API access: '{}' in crate '{}' calls private function '{}' in crate '{}'",
@@ -296,22 +269,12 @@ impl ArchitectureAnalyzer {
     pub async fn mark_violations_in_graph(&self, violations: &[ArchitectureViolation]) -> Result<()> {
         for violation in violations {
             if violation.kind == "layer_violation" {
-                let mark_query = r#"
-                    MATCH (caller:Function {qualified_name: $caller})
-                    MATCH (callee:Function {qualified_name: $callee})
-                    MATCH (caller)-[r:CALLS]->(callee)
-                    SET r.violates_architecture = true,
-                        r.violation_kind = $kind,
-                        r.violation_severity = $severity
-                "#;
-
-                let query = Query::new(mark_query.to_string())
-                    .param("caller", violation.from.clone())
-                    .param("callee", violation.to.clone())
-                    .param("kind", violation.kind.clone())
-                    .param("severity", format!("{:?}", violation.severity));
-
-                let _ = self.graph.execute_query(query).await;
+                let _ = self.graph.mark_call_violation(
+                    &violation.from,
+                    &violation.to,
+                    &violation.kind,
+                    &format!("{:?}", violation.severity),
+                ).await;
             }
         }
 
@@ -320,47 +283,29 @@ impl ArchitectureAnalyzer {
     }
 
     pub async fn get_violations_for_function(&self, function_name: &str) -> Result<Vec<ArchitectureViolation>> {
-        let query = r#"
-            MATCH (f:Function {qualified_name: $function_name})
-            MATCH (f)-[r:CALLS {violates_architecture: true}]->(target:Function)
-            RETURN f.qualified_name as caller, target.qualified_name as callee,
-                   f.crate as caller_crate, target.crate as callee_crate,
-                   f.file as file, r.line as line,
-                   r.violation_kind as kind, r.violation_severity as severity
-        "#;
-
-        let query_obj = Query::new(query.to_string())
-            .param("function_name", function_name);
-
-        let result = self.graph.execute_query(query_obj).await?;
+        let edges = self.graph.find_callees(function_name).await?;
         let mut violations = Vec::new();
 
-        for row in result {
-            let caller: String = row.get("caller").unwrap_or_default();
-            let callee: String = row.get("callee").unwrap_or_default();
-            let caller_crate: String = row.get("caller_crate").unwrap_or_default();
-            let callee_crate: String = row.get("callee_crate").unwrap_or_default();
-            let file: String = row.get("file").unwrap_or_default();
-            let line: i64 = row.get("line").unwrap_or(0);
-            let kind: String = row.get("kind").unwrap_or_default();
-            let severity_str: String = row.get("severity").unwrap_or_default();
-
-            let severity = match severity_str.as_str() {
-                "Error" => ViolationSeverity::Error,
-                "Warning" => ViolationSeverity::Warning,
+        for edge in edges.into_iter().filter(|e| e.violates_architecture) {
+            let caller = edge.caller.qualified_name;
+            let callee = edge.callee.qualified_name;
+
+            let severity = match edge.violation_severity.as_deref() {
+                Some("Error") => ViolationSeverity::Error,
+                Some("Warning") => ViolationSeverity::Warning,
                 _ => ViolationSeverity::Info,
             };
 
             violations.push(ArchitectureViolation {
-                kind,
+                kind: edge.violation_kind.unwrap_or_default(),
                 from: caller.clone(),
                 to: callee.clone(),
                 from_layer: "unknown".to_string(),
                 to_layer: "unknown".to_string(),
-                from_crate: caller_crate.clone(),
-                to_crate: callee_crate.clone(),
-                file,
-                line: line as usize,
+                from_crate: edge.caller.crate_name,
+                to_crate: edge.callee.crate_name,
+                file: edge.caller.file,
+                line: edge.line,
                 severity,
                 message: format!("Function '{}' violates architecture by calling '{}'", caller, callee),
             });
@@ -381,22 +326,12 @@ impl ArchitectureAnalyzer {
     }
 
     async fn calculate_layer_health(&self, layer_name: &str) -> Result<LayerHealth> {
-        let violations_query = r#"
-            MATCH (c:Crate {layer: $layer})
-            MATCH (f:Function {crate: c.name})
-            MATCH (f)-[r:CALLS {violates_architecture: true}]->()
-            RETURN count(r) as violations
-        "#;
-
-        let query = Query::new(violations_query.to_string())
-            .param("layer", layer_name);
-
-        let result = self.graph.execute_query(query).await?;
-        let violations = if let Some(row) = result.first() {
-            row.get::<i64>("violations").unwrap_or(0) as usize
-        } else {
-            0
-        };
+        let layers = self.crate_layers().await?;
+        let violations = self.graph.cross_crate_calls().await?
+            .iter()
+            .filter(|edge| edge.violates_architecture)
+            .filter(|edge| layers.get(&edge.caller.crate_name).map(String::as_str) == Some(layer_name))
+            .count();
 
         let health_score = if violations == 0 {
             100.0
@@ -419,6 +354,41 @@ impl ArchitectureAnalyzer {
     }
 }
 
+/// Pairs of distinct crates that can each reach the other through DEPENDS_ON edges
+fn find_dependency_cycles(crates: &[CrateNode]) -> Vec<(String, String)> {
+    let edges: HashMap<&str, Vec<&str>> = crates.iter()
+        .map(|c| (c.name.as_str(), c.dependencies.iter().map(String::as_str).collect()))
+        .collect();
+
+    let reachable_from = |start: &str| {
+        let mut seen = HashSet::new();
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            for &dep in edges.get(current).into_iter().flatten() {
+                if seen.insert(dep) {
+                    stack.push(dep);
+                }
+            }
+        }
+        seen
+    };
+
+    let reachability: HashMap<&str, HashSet<&str>> = edges.keys()
+        .map(|&name| (name, reachable_from(name)))
+        .collect();
+
+    let mut cycles = BTreeSet::new();
+    for (&from, reachable) in &reachability {
+        for &to in reachable {
+            if from < to && reachability.get(to).is_some_and(|back| back.contains(from)) {
+                cycles.insert((from.to_string(), to.to_string()));
+            }
+        }
+    }
+
+    cycles.into_iter().collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayerHealth {
     pub layer: String,
@@ -0,0 +1,397 @@
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use crate::analyzer::impact::layer_lookup;
+use crate::analyzer::{ImpactTarget, TargetKind};
+use crate::config::Config;
+use crate::parser::symbols::ParsedSymbols;
+
+/// Which changes of a repository to read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeSpec {
+    /// Uncommitted changes, staged or not, and untracked files
+    WorkingTree,
+    /// Everything since the merge base with a revision, committed or not, as a pull
+    /// request against it would show
+    Since(String),
+    /// A commit range such as `v1.0..HEAD`, as `git diff` reads it
+    Range(String),
+}
+
+/// A hunk of a unified diff, with line numbers 1-based as git prints them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+}
+
+impl Hunk {
+    /// Whether the hunk touches the 0-based lines `start..=end` of the new file. A pure
+    /// deletion touches an item only when lines of the item remain on both sides of it.
+    pub fn touches(&self, start: usize, end: usize) -> bool {
+        if self.new_lines == 0 {
+            // Lines were removed after line `new_start`, between 0-based rows `new_start - 1` and `new_start`
+            return start < self.new_start && end >= self.new_start;
+        }
+        let first = self.new_start.saturating_sub(1);
+        let last = first + self.new_lines - 1;
+        start <= last && end >= first
+    }
+}
+
+/// One changed file, by its path relative to the repository root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    /// New path, or the old one for deleted files
+    pub path: String,
+    pub deleted: bool,
+    pub hunks: Vec<Hunk>,
+}
+
+/// The files and lines that changed between two states of a repository
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeSet {
+    /// What was compared, e.g. `working tree against HEAD`
+    pub description: String,
+    /// Repository root the file paths are relative to
+    pub root: PathBuf,
+    pub files: Vec<FileChange>,
+}
+
+impl ChangeSet {
+    /// Read the changes of the git repository containing `repo` by running the `git` binary.
+    ///
+    /// Untracked files count as added unless the spec is a commit range.
+    pub fn from_git(repo: &Path, spec: &ChangeSpec) -> Result<Self> {
+        if let ChangeSpec::Since(rev) | ChangeSpec::Range(rev) = spec {
+            // Anything git would read as an option is not a revision
+            if rev.starts_with('-') {
+                bail!("invalid revision '{}'", rev);
+            }
+        }
+        let root = repository_root(repo)?;
+        let (base, description) = match spec {
+            ChangeSpec::WorkingTree => ("HEAD".to_string(), "working tree against HEAD".to_string()),
+            ChangeSpec::Since(rev) => {
+                let base = git(&root, &["merge-base", rev, "HEAD"])?.trim().to_string();
+                (base, format!("working tree against the merge base with {}", rev))
+            }
+            ChangeSpec::Range(range) => (range.clone(), range.clone()),
+        };
+        let diff = git(&root, &[
+            "diff", "--no-color", "--no-ext-diff", "--no-textconv", "--unified=0", "--src-prefix=a/", "--dst-prefix=b/",
+            &base, "--",
+        ])?;
+        let mut changes = Self::from_diff(description, &root, &diff);
+
+        if !matches!(spec, ChangeSpec::Range(_)) {
+            for path in git(&root, &["ls-files", "--others", "--exclude-standard", "--full-name"])?.lines() {
+                let lines = std::fs::read_to_string(root.join(path)).map(|text| text.lines().count()).unwrap_or(0);
+                let hunks = match lines {
+                    0 => Vec::new(),
+                    lines => vec![Hunk { old_start: 0, old_lines: 0, new_start: 1, new_lines: lines }],
+                };
+                changes.files.push(FileChange { path: path.to_string(), deleted: false, hunks });
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Changes described by unified diff text, such as the output of `git diff`, with paths
+    /// relative to `root`
+    pub fn from_diff(description: impl Into<String>, root: impl Into<PathBuf>, diff: &str) -> Self {
+        ChangeSet { description: description.into(), root: root.into(), files: parse_diff(diff) }
+    }
+
+    /// Functions and types a hunk touches within their `line_start..=line_end` span, in file
+    /// order. A symbol's file must be the changed file under [`ChangeSet::root`].
+    pub fn changed_targets(&self, symbols: &ParsedSymbols, config: &Config) -> Vec<ImpactTarget> {
+        let layer_of = layer_lookup(config);
+        let changed: HashMap<PathBuf, &FileChange> = self.files.iter()
+            .filter(|file| !file.deleted)
+            .map(|file| (normalize(&self.root.join(&file.path)), file))
+            .collect();
+        let mut normalized: HashMap<&str, PathBuf> = HashMap::new();
+        let mut touched = |file_path, start, end| {
+            let path = normalized.entry(file_path).or_insert_with(|| normalize(Path::new(file_path)));
+            changed.get(path).is_some_and(|file| file.hunks.iter().any(|hunk| hunk.touches(start, end)))
+        };
+
+        let functions: Vec<_> = symbols.functions.iter()
+            .filter(|f| touched(&f.file_path, f.line_start, f.line_end))
+            .map(|f| (TargetKind::Function, &f.qualified_name, &f.crate_name, &f.file_path, f.line_start))
+            .collect();
+        let types: Vec<_> = symbols.types.iter()
+            .filter(|t| touched(&t.file_path, t.line_start, t.line_end))
+            .map(|t| (TargetKind::of_type(t.kind), &t.qualified_name, &t.crate_name, &t.file_path, t.line_start))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut targets: Vec<ImpactTarget> = functions.into_iter().chain(types)
+            .filter(|(_, name, crate_name, _, _)| seen.insert((*name, *crate_name)))
+            .map(|(kind, name, crate_name, file_path, line)| ImpactTarget {
+                kind,
+                qualified_name: name.clone(),
+                crate_name: crate_name.clone(),
+                layer: layer_of(crate_name),
+                file_path: file_path.clone(),
+                line,
+            })
+            .collect();
+        targets.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+        targets
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Top-level directory of the git repository containing `path`
+pub fn repository_root(path: &Path) -> Result<PathBuf> {
+    Ok(PathBuf::from(git(path, &["rev-parse", "--show-toplevel"])?.trim()))
+}
+
+/// `path` with symlinks resolved when it exists, otherwise with `.` and `..` folded away
+fn normalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            }
+        }
+        normalized
+    })
+}
+
+/// Run git in `repo` and return its standard output
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run git in {}", repo.display()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+        bail!("git {} failed ({}): {}", args.join(" "), output.status, reason.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Files and hunks of a unified diff, with or without `diff --git` headers.
+///
+/// Hunk bodies are skipped by their line counts, so removed lines that look like headers
+/// (`--- a/x` for a removed `-- a/x`) are not mistaken for one.
+fn parse_diff(diff: &str) -> Vec<FileChange> {
+    let mut files: Vec<FileChange> = Vec::new();
+    // Whether the last file's header is still being read, before its first hunk
+    let mut in_header = false;
+    let (mut old_left, mut new_left) = (0usize, 0usize);
+
+    for line in diff.lines() {
+        if old_left > 0 || new_left > 0 {
+            match line.chars().next() {
+                Some('-') => old_left = old_left.saturating_sub(1),
+                Some('+') => new_left = new_left.saturating_sub(1),
+                Some('\\') => {}
+                _ => {
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+            }
+            continue;
+        }
+
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let path = paths.rsplit_once(" b/").map(|(_, path)| path).unwrap_or(paths);
+            files.push(FileChange { path: path.to_string(), deleted: false, hunks: Vec::new() });
+            in_header = true;
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            if !in_header {
+                let path = path.split('\t').next().unwrap_or(path);
+                files.push(FileChange { path: path.trim_start_matches("a/").to_string(), deleted: false, hunks: Vec::new() });
+                in_header = true;
+            }
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split('\t').next().unwrap_or(path);
+            if let Some(file) = files.last_mut().filter(|_| in_header) {
+                match path {
+                    "/dev/null" => file.deleted = true,
+                    _ => file.path = path.strip_prefix("b/").unwrap_or(path).to_string(),
+                }
+            }
+        } else if line.starts_with("deleted file mode") {
+            if let Some(file) = files.last_mut() {
+                file.deleted = true;
+            }
+        } else if let Some(hunk) = parse_hunk_header(line) {
+            if let Some(file) = files.last_mut() {
+                file.hunks.push(hunk);
+                in_header = false;
+                (old_left, new_left) = (hunk.old_lines, hunk.new_lines);
+            }
+        }
+    }
+    files
+}
+
+/// `@@ -12,3 +12,4 @@ fn context`; a missing count means one line
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let mut ranges = line.strip_prefix("@@ ")?.split_whitespace();
+    let range = |text: &str| -> Option<(usize, usize)> {
+        match text.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((text.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(ranges.next()?.strip_prefix('-')?)?;
+    let (new_start, new_lines) = range(ranges.next()?.strip_prefix('+')?)?;
+    Some(Hunk { old_start, old_lines, new_start, new_lines })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RustParser;
+
+    const DIFF: &str = "\
+diff --git a/orders/src/lib.rs b/orders/src/lib.rs
+index 1111111..2222222 100644
+--- a/orders/src/lib.rs
++++ b/orders/src/lib.rs
+@@ -3 +3 @@ pub struct Order {
+-    pub id: u32,
++    pub id: u64,
+@@ -11,2 +10,0 @@ impl Order {
+-    --- a/not/a/header
+-    // removed
+@@ -20,0 +19,2 @@ pub fn cancel() {
++    log();
++    notify();
+diff --git a/orders/README.md b/orders/README.md
+deleted file mode 100644
+--- a/orders/README.md
++++ /dev/null
+@@ -1 +0,0 @@
+-# Orders
+";
+
+    const ORDERS: &str = r#"
+pub struct Order {
+    pub id: u64,
+}
+
+impl Order {
+    pub fn new(id: u64) -> Self {
+        Order { id }
+    }
+
+    pub fn total(&self) -> u64 {
+        0
+    }
+}
+
+pub fn place() {}
+
+pub fn cancel() {
+    log();
+    notify();
+}
+"#;
+
+    #[test]
+    fn test_parse_diff_files_and_hunks() {
+        let files = parse_diff(DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "orders/src/lib.rs");
+        assert_eq!(
+            files[0].hunks,
+            vec![
+                Hunk { old_start: 3, old_lines: 1, new_start: 3, new_lines: 1 },
+                Hunk { old_start: 11, old_lines: 2, new_start: 10, new_lines: 0 },
+                Hunk { old_start: 20, old_lines: 0, new_start: 19, new_lines: 2 },
+            ]
+        );
+        assert_eq!((files[1].path.as_str(), files[1].deleted), ("orders/README.md", true));
+
+        // A plain unified diff without git headers
+        let plain = parse_diff("--- a/src/main.rs\t2024-01-01\n+++ b/src/main.rs\t2024-01-02\n@@ -1 +1 @@\n-a\n+b\n");
+        assert_eq!(plain, vec![FileChange {
+            path: "src/main.rs".to_string(),
+            deleted: false,
+            hunks: vec![Hunk { old_start: 1, old_lines: 1, new_start: 1, new_lines: 1 }],
+        }]);
+    }
+
+    #[test]
+    fn test_hunks_map_to_item_spans() {
+        let mut parser = RustParser::new().unwrap();
+        let mut symbols = parser.parse_source(ORDERS, Path::new("/work/orders/src/lib.rs"), "orders").unwrap();
+        // Same path relative to another directory, which must not match
+        symbols.merge(parser.parse_source(ORDERS, Path::new("/work/vendor/orders/src/lib.rs"), "vendored").unwrap());
+        let targets = ChangeSet::from_diff("test", "/work/./vendor/..", DIFF).changed_targets(&symbols, &Config::default());
+
+        assert!(targets.iter().all(|t| t.crate_name == "orders"));
+        let names: Vec<(TargetKind, &str)> = targets.iter().map(|t| (t.kind, t.qualified_name.as_str())).collect();
+        // Line 3 is in `Order`; the lines deleted after line 10 were between `new` and `total`
+        assert_eq!(names, vec![(TargetKind::Struct, "Order"), (TargetKind::Function, "cancel")]);
+    }
+
+    #[test]
+    fn test_deletions_inside_an_item_touch_it() {
+        let deletion = Hunk { old_start: 5, old_lines: 1, new_start: 4, new_lines: 0 };
+        assert!(deletion.touches(2, 6));
+        assert!(!deletion.touches(4, 6));
+        assert!(!deletion.touches(0, 3));
+    }
+
+    #[test]
+    fn test_from_git_reads_the_working_tree() {
+        let repo = tempfile::tempdir().unwrap();
+        let run = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com", "-C"])
+                .arg(repo.path())
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {:?}", args);
+        };
+        run(&["init", "--quiet"]);
+        std::fs::write(repo.path().join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        run(&["add", "lib.rs"]);
+        run(&["commit", "--quiet", "-m", "init"]);
+
+        std::fs::write(repo.path().join("lib.rs"), "fn a() {}\nfn b() { c(); }\n").unwrap();
+        std::fs::write(repo.path().join("new.rs"), "fn c() {}\n").unwrap();
+
+        let changes = ChangeSet::from_git(repo.path(), &ChangeSpec::WorkingTree).unwrap();
+        assert_eq!(changes.files, vec![
+            FileChange {
+                path: "lib.rs".to_string(),
+                deleted: false,
+                hunks: vec![Hunk { old_start: 2, old_lines: 1, new_start: 2, new_lines: 1 }],
+            },
+            FileChange {
+                path: "new.rs".to_string(),
+                deleted: false,
+                hunks: vec![Hunk { old_start: 0, old_lines: 0, new_start: 1, new_lines: 1 }],
+            },
+        ]);
+
+        assert!(ChangeSet::from_git(repo.path(), &ChangeSpec::Since("no-such-rev".to_string())).is_err());
+        assert!(ChangeSet::from_git(repo.path(), &ChangeSpec::Range("--output=x".to_string())).is_err());
+    }
+}
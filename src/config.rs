@@ -13,6 +13,14 @@ pub struct Config {
     pub performance: PerformanceConfig,
     pub framework: FrameworkConfig,
     pub cross_crate: CrossCrateConfig,
+    #[serde(default)]
+    pub graph: GraphConfig,
+    #[serde(default)]
+    pub cfg: CfgConfig,
+    #[serde(default)]
+    pub dead_code: DeadCodeConfig,
+    #[serde(default)]
+    pub expand: ExpandConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,6 +118,36 @@ pub struct EmbeddingsConfig {
     pub model: String,
     #[serde(default = "default_embedding_fields")]
     pub include_in_embedding: Vec<String>,
+    /// Directory holding `config.json`, `tokenizer.json` and `model.safetensors` for `model = "local"`
+    #[serde(default)]
+    pub model_path: Option<PathBuf>,
+    /// OpenAI-compatible embeddings endpoint used for any other model name
+    #[serde(default = "default_embedding_endpoint")]
+    pub endpoint: String,
+    /// Environment variable holding the bearer token for `endpoint`
+    #[serde(default = "default_embedding_api_key_env")]
+    pub api_key_env: String,
+    #[serde(default = "default_embedding_batch_size")]
+    pub batch_size: usize,
+    /// Where vectors are cached between runs (default: `target/workspace-analyzer/embeddings` under the workspace root)
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+    /// Length of the vectors `model` produces; sizes the Memgraph vector index (default: known size for the model)
+    #[serde(default)]
+    pub dimensions: Option<usize>,
+}
+
+impl EmbeddingsConfig {
+    /// Vector length for the configured model, if it is set or the model is one we know
+    pub fn vector_dimensions(&self) -> Option<usize> {
+        self.dimensions.or(match self.model.as_str() {
+            "hashing" | "local" => Some(384),
+            "text-embedding-3-small" | "text-embedding-ada-002" => Some(1536),
+            "text-embedding-3-large" => Some(3072),
+            "nomic-embed-text" => Some(768),
+            _ => None,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -152,6 +190,111 @@ pub struct CrossCrateConfig {
     pub max_index_memory_mb: usize,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphConfig {
+    #[serde(default)]
+    pub backend: GraphBackendKind,
+}
+
+/// Which `#[cfg(...)]` configuration the parsed code is analysed under.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CfgConfig {
+    #[serde(default)]
+    pub mode: CfgMode,
+    /// Enabled features per crate; crates not listed use their `default` feature set
+    #[serde(default)]
+    pub features: HashMap<String, Vec<String>>,
+    /// `target_os` to evaluate against, defaults to the host OS
+    #[serde(default)]
+    pub target_os: Option<String>,
+    /// Extra bare cfg options to treat as set, e.g. `tokio_unstable`
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+/// What to do with items whose cfg predicate is false for the active configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CfgMode {
+    /// Keep every item and record its predicate as a `cfg` property
+    #[default]
+    Annotate,
+    /// Drop items that are compiled out before analysis and graph population
+    Filter,
+}
+
+/// Entry points the dead code report walks reachability from.
+///
+/// Binaries' `main` functions are always roots; everything else can be switched off to see
+/// what is only kept alive by tests, benches or the public API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadCodeConfig {
+    /// `#[test]` functions and code under `tests/`
+    #[serde(default = "default_true")]
+    pub tests: bool,
+    /// Code under `benches/` and `#[bench]` functions
+    #[serde(default = "default_true")]
+    pub benches: bool,
+    /// `main` functions under `examples/`
+    #[serde(default = "default_true")]
+    pub examples: bool,
+    /// `pub` items of library crates, which other workspaces may depend on
+    #[serde(default = "default_true")]
+    pub public_api: bool,
+    /// Entry points and trait dispatch described by the framework patterns
+    #[serde(default = "default_true")]
+    pub framework_entry_points: bool,
+    /// Extra roots, matched against function names or qualified names
+    #[serde(default)]
+    pub entry_points: Vec<String>,
+}
+
+impl Default for DeadCodeConfig {
+    fn default() -> Self {
+        Self {
+            tests: true,
+            benches: true,
+            examples: true,
+            public_api: true,
+            framework_entry_points: true,
+            entry_points: Vec::new(),
+        }
+    }
+}
+
+/// Crates whose macro-expanded source is parsed alongside the files on disk, so that impls
+/// and calls generated by proc macros (derives, attribute macros) reach the graph.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExpandConfig {
+    /// Crates to expand; expanding builds the crate, so it is opt-in per crate
+    #[serde(default)]
+    pub crates: Vec<String>,
+    #[serde(default)]
+    pub command: ExpandCommand,
+}
+
+/// How the expanded source of a crate is produced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExpandCommand {
+    /// `cargo expand`, which must be installed (`cargo install cargo-expand`)
+    #[default]
+    CargoExpand,
+    /// `cargo rustc -- -Zunpretty=expanded`, which needs a nightly toolchain
+    Rustc,
+}
+
+/// Which graph store analysis results are written to and queried from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphBackendKind {
+    /// External Memgraph server reached over bolt (see `[memgraph]`)
+    #[default]
+    Memgraph,
+    /// Embedded in-process graph, no server required
+    Memory,
+}
+
 fn default_true() -> bool {
     true
 }
@@ -164,6 +307,18 @@ fn default_embedding_model() -> String {
     "text-embedding-3-small".to_string()
 }
 
+fn default_embedding_endpoint() -> String {
+    "https://api.openai.com/v1/embeddings".to_string()
+}
+
+fn default_embedding_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
+}
+
+fn default_embedding_batch_size() -> usize {
+    32
+}
+
 fn default_embedding_fields() -> Vec<String> {
     vec![
         "function_name".to_string(),
@@ -295,7 +450,7 @@ impl Config {
         std::iter::once(&self.workspace.root).chain(self.workspace.additional_roots.iter())
     }
 
-    fn init_caches(&mut self) {
+    pub fn init_caches(&mut self) {
         let mut layer_index = HashMap::new();
         for (idx, layer) in self.architecture.layers.iter().enumerate() {
             for crate_name in &layer.crates {
@@ -355,6 +510,12 @@ impl Default for Config {
                 enabled: true,
                 model: default_embedding_model(),
                 include_in_embedding: default_embedding_fields(),
+                model_path: None,
+                endpoint: default_embedding_endpoint(),
+                api_key_env: default_embedding_api_key_env(),
+                batch_size: default_embedding_batch_size(),
+                cache_dir: None,
+                dimensions: None,
             },
             performance: PerformanceConfig {
                 max_threads: default_max_threads(),
@@ -376,6 +537,10 @@ impl Default for Config {
                 incremental_updates: true,
                 max_index_memory_mb: default_max_index_memory_mb(),
             },
+            graph: GraphConfig::default(),
+            cfg: CfgConfig::default(),
+            dead_code: DeadCodeConfig::default(),
+            expand: ExpandConfig::default(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Vectors for one provider keyed by the SHA-256 of their embedding text,
+/// persisted as `<dir>/<provider>.bin` when a directory is given
+#[derive(Debug, Default)]
+pub struct EmbeddingCache {
+    path: Option<PathBuf>,
+    vectors: HashMap<String, Vec<f32>>,
+    dirty: bool,
+}
+
+impl EmbeddingCache {
+    /// An unpersisted cache
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load the provider's cache file from `dir`; a missing or unreadable file starts empty
+    pub fn open(dir: &Path, provider_name: &str) -> Self {
+        let file_name: String = provider_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+            .collect();
+        let path = dir.join(format!("{}.bin", file_name));
+
+        let vectors = match std::fs::read(&path) {
+            Ok(bytes) => bincode::deserialize(&bytes).unwrap_or_else(|e| {
+                eprintln!("⚠️ Ignoring corrupt embedding cache {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self {
+            path: Some(path),
+            vectors,
+            dirty: false,
+        }
+    }
+
+    pub fn key(text: &str) -> String {
+        format!("{:x}", Sha256::digest(text.as_bytes()))
+    }
+
+    pub fn get(&self, text: &str) -> Option<&Vec<f32>> {
+        self.vectors.get(&Self::key(text))
+    }
+
+    pub fn insert(&mut self, text: &str, vector: Vec<f32>) {
+        self.vectors.insert(Self::key(text), vector);
+        self.dirty = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Write the cache file if anything was added since it was loaded
+    pub fn save(&mut self) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create embedding cache directory {}", parent.display()))?;
+        }
+        let bytes = bincode::serialize(&self.vectors)?;
+        // Write then rename so an interrupted run never leaves a truncated cache
+        let tmp = path.with_extension("bin.tmp");
+        std::fs::write(&tmp, bytes).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_round_trips_through_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut cache = EmbeddingCache::open(temp_dir.path(), "http-text-embedding-3-small");
+        assert!(cache.is_empty());
+        cache.insert("function: alpha", vec![0.25, 0.75]);
+        cache.save().unwrap();
+
+        let reopened = EmbeddingCache::open(temp_dir.path(), "http-text-embedding-3-small");
+        assert_eq!(reopened.get("function: alpha"), Some(&vec![0.25, 0.75]));
+        assert!(reopened.get("function: beta").is_none());
+        assert!(EmbeddingCache::open(temp_dir.path(), "hashing-384").is_empty());
+    }
+}
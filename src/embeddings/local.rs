@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use std::path::Path;
+use std::sync::Arc;
+use tokenizers::{PaddingParams, Tokenizer};
+
+use super::provider::{normalize, EmbeddingProvider};
+
+/// A BERT-family sentence-embedding model (e.g. all-MiniLM-L6-v2) run on the CPU with candle.
+///
+/// `model_path` must contain the Hugging Face `config.json`, `tokenizer.json` and `model.safetensors`.
+pub struct LocalModelProvider {
+    name: String,
+    model: Arc<LocalModel>,
+}
+
+/// The loaded weights and tokenizer, shared with the blocking threads that run inference
+struct LocalModel {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+}
+
+impl LocalModelProvider {
+    pub fn load(model_path: &Path) -> Result<Self> {
+        let device = Device::Cpu;
+        let config_path = model_path.join("config.json");
+        let config: Config = serde_json::from_str(
+            &std::fs::read_to_string(&config_path).with_context(|| format!("Failed to read {}", config_path.display()))?,
+        )
+        .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+
+        let mut tokenizer = Tokenizer::from_file(model_path.join("tokenizer.json"))
+            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer from {}: {}", model_path.display(), e))?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+
+        let weights = model_path.join("model.safetensors");
+        // SAFETY: the weights file is only read, and is not expected to change while mapped
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[&weights], DTYPE, &device) }
+            .with_context(|| format!("Failed to map {}", weights.display()))?;
+        let model = BertModel::load(vb, &config)?;
+
+        let name = format!(
+            "local-{}",
+            model_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+        );
+        tracing::info!("Loaded local embedding model from {}", model_path.display());
+        Ok(Self { name, model: Arc::new(LocalModel { model, tokenizer, device }) })
+    }
+}
+
+impl LocalModel {
+    fn embed_sync(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+
+        let ids = encodings
+            .iter()
+            .map(|e| Tensor::new(e.get_ids(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+        let masks = encodings
+            .iter()
+            .map(|e| Tensor::new(e.get_attention_mask(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+        let input_ids = Tensor::stack(&ids, 0)?;
+        let attention_mask = Tensor::stack(&masks, 0)?;
+        let token_type_ids = input_ids.zeros_like()?;
+
+        let hidden = self.model.forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+
+        // Mean pooling over real (unpadded) tokens
+        let mask = attention_mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+        let summed = hidden.broadcast_mul(&mask)?.sum(1)?;
+        let counts = mask.sum(1)?;
+        let pooled = summed.broadcast_div(&counts)?;
+
+        let mut vectors = pooled.to_vec2::<f32>()?;
+        for vector in vectors.iter_mut() {
+            normalize(vector);
+        }
+        Ok(vectors)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalModelProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        // Inference is CPU-bound; keep it off the async worker threads
+        let model = Arc::clone(&self.model);
+        let texts = texts.to_vec();
+        tokio::task::spawn_blocking(move || model.embed_sync(&texts))
+            .await
+            .context("Local embedding task failed")?
+    }
+}
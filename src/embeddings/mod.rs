@@ -1,15 +1,26 @@
+pub mod cache;
+#[cfg(feature = "local-embeddings")]
+pub mod local;
+pub mod provider;
+
+pub use cache::EmbeddingCache;
+pub use provider::{provider_from_config, EmbeddingProvider, HashingProvider, HttpEmbeddingProvider};
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 use crate::config::Config;
 use crate::parser::symbols::{RustFunction, RustType, ParsedSymbols};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct EmbeddingGenerator {
     config: Config,
-    model: String,
     enabled: bool,
+    provider: Arc<dyn EmbeddingProvider>,
+    cache: Arc<Mutex<EmbeddingCache>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,13 +69,45 @@ pub enum SearchResultType {
 
 impl EmbeddingGenerator {
     pub fn new(config: Config) -> Self {
+        let provider = provider_from_config(&config.embeddings).unwrap_or_else(|e| {
+            eprintln!("⚠️ {:#}; using hashing embeddings", e);
+            Arc::new(HashingProvider::default())
+        });
+        Self::with_provider(config, provider)
+    }
+
+    /// Use `provider` regardless of `[embeddings] model`
+    pub fn with_provider(config: Config, provider: Arc<dyn EmbeddingProvider>) -> Self {
+        let cache = if config.embeddings.enabled {
+            EmbeddingCache::open(&Self::cache_dir(&config), provider.name())
+        } else {
+            EmbeddingCache::in_memory()
+        };
         Self {
-            model: config.embeddings.model.clone(),
             enabled: config.embeddings.enabled,
             config,
+            provider,
+            cache: Arc::new(Mutex::new(cache)),
         }
     }
 
+    fn cache_dir(config: &Config) -> PathBuf {
+        match &config.embeddings.cache_dir {
+            Some(dir) if dir.is_absolute() => dir.clone(),
+            Some(dir) => config.workspace.root.join(dir),
+            None => config.workspace.root.join("target").join("workspace-analyzer").join("embeddings"),
+        }
+    }
+
+    pub fn provider(&self) -> Arc<dyn EmbeddingProvider> {
+        self.provider.clone()
+    }
+
+    /// The vector computed for `embedding_text`, if any
+    pub fn vector_for(&self, embedding_text: &str) -> Option<Vec<f32>> {
+        self.cache.lock().unwrap().get(embedding_text).cloned()
+    }
+
     pub async fn generate_embeddings(&self, symbols: &mut ParsedSymbols) -> Result<()> {
         if !self.enabled {
             eprintln!("📝 Embeddings disabled, skipping generation");
@@ -75,117 +118,92 @@ impl EmbeddingGenerator {
 
         self.generate_function_embeddings(&mut symbols.functions).await?;
         self.generate_type_embeddings(&mut symbols.types).await?;
+        self.attach_vectors(symbols);
 
         let duration = start.elapsed();
         eprintln!("🧠 Generated embeddings in {}ms", duration.as_millis());
         Ok(())
     }
 
-    async fn generate_function_embeddings(&self, functions: &mut Vec<RustFunction>) -> Result<()> {
+    async fn generate_function_embeddings(&self, functions: &mut [RustFunction]) -> Result<()> {
+        let mut texts = Vec::with_capacity(functions.len());
         for function in functions.iter_mut() {
             let embedding_text = function.generate_embedding_text(&self.config.embeddings.include_in_embedding);
-            
-            if self.config.embeddings.enabled {
-                let _embedding_vector = self.get_embedding_vector(&embedding_text).await
-                    .unwrap_or_else(|e| {
-                        eprintln!("⚠️ Failed to get embedding for {}: {}", function.qualified_name, e);
-                        Vec::new()
-                    });
-                
-                function.embedding_text = Some(embedding_text);
-            } else {
-                function.embedding_text = Some(embedding_text);
-            }
+            texts.push(embedding_text.clone());
+            function.embedding_text = Some(embedding_text);
+        }
+        if self.enabled {
+            self.embed_missing(&texts).await;
         }
 
         eprintln!("🔧 Generated embeddings for {} functions", functions.len());
         Ok(())
     }
 
-    async fn generate_type_embeddings(&self, types: &mut Vec<RustType>) -> Result<()> {
+    async fn generate_type_embeddings(&self, types: &mut [RustType]) -> Result<()> {
+        let mut texts = Vec::with_capacity(types.len());
         for rust_type in types.iter_mut() {
             let embedding_text = rust_type.generate_embedding_text(&self.config.embeddings.include_in_embedding);
-            
-            if self.config.embeddings.enabled {
-                let _embedding_vector = self.get_embedding_vector(&embedding_text).await
-                    .unwrap_or_else(|e| {
-                        eprintln!("⚠️ Failed to get embedding for {}: {}", rust_type.qualified_name, e);
-                        Vec::new()
-                    });
-                
-                rust_type.embedding_text = Some(embedding_text);
-            } else {
-                rust_type.embedding_text = Some(embedding_text);
-            }
+            texts.push(embedding_text.clone());
+            rust_type.embedding_text = Some(embedding_text);
+        }
+        if self.enabled {
+            self.embed_missing(&texts).await;
         }
 
         eprintln!("📐 Generated embeddings for {} types", types.len());
         Ok(())
     }
 
-    async fn get_embedding_vector(&self, text: &str) -> Result<Vec<f32>> {
-        match self.model.as_str() {
-            "text-embedding-3-small" => {
-                self.get_openai_embedding(text).await
-            },
-            "local" => {
-                self.get_local_embedding(text).await
-            },
-            _ => {
-                anyhow::bail!("Unsupported embedding model: {}", self.model);
-            }
+    /// Embed the texts that have no cached vector yet, in batches, and persist the cache.
+    /// Provider failures are logged; the affected texts simply stay without a vector.
+    async fn embed_missing(&self, texts: &[String]) {
+        let missing: Vec<String> = {
+            let cache = self.cache.lock().unwrap();
+            let mut seen = HashSet::new();
+            texts
+                .iter()
+                .filter(|text| cache.get(text).is_none() && seen.insert(text.as_str()))
+                .cloned()
+                .collect()
+        };
+        if missing.is_empty() {
+            return;
         }
-    }
 
-    async fn get_openai_embedding(&self, text: &str) -> Result<Vec<f32>> {
-        // Use local text-based embedding for now
-        // This generates a deterministic embedding based on text content
-        // suitable for semantic similarity within the same codebase
-        self.generate_local_text_embedding(text, 1536)
-    }
+        let batch_size = self.config.embeddings.batch_size.max(1);
+        for batch in missing.chunks(batch_size) {
+            match self.provider.embed_batch(batch).await {
+                Ok(vectors) => {
+                    let mut cache = self.cache.lock().unwrap();
+                    for (text, vector) in batch.iter().zip(vectors) {
+                        cache.insert(text, vector);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("⚠️ {} failed to embed {} texts: {:#}", self.provider.name(), batch.len(), e);
+                    break;
+                }
+            }
+        }
 
-    async fn get_local_embedding(&self, text: &str) -> Result<Vec<f32>> {
-        // Use local text-based embedding
-        self.generate_local_text_embedding(text, 384)
+        if let Err(e) = self.cache.lock().unwrap().save() {
+            eprintln!("⚠️ Failed to save embedding cache: {:#}", e);
+        }
     }
 
-    fn generate_local_text_embedding(&self, text: &str, dim: usize) -> Result<Vec<f32>> {
-        // Generate deterministic embeddings based on text content
-        // This provides meaningful similarity for code analysis without external APIs
-        let mut embedding = vec![0.0; dim];
-        
-        // Use multiple text features to create embedding
-        let words: Vec<&str> = text.split_whitespace().collect();
-        let chars: Vec<char> = text.chars().collect();
-        
-        // Feature 1: Character distribution (normalized)
-        for (i, &ch) in chars.iter().enumerate() {
-            let idx = (ch as u32 % dim as u32) as usize;
-            embedding[idx] += 1.0 / (i + 1) as f32;
-        }
-        
-        // Feature 2: Word position weighting
-        for (i, word) in words.iter().enumerate() {
-            let hash = word.chars().map(|c| c as u32).sum::<u32>();
-            let idx = (hash % dim as u32) as usize;
-            embedding[idx] += 0.5 / (i + 1) as f32;
-        }
-        
-        // Feature 3: Text length and structure
-        let text_len = text.len() as f32;
-        for (i, val) in embedding.iter_mut().enumerate() {
-            *val += (text_len * (i + 1) as f32).sin() * 0.1;
-        }
-        
-        // Normalize to unit vector
-        let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
-        if norm > 0.0 {
-            for val in &mut embedding {
-                *val /= norm;
+    /// Record each function's and type's cached vector in `symbols.embeddings` so graph
+    /// population can store it on the node
+    fn attach_vectors(&self, symbols: &mut ParsedSymbols) {
+        let cache = self.cache.lock().unwrap();
+        let texts = symbols.functions.iter()
+            .map(|f| (&f.id, &f.embedding_text))
+            .chain(symbols.types.iter().map(|t| (&t.id, &t.embedding_text)));
+        for (id, text) in texts {
+            if let Some(vector) = text.as_ref().and_then(|text| cache.get(text)) {
+                symbols.embeddings.insert(id.clone(), vector.clone());
             }
         }
-        
-        Ok(embedding)
     }
 
     pub fn extract_function_embeddings(&self, functions: &[RustFunction]) -> Vec<FunctionEmbedding> {
@@ -197,7 +215,7 @@ impl EmbeddingGenerator {
                         function_id: f.id.clone(),
                         qualified_name: f.qualified_name.clone(),
                         embedding_text: embedding_text.clone(),
-                        embedding_vector: None,
+                        embedding_vector: self.vector_for(embedding_text),
                         metadata: EmbeddingMetadata {
                             crate_name: f.crate_name.clone(),
                             module_path: f.module_path.clone(),
@@ -223,7 +241,7 @@ impl EmbeddingGenerator {
                         type_id: t.id.clone(),
                         qualified_name: t.qualified_name.clone(),
                         embedding_text: embedding_text.clone(),
-                        embedding_vector: None,
+                        embedding_vector: self.vector_for(embedding_text),
                         metadata: EmbeddingMetadata {
                             crate_name: t.crate_name.clone(),
                             module_path: t.module_path.clone(),
@@ -282,13 +300,20 @@ impl EmbeddingGenerator {
 pub struct SemanticSearch {
     embeddings: HashMap<String, Vec<f32>>,
     metadata: HashMap<String, EmbeddingMetadata>,
+    provider: Arc<dyn EmbeddingProvider>,
 }
 
 impl SemanticSearch {
     pub fn new() -> Self {
+        Self::with_provider(Arc::new(HashingProvider::default()))
+    }
+
+    /// Embed queries with `provider`; it must be the one that produced the indexed vectors
+    pub fn with_provider(provider: Arc<dyn EmbeddingProvider>) -> Self {
         Self {
             embeddings: HashMap::new(),
             metadata: HashMap::new(),
+            provider,
         }
     }
 
@@ -340,28 +365,20 @@ impl SemanticSearch {
     }
 
     async fn get_query_embedding(&self, query: &str) -> Result<Vec<f32>> {
-        let query_hash = query.chars().map(|c| c as u32).sum::<u32>() as f32;
-        let mut embedding = Vec::new();
-        for i in 0..384 {
-            embedding.push((query_hash + i as f32) / 384.0);
-        }
-        Ok(embedding)
+        self.provider.embed(query).await
     }
 
-    fn cosine_similarity(&self, a: &[f32], b: &[f32]) -> f32 {
-        if a.len() != b.len() {
-            return 0.0;
-        }
+    /// Embed a free-text query with the provider that produced the indexed vectors
+    pub async fn embed_query(&self, query: &str) -> Result<Vec<f32>> {
+        self.get_query_embedding(query).await
+    }
 
-        let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-        let magnitude_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-        let magnitude_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    pub fn is_empty(&self) -> bool {
+        self.embeddings.is_empty()
+    }
 
-        if magnitude_a == 0.0 || magnitude_b == 0.0 {
-            0.0
-        } else {
-            dot_product / (magnitude_a * magnitude_b)
-        }
+    fn cosine_similarity(&self, a: &[f32], b: &[f32]) -> f32 {
+        cosine_similarity(a, b)
     }
 
     pub fn get_similar_functions(&self, function_id: &str, limit: usize) -> Vec<String> {
@@ -384,6 +401,23 @@ impl SemanticSearch {
     }
 }
 
+/// Cosine similarity of two vectors; 0.0 when lengths differ or either is zero
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+
+    let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let magnitude_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let magnitude_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if magnitude_a == 0.0 || magnitude_b == 0.0 {
+        0.0
+    } else {
+        dot_product / (magnitude_a * magnitude_b)
+    }
+}
+
 impl Default for SemanticSearch {
     fn default() -> Self {
         Self::new()
@@ -427,6 +461,8 @@ mod tests {
             return_type: Some("i32".to_string()),
             embedding_text: None,
             module: "crate".to_string(),
+            cfg: None,
+            attributes: Vec::new(),
         }
     }
 
@@ -451,6 +487,7 @@ mod tests {
             type_kind: "Struct".to_string(),
             module: "crate".to_string(),
             is_test: false,
+            cfg: None,
         }
     }
 
@@ -495,4 +532,117 @@ mod tests {
         assert_eq!(similarity1, 0.0);
         assert_eq!(similarity2, 1.0);
     }
-}
\ No newline at end of file
+
+    /// Minimal OpenAI-compatible `/v1/embeddings` server; each input is embedded as
+    /// `[input length, 1.0]`. Returns the endpoint URL and a request counter.
+    async fn spawn_embedding_stub() -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/v1/embeddings", listener.local_addr().unwrap());
+        let requests = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).await.unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).await.unwrap();
+                let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                let data: Vec<_> = request["input"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .map(|(index, text)| serde_json::json!({"index": index, "embedding": [text.as_str().unwrap().len() as f32, 1.0]}))
+                    .collect();
+                let response = serde_json::json!({"object": "list", "data": data}).to_string();
+                let mut stream = reader.into_inner();
+                stream
+                    .write_all(format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", response.len(), response).as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+        (endpoint, requests)
+    }
+
+    #[test]
+    fn test_provider_selected_by_model() {
+        let mut config = EmbeddingsConfig {
+            model: "hashing".to_string(),
+            ..crate::config::Config::default().embeddings
+        };
+        assert_eq!(provider_from_config(&config).unwrap().name(), "hashing-384");
+
+        config.model = "local".to_string();
+        assert_eq!(provider_from_config(&config).unwrap().name(), "hashing-384");
+
+        config.model = "nomic-embed-text".to_string();
+        config.endpoint = "http://127.0.0.1:11434/v1/embeddings".to_string();
+        assert_eq!(provider_from_config(&config).unwrap().name(), "http-nomic-embed-text");
+    }
+
+    #[tokio::test]
+    async fn test_http_embeddings_are_cached_across_generators() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (endpoint, requests) = spawn_embedding_stub().await;
+
+        let mut config = crate::config::Config::default();
+        config.embeddings.model = "stub-model".to_string();
+        config.embeddings.endpoint = endpoint;
+        config.embeddings.cache_dir = Some(temp_dir.path().to_path_buf());
+
+        let mut symbols = ParsedSymbols::new();
+        symbols.functions.push(create_test_function());
+        symbols.types.push(create_test_type());
+
+        let generator = EmbeddingGenerator::new(config.clone());
+        generator.generate_embeddings(&mut symbols).await.unwrap();
+        let text = symbols.functions[0].embedding_text.clone().unwrap();
+        assert_eq!(generator.vector_for(&text), Some(vec![text.len() as f32, 1.0]));
+        let embeddings = generator.extract_type_embeddings(&symbols.types);
+        assert!(embeddings[0].embedding_vector.is_some());
+        assert_eq!(symbols.embeddings.get(&symbols.functions[0].id), Some(&vec![text.len() as f32, 1.0]));
+        assert!(symbols.embeddings.contains_key(&symbols.types[0].id));
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        // A fresh generator reads the vectors back from disk instead of asking the endpoint again
+        let reloaded = EmbeddingGenerator::new(config);
+        reloaded.generate_embeddings(&mut symbols).await.unwrap();
+        assert_eq!(reloaded.vector_for(&text), Some(vec![text.len() as f32, 1.0]));
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_embeds_query_with_index_provider() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = crate::config::Config::default();
+        config.embeddings.model = "hashing".to_string();
+        config.embeddings.cache_dir = Some(temp_dir.path().to_path_buf());
+        let generator = EmbeddingGenerator::new(config);
+
+        let mut symbols = ParsedSymbols::new();
+        symbols.functions.push(create_test_function());
+        generator.generate_embeddings(&mut symbols).await.unwrap();
+
+        let mut search = SemanticSearch::with_provider(generator.provider());
+        search.index_function_embeddings(&generator.extract_function_embeddings(&symbols.functions));
+        let text = symbols.functions[0].embedding_text.clone().unwrap();
+        let results = search.search(&text, 5).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!((results[0].similarity_score - 1.0).abs() < 1e-5);
+    }
+}
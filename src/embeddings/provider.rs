@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::config::EmbeddingsConfig;
+
+/// Turns embedding text into vectors
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Identifies the model; vectors from different providers are never mixed in the cache
+    fn name(&self) -> &str;
+
+    /// One vector per input text, in input order
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_batch(&[text.to_string()])
+            .await?
+            .pop()
+            .context("Embedding provider returned no vector")
+    }
+}
+
+/// Pick the provider named by `[embeddings] model`:
+/// `"hashing"`, `"local"` (needs `model_path` and the `local-embeddings` feature),
+/// or any other name for the OpenAI-compatible HTTP endpoint.
+pub fn provider_from_config(config: &EmbeddingsConfig) -> Result<Arc<dyn EmbeddingProvider>> {
+    match config.model.as_str() {
+        "hashing" => Ok(Arc::new(HashingProvider::default())),
+        "local" => match &config.model_path {
+            Some(model_path) => local_provider(model_path),
+            None => {
+                eprintln!("⚠️ [embeddings] model = \"local\" without model_path, using hashing embeddings");
+                Ok(Arc::new(HashingProvider::default()))
+            }
+        },
+        model => {
+            let api_key = std::env::var(&config.api_key_env).ok();
+            if api_key.is_none() && config.endpoint.starts_with("https://api.openai.com") {
+                eprintln!("⚠️ {} is not set, using hashing embeddings instead of {}", config.api_key_env, model);
+                return Ok(Arc::new(HashingProvider::default()));
+            }
+            Ok(Arc::new(HttpEmbeddingProvider::new(&config.endpoint, model, api_key)))
+        }
+    }
+}
+
+#[cfg(feature = "local-embeddings")]
+fn local_provider(model_path: &std::path::Path) -> Result<Arc<dyn EmbeddingProvider>> {
+    Ok(Arc::new(super::local::LocalModelProvider::load(model_path)?))
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+fn local_provider(model_path: &std::path::Path) -> Result<Arc<dyn EmbeddingProvider>> {
+    anyhow::bail!(
+        "Cannot load {}: rebuild with `--features local-embeddings` to run local embedding models",
+        model_path.display()
+    )
+}
+
+/// Deterministic character/word hashing; no model, only lexical similarity
+pub struct HashingProvider {
+    name: String,
+    dimensions: usize,
+}
+
+impl HashingProvider {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            name: format!("hashing-{}", dimensions),
+            dimensions,
+        }
+    }
+
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let dim = self.dimensions;
+        let mut embedding = vec![0.0; dim];
+
+        // Use multiple text features to create embedding
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let chars: Vec<char> = text.chars().collect();
+
+        // Feature 1: Character distribution (normalized)
+        for (i, &ch) in chars.iter().enumerate() {
+            let idx = (ch as u32 % dim as u32) as usize;
+            embedding[idx] += 1.0 / (i + 1) as f32;
+        }
+
+        // Feature 2: Word position weighting
+        for (i, word) in words.iter().enumerate() {
+            let hash = word.chars().map(|c| c as u32).sum::<u32>();
+            let idx = (hash % dim as u32) as usize;
+            embedding[idx] += 0.5 / (i + 1) as f32;
+        }
+
+        // Feature 3: Text length and structure
+        let text_len = text.len() as f32;
+        for (i, val) in embedding.iter_mut().enumerate() {
+            *val += (text_len * (i + 1) as f32).sin() * 0.1;
+        }
+
+        normalize(&mut embedding);
+        embedding
+    }
+}
+
+impl Default for HashingProvider {
+    fn default() -> Self {
+        Self::new(384)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashingProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed_text(text)).collect())
+    }
+}
+
+/// Any server speaking the OpenAI `POST /v1/embeddings` protocol
+pub struct HttpEmbeddingProvider {
+    name: String,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl HttpEmbeddingProvider {
+    pub fn new(endpoint: &str, model: &str, api_key: Option<String>) -> Self {
+        Self {
+            name: format!("http-{}", model),
+            endpoint: endpoint.to_string(),
+            model: model.to_string(),
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HttpEmbeddingProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .json(&serde_json::json!({"model": self.model, "input": texts}));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Embedding request to {} failed", self.endpoint))?
+            .error_for_status()
+            .with_context(|| format!("Embedding request to {} was rejected", self.endpoint))?;
+        let mut body: EmbeddingResponse = response.json().await.context("Malformed embedding response")?;
+
+        anyhow::ensure!(
+            body.data.len() == texts.len(),
+            "Embedding endpoint returned {} vectors for {} inputs",
+            body.data.len(),
+            texts.len()
+        );
+        body.data.sort_by_key(|item| item.index);
+        Ok(body.data.into_iter().map(|item| item.embedding).collect())
+    }
+}
+
+pub(crate) fn normalize(vector: &mut [f32]) {
+    let norm: f32 = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for val in vector.iter_mut() {
+            *val /= norm;
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use crate::config::{Config, GraphBackendKind};
+use crate::graph::{CypherQuery, GraphStatistics, InMemoryGraph, MemgraphClient};
+use crate::parser::symbols::{FnReference, LifecycleHook, ParsedSymbols, RustFunction};
+use crate::workspace::CrateMetadata;
+
+/// Function node as seen through a graph backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionNode {
+    pub id: String,
+    pub name: String,
+    pub qualified_name: String,
+    pub crate_name: String,
+    pub file: String,
+    pub line_start: usize,
+    pub visibility: String,
+    pub is_test: bool,
+    /// `cfg` predicate gating the function, if any
+    #[serde(default)]
+    pub cfg: Option<String>,
+}
+
+/// CALLS relationship between two function nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallEdge {
+    pub caller: FunctionNode,
+    pub callee: FunctionNode,
+    pub line: usize,
+    pub call_type: String,
+    pub cross_crate: bool,
+    pub violates_architecture: bool,
+    pub violation_kind: Option<String>,
+    pub violation_severity: Option<String>,
+    /// How the edge was established, see [`crate::parser::symbols::CallProvenance`]
+    pub provenance: String,
+    /// `cfg` predicate the call site is compiled under, if any
+    #[serde(default)]
+    pub cfg: Option<String>,
+}
+
+/// SPAWNS relationship between two actors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpawnEdge {
+    pub parent: String,
+    pub parent_crate: String,
+    pub child: String,
+    pub child_crate: String,
+    pub method: String,
+    pub context: String,
+    pub line: usize,
+    pub file_path: String,
+}
+
+/// SUPERVISES relationship from an actor to an actor linked to it, with the lifecycle
+/// hooks that decide what happens when either side dies. A hook is `None` when the
+/// actor keeps kameo's default, which stops on any abnormal death.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisionEdge {
+    pub supervisor: String,
+    pub supervisor_crate: String,
+    pub child: String,
+    pub child_crate: String,
+    /// `spawn_link` or `link`
+    pub kind: String,
+    pub supervisor_on_link_died: Option<String>,
+    pub child_on_link_died: Option<String>,
+    pub child_on_panic: Option<String>,
+    pub line: usize,
+    pub file_path: String,
+}
+
+/// MAY_DISPATCH_TO relationship from a function calling a trait method on a `dyn`, `impl` or
+/// generic receiver to one implementation the call can reach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DispatchEdge {
+    pub caller: String,
+    pub caller_crate: String,
+    pub target: String,
+    pub target_crate: String,
+    pub trait_name: String,
+    pub method: String,
+    /// Receiver type as written at the call site
+    pub receiver: String,
+    pub implementing_type: String,
+    pub line: usize,
+    pub file_path: String,
+}
+
+/// USES_TYPE relationship from a function or type to a workspace type it names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeUsageEdge {
+    pub user: String,
+    pub user_crate: String,
+    /// `Function` or `Type`
+    pub user_kind: String,
+    pub type_name: String,
+    pub type_crate: String,
+    /// Where the type is named, see [`crate::parser::symbols::TypeUsageKind`]
+    pub usage: String,
+    pub line: usize,
+    pub file_path: String,
+}
+
+/// HANDLES relationship from an actor to a message type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandlerEdge {
+    pub actor: String,
+    pub actor_crate: String,
+    pub message_type: String,
+    pub reply_type: String,
+    pub is_async: bool,
+    pub line: usize,
+    pub file_path: String,
+}
+
+/// SENDS relationship from a sender to a message type, annotated with the receiving actor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendEdge {
+    pub sender: String,
+    pub sender_crate: String,
+    pub receiver: String,
+    pub receiver_crate: Option<String>,
+    pub message_type: String,
+    pub method: String,
+    pub line: usize,
+    pub file_path: String,
+}
+
+/// Crate node with its architecture layer and DEPENDS_ON targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateNode {
+    pub name: String,
+    pub layer: Option<String>,
+    pub is_workspace: bool,
+    pub dependencies: Vec<String>,
+}
+
+/// Storage-agnostic view of the code graph.
+///
+/// Analysis code should go through these methods rather than raw Cypher so that it
+/// works against both Memgraph and the embedded [`InMemoryGraph`].
+#[async_trait]
+pub trait GraphBackend: Send + Sync {
+    fn kind(&self) -> GraphBackendKind;
+
+    async fn clear_workspace(&self) -> Result<()>;
+    async fn create_crate_nodes(&self, crates: &[CrateMetadata]) -> Result<()>;
+    async fn populate_from_symbols(&self, symbols: &ParsedSymbols) -> Result<()>;
+    /// Drop every node and relationship that originates from `file_path`
+    async fn remove_file(&self, file_path: &str) -> Result<()>;
+
+    async fn get_statistics(&self) -> Result<GraphStatistics>;
+    async fn health_check(&self) -> Result<bool>;
+    async fn test_connection(&self) -> Result<()>;
+    async fn verify_population(&self) -> Result<()>;
+
+    async fn get_crates(&self) -> Result<Vec<CrateNode>>;
+    /// Every function node, ordered by crate and name
+    async fn functions(&self) -> Result<Vec<FunctionNode>>;
+    /// Every CALLS edge
+    async fn calls(&self) -> Result<Vec<CallEdge>>;
+    /// Direct callers of the function with the given qualified name
+    async fn find_callers(&self, qualified_name: &str) -> Result<Vec<CallEdge>>;
+    /// Direct callees of the function with the given qualified name
+    async fn find_callees(&self, qualified_name: &str) -> Result<Vec<CallEdge>>;
+    /// Functions that use `qualified_name` as a value (REFERENCES_FN), e.g. `map(Self::convert)`
+    /// or `Box::new(handler)`; the edges carry the `Reference` call type
+    async fn find_referrers(&self, qualified_name: &str) -> Result<Vec<CallEdge>>;
+    /// All CALLS edges whose endpoints live in different crates
+    async fn cross_crate_calls(&self) -> Result<Vec<CallEdge>>;
+    /// Functions with no incoming CALLS edge
+    async fn find_unreferenced_functions(&self) -> Result<Vec<FunctionNode>>;
+    async fn actor_spawns(&self) -> Result<Vec<SpawnEdge>>;
+    async fn supervision_links(&self) -> Result<Vec<SupervisionEdge>>;
+    /// Functions and types that name the type `qualified_name`, see [`TypeUsageEdge`]
+    async fn type_users(&self, qualified_name: &str) -> Result<Vec<TypeUsageEdge>>;
+    /// Implementations reachable from trait method calls, see [`DispatchEdge`]
+    async fn trait_dispatches(&self) -> Result<Vec<DispatchEdge>>;
+    async fn message_handlers(&self) -> Result<Vec<HandlerEdge>>;
+    async fn message_sends(&self) -> Result<Vec<SendEdge>>;
+    async fn mark_call_violation(&self, caller: &str, callee: &str, kind: &str, severity: &str) -> Result<()>;
+
+    /// Layer of a crate as recorded on its Crate node
+    async fn crate_layer(&self, crate_name: &str) -> Result<Option<String>> {
+        Ok(self.get_crates().await?
+            .into_iter()
+            .find(|c| c.name == crate_name)
+            .and_then(|c| c.layer))
+    }
+
+    /// Callers of `qualified_name` up to `depth` CALLS or REFERENCES_FN hops away, paired
+    /// with their shortest distance and ordered by distance.
+    async fn transitive_callers(&self, qualified_name: &str, depth: usize) -> Result<Vec<(FunctionNode, usize)>> {
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        let mut queue = VecDeque::from([(qualified_name.to_string(), 0usize)]);
+        seen.insert(qualified_name.to_string());
+
+        while let Some((target, distance)) = queue.pop_front() {
+            if distance >= depth {
+                continue;
+            }
+            let mut edges = self.find_callers(&target).await?;
+            edges.extend(self.find_referrers(&target).await?);
+            for edge in edges {
+                if seen.insert(edge.caller.qualified_name.clone()) {
+                    queue.push_back((edge.caller.qualified_name.clone(), distance + 1));
+                    result.push((edge.caller, distance + 1));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Functions whose stored embedding is closest to `vector`, most similar first, paired with
+    /// their cosine similarity. With `caller_of`, only direct callers of that qualified name count.
+    async fn similar_functions(&self, _vector: &[f32], _limit: usize, _caller_of: Option<&str>) -> Result<Vec<(FunctionNode, f32)>> {
+        anyhow::bail!("The '{:?}' backend does not store embeddings", self.kind())
+    }
+
+    /// Raw Cypher escape hatch for tools that have not been ported to the methods above.
+    /// Queries come from [`crate::graph::queries`] so inputs are always bound as parameters.
+    /// Backends without a Cypher engine return an error.
+    async fn execute_query(&self, _query: CypherQuery) -> Result<Vec<neo4rs::Row>> {
+        anyhow::bail!(
+            "This query requires the Memgraph backend; the '{:?}' backend does not execute Cypher",
+            self.kind()
+        )
+    }
+}
+
+/// Number of incoming CALLS edges per callee id, all of them and those from test functions
+pub fn incoming_call_counts(calls: &[CallEdge]) -> HashMap<&str, (usize, usize)> {
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for call in calls {
+        let (all, from_tests) = counts.entry(call.callee.id.as_str()).or_default();
+        *all += 1;
+        if call.caller.is_test {
+            *from_tests += 1;
+        }
+    }
+    counts
+}
+
+/// Build the backend selected by `[graph] backend` in the config
+pub async fn connect_backend(config: &Config) -> Result<Arc<dyn GraphBackend>> {
+    match config.graph.backend {
+        GraphBackendKind::Memgraph => Ok(Arc::new(MemgraphClient::new(config).await?)),
+        GraphBackendKind::Memory => {
+            eprintln!("🧠 Using in-memory graph backend (no Memgraph server required)");
+            Ok(Arc::new(InMemoryGraph::new(config.clone())))
+        }
+    }
+}
+
+/// All function nodes a populate pass should create: free functions plus impl methods.
+///
+/// When an impl method duplicates a known function (same qualified name within 5 lines),
+/// the existing entry is kept and only upgraded to `is_trait_impl` if the impl says so.
+pub fn collect_function_nodes(symbols: &ParsedSymbols) -> Vec<RustFunction> {
+    let mut all_functions = symbols.functions.clone();
+    let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (idx, function) in all_functions.iter().enumerate() {
+        by_name.entry(function.qualified_name.clone()).or_default().push(idx);
+    }
+
+    for impl_block in &symbols.impls {
+        for method in &impl_block.methods {
+            let existing = by_name.get(&method.qualified_name).and_then(|indices| {
+                indices.iter().copied().find(|&idx| {
+                    (all_functions[idx].line_start as i64 - method.line_start as i64).abs() <= 5
+                })
+            });
+
+            match existing {
+                Some(idx) => {
+                    if method.is_trait_impl {
+                        all_functions[idx].is_trait_impl = true;
+                    }
+                }
+                None => {
+                    by_name.entry(method.qualified_name.clone()).or_default().push(all_functions.len());
+                    all_functions.push(method.clone());
+                }
+            }
+        }
+    }
+
+    all_functions
+}
+
+/// MAY_DISPATCH_TO edges for every trait method call with a known implementation
+pub fn collect_dispatch_edges(symbols: &ParsedSymbols) -> Vec<DispatchEdge> {
+    let mut seen = HashSet::new();
+    symbols.trait_dispatches.iter()
+        .filter(|d| seen.insert((d.caller_id.clone(), d.target.clone(), d.file_path.clone(), d.line)))
+        .map(|d| DispatchEdge {
+            caller: d.caller_id.clone(),
+            caller_crate: d.from_crate.clone(),
+            target: d.target.clone(),
+            target_crate: d.target_crate.clone(),
+            trait_name: d.trait_name.clone(),
+            method: d.method_name.clone(),
+            receiver: d.receiver.clone(),
+            implementing_type: d.implementing_type.clone(),
+            line: d.line,
+            file_path: d.file_path.clone(),
+        })
+        .collect()
+}
+
+/// USES_TYPE edges for every resolved type usage, one per user, type, usage kind and line.
+/// A type naming itself (`next: Option<Box<Node>>`) is left out.
+pub fn collect_type_usage_edges(symbols: &ParsedSymbols) -> Vec<TypeUsageEdge> {
+    let mut seen = HashSet::new();
+    symbols.type_usages.iter()
+        .filter_map(|u| Some((u, u.qualified_type.as_ref()?, u.to_crate.as_ref()?)))
+        .filter(|(u, type_name, _)| !(u.user_is_type && u.user == **type_name))
+        .filter(|(u, type_name, _)| seen.insert((&u.user, *type_name, u.kind, &u.file_path, u.line)))
+        .map(|(u, type_name, type_crate)| TypeUsageEdge {
+            user: u.user.clone(),
+            user_crate: u.from_crate.clone(),
+            user_kind: if u.user_is_type { "Type" } else { "Function" }.to_string(),
+            type_name: type_name.clone(),
+            type_crate: type_crate.clone(),
+            usage: u.kind.as_str().to_string(),
+            line: u.line,
+            file_path: u.file_path.clone(),
+        })
+        .collect()
+}
+
+/// Resolved function references, one per referring function, target and site
+pub fn collect_fn_references(symbols: &ParsedSymbols) -> Vec<&FnReference> {
+    let mut seen = HashSet::new();
+    symbols.fn_references.iter()
+        .filter(|r| r.qualified_target.is_some() && r.to_crate.is_some())
+        .filter(|r| seen.insert((&r.caller_id, &r.qualified_target, &r.file_path, r.line)))
+        .collect()
+}
+
+/// SUPERVISES edges for every actor link, annotated with the overridden hooks of both ends.
+///
+/// Links are keyed by actor name within the linking crate; the child is looked up among
+/// all actors so links to actors from other workspace crates keep their crate.
+pub fn collect_supervision_edges(symbols: &ParsedSymbols) -> Vec<SupervisionEdge> {
+    let hook = |actor: &str, crate_name: &str, kind: LifecycleHook| {
+        symbols.actor_hooks.iter()
+            .find(|h| h.actor_name == actor && h.crate_name == crate_name && h.hook == kind)
+            .map(|h| h.behavior.to_string())
+    };
+    let actor_crate = |actor: &str, preferred: &str| {
+        let crates: Vec<&str> = symbols.actors.iter()
+            .filter(|a| a.name == actor)
+            .map(|a| a.crate_name.as_str())
+            .collect();
+        match crates.first() {
+            Some(first) if !crates.contains(&preferred) => first.to_string(),
+            _ => preferred.to_string(),
+        }
+    };
+
+    let mut seen = HashSet::new();
+    symbols.actor_links.iter()
+        .filter(|link| seen.insert((link.supervisor.clone(), link.child.clone(), link.file_path.clone(), link.line)))
+        .map(|link| {
+            let supervisor_crate = actor_crate(&link.supervisor, &link.from_crate);
+            let child_crate = actor_crate(&link.child, &link.from_crate);
+            SupervisionEdge {
+                supervisor_on_link_died: hook(&link.supervisor, &supervisor_crate, LifecycleHook::OnLinkDied),
+                child_on_link_died: hook(&link.child, &child_crate, LifecycleHook::OnLinkDied),
+                child_on_panic: hook(&link.child, &child_crate, LifecycleHook::OnPanic),
+                supervisor: link.supervisor.clone(),
+                supervisor_crate,
+                child: link.child.clone(),
+                child_crate,
+                kind: link.kind.to_string(),
+                line: link.line,
+                file_path: link.file_path.clone(),
+            }
+        })
+        .collect()
+}
@@ -6,9 +6,15 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 
-use crate::config::Config;
+use crate::config::{Config, GraphBackendKind};
 use crate::parser::symbols::*;
 use crate::graph::pool::ConnectionPool;
+use crate::graph::backend::{
+    collect_dispatch_edges, collect_fn_references, collect_supervision_edges, collect_type_usage_edges, CallEdge, CrateNode,
+    DispatchEdge, FunctionNode, GraphBackend, HandlerEdge, SendEdge, SpawnEdge, SupervisionEdge, TypeUsageEdge,
+};
+use crate::graph::queries::{self, CypherQuery};
+use crate::embeddings::cosine_similarity;
 
 /// Enhanced error types for better Memgraph operation categorization
 #[derive(Error, Debug)]
@@ -59,6 +65,9 @@ impl StorageMode {
     }
 }
 
+/// Initial size of each vector index; Memgraph grows it past this as nodes are added
+const VECTOR_INDEX_CAPACITY: usize = 10_000;
+
 #[derive(Clone)]
 pub struct MemgraphClient {
     pub pool: ConnectionPool,
@@ -244,11 +253,34 @@ impl MemgraphClient {
                 let _ = conn.run(Query::new(query.to_string())).await;
             }
         }
+
+        self.setup_vector_indexes().await;
         
         eprintln!("✅ Optimized Memgraph schema with high-cardinality indexes only");
         Ok(())
     }
 
+    /// Create the vector indexes semantic search runs against. Needs the vector length up front,
+    /// so an unknown model without `[embeddings] dimensions` gets no index.
+    async fn setup_vector_indexes(&self) {
+        if !self.config.embeddings.enabled {
+            return;
+        }
+        let Some(dimensions) = self.config.embeddings.vector_dimensions() else {
+            eprintln!("⚠️ Unknown vector length for embedding model '{}'; set [embeddings] dimensions to enable graph vector search",
+                self.config.embeddings.model);
+            return;
+        };
+
+        for (index, label) in [(queries::FUNCTION_VECTOR_INDEX, "Function"), (queries::TYPE_VECTOR_INDEX, "Type")] {
+            let ddl = queries::create_vector_index(index, label, dimensions, VECTOR_INDEX_CAPACITY);
+            if let Ok(mut conn) = self.get_connection().await {
+                // Fails harmlessly when the index already exists
+                let _ = conn.run(Query::new(ddl)).await;
+            }
+        }
+    }
+
     pub async fn clear_workspace(&self) -> Result<()> {
         let start = Instant::now();
         
@@ -444,33 +476,12 @@ impl MemgraphClient {
         eprintln!("📝 Creating function nodes...");
         
         // Collect all functions: both standalone and from impl blocks
-        let mut all_functions = symbols.functions.clone();
-        
-        // Add functions from impl blocks (these have correct is_trait_impl values)
-        // When there are duplicates, prefer the one with is_trait_impl: true
-        for impl_block in &symbols.impls {
-            for method in &impl_block.methods {
-                // Find if this function already exists
-                // Match by qualified name and approximate line number (within 5 lines)
-                if let Some(existing) = all_functions.iter_mut().find(|f| 
-                    f.qualified_name == method.qualified_name && 
-                    (f.line_start as i32 - method.line_start as i32).abs() <= 5
-                ) {
-                    // If the impl version has is_trait_impl: true, update the existing one
-                    if method.is_trait_impl && !existing.is_trait_impl {
-                        existing.is_trait_impl = true;
-                    }
-                } else {
-                    // Function doesn't exist yet, add it
-                    all_functions.push(method.clone());
-                }
-            }
-        }
+        let all_functions = crate::graph::collect_function_nodes(symbols);
         
         let total_functions = all_functions.len();
-        self.create_function_nodes(&all_functions).await?;
+        self.create_function_nodes(&all_functions, &symbols.embeddings).await?;
         eprintln!("📝 Creating type nodes...");
-        self.create_type_nodes(&symbols.types).await?;
+        self.create_type_nodes(&symbols.types, &symbols.embeddings).await?;
         
         // Check current relationship count before creating calls
         let pre_query = Query::new("MATCH ()-[r:CALLS]->() RETURN count(r) as call_count".to_string());
@@ -483,10 +494,14 @@ impl MemgraphClient {
         }
         
         self.create_call_relationships(&symbols.calls).await?;
+        self.create_dispatch_relationships(&collect_dispatch_edges(symbols)).await?;
+        self.create_reference_relationships(&collect_fn_references(symbols)).await?;
+        self.create_type_usage_relationships(&collect_type_usage_edges(symbols)).await?;
         self.create_impl_relationships(&symbols.impls).await?;
         self.create_actor_nodes(&symbols.actors).await?;
         self.update_distributed_actors(&symbols.distributed_actors).await?;
         self.create_actor_spawn_relationships(&symbols.actor_spawns).await?;
+        self.create_supervision_relationships(&collect_supervision_edges(symbols)).await?;
         self.create_message_type_nodes(&symbols.message_types).await?;
         self.create_macro_expansion_nodes(&symbols.macro_expansions).await?;
         self.create_contains_macro_relationships(symbols).await?;
@@ -520,6 +535,9 @@ impl MemgraphClient {
         eprintln!("    • {} MessageTypes", symbols.message_types.len());
         eprintln!("  Relationships created:");
         eprintln!("    • {} CALLS", symbols.calls.len());
+        eprintln!("    • {} MAY_DISPATCH_TO", symbols.trait_dispatches.len());
+        eprintln!("    • {} REFERENCES_FN", collect_fn_references(symbols).len());
+        eprintln!("    • {} USES_TYPE", collect_type_usage_edges(symbols).len());
         eprintln!("    • {} IMPLEMENTS", symbols.impls.len());
         eprintln!("    • {} SPAWNS", symbols.actor_spawns.len());
         eprintln!("    • {} HANDLES", symbols.message_handlers.len());
@@ -623,7 +641,7 @@ impl MemgraphClient {
         Ok(())
     }
 
-    async fn create_function_nodes(&self, functions: &[RustFunction]) -> Result<()> {
+    async fn create_function_nodes(&self, functions: &[RustFunction], embeddings: &HashMap<String, Vec<f32>>) -> Result<()> {
         if functions.is_empty() {
             return Ok(());
         }
@@ -657,7 +675,9 @@ impl MemgraphClient {
                     func.signature = $signature,
                     func.parameter_types = $parameter_types,
                     func.return_type = $return_type,
-                    func.embedding_text = $embedding_text
+                    func.embedding_text = $embedding_text,
+                    func.embedding = $embedding,
+                    func.cfg = $cfg
                 ON MATCH SET 
                     func.name = $name,
                     func.qualified_name = $qualified_name,
@@ -676,7 +696,9 @@ impl MemgraphClient {
                     func.signature = $signature,
                     func.parameter_types = $parameter_types,
                     func.return_type = $return_type,
-                    func.embedding_text = $embedding_text".to_string())
+                    func.embedding_text = $embedding_text,
+                    func.embedding = $embedding,
+                    func.cfg = $cfg".to_string())
                 .param("id", function.id.clone())
                 .param("name", function.name.clone())
                 .param("qualified_name", function.qualified_name.clone())
@@ -695,7 +717,9 @@ impl MemgraphClient {
                 .param("signature", function.signature.clone())
                 .param("parameter_types", param_types_str)
                 .param("return_type", function.return_type.as_ref().unwrap_or(&String::new()).clone())
-                .param("embedding_text", function.embedding_text.as_ref().unwrap_or(&String::new()).clone());
+                .param("embedding_text", function.embedding_text.as_ref().unwrap_or(&String::new()).clone())
+                .param("embedding", embeddings.get(&function.id).cloned())
+                .param("cfg", function.cfg.clone());
 
                 match self.execute_with_retry(query).await {
                     Ok(_) => {
@@ -713,7 +737,7 @@ impl MemgraphClient {
         Ok(())
     }
 
-    async fn create_type_nodes(&self, types: &[RustType]) -> Result<()> {
+    async fn create_type_nodes(&self, types: &[RustType], embeddings: &HashMap<String, Vec<f32>>) -> Result<()> {
         if types.is_empty() {
             return Ok(());
         }
@@ -747,7 +771,9 @@ impl MemgraphClient {
                     type.fields = $fields,
                     type.variants = $variants,
                     type.methods = $methods,
-                    type.embedding_text = $embedding_text
+                    type.embedding_text = $embedding_text,
+                    type.embedding = $embedding,
+                    type.cfg = $cfg
                 ON MATCH SET
                     type.name = $name,
                     type.qualified_name = $qualified_name,
@@ -764,7 +790,9 @@ impl MemgraphClient {
                     type.fields = $fields,
                     type.variants = $variants,
                     type.methods = $methods,
-                    type.embedding_text = $embedding_text".to_string())
+                    type.embedding_text = $embedding_text,
+                    type.embedding = $embedding,
+                    type.cfg = $cfg".to_string())
                 .param("id", rust_type.id.clone())
                 .param("name", rust_type.name.clone())
                 .param("qualified_name", rust_type.qualified_name.clone())
@@ -781,7 +809,9 @@ impl MemgraphClient {
                 .param("fields", field_names_str)
                 .param("variants", variant_names_str)
                 .param("methods", methods_str)
-                .param("embedding_text", rust_type.embedding_text.as_ref().unwrap_or(&String::new()).clone());
+                .param("embedding_text", rust_type.embedding_text.as_ref().unwrap_or(&String::new()).clone())
+                .param("embedding", embeddings.get(&rust_type.id).cloned())
+                .param("cfg", rust_type.cfg.clone());
 
                 match self.execute_with_retry(query).await {
                     Ok(_) => {
@@ -849,7 +879,9 @@ impl MemgraphClient {
                                  r.is_synthetic = true,
                                  r.created_by_macro = true,
                                  r.cross_crate = $cross_crate,
-                                 r.violates_architecture = $violates_architecture
+                                 r.violates_architecture = $violates_architecture,
+                                 r.provenance = $provenance,
+                                 r.cfg = $cfg
                              RETURN 1 as created
                              UNION
                              WITH caller, target
@@ -864,7 +896,9 @@ impl MemgraphClient {
                                  r.is_synthetic = true,
                                  r.created_by_macro = true,
                                  r.cross_crate = $cross_crate,
-                                 r.violates_architecture = $violates_architecture
+                                 r.violates_architecture = $violates_architecture,
+                                 r.provenance = $provenance,
+                                 r.cfg = $cfg
                              RETURN 1 as created
                          }
                          RETURN created".to_string()
@@ -875,7 +909,7 @@ impl MemgraphClient {
                         "MATCH (caller:Function {id: $caller_id})
                          MATCH (callee:Function {qualified_name: $callee_name})
                          MERGE (caller)-[r:CALLS {line: $line}]->(callee)
-                         SET r.call_type = $call_type, r.cross_crate = $cross_crate, r.violates_architecture = $violates_architecture".to_string()
+                         SET r.call_type = $call_type, r.cross_crate = $cross_crate, r.violates_architecture = $violates_architecture, r.provenance = $provenance, r.cfg = $cfg".to_string()
                     )
                 } else {
                     // For within-crate calls without qualified names, we have ambiguity
@@ -886,7 +920,7 @@ impl MemgraphClient {
                         "MATCH (caller:Function {id: $caller_id})
                          MATCH (callee:Function {name: $callee_name, crate: $from_crate})
                          MERGE (caller)-[r:CALLS {line: $line}]->(callee)
-                         SET r.call_type = $call_type, r.cross_crate = $cross_crate, r.violates_architecture = $violates_architecture".to_string()
+                         SET r.call_type = $call_type, r.cross_crate = $cross_crate, r.violates_architecture = $violates_architecture, r.provenance = $provenance, r.cfg = $cfg".to_string()
                     )
                 };
 
@@ -915,6 +949,8 @@ impl MemgraphClient {
                         .param("call_type", format!("{:?}", call.call_type))
                         .param("cross_crate", call.cross_crate)
                         .param("violates_architecture", violation)
+                        .param("provenance", call.provenance.as_str())
+                        .param("cfg", call.cfg.clone())
                 } else {
                     query
                         .param("caller_id", call.caller_id.clone())
@@ -924,6 +960,8 @@ impl MemgraphClient {
                         .param("call_type", format!("{:?}", call.call_type))
                         .param("cross_crate", call.cross_crate)
                         .param("violates_architecture", violation)
+                        .param("provenance", call.provenance.as_str())
+                        .param("cfg", call.cfg.clone())
                 };
 
                 match self.execute_with_retry(query).await {
@@ -1082,18 +1120,13 @@ impl MemgraphClient {
                 continue;
             }
             
-            // Create SPAWNS relationship between Type:Actor nodes
+            // Merge the SPAWNS relationship on its call site so re-population does not duplicate it
             // Match on Type nodes with Actor label for proper integration
             let query = Query::new(
                 "MATCH (parent:Type:Actor {name: $parent_name, crate: $parent_crate})
                  MATCH (child:Type:Actor {name: $child_name, crate: $child_crate})
-                 CREATE (parent)-[:SPAWNS {
-                     method: $spawn_method,
-                     context: $context,
-                     line: $line,
-                     file_path: $file_path,
-                     spawn_pattern: $spawn_pattern
-                 }]->(child)".to_string()
+                 MERGE (parent)-[r:SPAWNS {line: $line, file_path: $file_path}]->(child)
+                 SET r.method = $spawn_method, r.context = $context, r.spawn_pattern = $spawn_pattern".to_string()
             );
 
             let query = query
@@ -1128,6 +1161,158 @@ impl MemgraphClient {
         Ok(())
     }
 
+    async fn create_reference_relationships(&self, references: &[&FnReference]) -> Result<()> {
+        if references.is_empty() {
+            return Ok(());
+        }
+
+        let mut created_count = 0;
+        for reference in references {
+            let (Some(target), Some(to_crate)) = (&reference.qualified_target, &reference.to_crate) else { continue };
+            let query = Query::new(
+                "MATCH (caller:Function {qualified_name: $caller, crate: $caller_crate})
+                 MATCH (target:Function {qualified_name: $target, crate: $target_crate})
+                 MERGE (caller)-[r:REFERENCES_FN {line: $line}]->(target)
+                 SET r.call_type = 'Reference', r.file = $file, r.cross_crate = $cross_crate,
+                     r.violates_architecture = $violates_architecture, r.cfg = $cfg
+                 RETURN count(*) as created".to_string()
+            )
+            .param("caller", reference.caller_id.clone())
+            .param("caller_crate", reference.from_crate.clone())
+            .param("target", target.clone())
+            .param("target_crate", to_crate.clone())
+            .param("line", reference.line as i64)
+            .param("file", reference.file_path.clone())
+            .param("cross_crate", reference.from_crate != *to_crate)
+            .param("violates_architecture", self.config.is_layer_violation(&reference.from_crate, to_crate))
+            .param("cfg", reference.cfg.clone());
+
+            match self.execute_query_single(query).await {
+                Ok(Some(row)) => created_count += row.get::<i64>("created").unwrap_or(0),
+                Ok(None) => {}
+                Err(e) => eprintln!("⚠️ Failed to create reference edge from {} to {}: {}",
+                                    reference.caller_id, target, e),
+            }
+        }
+
+        eprintln!("🔗 Created {} function reference relationships out of {} references", created_count, references.len());
+        Ok(())
+    }
+
+    async fn create_type_usage_relationships(&self, edges: &[TypeUsageEdge]) -> Result<()> {
+        if edges.is_empty() {
+            return Ok(());
+        }
+
+        let mut created_count = 0;
+        for edge in edges {
+            // Labels cannot be parameters; the user is either a Function or a Type node
+            let user_label = if edge.user_kind == "Type" { "Type" } else { "Function" };
+            let query = Query::new(format!(
+                "MATCH (user:{} {{qualified_name: $user, crate: $user_crate}})
+                 MATCH (type:Type {{qualified_name: $type_name, crate: $type_crate}})
+                 MERGE (user)-[r:USES_TYPE {{line: $line, usage: $usage}}]->(type)
+                 SET r.file = $file
+                 RETURN count(*) as created",
+                user_label
+            ))
+            .param("user", edge.user.clone())
+            .param("user_crate", edge.user_crate.clone())
+            .param("type_name", edge.type_name.clone())
+            .param("type_crate", edge.type_crate.clone())
+            .param("line", edge.line as i64)
+            .param("usage", edge.usage.clone())
+            .param("file", edge.file_path.clone());
+
+            match self.execute_query_single(query).await {
+                Ok(Some(row)) => created_count += row.get::<i64>("created").unwrap_or(0),
+                Ok(None) => {}
+                Err(e) => eprintln!("⚠️ Failed to create type usage edge from {} to {}: {}",
+                                    edge.user, edge.type_name, e),
+            }
+        }
+
+        eprintln!("🧩 Created {} type usage relationships out of {} edges", created_count, edges.len());
+        Ok(())
+    }
+
+    async fn create_dispatch_relationships(&self, edges: &[DispatchEdge]) -> Result<()> {
+        if edges.is_empty() {
+            return Ok(());
+        }
+
+        let mut created_count = 0;
+        for edge in edges {
+            let query = Query::new(
+                "MATCH (caller:Function {qualified_name: $caller, crate: $caller_crate})
+                 MATCH (target:Function {qualified_name: $target, crate: $target_crate})
+                 MERGE (caller)-[r:MAY_DISPATCH_TO {line: $line, file_path: $file_path}]->(target)
+                 SET r.trait_name = $trait_name, r.method = $method, r.receiver = $receiver,
+                     r.implementing_type = $implementing_type
+                 RETURN count(*) as created".to_string()
+            )
+            .param("caller", edge.caller.clone())
+            .param("caller_crate", edge.caller_crate.clone())
+            .param("target", edge.target.clone())
+            .param("target_crate", edge.target_crate.clone())
+            .param("trait_name", edge.trait_name.clone())
+            .param("method", edge.method.clone())
+            .param("receiver", edge.receiver.clone())
+            .param("implementing_type", edge.implementing_type.clone())
+            .param("line", edge.line as i64)
+            .param("file_path", edge.file_path.clone());
+
+            match self.execute_query_single(query).await {
+                Ok(Some(row)) => created_count += row.get::<i64>("created").unwrap_or(0),
+                Ok(None) => {}
+                Err(e) => eprintln!("⚠️ Failed to create dispatch edge from {} to {}: {}",
+                                    edge.caller, edge.target, e),
+            }
+        }
+
+        eprintln!("🔀 Created {} dispatch relationships out of {} edges", created_count, edges.len());
+        Ok(())
+    }
+
+    async fn create_supervision_relationships(&self, edges: &[SupervisionEdge]) -> Result<()> {
+        if edges.is_empty() {
+            return Ok(());
+        }
+
+        let mut created_count = 0;
+        for edge in edges {
+            // MATCH rather than MERGE the endpoints: links to actors we never saw are dropped
+            let query = Query::new(
+                "MATCH (supervisor:Type:Actor {name: $supervisor, crate: $supervisor_crate})
+                 MATCH (child:Type:Actor {name: $child, crate: $child_crate})
+                 MERGE (supervisor)-[r:SUPERVISES {line: $line, file_path: $file_path}]->(child)
+                 SET r.kind = $kind, r.supervisor_on_link_died = $supervisor_on_link_died,
+                     r.child_on_link_died = $child_on_link_died, r.child_on_panic = $child_on_panic
+                 RETURN count(*) as created".to_string()
+            )
+            .param("supervisor", edge.supervisor.clone())
+            .param("supervisor_crate", edge.supervisor_crate.clone())
+            .param("child", edge.child.clone())
+            .param("child_crate", edge.child_crate.clone())
+            .param("kind", edge.kind.clone())
+            .param("supervisor_on_link_died", edge.supervisor_on_link_died.clone().unwrap_or_default())
+            .param("child_on_link_died", edge.child_on_link_died.clone().unwrap_or_default())
+            .param("child_on_panic", edge.child_on_panic.clone().unwrap_or_default())
+            .param("line", edge.line as i64)
+            .param("file_path", edge.file_path.clone());
+
+            match self.execute_query_single(query).await {
+                Ok(Some(row)) => created_count += row.get::<i64>("created").unwrap_or(0),
+                Ok(None) => {}
+                Err(e) => eprintln!("⚠️ Failed to create supervision link from {} to {}: {}",
+                                    edge.supervisor, edge.child, e),
+            }
+        }
+
+        eprintln!("🛡️ Created {} supervision relationships out of {} links", created_count, edges.len());
+        Ok(())
+    }
+
     async fn create_message_type_nodes(&self, message_types: &[MessageType]) -> Result<()> {
         if message_types.is_empty() {
             return Ok(());
@@ -2040,7 +2225,8 @@ impl MemgraphClient {
                          line: $line,
                          call_type: $call_type,
                          cross_crate: $cross_crate,
-                         violates_architecture: $violates_architecture
+                         violates_architecture: $violates_architecture,
+                         provenance: $provenance
                      }]->(callee)".to_string()
                 );
 
@@ -2050,7 +2236,8 @@ impl MemgraphClient {
                     .param("line", call.line as i64)
                     .param("call_type", format!("{:?}", call.call_type))
                     .param("cross_crate", call.cross_crate)
-                    .param("violates_architecture", violation);
+                    .param("violates_architecture", violation)
+                    .param("provenance", call.provenance.as_str());
 
                 match txn.execute(query).await {
                     Ok(_) => {},
@@ -2145,6 +2332,7 @@ impl MemgraphClient {
             actor_type: ActorImplementationType::Unknown.into(),
             local_messages: Vec::new(), // Fallback actors have no message handlers
             inferred_from_message: false, // Fallback actors are not inferred from Message impl
+            cfg: None,
         };
         
         // Create the fallback actor node
@@ -2918,6 +3106,316 @@ impl MemgraphClient {
     }
 }
 
+fn function_node_from(node: &Node) -> FunctionNode {
+    FunctionNode {
+        id: node.get("id").unwrap_or_default(),
+        name: node.get("name").unwrap_or_default(),
+        qualified_name: node.get("qualified_name").unwrap_or_default(),
+        crate_name: node.get("crate").unwrap_or_default(),
+        file: node.get("file").unwrap_or_default(),
+        line_start: node.get::<i64>("line_start").unwrap_or(0) as usize,
+        visibility: node.get("visibility").unwrap_or_default(),
+        is_test: node.get("is_test").unwrap_or(false),
+        cfg: node.get("cfg").ok(),
+    }
+}
+
+fn call_edge_from(row: &Row) -> Option<CallEdge> {
+    let caller: Node = row.get("caller").ok()?;
+    let callee: Node = row.get("callee").ok()?;
+    let call: Relation = row.get("call").ok()?;
+
+    Some(CallEdge {
+        caller: function_node_from(&caller),
+        callee: function_node_from(&callee),
+        line: call.get::<i64>("line").unwrap_or(0) as usize,
+        call_type: call.get("call_type").unwrap_or_default(),
+        cross_crate: call.get("cross_crate").unwrap_or(false),
+        violates_architecture: call.get("violates_architecture").unwrap_or(false),
+        violation_kind: call.get("violation_kind").ok(),
+        violation_severity: call.get("violation_severity").ok(),
+        provenance: call.get("provenance").unwrap_or_else(|_| CallProvenance::TreeSitter.as_str().to_string()),
+        cfg: call.get("cfg").ok(),
+    })
+}
+
+#[async_trait::async_trait]
+impl GraphBackend for MemgraphClient {
+    fn kind(&self) -> GraphBackendKind {
+        GraphBackendKind::Memgraph
+    }
+
+    async fn clear_workspace(&self) -> Result<()> {
+        MemgraphClient::clear_workspace(self).await
+    }
+
+    async fn create_crate_nodes(&self, crates: &[crate::workspace::CrateMetadata]) -> Result<()> {
+        MemgraphClient::create_crate_nodes(self, crates).await
+    }
+
+    async fn populate_from_symbols(&self, symbols: &ParsedSymbols) -> Result<()> {
+        MemgraphClient::populate_from_symbols(self, symbols).await
+    }
+
+    async fn remove_file(&self, file_path: &str) -> Result<()> {
+        for query in queries::remove_file(file_path) {
+            let _ = self.execute_query(query.into()).await;
+        }
+
+        Ok(())
+    }
+
+    async fn get_statistics(&self) -> Result<GraphStatistics> {
+        MemgraphClient::get_statistics(self).await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        MemgraphClient::health_check(self).await
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        MemgraphClient::test_connection(self).await
+    }
+
+    async fn verify_population(&self) -> Result<()> {
+        MemgraphClient::verify_population(self).await
+    }
+
+    async fn get_crates(&self) -> Result<Vec<CrateNode>> {
+        let query = queries::crates();
+
+        let mut crates = Vec::new();
+        for row in self.execute_query(query.into()).await? {
+            let layer: String = row.get("layer").unwrap_or_default();
+            crates.push(CrateNode {
+                name: row.get("name").unwrap_or_default(),
+                layer: if layer.is_empty() { None } else { Some(layer) },
+                is_workspace: row.get("is_workspace").unwrap_or(false),
+                dependencies: row.get("dependencies").unwrap_or_default(),
+            });
+        }
+        Ok(crates)
+    }
+
+    async fn functions(&self) -> Result<Vec<FunctionNode>> {
+        let query = queries::functions();
+
+        let rows = self.execute_query(query.into()).await?;
+        Ok(rows.iter()
+            .filter_map(|row| row.get::<Node>("f").ok())
+            .map(|node| function_node_from(&node))
+            .collect())
+    }
+
+    async fn calls(&self) -> Result<Vec<CallEdge>> {
+        let query = queries::calls();
+
+        Ok(self.execute_query(query.into()).await?.iter().filter_map(call_edge_from).collect())
+    }
+
+    async fn find_callers(&self, qualified_name: &str) -> Result<Vec<CallEdge>> {
+        let query = queries::callers_of(qualified_name);
+
+        Ok(self.execute_query(query.into()).await?.iter().filter_map(call_edge_from).collect())
+    }
+
+    async fn find_referrers(&self, qualified_name: &str) -> Result<Vec<CallEdge>> {
+        let query = queries::referrers_of(qualified_name);
+
+        Ok(self.execute_query(query.into()).await?.iter().filter_map(call_edge_from).collect())
+    }
+
+    async fn find_callees(&self, qualified_name: &str) -> Result<Vec<CallEdge>> {
+        let query = queries::callees_of(qualified_name);
+
+        Ok(self.execute_query(query.into()).await?.iter().filter_map(call_edge_from).collect())
+    }
+
+    async fn cross_crate_calls(&self) -> Result<Vec<CallEdge>> {
+        let query = queries::cross_crate_calls();
+
+        Ok(self.execute_query(query.into()).await?.iter().filter_map(call_edge_from).collect())
+    }
+
+    async fn find_unreferenced_functions(&self) -> Result<Vec<FunctionNode>> {
+        let query = queries::unreferenced_functions();
+
+        let rows = self.execute_query(query.into()).await?;
+        Ok(rows.iter()
+            .filter_map(|row| row.get::<Node>("f").ok())
+            .map(|node| function_node_from(&node))
+            .collect())
+    }
+
+    async fn actor_spawns(&self) -> Result<Vec<SpawnEdge>> {
+        let query = queries::actor_spawns();
+
+        let mut spawns = Vec::new();
+        for row in self.execute_query(query.into()).await? {
+            spawns.push(SpawnEdge {
+                parent: row.get("parent").unwrap_or_default(),
+                parent_crate: row.get("parent_crate").unwrap_or_default(),
+                child: row.get("child").unwrap_or_default(),
+                child_crate: row.get("child_crate").unwrap_or_default(),
+                method: row.get("method").unwrap_or_default(),
+                context: row.get("context").unwrap_or_default(),
+                line: row.get::<i64>("line").unwrap_or(0) as usize,
+                file_path: row.get("file_path").unwrap_or_default(),
+            });
+        }
+        Ok(spawns)
+    }
+
+    async fn supervision_links(&self) -> Result<Vec<SupervisionEdge>> {
+        let query = queries::supervision_links();
+        // Hooks are stored as "" when the actor keeps kameo's default
+        let hook = |value: Option<String>| value.filter(|v| !v.is_empty());
+
+        let mut edges = Vec::new();
+        for row in self.execute_query(query.into()).await? {
+            edges.push(SupervisionEdge {
+                supervisor: row.get("supervisor").unwrap_or_default(),
+                supervisor_crate: row.get("supervisor_crate").unwrap_or_default(),
+                child: row.get("child").unwrap_or_default(),
+                child_crate: row.get("child_crate").unwrap_or_default(),
+                kind: row.get("kind").unwrap_or_default(),
+                supervisor_on_link_died: hook(row.get("supervisor_on_link_died").ok()),
+                child_on_link_died: hook(row.get("child_on_link_died").ok()),
+                child_on_panic: hook(row.get("child_on_panic").ok()),
+                line: row.get::<i64>("line").unwrap_or(0) as usize,
+                file_path: row.get("file_path").unwrap_or_default(),
+            });
+        }
+        Ok(edges)
+    }
+
+    async fn type_users(&self, qualified_name: &str) -> Result<Vec<TypeUsageEdge>> {
+        let query = queries::type_users(qualified_name);
+        let mut edges = Vec::new();
+        for row in self.execute_query(query.into()).await? {
+            edges.push(TypeUsageEdge {
+                user: row.get("user").unwrap_or_default(),
+                user_crate: row.get("user_crate").unwrap_or_default(),
+                user_kind: row.get("user_kind").unwrap_or_default(),
+                type_name: row.get("type_name").unwrap_or_default(),
+                type_crate: row.get("type_crate").unwrap_or_default(),
+                usage: row.get("usage").unwrap_or_default(),
+                line: row.get::<i64>("line").unwrap_or(0) as usize,
+                file_path: row.get("file_path").unwrap_or_default(),
+            });
+        }
+        Ok(edges)
+    }
+
+    async fn trait_dispatches(&self) -> Result<Vec<DispatchEdge>> {
+        let query = queries::trait_dispatches();
+        let mut edges = Vec::new();
+        for row in self.execute_query(query.into()).await? {
+            edges.push(DispatchEdge {
+                caller: row.get("caller").unwrap_or_default(),
+                caller_crate: row.get("caller_crate").unwrap_or_default(),
+                target: row.get("target").unwrap_or_default(),
+                target_crate: row.get("target_crate").unwrap_or_default(),
+                trait_name: row.get("trait_name").unwrap_or_default(),
+                method: row.get("method").unwrap_or_default(),
+                receiver: row.get("receiver").unwrap_or_default(),
+                implementing_type: row.get("implementing_type").unwrap_or_default(),
+                line: row.get::<i64>("line").unwrap_or(0) as usize,
+                file_path: row.get("file_path").unwrap_or_default(),
+            });
+        }
+        Ok(edges)
+    }
+
+    async fn message_handlers(&self) -> Result<Vec<HandlerEdge>> {
+        let query = queries::message_handlers();
+
+        let mut handlers = Vec::new();
+        for row in self.execute_query(query.into()).await? {
+            handlers.push(HandlerEdge {
+                actor: row.get("actor").unwrap_or_default(),
+                actor_crate: row.get("actor_crate").unwrap_or_default(),
+                message_type: row.get("message_type").unwrap_or_default(),
+                reply_type: row.get("reply_type").unwrap_or_default(),
+                is_async: row.get("is_async").unwrap_or(false),
+                line: row.get::<i64>("line").unwrap_or(0) as usize,
+                file_path: row.get("file_path").unwrap_or_default(),
+            });
+        }
+        Ok(handlers)
+    }
+
+    async fn message_sends(&self) -> Result<Vec<SendEdge>> {
+        let query = queries::message_sends();
+
+        let mut sends = Vec::new();
+        for row in self.execute_query(query.into()).await? {
+            sends.push(SendEdge {
+                sender: row.get("sender").unwrap_or_default(),
+                sender_crate: row.get("sender_crate").unwrap_or_default(),
+                receiver: row.get("receiver").unwrap_or_default(),
+                receiver_crate: row.get("receiver_crate").ok(),
+                message_type: row.get("message_type").unwrap_or_default(),
+                method: row.get("method").unwrap_or_default(),
+                line: row.get::<i64>("line").unwrap_or(0) as usize,
+                file_path: row.get("file_path").unwrap_or_default(),
+            });
+        }
+        Ok(sends)
+    }
+
+    async fn mark_call_violation(&self, caller: &str, callee: &str, kind: &str, severity: &str) -> Result<()> {
+        let query = queries::mark_call_violation(caller, callee, kind, severity);
+
+        self.execute_query(query.into()).await?;
+        Ok(())
+    }
+
+    async fn transitive_callers(&self, qualified_name: &str, depth: usize) -> Result<Vec<(FunctionNode, usize)>> {
+        // Variable-length bounds cannot be parameters; depth is a plain integer
+        let query = queries::transitive_callers(qualified_name, depth);
+
+        let rows = self.execute_query(query.into()).await?;
+        Ok(rows.iter()
+            .filter_map(|row| {
+                let node: Node = row.get("caller").ok()?;
+                let distance: i64 = row.get("distance").ok()?;
+                Some((function_node_from(&node), distance as usize))
+            })
+            .collect())
+    }
+
+    async fn similar_functions(&self, vector: &[f32], limit: usize, caller_of: Option<&str>) -> Result<Vec<(FunctionNode, f32)>> {
+        let Some(target) = caller_of else {
+            let rows = self.execute_query(queries::similar_functions(vector.to_vec(), limit).into()).await?;
+            return Ok(rows.iter()
+                .filter_map(|row| {
+                    let node: Node = row.get("f").ok()?;
+                    let similarity: f64 = row.get("similarity").ok()?;
+                    Some((function_node_from(&node), similarity as f32))
+                })
+                .collect());
+        };
+
+        // Rank every caller exactly instead of filtering an approximate top-k
+        let rows = self.execute_query(queries::embedded_callers_of(target).into()).await?;
+        let mut results: Vec<(FunctionNode, f32)> = rows.iter()
+            .filter_map(|row| {
+                let node: Node = row.get("f").ok()?;
+                let embedding: Vec<f32> = row.get::<Vec<f64>>("embedding").ok()?.into_iter().map(|v| v as f32).collect();
+                Some((function_node_from(&node), cosine_similarity(vector, &embedding)))
+            })
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.qualified_name.cmp(&b.0.qualified_name)));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    async fn execute_query(&self, query: CypherQuery) -> Result<Vec<Row>> {
+        MemgraphClient::execute_query(self, query.into()).await
+    }
+}
+
 /// Optimized import pipeline following Memgraph best practices
 pub struct ImportPipeline {
     client: MemgraphClient,
@@ -3001,11 +3499,11 @@ impl ImportPipeline {
         }
         
         if !symbols.types.is_empty() {
-            self.import_types_batch(&symbols.types).await?;
+            self.import_types_batch(&symbols.types, &symbols.embeddings).await?;
         }
         
         if !symbols.functions.is_empty() {
-            self.import_functions_batch(&symbols.functions).await?;
+            self.import_functions_batch(&symbols.functions, &symbols.embeddings).await?;
         }
         
         if !symbols.actors.is_empty() {
@@ -3027,6 +3525,16 @@ impl ImportPipeline {
             self.client.create_call_relationships(&symbols.calls).await?;
         }
         
+        if !symbols.type_usages.is_empty() {
+            self.client.create_type_usage_relationships(&collect_type_usage_edges(symbols)).await?;
+        }
+        if !symbols.fn_references.is_empty() {
+            self.client.create_reference_relationships(&collect_fn_references(symbols)).await?;
+        }
+        if !symbols.trait_dispatches.is_empty() {
+            self.client.create_dispatch_relationships(&collect_dispatch_edges(symbols)).await?;
+        }
+        
         if !symbols.impls.is_empty() {
             self.client.create_impl_relationships(&symbols.impls).await?;
         }
@@ -3035,6 +3543,10 @@ impl ImportPipeline {
             self.client.create_actor_spawn_relationships(&symbols.actor_spawns).await?;
         }
         
+        if !symbols.actor_links.is_empty() {
+            self.client.create_supervision_relationships(&collect_supervision_edges(symbols)).await?;
+        }
+        
         if !symbols.message_handlers.is_empty() {
             self.client.create_message_handler_relationships(&symbols.message_handlers).await?;
         }
@@ -3057,23 +3569,23 @@ impl ImportPipeline {
         Ok(())
     }
     
-    async fn import_types_batch(&self, types: &[RustType]) -> Result<()> {
+    async fn import_types_batch(&self, types: &[RustType], embeddings: &HashMap<String, Vec<f32>>) -> Result<()> {
         eprintln!("📐 Batch importing {} types", types.len());
         
         let batches: Vec<_> = types.chunks(self.batch_size).collect();
         for batch in batches {
-            self.client.create_type_nodes(batch).await?;
+            self.client.create_type_nodes(batch, embeddings).await?;
         }
         
         Ok(())
     }
     
-    async fn import_functions_batch(&self, functions: &[RustFunction]) -> Result<()> {
+    async fn import_functions_batch(&self, functions: &[RustFunction], embeddings: &HashMap<String, Vec<f32>>) -> Result<()> {
         eprintln!("🔧 Batch importing {} functions", functions.len());
         
         let batches: Vec<_> = functions.chunks(self.batch_size).collect();
         for batch in batches {
-            self.client.create_function_nodes(batch).await?;
+            self.client.create_function_nodes(batch, embeddings).await?;
         }
         
         Ok(())
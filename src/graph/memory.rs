@@ -0,0 +1,862 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::RwLock;
+
+use crate::analyzer::WorkspaceSnapshot;
+use crate::config::{Config, GraphBackendKind};
+use crate::graph::backend::{
+    collect_dispatch_edges, collect_fn_references, collect_function_nodes, collect_supervision_edges, collect_type_usage_edges,
+    CallEdge, CrateNode, DispatchEdge, FunctionNode, GraphBackend, HandlerEdge, SendEdge, SpawnEdge, SupervisionEdge,
+    TypeUsageEdge,
+};
+use crate::embeddings::cosine_similarity;
+use crate::graph::GraphStatistics;
+use crate::parser::symbols::{CallProvenance, FunctionCall, ParsedSymbols, SendMethod};
+use crate::workspace::CrateMetadata;
+
+#[derive(Debug, Clone)]
+struct CallRecord {
+    caller_id: String,
+    callee_id: String,
+    line: usize,
+    call_type: String,
+    cross_crate: bool,
+    file: String,
+    violates_architecture: bool,
+    violation_kind: Option<String>,
+    violation_severity: Option<String>,
+    provenance: CallProvenance,
+    cfg: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct TypeRecord {
+    qualified_name: String,
+    crate_name: String,
+    file: String,
+}
+
+#[derive(Debug, Default)]
+struct GraphState {
+    crates: BTreeMap<String, CrateNode>,
+    functions: BTreeMap<String, FunctionNode>,
+    function_embeddings: HashMap<String, Vec<f32>>,
+    calls: Vec<CallRecord>,
+    /// REFERENCES_FN edges, stored like calls with the `Reference` call type
+    references: Vec<CallRecord>,
+    types: BTreeMap<String, TypeRecord>,
+    modules: BTreeMap<String, String>,
+    actors: HashSet<(String, String)>,
+    implements_edges: usize,
+    spawns: Vec<SpawnEdge>,
+    supervision: Vec<SupervisionEdge>,
+    dispatches: Vec<DispatchEdge>,
+    type_usages: Vec<TypeUsageEdge>,
+    handlers: Vec<HandlerEdge>,
+    sends: Vec<SendEdge>,
+}
+
+/// Embedded graph backend that keeps nodes and relationships in process memory.
+///
+/// Mirrors the node and relationship model `MemgraphClient` writes, so analysis works
+/// on machines without a Memgraph server. Selected with `[graph] backend = "memory"`.
+pub struct InMemoryGraph {
+    config: Config,
+    state: RwLock<GraphState>,
+}
+
+impl InMemoryGraph {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            state: RwLock::new(GraphState::default()),
+        }
+    }
+
+    pub fn from_symbols(config: Config, symbols: &ParsedSymbols) -> Self {
+        let graph = Self::new(config);
+        graph.insert_symbols(symbols);
+        graph
+    }
+
+    pub fn from_snapshot(config: Config, snapshot: &WorkspaceSnapshot) -> Self {
+        let graph = Self::new(config);
+        graph.insert_crates(&snapshot.crates);
+
+        // Merge per-crate symbols in a stable order so edge lists are reproducible
+        let mut crate_names: Vec<&String> = snapshot.symbols.keys().collect();
+        crate_names.sort();
+        let mut all_symbols = ParsedSymbols::new();
+        for crate_name in crate_names {
+            all_symbols.merge(snapshot.symbols[crate_name].clone());
+        }
+
+        graph.insert_symbols(&all_symbols);
+        graph
+    }
+
+    fn insert_crates(&self, crates: &[CrateMetadata]) {
+        let mut state = self.state.write().unwrap();
+        for crate_meta in crates {
+            state.crates.insert(crate_meta.name.clone(), CrateNode {
+                name: crate_meta.name.clone(),
+                layer: crate_meta.layer.clone().filter(|layer| !layer.is_empty()),
+                is_workspace: crate_meta.is_workspace_member,
+                dependencies: crate_meta.dependencies.clone(),
+            });
+        }
+    }
+
+    fn insert_symbols(&self, symbols: &ParsedSymbols) {
+        let mut state = self.state.write().unwrap();
+
+        for module in &symbols.modules {
+            state.modules.insert(module.path.clone(), module.file_path.clone());
+        }
+
+        for function in collect_function_nodes(symbols) {
+            if let Some(vector) = symbols.embeddings.get(&function.id) {
+                state.function_embeddings.insert(function.id.clone(), vector.clone());
+            }
+            state.functions.insert(function.id.clone(), FunctionNode {
+                id: function.id,
+                name: function.name,
+                qualified_name: function.qualified_name,
+                crate_name: function.crate_name,
+                file: function.file_path,
+                line_start: function.line_start,
+                visibility: function.visibility,
+                is_test: function.is_test,
+                cfg: function.cfg,
+            });
+        }
+
+        for rust_type in &symbols.types {
+            state.types.insert(rust_type.id.clone(), TypeRecord {
+                qualified_name: rust_type.qualified_name.clone(),
+                crate_name: rust_type.crate_name.clone(),
+                file: rust_type.file_path.clone(),
+            });
+        }
+
+        let mut by_qualified: HashMap<String, Vec<String>> = HashMap::new();
+        let mut by_name_and_crate: HashMap<(String, String), Vec<String>> = HashMap::new();
+        for function in state.functions.values() {
+            by_qualified.entry(function.qualified_name.clone()).or_default().push(function.id.clone());
+            by_name_and_crate
+                .entry((function.name.clone(), function.crate_name.clone()))
+                .or_default()
+                .push(function.id.clone());
+        }
+
+        let mut existing: HashSet<(String, String, usize)> = state.calls.iter()
+            .map(|c| (c.caller_id.clone(), c.callee_id.clone(), c.line))
+            .collect();
+
+        for call in &symbols.calls {
+            // Calls name their caller by qualified name; prefer the definition in the call's file
+            let Some(caller_id) = by_qualified.get(&call.caller_id).and_then(|ids| {
+                let in_crate = || ids.iter().filter(|id| state.functions[*id].crate_name == call.from_crate);
+                in_crate().find(|id| state.functions[*id].file == call.file_path).or_else(|| in_crate().next()).cloned()
+            }) else {
+                continue;
+            };
+
+            for callee_id in self.resolve_callees(&mut state, call, &by_qualified, &by_name_and_crate) {
+                if !existing.insert((caller_id.clone(), callee_id.clone(), call.line)) {
+                    continue;
+                }
+                let violation = call.to_crate.as_ref()
+                    .map(|to_crate| self.config.is_layer_violation(&call.from_crate, to_crate))
+                    .unwrap_or(false);
+                state.calls.push(CallRecord {
+                    caller_id: caller_id.clone(),
+                    callee_id,
+                    line: call.line,
+                    call_type: format!("{:?}", call.call_type),
+                    cross_crate: call.cross_crate,
+                    file: call.file_path.clone(),
+                    violates_architecture: violation,
+                    violation_kind: None,
+                    violation_severity: None,
+                    provenance: call.provenance,
+                    cfg: call.cfg.clone(),
+                });
+            }
+        }
+
+        for reference in collect_fn_references(symbols) {
+            let (Some(target), Some(to_crate)) = (&reference.qualified_target, &reference.to_crate) else { continue };
+            let find = |name: &str, crate_name: &str| {
+                by_qualified.get(name)?.iter().find(|id| state.functions[*id].crate_name == crate_name).cloned()
+            };
+            let (Some(caller_id), Some(callee_id)) = (find(&reference.caller_id, &reference.from_crate), find(target, to_crate)) else {
+                continue;
+            };
+            state.references.push(CallRecord {
+                caller_id,
+                callee_id,
+                line: reference.line,
+                call_type: "Reference".to_string(),
+                cross_crate: reference.from_crate != *to_crate,
+                file: reference.file_path.clone(),
+                violates_architecture: self.config.is_layer_violation(&reference.from_crate, to_crate),
+                violation_kind: None,
+                violation_severity: None,
+                provenance: CallProvenance::TreeSitter,
+                cfg: reference.cfg.clone(),
+            });
+        }
+
+        let type_names: HashSet<&str> = symbols.types.iter().map(|t| t.name.as_str()).collect();
+        state.implements_edges += symbols.impls.iter()
+            .filter_map(|i| i.trait_name.as_deref())
+            .filter(|trait_name| type_names.contains(trait_name))
+            .count();
+
+        for actor in &symbols.actors {
+            state.actors.insert((actor.name.clone(), actor.crate_name.clone()));
+        }
+
+        for spawn in &symbols.actor_spawns {
+            let parent = (spawn.parent_actor_name.clone(), spawn.from_crate.clone());
+            let child = (spawn.child_actor_name.clone(), spawn.to_crate.clone());
+            if !state.actors.contains(&parent) || !state.actors.contains(&child) {
+                continue;
+            }
+            state.spawns.push(SpawnEdge {
+                parent: spawn.parent_actor_name.clone(),
+                parent_crate: spawn.from_crate.clone(),
+                child: spawn.child_actor_name.clone(),
+                child_crate: spawn.to_crate.clone(),
+                method: format!("{:?}", spawn.spawn_method),
+                context: spawn.context.clone(),
+                line: spawn.line,
+                file_path: spawn.file_path.clone(),
+            });
+        }
+
+        for edge in collect_supervision_edges(symbols) {
+            let supervisor = (edge.supervisor.clone(), edge.supervisor_crate.clone());
+            let child = (edge.child.clone(), edge.child_crate.clone());
+            if state.actors.contains(&supervisor) && state.actors.contains(&child) {
+                state.supervision.push(edge);
+            }
+        }
+
+        let function_names: HashSet<String> = state.functions.values().map(|f| f.qualified_name.clone()).collect();
+        for edge in collect_dispatch_edges(symbols) {
+            if function_names.contains(&edge.caller) && function_names.contains(&edge.target) {
+                state.dispatches.push(edge);
+            }
+        }
+
+        let type_keys: HashSet<(&str, &str)> = state.types.values()
+            .map(|t| (t.qualified_name.as_str(), t.crate_name.as_str()))
+            .collect();
+        let function_keys: HashSet<(&str, &str)> = state.functions.values()
+            .map(|f| (f.qualified_name.as_str(), f.crate_name.as_str()))
+            .collect();
+        let usages: Vec<TypeUsageEdge> = collect_type_usage_edges(symbols).into_iter()
+            .filter(|edge| type_keys.contains(&(edge.type_name.as_str(), edge.type_crate.as_str())))
+            .filter(|edge| {
+                let user = (edge.user.as_str(), edge.user_crate.as_str());
+                if edge.user_kind == "Type" { type_keys.contains(&user) } else { function_keys.contains(&user) }
+            })
+            .collect();
+        state.type_usages.extend(usages);
+
+        for handler in &symbols.message_handlers {
+            state.actors.insert((handler.actor_name.clone(), handler.crate_name.clone()));
+            state.handlers.push(HandlerEdge {
+                actor: handler.actor_name.clone(),
+                actor_crate: handler.crate_name.clone(),
+                message_type: handler.message_type.clone(),
+                reply_type: handler.reply_type.clone(),
+                is_async: handler.is_async,
+                line: handler.line,
+                file_path: handler.file_path.clone(),
+            });
+        }
+
+        for send in &symbols.message_sends {
+            if send.sender_actor == "Unknown" || send.message_type == "Unknown" {
+                continue;
+            }
+            let receiver_crate = send.to_crate.clone().or_else(|| {
+                state.actors.iter()
+                    .find(|(name, _)| *name == send.receiver_actor)
+                    .map(|(_, crate_name)| crate_name.clone())
+            });
+            state.sends.push(SendEdge {
+                sender: send.sender_actor.clone(),
+                sender_crate: send.from_crate.clone(),
+                receiver: send.receiver_actor.clone(),
+                receiver_crate,
+                message_type: send.message_type.clone(),
+                method: match send.send_method {
+                    SendMethod::Tell => "Tell".to_string(),
+                    SendMethod::Ask => "Ask".to_string(),
+                },
+                line: send.line,
+                file_path: send.file_path.clone(),
+            });
+        }
+    }
+
+    /// Resolve a call to callee ids the same way `MemgraphClient` matches CALLS targets:
+    /// by qualified name when known, otherwise by bare name within the calling crate.
+    /// Unresolvable synthetic calls get a placeholder node, as they do in Memgraph.
+    fn resolve_callees(
+        &self,
+        state: &mut GraphState,
+        call: &FunctionCall,
+        by_qualified: &HashMap<String, Vec<String>>,
+        by_name_and_crate: &HashMap<(String, String), Vec<String>>,
+    ) -> Vec<String> {
+        let target = call.qualified_callee.as_ref().unwrap_or(&call.callee_name);
+
+        if call.is_synthetic {
+            if let Some(ids) = by_qualified.get(target) {
+                return ids.iter().take(1).cloned().collect();
+            }
+            let parts: Vec<&str> = target.split("::").collect();
+            let suffix = if parts.len() >= 3 {
+                format!("::{}", parts[parts.len() - 3..].join("::"))
+            } else {
+                format!("::{}", target)
+            };
+            if let Some(function) = state.functions.values().find(|f| f.qualified_name.ends_with(&suffix)) {
+                return vec![function.id.clone()];
+            }
+
+            let synthetic_id = format!("synthetic:{}", target);
+            state.functions.entry(synthetic_id.clone()).or_insert_with(|| FunctionNode {
+                id: synthetic_id.clone(),
+                name: target.rsplit("::").next().unwrap_or(target).to_string(),
+                qualified_name: target.clone(),
+                crate_name: call.from_crate.clone(),
+                file: String::new(),
+                line_start: 0,
+                visibility: String::new(),
+                is_test: false,
+                cfg: None,
+            });
+            return vec![synthetic_id];
+        }
+
+        if call.qualified_callee.is_some() {
+            by_qualified.get(target).cloned().unwrap_or_default()
+        } else {
+            by_name_and_crate
+                .get(&(call.callee_name.clone(), call.from_crate.clone()))
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+
+    fn edge(state: &GraphState, record: &CallRecord) -> Option<CallEdge> {
+        Some(CallEdge {
+            caller: state.functions.get(&record.caller_id)?.clone(),
+            callee: state.functions.get(&record.callee_id)?.clone(),
+            line: record.line,
+            call_type: record.call_type.clone(),
+            cross_crate: record.cross_crate,
+            violates_architecture: record.violates_architecture,
+            violation_kind: record.violation_kind.clone(),
+            violation_severity: record.violation_severity.clone(),
+            provenance: record.provenance.as_str().to_string(),
+            cfg: record.cfg.clone(),
+        })
+    }
+
+    fn edges_where(&self, predicate: impl Fn(&FunctionNode, &FunctionNode) -> bool) -> Vec<CallEdge> {
+        let state = self.state.read().unwrap();
+        state.calls.iter()
+            .filter_map(|record| Self::edge(&state, record))
+            .filter(|edge| predicate(&edge.caller, &edge.callee))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl GraphBackend for InMemoryGraph {
+    fn kind(&self) -> GraphBackendKind {
+        GraphBackendKind::Memory
+    }
+
+    async fn clear_workspace(&self) -> Result<()> {
+        *self.state.write().unwrap() = GraphState::default();
+        Ok(())
+    }
+
+    async fn create_crate_nodes(&self, crates: &[CrateMetadata]) -> Result<()> {
+        self.insert_crates(crates);
+        eprintln!("📦 Processed {} crate nodes (in-memory)", crates.len());
+        Ok(())
+    }
+
+    async fn populate_from_symbols(&self, symbols: &ParsedSymbols) -> Result<()> {
+        let start = std::time::Instant::now();
+        self.insert_symbols(symbols);
+        eprintln!("📊 In-memory graph populated in {}ms", start.elapsed().as_millis());
+        Ok(())
+    }
+
+    async fn remove_file(&self, file_path: &str) -> Result<()> {
+        let mut state = self.state.write().unwrap();
+        let removed: HashSet<String> = state.functions.values()
+            .filter(|f| f.file == file_path)
+            .map(|f| f.id.clone())
+            .collect();
+
+        state.functions.retain(|id, _| !removed.contains(id));
+        state.function_embeddings.retain(|id, _| !removed.contains(id));
+        state.calls.retain(|c| {
+            c.file != file_path && !removed.contains(&c.caller_id) && !removed.contains(&c.callee_id)
+        });
+        state.references.retain(|r| {
+            r.file != file_path && !removed.contains(&r.caller_id) && !removed.contains(&r.callee_id)
+        });
+        state.types.retain(|_, t| t.file != file_path);
+        state.modules.retain(|_, file| file != file_path);
+        state.spawns.retain(|s| s.file_path != file_path);
+        state.supervision.retain(|s| s.file_path != file_path);
+        let GraphState { functions, dispatches, .. } = &mut *state;
+        let remaining: HashSet<&str> = functions.values().map(|f| f.qualified_name.as_str()).collect();
+        dispatches.retain(|d| {
+            d.file_path != file_path && remaining.contains(d.caller.as_str()) && remaining.contains(d.target.as_str())
+        });
+        let GraphState { functions, types, type_usages, .. } = &mut *state;
+        let remaining_types: HashSet<(&str, &str)> = types.values().map(|t| (t.qualified_name.as_str(), t.crate_name.as_str())).collect();
+        let remaining_functions: HashSet<(&str, &str)> = functions.values().map(|f| (f.qualified_name.as_str(), f.crate_name.as_str())).collect();
+        type_usages.retain(|u| {
+            let user = (u.user.as_str(), u.user_crate.as_str());
+            let user_remains = if u.user_kind == "Type" { remaining_types.contains(&user) } else { remaining_functions.contains(&user) };
+            u.file_path != file_path && user_remains && remaining_types.contains(&(u.type_name.as_str(), u.type_crate.as_str()))
+        });
+        state.handlers.retain(|h| h.file_path != file_path);
+        state.sends.retain(|s| s.file_path != file_path);
+        Ok(())
+    }
+
+    async fn get_statistics(&self) -> Result<GraphStatistics> {
+        let state = self.state.read().unwrap();
+        Ok(GraphStatistics {
+            crate_nodes: state.crates.len(),
+            function_nodes: state.functions.len(),
+            type_nodes: state.types.len(),
+            module_nodes: state.modules.len(),
+            actor_nodes: state.actors.len(),
+            call_edges: state.calls.len(),
+            implements_edges: state.implements_edges,
+            spawn_edges: state.spawns.len(),
+            depends_on_edges: state.crates.values()
+                .flat_map(|c| c.dependencies.iter())
+                .filter(|dep| state.crates.contains_key(*dep))
+                .count(),
+        })
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn verify_population(&self) -> Result<()> {
+        let stats = self.get_statistics().await?;
+        eprintln!("🔍 In-memory graph: {} functions, {} types, {} CALLS, {} SPAWNS",
+            stats.function_nodes, stats.type_nodes, stats.call_edges, stats.spawn_edges);
+        Ok(())
+    }
+
+    async fn get_crates(&self) -> Result<Vec<CrateNode>> {
+        let state = self.state.read().unwrap();
+        Ok(state.crates.values()
+            .map(|c| CrateNode {
+                dependencies: c.dependencies.iter()
+                    .filter(|dep| state.crates.contains_key(*dep))
+                    .cloned()
+                    .collect(),
+                ..c.clone()
+            })
+            .collect())
+    }
+
+    async fn functions(&self) -> Result<Vec<FunctionNode>> {
+        let state = self.state.read().unwrap();
+        let mut functions: Vec<FunctionNode> = state.functions.values().cloned().collect();
+        functions.sort_by(|a, b| (&a.crate_name, &a.name).cmp(&(&b.crate_name, &b.name)));
+        Ok(functions)
+    }
+
+    async fn calls(&self) -> Result<Vec<CallEdge>> {
+        Ok(self.edges_where(|_, _| true))
+    }
+
+    async fn find_callers(&self, qualified_name: &str) -> Result<Vec<CallEdge>> {
+        Ok(self.edges_where(|_, callee| callee.qualified_name == qualified_name))
+    }
+
+    async fn find_referrers(&self, qualified_name: &str) -> Result<Vec<CallEdge>> {
+        let state = self.state.read().unwrap();
+        Ok(state.references.iter()
+            .filter_map(|record| Self::edge(&state, record))
+            .filter(|edge| edge.callee.qualified_name == qualified_name)
+            .collect())
+    }
+
+    async fn find_callees(&self, qualified_name: &str) -> Result<Vec<CallEdge>> {
+        Ok(self.edges_where(|caller, _| caller.qualified_name == qualified_name))
+    }
+
+    async fn cross_crate_calls(&self) -> Result<Vec<CallEdge>> {
+        Ok(self.edges_where(|caller, callee| caller.crate_name != callee.crate_name))
+    }
+
+    async fn find_unreferenced_functions(&self) -> Result<Vec<FunctionNode>> {
+        let state = self.state.read().unwrap();
+        let called: HashSet<&str> = state.calls.iter().chain(&state.references).map(|c| c.callee_id.as_str()).collect();
+        let mut functions: Vec<FunctionNode> = state.functions.values()
+            .filter(|f| !called.contains(f.id.as_str()))
+            .cloned()
+            .collect();
+        functions.sort_by(|a, b| (&a.crate_name, &a.name).cmp(&(&b.crate_name, &b.name)));
+        Ok(functions)
+    }
+
+    async fn similar_functions(&self, vector: &[f32], limit: usize, caller_of: Option<&str>) -> Result<Vec<(FunctionNode, f32)>> {
+        let callers: Option<HashSet<String>> = match caller_of {
+            Some(target) => Some(self.find_callers(target).await?.into_iter().map(|e| e.caller.id).collect()),
+            None => None,
+        };
+
+        let state = self.state.read().unwrap();
+        let mut results: Vec<(FunctionNode, f32)> = state.function_embeddings.iter()
+            .filter(|(id, _)| callers.as_ref().is_none_or(|callers| callers.contains(*id)))
+            .filter_map(|(id, embedding)| {
+                let function = state.functions.get(id)?;
+                Some((function.clone(), cosine_similarity(vector, embedding)))
+            })
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.qualified_name.cmp(&b.0.qualified_name)));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    async fn actor_spawns(&self) -> Result<Vec<SpawnEdge>> {
+        Ok(self.state.read().unwrap().spawns.clone())
+    }
+
+    async fn supervision_links(&self) -> Result<Vec<SupervisionEdge>> {
+        Ok(self.state.read().unwrap().supervision.clone())
+    }
+
+    async fn type_users(&self, qualified_name: &str) -> Result<Vec<TypeUsageEdge>> {
+        let state = self.state.read().unwrap();
+        let mut users: Vec<TypeUsageEdge> = state.type_usages.iter()
+            .filter(|u| u.type_name == qualified_name)
+            .cloned()
+            .collect();
+        users.sort_by(|a, b| (&a.user_crate, &a.user, a.line).cmp(&(&b.user_crate, &b.user, b.line)));
+        Ok(users)
+    }
+
+    async fn trait_dispatches(&self) -> Result<Vec<DispatchEdge>> {
+        Ok(self.state.read().unwrap().dispatches.clone())
+    }
+
+    async fn message_handlers(&self) -> Result<Vec<HandlerEdge>> {
+        Ok(self.state.read().unwrap().handlers.clone())
+    }
+
+    async fn message_sends(&self) -> Result<Vec<SendEdge>> {
+        Ok(self.state.read().unwrap().sends.clone())
+    }
+
+    async fn mark_call_violation(&self, caller: &str, callee: &str, kind: &str, severity: &str) -> Result<()> {
+        let mut state = self.state.write().unwrap();
+        let GraphState { functions, calls, .. } = &mut *state;
+        for record in calls.iter_mut() {
+            let matches = functions.get(&record.caller_id).is_some_and(|f| f.qualified_name == caller)
+                && functions.get(&record.callee_id).is_some_and(|f| f.qualified_name == callee);
+            if matches {
+                record.violates_architecture = true;
+                record.violation_kind = Some(kind.to_string());
+                record.violation_severity = Some(severity.to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Layer;
+    use crate::graph::incoming_call_counts;
+    use crate::parser::symbols::*;
+    use std::path::PathBuf;
+
+    fn function(crate_name: &str, name: &str, is_test: bool) -> RustFunction {
+        RustFunction {
+            id: format!("{}::{}:1", crate_name, name),
+            name: name.to_string(),
+            qualified_name: format!("{}::{}", crate_name, name),
+            crate_name: crate_name.to_string(),
+            module_path: crate_name.to_string(),
+            file_path: format!("{}/src/lib.rs", crate_name),
+            line_start: 1,
+            line_end: 3,
+            visibility: "pub".to_string(),
+            is_async: false,
+            is_unsafe: false,
+            is_generic: false,
+            is_test,
+            is_trait_impl: false,
+            is_method: false,
+            function_context: FunctionContext::Free,
+            doc_comment: None,
+            signature: format!("pub fn {}()", name),
+            parameters: Vec::new(),
+            return_type: None,
+            embedding_text: None,
+            module: crate_name.to_string(),
+            cfg: None,
+            attributes: Vec::new(),
+        }
+    }
+
+    fn call(from: &RustFunction, to: &RustFunction, line: usize) -> FunctionCall {
+        FunctionCall {
+            caller_id: from.qualified_name.clone(),
+            caller_module: from.module_path.clone(),
+            callee_name: to.name.clone(),
+            qualified_callee: Some(to.qualified_name.clone()),
+            call_type: CallType::Direct,
+            line,
+            cross_crate: from.crate_name != to.crate_name,
+            from_crate: from.crate_name.clone(),
+            to_crate: Some(to.crate_name.clone()),
+            file_path: from.file_path.clone(),
+            is_synthetic: false,
+            macro_context: None,
+            synthetic_confidence: 0.0,
+            provenance: CallProvenance::TreeSitter,
+            receiver_type: None,
+            cfg: None,
+        }
+    }
+
+    fn actor(crate_name: &str, name: &str) -> RustActor {
+        RustActor {
+            id: format!("{}::{}", crate_name, name),
+            name: name.to_string(),
+            qualified_name: format!("{}::{}", crate_name, name),
+            crate_name: crate_name.to_string(),
+            module_path: crate_name.to_string(),
+            file_path: format!("{}/src/lib.rs", crate_name),
+            line_start: 1,
+            line_end: 10,
+            visibility: "pub".to_string(),
+            doc_comment: None,
+            is_distributed: false,
+            is_test: false,
+            actor_type: ActorType::Local,
+            local_messages: Vec::new(),
+            inferred_from_message: false,
+            cfg: None,
+        }
+    }
+
+    fn crate_meta(name: &str, layer: &str, dependencies: &[&str]) -> CrateMetadata {
+        CrateMetadata {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            path: PathBuf::from(name),
+            layer: Some(layer.to_string()),
+            depth: 0,
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            is_workspace_member: true,
+            is_external: false,
+            features: Vec::new(),
+            targets: Vec::new(),
+        }
+    }
+
+    fn sample_graph() -> InMemoryGraph {
+        let mut config = Config::default();
+        config.architecture.layers = vec![
+            Layer { name: "core".to_string(), crates: vec!["core_lib".to_string()] },
+            Layer { name: "app".to_string(), crates: vec!["app".to_string()] },
+        ];
+        config.init_caches();
+
+        let run = function("app", "run", false);
+        let helper = function("core_lib", "helper", false);
+        let leaf = function("core_lib", "leaf", false);
+        let test = function("core_lib", "test_leaf", true);
+
+        let mut symbols = ParsedSymbols::new();
+        symbols.calls = vec![
+            call(&run, &helper, 5),
+            call(&helper, &leaf, 7),
+            call(&helper, &run, 8),
+            call(&test, &leaf, 2),
+        ];
+        symbols.functions = vec![run, helper, leaf, test];
+        symbols.actors = vec![actor("app", "Supervisor"), actor("app", "Worker")];
+        symbols.actor_spawns = vec![ActorSpawn {
+            parent_actor_id: "app::Supervisor".to_string(),
+            parent_actor_name: "Supervisor".to_string(),
+            child_actor_name: "Worker".to_string(),
+            spawn_method: SpawnMethod::Spawn,
+            spawn_pattern: SpawnPattern::DirectType,
+            context: "on_start".to_string(),
+            arguments: None,
+            line: 12,
+            file_path: "app/src/lib.rs".to_string(),
+            from_crate: "app".to_string(),
+            to_crate: "app".to_string(),
+            cfg: None,
+        }];
+        symbols.actor_links = vec![ActorLink {
+            supervisor: "Supervisor".to_string(),
+            child: "Worker".to_string(),
+            kind: LinkKind::SpawnLink,
+            line: 12,
+            file_path: "app/src/lib.rs".to_string(),
+            from_crate: "app".to_string(),
+            cfg: None,
+        }];
+        symbols.actor_hooks = vec![ActorLifecycleHook {
+            actor_name: "Supervisor".to_string(),
+            hook: LifecycleHook::OnLinkDied,
+            behavior: HookBehavior::Restart,
+            line: 20,
+            file_path: "app/src/lib.rs".to_string(),
+            crate_name: "app".to_string(),
+        }];
+        symbols.message_handlers = vec![MessageHandler {
+            id: "app::Worker::Job".to_string(),
+            actor_name: "Worker".to_string(),
+            actor_qualified: "app::Worker".to_string(),
+            message_type: "Job".to_string(),
+            message_qualified: "app::Job".to_string(),
+            reply_type: "()".to_string(),
+            is_async: true,
+            file_path: "app/src/lib.rs".to_string(),
+            line: 30,
+            crate_name: "app".to_string(),
+            cfg: None,
+        }];
+
+        let graph = InMemoryGraph::new(config);
+        graph.insert_crates(&[crate_meta("app", "app", &["core_lib"]), crate_meta("core_lib", "core", &[])]);
+        graph.insert_symbols(&symbols);
+        graph
+    }
+
+    #[tokio::test]
+    async fn test_callers_and_callees() {
+        let graph = sample_graph();
+
+        let callers = graph.find_callers("core_lib::leaf").await.unwrap();
+        let mut names: Vec<_> = callers.iter().map(|e| e.caller.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["helper", "test_leaf"]);
+
+        let callees = graph.find_callees("core_lib::helper").await.unwrap();
+        assert_eq!(callees.len(), 2);
+
+        let transitive = graph.transitive_callers("core_lib::leaf", 3).await.unwrap();
+        assert!(transitive.iter().any(|(f, d)| f.name == "run" && *d == 2));
+    }
+
+    #[tokio::test]
+    async fn test_all_functions_and_call_counts() {
+        let graph = sample_graph();
+
+        let functions = graph.functions().await.unwrap();
+        let names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["run", "helper", "leaf", "test_leaf"]);
+
+        let calls = graph.calls().await.unwrap();
+        assert_eq!(calls.len(), 4);
+        let counts = incoming_call_counts(&calls);
+        assert_eq!(counts.get("core_lib::leaf:1"), Some(&(2, 1)));
+        assert_eq!(counts.get("core_lib::helper:1"), Some(&(1, 0)));
+        assert_eq!(counts.get("core_lib::test_leaf:1"), None);
+    }
+
+    #[tokio::test]
+    async fn test_layer_violation_flag_and_lookup() {
+        let graph = sample_graph();
+
+        assert_eq!(graph.crate_layer("core_lib").await.unwrap().as_deref(), Some("core"));
+
+        let cross = graph.cross_crate_calls().await.unwrap();
+        let upward = cross.iter().find(|e| e.caller.name == "helper" && e.callee.name == "run").unwrap();
+        assert!(upward.violates_architecture);
+        let downward = cross.iter().find(|e| e.caller.name == "run").unwrap();
+        assert!(!downward.violates_architecture);
+    }
+
+    #[tokio::test]
+    async fn test_spawns_handlers_and_unreferenced() {
+        let graph = sample_graph();
+
+        let spawns = graph.actor_spawns().await.unwrap();
+        assert_eq!(spawns.len(), 1);
+        assert_eq!(spawns[0].child, "Worker");
+
+        let handlers = graph.message_handlers().await.unwrap();
+        assert_eq!(handlers[0].message_type, "Job");
+
+        let supervision = graph.supervision_links().await.unwrap();
+        assert_eq!(supervision.len(), 1);
+        assert_eq!(supervision[0].supervisor_on_link_died.as_deref(), Some("restart"));
+        assert_eq!(supervision[0].child_on_link_died, None);
+
+        let unreferenced = graph.find_unreferenced_functions().await.unwrap();
+        let names: Vec<_> = unreferenced.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["test_leaf"]);
+    }
+
+    #[tokio::test]
+    async fn test_similar_functions_filtered_to_callers() {
+        let run = function("app", "run", false);
+        let helper = function("core_lib", "helper", false);
+        let leaf = function("core_lib", "leaf", false);
+
+        let mut symbols = ParsedSymbols::new();
+        symbols.calls = vec![call(&run, &leaf, 5), call(&helper, &leaf, 7)];
+        symbols.embeddings.insert(run.id.clone(), vec![0.9, 0.1]);
+        symbols.embeddings.insert(helper.id.clone(), vec![0.1, 0.9]);
+        symbols.embeddings.insert(leaf.id.clone(), vec![1.0, 0.0]);
+        symbols.functions = vec![run, helper, leaf];
+        let graph = InMemoryGraph::from_symbols(Config::default(), &symbols);
+
+        let nearest = graph.similar_functions(&[1.0, 0.0], 2, None).await.unwrap();
+        let names: Vec<_> = nearest.iter().map(|(f, _)| f.name.as_str()).collect();
+        assert_eq!(names, vec!["leaf", "run"]);
+
+        let callers = graph.similar_functions(&[0.0, 1.0], 5, Some("core_lib::leaf")).await.unwrap();
+        let names: Vec<_> = callers.iter().map(|(f, _)| f.name.as_str()).collect();
+        assert_eq!(names, vec!["helper", "run"]);
+    }
+
+    #[tokio::test]
+    async fn test_remove_file_drops_nodes_and_edges() {
+        let graph = sample_graph();
+        graph.remove_file("core_lib/src/lib.rs").await.unwrap();
+
+        let stats = graph.get_statistics().await.unwrap();
+        assert_eq!(stats.function_nodes, 1);
+        assert_eq!(stats.call_edges, 0);
+    }
+}
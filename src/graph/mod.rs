@@ -1,4 +1,11 @@
+pub mod backend;
 pub mod memgraph_client;
+pub mod memory;
 pub mod pool;
+pub mod queries;
+
+pub use backend::*;
 pub use memgraph_client::*;
+pub use memory::*;
 pub use pool::*;
+pub use queries::{CypherQuery, QueryParam};
@@ -0,0 +1,409 @@
+//! Cypher queries used against the graph, with every caller-supplied value bound as a parameter.
+//!
+//! Query text is assembled only from fixed fragments; crate names, file paths, qualified names and
+//! limits always travel as `$params`. Nothing in here should ever interpolate user input into the text.
+
+use neo4rs::{BoltType, Query};
+
+/// A value bound to a `$name` placeholder
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryParam {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    FloatList(Vec<f32>),
+}
+
+impl From<&str> for QueryParam {
+    fn from(value: &str) -> Self {
+        QueryParam::String(value.to_string())
+    }
+}
+
+impl From<String> for QueryParam {
+    fn from(value: String) -> Self {
+        QueryParam::String(value)
+    }
+}
+
+impl From<i64> for QueryParam {
+    fn from(value: i64) -> Self {
+        QueryParam::Integer(value)
+    }
+}
+
+impl From<usize> for QueryParam {
+    fn from(value: usize) -> Self {
+        QueryParam::Integer(value.min(i64::MAX as usize) as i64)
+    }
+}
+
+impl From<bool> for QueryParam {
+    fn from(value: bool) -> Self {
+        QueryParam::Boolean(value)
+    }
+}
+
+impl From<Vec<f32>> for QueryParam {
+    fn from(value: Vec<f32>) -> Self {
+        QueryParam::FloatList(value)
+    }
+}
+
+impl From<QueryParam> for BoltType {
+    fn from(value: QueryParam) -> Self {
+        match value {
+            QueryParam::String(value) => value.into(),
+            QueryParam::Integer(value) => value.into(),
+            QueryParam::Boolean(value) => value.into(),
+            QueryParam::FloatList(value) => value.into(),
+        }
+    }
+}
+
+/// Cypher text plus its bound parameters.
+///
+/// Unlike `neo4rs::Query` the text and parameters stay inspectable, which is what lets tests assert
+/// that no input leaked into the query text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CypherQuery {
+    text: String,
+    params: Vec<(String, QueryParam)>,
+}
+
+impl CypherQuery {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Append a fixed clause on a new line
+    pub fn clause(mut self, clause: &str) -> Self {
+        self.text.push('\n');
+        self.text.push_str(clause);
+        self
+    }
+
+    /// Bind `$key`; a later binding for the same key replaces the earlier one
+    pub fn param(mut self, key: &str, value: impl Into<QueryParam>) -> Self {
+        let value = value.into();
+        match self.params.iter_mut().find(|(existing, _)| existing == key) {
+            Some((_, slot)) => *slot = value,
+            None => self.params.push((key.to_string(), value)),
+        }
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn params(&self) -> &[(String, QueryParam)] {
+        &self.params
+    }
+
+    pub fn get_param(&self, key: &str) -> Option<&QueryParam> {
+        self.params.iter().find(|(existing, _)| existing == key).map(|(_, value)| value)
+    }
+
+}
+
+impl From<CypherQuery> for Query {
+    fn from(query: CypherQuery) -> Self {
+        query
+            .params
+            .into_iter()
+            .fold(Query::new(query.text), |q, (key, value)| q.param(&key, value))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Backend queries
+// ---------------------------------------------------------------------------
+
+pub fn crates() -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (c:Crate)
+         OPTIONAL MATCH (c)-[:DEPENDS_ON]->(dep:Crate)
+         RETURN c.name as name, c.layer as layer, c.is_workspace as is_workspace,
+                collect(dep.name) as dependencies
+         ORDER BY name",
+    )
+}
+
+pub fn callers_of(qualified_name: &str) -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (caller:Function)-[call:CALLS]->(callee:Function {qualified_name: $name})
+         RETURN caller, callee, call",
+    )
+    .param("name", qualified_name)
+}
+
+pub fn referrers_of(qualified_name: &str) -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (caller:Function)-[call:REFERENCES_FN]->(callee:Function {qualified_name: $name})
+         RETURN caller, callee, call",
+    )
+    .param("name", qualified_name)
+}
+
+pub fn callees_of(qualified_name: &str) -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (caller:Function {qualified_name: $name})-[call:CALLS]->(callee:Function)
+         RETURN caller, callee, call",
+    )
+    .param("name", qualified_name)
+}
+
+/// Callers up to `depth` CALLS or REFERENCES_FN hops away with their shortest distance.
+///
+/// Variable-length bounds cannot be parameters, so `depth` is clamped to a small integer and written
+/// into the pattern; it is the only caller-supplied value in this module that ends up in query text.
+pub fn transitive_callers(qualified_name: &str, depth: usize) -> CypherQuery {
+    let text = format!(
+        "MATCH (f:Function {{qualified_name: $name}})
+         MATCH path = (caller:Function)-[:CALLS|REFERENCES_FN*1..{}]->(f)
+         WHERE caller <> f
+         RETURN caller, min(size(path)) as distance
+         ORDER BY distance, caller.crate",
+        depth.clamp(1, 20)
+    );
+    CypherQuery::new(&text).param("name", qualified_name)
+}
+
+pub fn functions() -> CypherQuery {
+    CypherQuery::new("MATCH (f:Function) RETURN f ORDER BY f.crate, f.name")
+}
+
+pub fn calls() -> CypherQuery {
+    CypherQuery::new("MATCH (caller:Function)-[call:CALLS]->(callee:Function) RETURN caller, callee, call")
+}
+
+pub fn cross_crate_calls() -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (caller:Function)-[call:CALLS]->(callee:Function)
+         WHERE caller.crate <> callee.crate
+         RETURN caller, callee, call",
+    )
+}
+
+pub fn unreferenced_functions() -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (f:Function)
+         OPTIONAL MATCH (caller:Function)-[:CALLS|REFERENCES_FN]->(f)
+         WITH f, COUNT(caller) AS caller_count
+         WHERE caller_count = 0
+         RETURN f ORDER BY f.crate, f.name",
+    )
+}
+
+pub fn actor_spawns() -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (parent:Type:Actor)-[r:SPAWNS]->(child:Type:Actor)
+         RETURN parent.name as parent, parent.crate as parent_crate,
+                child.name as child, child.crate as child_crate,
+                r.method as method, r.context as context, r.line as line, r.file_path as file_path
+         ORDER BY parent, child",
+    )
+}
+
+pub fn supervision_links() -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (supervisor:Type:Actor)-[r:SUPERVISES]->(child:Type:Actor)
+         RETURN supervisor.name as supervisor, supervisor.crate as supervisor_crate,
+                child.name as child, child.crate as child_crate, r.kind as kind,
+                r.supervisor_on_link_died as supervisor_on_link_died,
+                r.child_on_link_died as child_on_link_died, r.child_on_panic as child_on_panic,
+                r.line as line, r.file_path as file_path
+         ORDER BY supervisor, child",
+    )
+}
+
+pub fn trait_dispatches() -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (caller:Function)-[r:MAY_DISPATCH_TO]->(target:Function)
+         RETURN caller.qualified_name as caller, caller.crate as caller_crate,
+                target.qualified_name as target, target.crate as target_crate,
+                r.trait_name as trait_name, r.method as method, r.receiver as receiver,
+                r.implementing_type as implementing_type, r.line as line, r.file_path as file_path
+         ORDER BY caller, target",
+    )
+}
+
+pub fn type_users(qualified_name: &str) -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (user)-[r:USES_TYPE]->(t:Type {qualified_name: $name})
+         WHERE user:Function OR user:Type
+         RETURN user.qualified_name as user, user.crate as user_crate,
+                CASE WHEN user:Function THEN 'Function' ELSE 'Type' END as user_kind,
+                t.qualified_name as type_name, t.crate as type_crate,
+                r.usage as usage, r.line as line, r.file as file_path
+         ORDER BY user_crate, user, line",
+    )
+    .param("name", qualified_name)
+}
+
+pub fn message_handlers() -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (actor:Actor)-[r:HANDLES]->(msg:MessageType)
+         RETURN actor.name as actor, actor.crate as actor_crate, msg.name as message_type,
+                r.reply_type as reply_type, r.is_async as is_async, r.line as line, r.file_path as file_path
+         ORDER BY actor, message_type",
+    )
+}
+
+pub fn message_sends() -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (sender)-[r:SENDS]->(msg:MessageType)
+         OPTIONAL MATCH (receiver:Actor {name: r.receiver_actor})
+         RETURN sender.name as sender, r.from_crate as sender_crate,
+                r.receiver_actor as receiver, receiver.crate as receiver_crate,
+                msg.name as message_type, r.method as method, r.line as line, r.file_path as file_path
+         ORDER BY sender, receiver, message_type",
+    )
+}
+
+pub fn mark_call_violation(caller: &str, callee: &str, kind: &str, severity: &str) -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (caller:Function {qualified_name: $caller})
+         MATCH (callee:Function {qualified_name: $callee})
+         MATCH (caller)-[r:CALLS]->(callee)
+         SET r.violates_architecture = true,
+             r.violation_kind = $kind,
+             r.violation_severity = $severity",
+    )
+    .param("caller", caller)
+    .param("callee", callee)
+    .param("kind", kind)
+    .param("severity", severity)
+}
+
+/// Memgraph vector index over `Function.embedding`
+pub const FUNCTION_VECTOR_INDEX: &str = "function_embedding_idx";
+/// Memgraph vector index over `Type.embedding`
+pub const TYPE_VECTOR_INDEX: &str = "type_embedding_idx";
+
+/// DDL for a cosine vector index on `label.embedding`.
+///
+/// Index DDL takes no parameters; `dimensions` and `capacity` come from the config, never from a caller.
+pub fn create_vector_index(index: &str, label: &str, dimensions: usize, capacity: usize) -> String {
+    format!(
+        "CREATE VECTOR INDEX {} ON :{}(embedding) WITH CONFIG {{\"dimension\": {}, \"capacity\": {}, \"metric\": \"cos\"}}",
+        index, label, dimensions, capacity
+    )
+}
+
+/// Functions nearest to `vector` by the function vector index
+pub fn similar_functions(vector: Vec<f32>, limit: usize) -> CypherQuery {
+    CypherQuery::new(&format!(
+        "CALL vector_search.search(\"{}\", $limit, $vector) YIELD node, similarity
+         RETURN node AS f, similarity
+         ORDER BY similarity DESC",
+        FUNCTION_VECTOR_INDEX
+    ))
+    .param("limit", limit)
+    .param("vector", vector)
+}
+
+/// Direct callers of `qualified_name` that have an embedding, with that embedding.
+///
+/// The approximate index cannot be restricted to a set of nodes, so callers are selected
+/// first and ranked by the client.
+pub fn embedded_callers_of(qualified_name: &str) -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (f:Function)-[:CALLS]->(:Function {qualified_name: $target})
+         WHERE f.embedding IS NOT NULL
+         RETURN DISTINCT f, f.embedding AS embedding",
+    )
+    .param("target", qualified_name)
+}
+
+/// Deletes for every node and relationship that originates from `file_path`
+pub fn remove_file(file_path: &str) -> Vec<CypherQuery> {
+    [
+        "MATCH ()-[r:CALLS]->() WHERE r.file = $file DELETE r",
+        "MATCH ()-[r:REFERENCES_FN]->() WHERE r.file = $file DELETE r",
+        "MATCH ()-[r:USES_TYPE]->() WHERE r.file = $file DELETE r",
+        "MATCH ()-[r:SUPERVISES]->() WHERE r.file_path = $file DELETE r",
+        "MATCH ()-[r:MAY_DISPATCH_TO]->() WHERE r.file_path = $file DELETE r",
+        "MATCH (f:Function) WHERE f.file = $file DETACH DELETE f",
+        "MATCH (t:Type) WHERE t.file = $file DETACH DELETE t",
+        "MATCH (m:Module) WHERE m.file = $file DETACH DELETE m",
+    ]
+    .into_iter()
+    .map(|text| CypherQuery::new(text).param("file", file_path))
+    .collect()
+}
+
+// ---------------------------------------------------------------------------
+// MCP tool queries
+// ---------------------------------------------------------------------------
+
+pub fn functions_named(name: &str) -> CypherQuery {
+    CypherQuery::new(
+        "MATCH (f:Function)
+         WHERE f.name = $name
+         RETURN f.qualified_name as qualified_name, f.file as file",
+    )
+    .param("name", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOSTILE: &str = "x' OR 1=1 WITH 1 AS _ MATCH (n) DETACH DELETE n //\"})";
+
+    fn assert_bound(query: &CypherQuery, key: &str) {
+        assert!(!query.text().contains(HOSTILE), "input leaked into query text: {}", query.text());
+        assert_eq!(query.get_param(key), Some(&QueryParam::String(HOSTILE.to_string())));
+        assert!(query.text().contains(&format!("${}", key)));
+    }
+
+    #[test]
+    fn test_caller_supplied_values_are_parameters() {
+        assert_bound(&callers_of(HOSTILE), "name");
+        assert_bound(&callees_of(HOSTILE), "name");
+        assert_bound(&transitive_callers(HOSTILE, 3), "name");
+        assert_bound(&functions_named(HOSTILE), "name");
+        assert_bound(&embedded_callers_of(HOSTILE), "target");
+        for query in remove_file(HOSTILE) {
+            assert_bound(&query, "file");
+        }
+
+        let violation = mark_call_violation(HOSTILE, HOSTILE, HOSTILE, HOSTILE);
+        for key in ["caller", "callee", "kind", "severity"] {
+            assert_bound(&violation, key);
+        }
+    }
+
+    #[test]
+    fn test_depth_is_bounded() {
+        assert!(transitive_callers("a", 0).text().contains("CALLS|REFERENCES_FN*1..1]"));
+        assert!(transitive_callers("a", 10_000).text().contains("CALLS|REFERENCES_FN*1..20]"));
+    }
+
+    #[test]
+    fn test_similar_functions_and_callers_are_separate_queries() {
+        let similar = similar_functions(vec![1.0, 0.0], 10);
+        assert_eq!(similar.get_param("limit"), Some(&QueryParam::Integer(10)));
+        assert_eq!(similar.get_param("vector"), Some(&QueryParam::FloatList(vec![1.0, 0.0])));
+        assert!(similar.text().contains(FUNCTION_VECTOR_INDEX));
+
+        let callers = embedded_callers_of("app::run");
+        assert!(!callers.text().contains(FUNCTION_VECTOR_INDEX));
+        assert_eq!(callers.get_param("target"), Some(&QueryParam::String("app::run".to_string())));
+    }
+
+    #[test]
+    fn test_param_rebinding_replaces_value() {
+        let query = CypherQuery::new("RETURN $a").param("a", "first").param("a", 2i64);
+        assert_eq!(query.params().len(), 1);
+        assert_eq!(query.get_param("a"), Some(&QueryParam::Integer(2)));
+
+        let neo_query: Query = query.into();
+        assert!(neo_query.has_param_key("a"));
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use blake3::Hasher;
+use cargo_metadata::DependencyKind;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -9,10 +10,12 @@ use std::time::SystemTime;
 use tokio::sync::{mpsc, RwLock};
 use walkdir::WalkDir;
 
-use crate::config::Config;
-use crate::graph::MemgraphClient;
-use crate::parser::{RustParser, ParsedSymbols, references::resolve_all_references};
-use crate::workspace::CrateMetadata;
+use crate::config::{CfgMode, Config};
+use crate::graph::GraphBackend;
+use crate::parser::cfg::{filter_inactive, ActiveCfg};
+use crate::parser::symbols::MacroDefinition;
+use crate::parser::{expand_declarative_macros, ActorFrameworks, MacroExpander, ModuleTree, RustParser, ParsedSymbols, references::resolve_all_references};
+use crate::workspace::{default_features, CrateMetadata};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileState {
@@ -34,9 +37,12 @@ pub struct IncrementalState {
 
 pub struct IncrementalUpdater {
     config: Config,
-    graph: Arc<MemgraphClient>,
+    graph: Arc<dyn GraphBackend>,
     parser: RustParser,
     state: Arc<RwLock<IncrementalState>>,
+    /// `macro_rules!` definitions of the whole workspace, so a re-parsed file still expands
+    /// invocations of macros defined in other files
+    macro_definitions: Vec<MacroDefinition>,
     file_watcher: Option<RecommendedWatcher>,
     change_sender: Option<mpsc::UnboundedSender<PathBuf>>,
 }
@@ -57,7 +63,7 @@ pub enum ChangeType {
 }
 
 impl IncrementalUpdater {
-    pub fn new(config: Config, graph: Arc<MemgraphClient>) -> Result<Self> {
+    pub fn new(config: Config, graph: Arc<dyn GraphBackend>) -> Result<Self> {
         let parser = RustParser::new()
             .context("Failed to create Rust parser")?;
 
@@ -72,11 +78,17 @@ impl IncrementalUpdater {
             graph,
             parser,
             state,
+            macro_definitions: Vec::new(),
             file_watcher: None,
             change_sender: None,
         })
     }
 
+    /// Remember the macro definitions of a full analysis for later incremental updates
+    pub fn set_macro_definitions(&mut self, definitions: Vec<MacroDefinition>) {
+        self.macro_definitions = definitions;
+    }
+
     pub async fn load_state(&mut self, state_file: &PathBuf) -> Result<()> {
         if state_file.exists() {
             let content = std::fs::read_to_string(state_file)
@@ -183,8 +195,8 @@ impl IncrementalUpdater {
         let mut affected_crates = HashSet::new();
         
         for file_path in &changed_files {
-            if let Some(crate_name) = self.determine_crate_for_file(file_path).await? {
-                affected_crates.insert(crate_name);
+            if let Some(crate_meta) = self.determine_crate_for_file(file_path).await? {
+                affected_crates.insert(crate_meta.name);
             }
         }
 
@@ -225,10 +237,19 @@ impl IncrementalUpdater {
 
         self.remove_file_symbols_from_graph(file_path).await?;
 
-        let crate_name = self.determine_crate_for_file(file_path).await?
-            .unwrap_or_else(|| "unknown".to_string());
+        let crate_meta = self.determine_crate_for_file(file_path).await?
+            .unwrap_or_else(unknown_crate);
 
-        let mut symbols = self.parser.parse_file(file_path, &crate_name)?;
+        let module_path = ModuleTree::build(&crate_meta.name, &crate_meta.path, &[]).module_path(file_path);
+        let frameworks = ActorFrameworks::for_crate(&crate_meta);
+        let mut symbols = self.parser.parse_file_in_module(file_path, &crate_meta.name, &frameworks, &module_path)?;
+        if self.config.cfg.mode == CfgMode::Filter {
+            filter_inactive(&mut symbols, &ActiveCfg::for_crate(&self.config.cfg, &crate_meta));
+        }
+        let file = file_path.to_string_lossy();
+        self.macro_definitions.retain(|definition| definition.file_path != file);
+        self.macro_definitions.extend(symbols.macro_definitions.iter().cloned());
+        MacroExpander::new(&self.macro_definitions).expand_into(&mut symbols);
         resolve_all_references(&mut symbols)?;
 
         let function_ids: HashSet<String> = symbols.functions.iter()
@@ -257,6 +278,8 @@ impl IncrementalUpdater {
 
     async fn remove_file(&mut self, file_path: &PathBuf) -> Result<()> {
         self.remove_file_symbols_from_graph(file_path).await?;
+        let file = file_path.to_string_lossy();
+        self.macro_definitions.retain(|definition| definition.file_path != file);
 
         let mut state = self.state.write().await;
         if let Some(removed) = state.files.remove(file_path) {
@@ -268,20 +291,7 @@ impl IncrementalUpdater {
 
     async fn remove_file_symbols_from_graph(&self, file_path: &PathBuf) -> Result<()> {
         let file_str = file_path.to_string_lossy();
-        
-        let delete_queries = vec![
-            format!("MATCH ()-[r:CALLS]->() WHERE r.file = '{}' DELETE r", file_str),
-            format!("MATCH ()-[r:USES_TYPE]->() WHERE r.file = '{}' DELETE r", file_str),
-            format!("MATCH (f:Function) WHERE f.file = '{}' DETACH DELETE f", file_str),
-            format!("MATCH (t:Type) WHERE t.file = '{}' DETACH DELETE t", file_str),
-            format!("MATCH (m:Module) WHERE m.file = '{}' DETACH DELETE m", file_str),
-        ];
-
-        for query_str in delete_queries {
-            let query = neo4rs::Query::new(query_str);
-            let _ = self.graph.execute_query(query).await;
-        }
-
+        let _ = self.graph.remove_file(&file_str).await;
         Ok(())
     }
 
@@ -296,7 +306,8 @@ impl IncrementalUpdater {
         Ok(hash.to_hex().to_string())
     }
 
-    async fn determine_crate_for_file(&self, file_path: &PathBuf) -> Result<Option<String>> {
+    /// Name, default features, directory and dependencies of the crate `file_path` belongs to
+    async fn determine_crate_for_file(&self, file_path: &PathBuf) -> Result<Option<CrateMetadata>> {
         let mut current_dir = file_path.parent();
         
         while let Some(dir) = current_dir {
@@ -306,8 +317,25 @@ impl IncrementalUpdater {
                     .manifest_path(&cargo_toml)
                     .exec()
                 {
-                    if let Some(package) = metadata.packages.first() {
-                        return Ok(Some(package.name.clone()));
+                    if let Some(package) = metadata.root_package().or(metadata.packages.first()) {
+                        let analysis = &self.config.analysis;
+                        let dependencies = package.dependencies.iter()
+                            .filter(|dep| match dep.kind {
+                                DependencyKind::Normal => true,
+                                DependencyKind::Development => analysis.include_dev_deps,
+                                DependencyKind::Build => analysis.include_build_deps,
+                                _ => false,
+                            })
+                            .map(|dep| dep.name.clone())
+                            .collect();
+                        return Ok(Some(CrateMetadata {
+                            name: package.name.clone(),
+                            version: package.version.to_string(),
+                            path: dir.to_path_buf(),
+                            dependencies,
+                            features: default_features(&package.features),
+                            ..unknown_crate()
+                        }));
                     }
                 }
             }
@@ -318,22 +346,11 @@ impl IncrementalUpdater {
     }
 
     async fn update_crate_dependencies(&self, crate_name: &str) -> Result<()> {
-        let dependency_query = format!(
-            "MATCH (c:Crate {{name: '{}'}})
-             MATCH (c)-[:DEPENDS_ON]->(dep:Crate)
-             RETURN dep.name as dependency",
-            crate_name
-        );
-
-        let query = neo4rs::Query::new(dependency_query);
-        let result = self.graph.execute_query(query).await?;
-        let mut dependencies = HashSet::new();
-
-        for row in result {
-            if let Ok(dep_name) = row.get::<String>("dependency") {
-                dependencies.insert(dep_name);
-            }
-        }
+        let dependencies: HashSet<String> = self.graph.get_crates().await?
+            .into_iter()
+            .find(|c| c.name == crate_name)
+            .map(|c| c.dependencies.into_iter().collect())
+            .unwrap_or_default();
 
         let mut state = self.state.write().await;
         state.crate_dependencies.insert(crate_name.to_string(), dependencies);
@@ -379,11 +396,16 @@ impl IncrementalUpdater {
 
         for crate_meta in crates {
             if crate_meta.is_workspace_member {
-                let crate_symbols = self.analyze_crate_files(&crate_meta.path, &crate_meta.name).await?;
+                let mut crate_symbols = self.analyze_crate_files(crate_meta, &ModuleTree::for_crate(crate_meta)).await?;
+                if self.config.cfg.mode == CfgMode::Filter {
+                    filter_inactive(&mut crate_symbols, &ActiveCfg::for_crate(&self.config.cfg, crate_meta));
+                }
                 all_symbols.merge(crate_symbols);
             }
         }
 
+        expand_declarative_macros(&mut all_symbols);
+        self.macro_definitions = all_symbols.macro_definitions.clone();
         resolve_all_references(&mut all_symbols)?;
         self.graph.populate_from_symbols(&all_symbols).await?;
 
@@ -395,9 +417,12 @@ impl IncrementalUpdater {
         Ok(())
     }
 
-    async fn analyze_crate_files(&mut self, crate_path: &PathBuf, crate_name: &str) -> Result<ParsedSymbols> {
+    async fn analyze_crate_files(&mut self, crate_meta: &CrateMetadata, tree: &ModuleTree) -> Result<ParsedSymbols> {
+        let frameworks = ActorFrameworks::for_crate(crate_meta);
         let mut symbols = ParsedSymbols::new();
-        let src_dir = crate_path.join("src");
+        symbols.modules.extend_from_slice(tree.modules());
+        symbols.reexports.extend_from_slice(tree.reexports());
+        let src_dir = crate_meta.path.join("src");
         
         if !src_dir.exists() {
             return Ok(symbols);
@@ -415,7 +440,7 @@ impl IncrementalUpdater {
 
         for entry in walker {
             let file_path = entry.path().to_path_buf();
-            let file_symbols = self.parser.parse_file(&file_path, crate_name)?;
+            let file_symbols = self.parser.parse_file_in_module(&file_path, &crate_meta.name, &frameworks, &tree.module_path(&file_path))?;
             symbols.merge(file_symbols);
 
             let file_hash = self.calculate_file_hash(&file_path)?;
@@ -466,6 +491,22 @@ impl IncrementalUpdater {
     }
 }
 
+/// Stand-in for the crate of a file no manifest claims
+fn unknown_crate() -> CrateMetadata {
+    CrateMetadata {
+        name: "unknown".to_string(),
+        version: String::new(),
+        path: PathBuf::new(),
+        layer: None,
+        depth: 0,
+        dependencies: Vec::new(),
+        is_workspace_member: true,
+        is_external: false,
+        features: Vec::new(),
+        targets: Vec::new(),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IncrementalStatistics {
     pub tracked_files: usize,
@@ -478,6 +519,7 @@ pub struct IncrementalStatistics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::InMemoryGraph;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -488,7 +530,7 @@ mod tests {
         std::fs::write(&file_path, "fn test() {}").unwrap();
         
         let config = crate::config::Config::default();
-        let graph = Arc::new(MemgraphClient::new(&config).await.unwrap());
+        let graph = Arc::new(InMemoryGraph::new(config.clone()));
         let updater = IncrementalUpdater::new(config, graph).unwrap();
         
         let hash1 = updater.calculate_file_hash(&file_path).unwrap();
@@ -508,7 +550,7 @@ mod tests {
         let state_file = temp_dir.path().join("state.json");
         
         let config = crate::config::Config::default();
-        let graph = Arc::new(MemgraphClient::new(&config).await.unwrap());
+        let graph = Arc::new(InMemoryGraph::new(config.clone()));
         let mut updater = IncrementalUpdater::new(config, graph).unwrap();
         
         {
@@ -531,7 +573,7 @@ mod tests {
         assert!(state_file.exists());
         
         let config2 = crate::config::Config::default();
-        let graph2 = Arc::new(MemgraphClient::new(&config2).await.unwrap());
+        let graph2 = Arc::new(InMemoryGraph::new(config2.clone()));
         let mut updater2 = IncrementalUpdater::new(config2, graph2).unwrap();
         updater2.load_state(&state_file).await.unwrap();
         
@@ -540,6 +582,26 @@ mod tests {
         assert!(state2.files.contains_key(&PathBuf::from("test.rs")));
     }
 
+    #[tokio::test]
+    async fn test_update_expands_macros_defined_in_other_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let macros = temp_dir.path().join("macros.rs");
+        let lib = temp_dir.path().join("lib.rs");
+        std::fs::write(&macros, "#[macro_export]\nmacro_rules! make_fn { ($name:ident) => { pub fn $name() {} }; }\n").unwrap();
+        std::fs::write(&lib, "make_fn!(generated);\n").unwrap();
+
+        let config = crate::config::Config::default();
+        let graph = Arc::new(InMemoryGraph::new(config.clone()));
+        let mut updater = IncrementalUpdater::new(config, graph.clone()).unwrap();
+        let definitions = RustParser::new().unwrap().parse_source(&std::fs::read_to_string(&macros).unwrap(), &macros, "app").unwrap();
+        updater.set_macro_definitions(definitions.macro_definitions);
+
+        updater.process_file_changes(vec![lib]).await.unwrap();
+
+        let functions = graph.functions().await.unwrap();
+        assert!(functions.iter().any(|f| f.name == "generated"), "{:?}", functions);
+    }
+
     #[test]
     fn test_is_rust_file() {
         assert!(IncrementalUpdater::is_rust_file(&[PathBuf::from("test.rs")]));
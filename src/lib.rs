@@ -5,6 +5,7 @@ pub mod graph;
 pub mod architecture;
 pub mod embeddings;
 pub mod incremental;
+pub mod changes;
 pub mod mcp;
 pub mod analyzer;
 pub mod lsp;
@@ -16,6 +17,7 @@ pub use graph::MemgraphClient;
 pub use architecture::{ArchitectureAnalyzer, ArchitectureViolation};
 pub use embeddings::{EmbeddingGenerator, SemanticSearch};
 pub use incremental::IncrementalUpdater;
+pub use changes::{ChangeSet, ChangeSpec};
 pub use analyzer::{WorkspaceAnalyzer, WorkspaceSnapshot, HybridWorkspaceAnalyzer};
 
 #[cfg(test)]
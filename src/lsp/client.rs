@@ -0,0 +1,365 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use url::Url;
+
+use super::LspServerConfig;
+
+type PendingRequests = Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value>>>>>;
+
+/// A position in a file as reported by the server; `line` and `character` are 0-based,
+/// `character` counts UTF-16 code units
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspLocation {
+    pub path: PathBuf,
+    pub line: u32,
+    pub character: u32,
+}
+
+/// A `CallHierarchyItem`; `raw` is sent back verbatim in `callHierarchy/incomingCalls`
+#[derive(Debug, Clone)]
+pub struct CallHierarchyItem {
+    pub name: String,
+    pub location: Option<LspLocation>,
+    pub raw: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct IncomingCall {
+    pub from: CallHierarchyItem,
+    /// Start of each call site inside `from`
+    pub from_ranges: Vec<LspLocation>,
+}
+
+/// JSON-RPC client for a language server spoken to over the child's stdin/stdout
+pub struct LspClient {
+    child: Child,
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    pending: PendingRequests,
+    next_id: AtomicI64,
+    request_timeout: Duration,
+    quiescent: watch::Receiver<bool>,
+    reader: JoinHandle<()>,
+}
+
+impl LspClient {
+    /// Spawn the configured server and complete the `initialize` handshake
+    pub async fn start(config: &LspServerConfig, workspace_root: &Path) -> Result<Self> {
+        let mut child = Command::new(&config.executable_path)
+            .args(&config.args)
+            .current_dir(workspace_root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start LSP server: {}", config.executable_path))?;
+
+        let stdin = Arc::new(tokio::sync::Mutex::new(child.stdin.take().context("LSP server stdin unavailable")?));
+        let stdout = child.stdout.take().context("LSP server stdout unavailable")?;
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let (quiescent_tx, quiescent) = watch::channel(false);
+        let reader = tokio::spawn(read_messages(stdout, stdin.clone(), pending.clone(), quiescent_tx));
+
+        let client = Self {
+            child,
+            stdin,
+            pending,
+            next_id: AtomicI64::new(1),
+            request_timeout: config.request_timeout(),
+            quiescent,
+            reader,
+        };
+        client.initialize(workspace_root, config).await?;
+        Ok(client)
+    }
+
+    async fn initialize(&self, workspace_root: &Path, config: &LspServerConfig) -> Result<()> {
+        let root_uri = file_uri(workspace_root)?;
+        let params = json!({
+            "processId": std::process::id(),
+            "rootUri": root_uri,
+            "workspaceFolders": [{"uri": root_uri, "name": workspace_root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()}],
+            "initializationOptions": config.additional_settings,
+            "capabilities": {
+                "general": {"positionEncodings": ["utf-16"]},
+                "textDocument": {
+                    "definition": {"linkSupport": true},
+                    "callHierarchy": {"dynamicRegistration": false}
+                },
+                "experimental": {"serverStatusNotification": true}
+            }
+        });
+        self.request_with_timeout("initialize", params, config.init_timeout())
+            .await
+            .context("LSP initialize failed")?;
+        self.notify("initialized", json!({})).await
+    }
+
+    /// Wait for rust-analyzer's `experimental/serverStatus` to report it has finished indexing.
+    /// Returns false if the server never says so within `timeout`.
+    pub async fn wait_until_quiescent(&self, timeout: Duration) -> bool {
+        let mut quiescent = self.quiescent.clone();
+        tokio::time::timeout(timeout, quiescent.wait_for(|ready| *ready))
+            .await
+            .map(|result| result.is_ok())
+            .unwrap_or(false)
+    }
+
+    pub async fn did_open(&self, path: &Path, text: &str) -> Result<()> {
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {"uri": file_uri(path)?, "languageId": "rust", "version": 1, "text": text}
+            }),
+        )
+        .await
+    }
+
+    pub async fn definition(&self, path: &Path, line: u32, character: u32) -> Result<Vec<LspLocation>> {
+        let result = self.request("textDocument/definition", position_params(path, line, character)?).await?;
+        let locations = match result {
+            Value::Null => vec![],
+            Value::Array(items) => items,
+            single => vec![single],
+        };
+        Ok(locations.iter().filter_map(parse_location).collect())
+    }
+
+    pub async fn prepare_call_hierarchy(&self, path: &Path, line: u32, character: u32) -> Result<Vec<CallHierarchyItem>> {
+        let result = self
+            .request("textDocument/prepareCallHierarchy", position_params(path, line, character)?)
+            .await?;
+        Ok(result
+            .as_array()
+            .map(|items| items.iter().map(parse_call_hierarchy_item).collect())
+            .unwrap_or_default())
+    }
+
+    pub async fn incoming_calls(&self, item: &CallHierarchyItem) -> Result<Vec<IncomingCall>> {
+        let result = self.request("callHierarchy/incomingCalls", json!({"item": item.raw})).await?;
+        Ok(result
+            .as_array()
+            .map(|calls| {
+                calls
+                    .iter()
+                    .map(|call| {
+                        let from = parse_call_hierarchy_item(&call["from"]);
+                        let from_ranges = match &from.location {
+                            Some(location) => call["fromRanges"]
+                                .as_array()
+                                .into_iter()
+                                .flatten()
+                                .filter_map(|range| {
+                                    Some(LspLocation {
+                                        path: location.path.clone(),
+                                        line: range["start"]["line"].as_u64()? as u32,
+                                        character: range["start"]["character"].as_u64()? as u32,
+                                    })
+                                })
+                                .collect(),
+                            None => vec![],
+                        };
+                        IncomingCall { from, from_ranges }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Send `shutdown`/`exit` and wait briefly for the process, killing it if it lingers
+    pub async fn shutdown(mut self) -> Result<()> {
+        let _ = self.request("shutdown", Value::Null).await;
+        let _ = self.notify("exit", Value::Null).await;
+        if tokio::time::timeout(Duration::from_secs(2), self.child.wait()).await.is_err() {
+            let _ = self.child.kill().await;
+        }
+        self.reader.abort();
+        Ok(())
+    }
+
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        self.request_with_timeout(method, params, self.request_timeout).await
+    }
+
+    async fn request_with_timeout(&self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        if let Err(e) = write_message(&self.stdin, &message).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => bail!("LSP server exited before answering {}", method),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                bail!("LSP request {} timed out after {:?}", method, timeout)
+            }
+        }
+    }
+
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        write_message(&self.stdin, &json!({"jsonrpc": "2.0", "method": method, "params": params})).await
+    }
+}
+
+async fn write_message(stdin: &tokio::sync::Mutex<ChildStdin>, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes()).await?;
+    stdin.write_all(&body).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+/// Read one `Content-Length` framed message; `None` on EOF
+async fn read_message(reader: &mut BufReader<ChildStdout>) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let length = content_length.ok_or_else(|| anyhow!("LSP message without Content-Length"))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+/// Route responses to their waiting requests, answer server requests with `null`
+/// and track `experimental/serverStatus`; other notifications are ignored.
+async fn read_messages(
+    stdout: ChildStdout,
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    pending: PendingRequests,
+    quiescent: watch::Sender<bool>,
+) {
+    let mut reader = BufReader::new(stdout);
+    while let Ok(Some(message)) = read_message(&mut reader).await {
+        match (message.get("id"), message.get("method").and_then(Value::as_str)) {
+            (Some(id), Some(_)) => {
+                let reply = json!({"jsonrpc": "2.0", "id": id, "result": null});
+                if write_message(&stdin, &reply).await.is_err() {
+                    break;
+                }
+            }
+            (Some(id), None) => {
+                let Some(sender) = id.as_i64().and_then(|id| pending.lock().unwrap().remove(&id)) else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(anyhow!("LSP error: {}", error)),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(result);
+            }
+            (None, Some("experimental/serverStatus")) if message["params"]["quiescent"].as_bool() == Some(true) => {
+                let _ = quiescent.send(true);
+            }
+            _ => {}
+        }
+    }
+    // Dropping the senders fails every request still waiting
+    pending.lock().unwrap().clear();
+}
+
+pub fn file_uri(path: &Path) -> Result<String> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    Url::from_file_path(&path)
+        .map(|url| url.to_string())
+        .map_err(|_| anyhow!("Cannot build file URI for {}", path.display()))
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+fn position_params(path: &Path, line: u32, character: u32) -> Result<Value> {
+    Ok(json!({
+        "textDocument": {"uri": file_uri(path)?},
+        "position": {"line": line, "character": character}
+    }))
+}
+
+/// Accepts both `Location` and `LocationLink`
+fn parse_location(value: &Value) -> Option<LspLocation> {
+    let (uri, range) = match value.get("targetUri") {
+        Some(uri) => (uri, value.get("targetSelectionRange").or_else(|| value.get("targetRange"))?),
+        None => (value.get("uri")?, value.get("range")?),
+    };
+    Some(LspLocation {
+        path: uri_to_path(uri.as_str()?)?,
+        line: range["start"]["line"].as_u64()? as u32,
+        character: range["start"]["character"].as_u64()? as u32,
+    })
+}
+
+fn parse_call_hierarchy_item(value: &Value) -> CallHierarchyItem {
+    let location = (|| {
+        let range = value.get("selectionRange").or_else(|| value.get("range"))?;
+        Some(LspLocation {
+            path: uri_to_path(value["uri"].as_str()?)?,
+            line: range["start"]["line"].as_u64()? as u32,
+            character: range["start"]["character"].as_u64()? as u32,
+        })
+    })();
+    CallHierarchyItem {
+        name: value["name"].as_str().unwrap_or_default().to_string(),
+        location,
+        raw: value.clone(),
+    }
+}
+
+/// Column of `byte_offset` within `line` in UTF-16 code units, as LSP positions expect
+pub fn utf16_column(line: &str, byte_offset: usize) -> u32 {
+    line[..byte_offset].encode_utf16().count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf16_column_counts_code_units() {
+        let line = "let é = \"😀\"; call();";
+        let offset = line.find("call").unwrap();
+        // 'é' is one UTF-16 unit (two bytes), '😀' is two units (four bytes)
+        assert_eq!(utf16_column(line, offset), offset as u32 - 1 - 2);
+    }
+
+    #[test]
+    fn test_parse_location_accepts_location_links() {
+        let link = json!({
+            "targetUri": "file:///tmp/lib.rs",
+            "targetRange": {"start": {"line": 1, "character": 0}, "end": {"line": 3, "character": 1}},
+            "targetSelectionRange": {"start": {"line": 1, "character": 7}, "end": {"line": 1, "character": 12}}
+        });
+        let location = parse_location(&link).unwrap();
+        assert_eq!(location.path, PathBuf::from("/tmp/lib.rs"));
+        assert_eq!((location.line, location.character), (1, 7));
+    }
+}
@@ -1,10 +1,137 @@
+pub mod client;
 pub mod models;
+pub mod verifier;
 
+pub use client::LspClient;
+pub use verifier::{CallVerifier, VerificationReport};
+
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Default)]
+/// Settings read from `lsp_config.toml`; sections and keys not listed here are ignored
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LspConfig {
-    // Basic LSP configuration
+    #[serde(default)]
+    pub server: LspServerConfig,
+    #[serde(default)]
+    pub fallback: LspFallbackConfig,
+    #[serde(default)]
+    pub features: LspFeaturesConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LspServerConfig {
+    /// Command used to start the server; any stdio LSP server works, not just rust-analyzer
+    #[serde(default = "default_executable_path")]
+    pub executable_path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds to wait for the `initialize` response
+    #[serde(default = "default_init_timeout")]
+    pub init_timeout: u64,
+    /// Seconds to wait for any other request
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: u64,
+    /// Sent as `initializationOptions`
+    #[serde(default)]
+    pub additional_settings: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LspFallbackConfig {
+    #[serde(default = "default_true")]
+    pub enable_graceful_fallback: bool,
+    #[serde(default = "default_true")]
+    pub show_warnings: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LspFeaturesConfig {
+    /// Check tree-sitter call edges with `textDocument/definition`
+    #[serde(default = "default_true")]
+    pub definition: bool,
+    /// Discover missed call edges with `callHierarchy/incomingCalls`
+    #[serde(default = "default_true")]
+    pub call_hierarchy: bool,
+}
+
+impl Default for LspServerConfig {
+    fn default() -> Self {
+        Self {
+            executable_path: default_executable_path(),
+            args: Vec::new(),
+            init_timeout: default_init_timeout(),
+            request_timeout: default_request_timeout(),
+            additional_settings: serde_json::Value::Null,
+        }
+    }
+}
+
+impl Default for LspFallbackConfig {
+    fn default() -> Self {
+        Self {
+            enable_graceful_fallback: true,
+            show_warnings: true,
+        }
+    }
+}
+
+impl Default for LspFeaturesConfig {
+    fn default() -> Self {
+        Self {
+            definition: true,
+            call_hierarchy: true,
+        }
+    }
+}
+
+impl LspServerConfig {
+    pub fn init_timeout(&self) -> Duration {
+        Duration::from_secs(self.init_timeout)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout)
+    }
+}
+
+impl LspConfig {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read LSP config file: {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse LSP config file: {}", path.display()))
+    }
+
+    /// Load `lsp_config.toml` or `config/lsp_config.toml` from the workspace root, else defaults
+    pub fn load_for_workspace<P: AsRef<Path>>(workspace_root: P) -> Result<Self> {
+        let root = workspace_root.as_ref();
+        for candidate in [root.join("lsp_config.toml"), root.join("config").join("lsp_config.toml")] {
+            if candidate.exists() {
+                return Self::from_file(candidate);
+            }
+        }
+        Ok(Self::default())
+    }
+}
+
+fn default_executable_path() -> String {
+    "rust-analyzer".to_string()
+}
+
+fn default_init_timeout() -> u64 {
+    30
+}
+
+fn default_request_timeout() -> u64 {
+    5
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,4 +150,19 @@ impl Default for LspStatus {
     fn default() -> Self {
         LspStatus::Disconnected
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shipped_lsp_config_parses() {
+        let config = LspConfig::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/config/lsp_config.toml")).unwrap();
+        assert_eq!(config.server.executable_path, "rust-analyzer");
+        assert_eq!(config.server.request_timeout, 5);
+        assert!(config.fallback.enable_graceful_fallback);
+        assert!(config.features.definition);
+        assert!(config.features.call_hierarchy);
+    }
+}
@@ -0,0 +1,274 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::client::{utf16_column, LspClient, LspLocation};
+use super::LspFeaturesConfig;
+use crate::parser::symbols::{CallProvenance, CallType, FunctionCall, ParsedSymbols, RustFunction};
+
+/// Outcome of checking tree-sitter call edges against a language server
+#[derive(Debug, Clone, Default)]
+pub struct VerificationReport {
+    pub confirmed: usize,
+    /// Calls whose callee the server resolved differently, after correction
+    pub corrected: Vec<FunctionCall>,
+    /// Calls only the server found
+    pub discovered: Vec<FunctionCall>,
+    /// Calls the server could not resolve to a workspace function (std, external crates, locals)
+    pub unresolved: usize,
+}
+
+/// Confirms or corrects `FunctionCall` edges with `textDocument/definition` and adds edges
+/// found by `callHierarchy/incomingCalls`, recording the result in `FunctionCall::provenance`.
+pub struct CallVerifier<'a> {
+    client: &'a LspClient,
+    features: &'a LspFeaturesConfig,
+    /// Lines of every file sent to the server with `didOpen`, by canonical path
+    files: HashMap<PathBuf, Vec<String>>,
+    /// Workspace functions by canonical file path
+    functions: HashMap<PathBuf, Vec<RustFunction>>,
+}
+
+impl<'a> CallVerifier<'a> {
+    pub fn new(client: &'a LspClient, features: &'a LspFeaturesConfig) -> Self {
+        Self {
+            client,
+            features,
+            files: HashMap::new(),
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Verify the calls of every crate in `symbols` (keyed by crate name) in place
+    pub async fn verify(&mut self, symbols: &mut HashMap<String, ParsedSymbols>) -> Result<VerificationReport> {
+        self.functions.clear();
+        for function in symbols.values().flat_map(|parsed| parsed.functions.iter()) {
+            self.functions.entry(canonical(&function.file_path)).or_default().push(function.clone());
+        }
+
+        let mut crate_names: Vec<String> = symbols.keys().cloned().collect();
+        crate_names.sort();
+
+        let mut report = VerificationReport::default();
+        if self.features.definition {
+            for crate_name in &crate_names {
+                let parsed = symbols.get_mut(crate_name).expect("crate name taken from map");
+                for call in parsed.calls.iter_mut() {
+                    self.verify_call(call, &mut report).await;
+                }
+            }
+        }
+        if self.features.call_hierarchy {
+            self.discover_calls(symbols, &crate_names, &mut report).await;
+        }
+        Ok(report)
+    }
+
+    async fn verify_call(&mut self, call: &mut FunctionCall, report: &mut VerificationReport) {
+        if call.is_synthetic || matches!(call.call_type, CallType::Macro) {
+            return;
+        }
+        let path = canonical(&call.file_path);
+        let callee = call.callee_name.rsplit("::").next().unwrap_or(&call.callee_name).to_string();
+        let Some(character) = self.identifier_column(&path, call.line, &callee).await else {
+            report.unresolved += 1;
+            return;
+        };
+
+        let locations = match self.client.definition(&path, call.line as u32, character).await {
+            Ok(locations) => locations,
+            Err(e) => {
+                eprintln!("⚠️ LSP definition failed for {} at {}:{}: {}", call.callee_name, call.file_path, call.line + 1, e);
+                report.unresolved += 1;
+                return;
+            }
+        };
+        let Some(target) = self.function_at_locations(&locations).await else {
+            report.unresolved += 1;
+            return;
+        };
+
+        let same_target = match &call.qualified_callee {
+            Some(qualified) => *qualified == target.qualified_name,
+            None => callee == target.name,
+        };
+        call.qualified_callee = Some(target.qualified_name.clone());
+        call.to_crate = Some(target.crate_name.clone());
+        call.cross_crate = target.crate_name != call.from_crate;
+        if same_target {
+            call.provenance = CallProvenance::LspConfirmed;
+            report.confirmed += 1;
+        } else {
+            call.provenance = CallProvenance::LspCorrected;
+            report.corrected.push(call.clone());
+        }
+    }
+
+    async fn discover_calls(&mut self, symbols: &mut HashMap<String, ParsedSymbols>, crate_names: &[String], report: &mut VerificationReport) {
+        let mut callees: Vec<RustFunction> = crate_names
+            .iter()
+            .flat_map(|name| symbols[name].functions.iter().cloned())
+            .collect();
+        callees.sort_by(|a, b| a.id.cmp(&b.id));
+
+        for callee in callees {
+            let path = canonical(&callee.file_path);
+            let Some((line, character)) = self.function_name_position(&path, &callee).await else {
+                continue;
+            };
+            let items = match self.client.prepare_call_hierarchy(&path, line, character).await {
+                Ok(items) => items,
+                Err(e) => {
+                    eprintln!("⚠️ LSP call hierarchy failed for {}: {}", callee.qualified_name, e);
+                    continue;
+                }
+            };
+            let Some(item) = items.first() else { continue };
+            let incoming = match self.client.incoming_calls(item).await {
+                Ok(incoming) => incoming,
+                Err(e) => {
+                    eprintln!("⚠️ LSP incoming calls failed for {}: {}", callee.qualified_name, e);
+                    continue;
+                }
+            };
+
+            for call in incoming {
+                let Some(from) = call.from.location.as_ref() else { continue };
+                let Some(caller) = self.function_at(from).await else { continue };
+                let mut seen_lines = HashSet::new();
+                for site in &call.from_ranges {
+                    let line = site.line as usize;
+                    if !seen_lines.insert(line) {
+                        continue;
+                    }
+                    let Some(parsed) = symbols.get_mut(&caller.crate_name) else { continue };
+                    let known = parsed.calls.iter().any(|existing| {
+                        called_from(existing, &caller) && existing.line == line && calls_function(existing, &callee)
+                    });
+                    if known {
+                        continue;
+                    }
+                    let discovered = FunctionCall {
+                        caller_id: caller.qualified_name.clone(),
+                        caller_module: caller.module_path.clone(),
+                        callee_name: callee.name.clone(),
+                        qualified_callee: Some(callee.qualified_name.clone()),
+                        call_type: if callee.is_method { CallType::Method } else { CallType::Direct },
+                        line,
+                        cross_crate: caller.crate_name != callee.crate_name,
+                        from_crate: caller.crate_name.clone(),
+                        to_crate: Some(callee.crate_name.clone()),
+                        file_path: caller.file_path.clone(),
+                        is_synthetic: false,
+                        macro_context: None,
+                        synthetic_confidence: 1.0,
+                        provenance: CallProvenance::LspDiscovered,
+                        receiver_type: None,
+                        cfg: None,
+                    };
+                    parsed.calls.push(discovered.clone());
+                    report.discovered.push(discovered);
+                }
+            }
+        }
+    }
+
+    /// Lines of `path`, sending the file to the server the first time it is needed
+    async fn lines(&mut self, path: &Path) -> Option<&Vec<String>> {
+        if !self.files.contains_key(path) {
+            let text = std::fs::read_to_string(path).ok()?;
+            if let Err(e) = self.client.did_open(path, &text).await {
+                eprintln!("⚠️ LSP didOpen failed for {}: {}", path.display(), e);
+            }
+            self.files.insert(path.to_path_buf(), text.lines().map(str::to_string).collect());
+        }
+        self.files.get(path)
+    }
+
+    /// UTF-16 column of the first whole-word occurrence of `identifier` on `line`
+    async fn identifier_column(&mut self, path: &Path, line: usize, identifier: &str) -> Option<u32> {
+        let text = self.lines(path).await?.get(line)?;
+        find_identifier(text, identifier).map(|offset| utf16_column(text, offset))
+    }
+
+    async fn function_name_position(&mut self, path: &Path, function: &RustFunction) -> Option<(u32, u32)> {
+        for line in function.line_start..=function.line_end {
+            let Some(text) = self.lines(path).await?.get(line) else { break };
+            if let Some(fn_offset) = find_identifier(text, "fn") {
+                let rest = &text[fn_offset + 2..];
+                let name_offset = fn_offset + 2 + (rest.len() - rest.trim_start().len());
+                if text[name_offset..].starts_with(&function.name) {
+                    return Some((line as u32, utf16_column(text, name_offset)));
+                }
+            }
+        }
+        None
+    }
+
+    async fn function_at_locations(&mut self, locations: &[LspLocation]) -> Option<RustFunction> {
+        for location in locations {
+            if let Some(function) = self.function_at(location).await {
+                return Some(function);
+            }
+        }
+        None
+    }
+
+    /// The innermost workspace function whose name is at `location`; locals and
+    /// anything outside the workspace resolve to `None`
+    async fn function_at(&mut self, location: &LspLocation) -> Option<RustFunction> {
+        let path = canonical(&location.path.to_string_lossy());
+        let text = self.lines(&path).await?.get(location.line as usize)?.clone();
+        let identifier = identifier_at_utf16(&text, location.character)?;
+        self.functions
+            .get(&path)?
+            .iter()
+            .filter(|f| f.name == identifier && f.line_start <= location.line as usize && location.line as usize <= f.line_end)
+            .min_by_key(|f| f.line_end - f.line_start)
+            .cloned()
+    }
+}
+
+/// Tree-sitter records the caller by qualified name, other producers by function id
+fn called_from(call: &FunctionCall, function: &RustFunction) -> bool {
+    call.caller_id == function.qualified_name || call.caller_id == function.id
+}
+
+fn calls_function(call: &FunctionCall, function: &RustFunction) -> bool {
+    match &call.qualified_callee {
+        Some(qualified) => *qualified == function.qualified_name,
+        None => call.callee_name.rsplit("::").next() == Some(function.name.as_str()),
+    }
+}
+
+fn canonical(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    path.canonicalize().unwrap_or(path)
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Byte offset of the first occurrence of `identifier` not embedded in a longer identifier
+fn find_identifier(text: &str, identifier: &str) -> Option<usize> {
+    text.match_indices(identifier).map(|(offset, _)| offset).find(|&offset| {
+        let before = text[..offset].chars().next_back();
+        let after = text[offset + identifier.len()..].chars().next();
+        !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+    })
+}
+
+/// The identifier starting at UTF-16 column `character`
+fn identifier_at_utf16(text: &str, character: u32) -> Option<String> {
+    let mut units = 0u32;
+    let start = text.char_indices().find_map(|(offset, c)| {
+        if units == character {
+            return Some(offset);
+        }
+        units += c.len_utf16() as u32;
+        None
+    })?;
+    let identifier: String = text[start..].chars().take_while(|c| is_identifier_char(*c)).collect();
+    (!identifier.is_empty()).then_some(identifier)
+}
@@ -26,7 +26,7 @@ enum Commands {
         config: PathBuf,
         #[arg(long)]
         output_json: Option<PathBuf>,
-        #[arg(long, help = "Populate Memgraph with analysis results")]
+        #[arg(long, help = "Populate the configured graph backend with analysis results")]
         populate_graph: bool,
     },
     #[command(about = "Check architecture violations")]
@@ -40,10 +40,55 @@ enum Commands {
         config: PathBuf,
         #[arg(short, long, help = "Symbol to analyze (e.g., 'MyStruct', 'my_function', 'MyTrait')")]
         symbol: String,
-        #[arg(long, help = "Symbol type: function, struct, trait, enum, type")]
+        #[arg(long, help = "Symbol type: function, struct, trait, enum, variant, type")]
         symbol_type: Option<String>,
+        #[arg(long, default_value_t = 3, help = "How many hops of users to follow for the blast radius")]
+        depth: usize,
+        #[arg(long)]
+        output_json: Option<PathBuf>,
+    },
+    #[command(about = "Analyze the impact of the changes in a git repository")]
+    Impact {
+        #[arg(short, long, default_value = "config.toml")]
+        config: PathBuf,
+        #[arg(long, help = "Compare the working tree with the merge base of this revision (e.g. 'origin/main')")]
+        since: Option<String>,
+        #[arg(long, conflicts_with = "since", help = "Compare a commit range instead (e.g. 'v1.0..HEAD')")]
+        range: Option<String>,
+        #[arg(long, help = "Repository to read (default: the workspace root)")]
+        repo: Option<PathBuf>,
+        #[arg(long, default_value_t = 3, help = "How many hops of users to follow for the blast radius")]
+        depth: usize,
+        #[arg(long)]
+        output_json: Option<PathBuf>,
+    },
+    #[command(about = "Report functions and types unreachable from the workspace's entry points")]
+    DeadCode {
+        #[arg(short, long, default_value = "config.toml")]
+        config: PathBuf,
+        #[arg(long = "crate", help = "Only report items from this crate")]
+        crate_name: Option<String>,
+        #[arg(long, help = "Do not treat tests as entry points")]
+        no_tests: bool,
+        #[arg(long, help = "Do not treat pub items of library crates as entry points")]
+        no_public_api: bool,
+        #[arg(long = "entry-point", help = "Extra entry point by function name or qualified name (repeatable)")]
+        entry_points: Vec<String>,
+        #[arg(long, help = "Explain which entry point keeps this function or type alive")]
+        explain: Option<String>,
+        #[arg(long)]
+        output_json: Option<PathBuf>,
+    },
+    #[command(about = "Report unhandled sends, unused handlers and orphaned actor message types")]
+    Messages {
+        #[arg(short, long, default_value = "config.toml")]
+        config: PathBuf,
+        #[arg(long = "crate", help = "Only report items from this crate")]
+        crate_name: Option<String>,
+        #[arg(long)]
+        output_json: Option<PathBuf>,
     },
-    #[command(about = "Health check Memgraph connection")]
+    #[command(about = "Health check the configured graph backend")]
     HealthCheck {
         #[arg(short, long, default_value = "config.toml")]
         config: PathBuf,
@@ -69,12 +114,30 @@ async fn main() -> Result<()> {
             eprintln!("🏗️ Checking architecture violations");
             check_architecture(config).await
         }
-        Commands::ImpactAnalysis { config, symbol, symbol_type } => {
+        Commands::ImpactAnalysis { config, symbol, symbol_type, depth, output_json } => {
             eprintln!("🎯 Analyzing impact of changes to symbol: {}", symbol);
-            analyze_symbol_impact(config, symbol, symbol_type).await
+            analyze_symbol_impact(config, symbol, symbol_type, depth, output_json).await
+        }
+        Commands::Impact { config, since, range, repo, depth, output_json } => {
+            let spec = match (since, range) {
+                (Some(rev), _) => workspace_analyzer::ChangeSpec::Since(rev),
+                (None, Some(range)) => workspace_analyzer::ChangeSpec::Range(range),
+                (None, None) => workspace_analyzer::ChangeSpec::WorkingTree,
+            };
+            eprintln!("🧮 Analyzing the impact of changes");
+            analyze_changes(config, spec, repo, depth, output_json).await
+        }
+        Commands::DeadCode { config, crate_name, no_tests, no_public_api, entry_points, explain, output_json } => {
+            eprintln!("🪦 Finding dead code");
+            let options = DeadCodeOptions { crate_name, no_tests, no_public_api, entry_points, explain, output_json };
+            find_dead_code(config, options).await
+        }
+        Commands::Messages { config, crate_name, output_json } => {
+            eprintln!("📨 Auditing actor messages");
+            audit_messages(config, crate_name, output_json).await
         }
         Commands::HealthCheck { config } => {
-            eprintln!("🏥 Checking Memgraph connection");
+            eprintln!("🏥 Checking graph backend");
             health_check(config).await
         }
     }
@@ -84,51 +147,9 @@ async fn start_mcp_server(config_path: PathBuf) -> Result<()> {
     let server = EnhancedMcpServer::new(config_path.to_str().unwrap()).await?;
     
     eprintln!("📡 MCP Server ready - send JSON-RPC requests via stdin");
-    eprintln!("💡 Available methods: initialize, workspace_context, analyze_change_impact, check_architecture_violations, semantic_search");
-    
-    let stdin = tokio::io::stdin();
-    let mut reader = tokio::io::BufReader::new(stdin);
-    let stdout = tokio::io::stdout();
-    let mut writer = tokio::io::BufWriter::new(stdout);
+    eprintln!("💡 {} tools available via tools/list", workspace_analyzer::mcp::tool_registry().len());
 
-    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
-    
-    loop {
-        let mut line = String::new();
-        match reader.read_line(&mut line).await {
-            Ok(0) => break,
-            Ok(_) => {
-                if let Ok(request_json) = serde_json::from_str::<serde_json::Value>(&line) {
-                    let request = workspace_analyzer::mcp::McpRequest {
-                        id: request_json.get("id").cloned(),
-                        method: request_json.get("method")
-                            .and_then(|m| m.as_str())
-                            .unwrap_or("unknown")
-                            .to_string(),
-                        params: request_json.get("params").cloned(),
-                    };
-                    
-                    let response = server.handle_request(request).await;
-                    let response_json = serde_json::json!({
-                        "jsonrpc": "2.0",
-                        "id": response.id,
-                        "result": response.result,
-                        "error": response.error
-                    });
-                    
-                    writer.write_all(response_json.to_string().as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                    writer.flush().await?;
-                }
-            }
-            Err(e) => {
-                eprintln!("Error reading from stdin: {}", e);
-                break;
-            }
-        }
-    }
-    
-    Ok(())
+    server.serve_stdio().await
 }
 
 async fn analyze_workspace(config_path: PathBuf, output_json: Option<PathBuf>, populate_graph: bool) -> Result<()> {
@@ -136,12 +157,12 @@ async fn analyze_workspace(config_path: PathBuf, output_json: Option<PathBuf>, p
     let mut analyzer = workspace_analyzer::WorkspaceAnalyzer::new_with_config(config.clone())?;
     
     let snapshot = if populate_graph {
-        eprintln!("🔍 Analyzing workspace and populating Memgraph...");
-        let graph = workspace_analyzer::MemgraphClient::new(&config).await?;
+        eprintln!("🔍 Analyzing workspace and populating graph ({:?} backend)...", config.graph.backend);
+        let graph = workspace_analyzer::graph::connect_backend(&config).await?;
         
         // First populate the graph with analysis
         let _ = analyzer.analyze_and_populate_graph(
-            Some(&graph), 
+            Some(&*graph), 
             None,  // embedding_gen
             None,  // architecture
             None,  // semantic_search  
@@ -197,8 +218,13 @@ async fn analyze_workspace(config_path: PathBuf, output_json: Option<PathBuf>, p
 
 async fn check_architecture(config_path: PathBuf) -> Result<()> {
     let config = Config::from_file(config_path.to_str().unwrap())?;
-    let graph = workspace_analyzer::MemgraphClient::new(&config).await?;
-    let analyzer = workspace_analyzer::ArchitectureAnalyzer::new(std::sync::Arc::new(graph), config);
+    let mut analyzer = workspace_analyzer::WorkspaceAnalyzer::new_with_config(config.clone())?;
+    let graph = workspace_analyzer::graph::connect_backend(&config).await?;
+    if config.graph.backend == workspace_analyzer::config::GraphBackendKind::Memory {
+        // The in-memory graph starts empty, so analyze the workspace into it first
+        analyzer.analyze_and_populate_graph(Some(&*graph), None, None, None, None).await?;
+    }
+    let analyzer = workspace_analyzer::ArchitectureAnalyzer::new(graph, config);
     
     eprintln!("🏗️ Analyzing architecture...");
     let report = analyzer.analyze_architecture().await?;
@@ -232,16 +258,193 @@ async fn check_architecture(config_path: PathBuf) -> Result<()> {
     Ok(())
 }
 
+struct DeadCodeOptions {
+    crate_name: Option<String>,
+    no_tests: bool,
+    no_public_api: bool,
+    entry_points: Vec<String>,
+    explain: Option<String>,
+    output_json: Option<PathBuf>,
+}
+
+/// Snapshot the workspace and merge every crate's symbols into one set
+async fn workspace_symbols(config: &Config) -> Result<(workspace_analyzer::WorkspaceSnapshot, workspace_analyzer::parser::ParsedSymbols)> {
+    let mut analyzer = workspace_analyzer::WorkspaceAnalyzer::new_with_config(config.clone())?;
+
+    eprintln!("🔍 Creating workspace snapshot...");
+    let snapshot = analyzer.create_snapshot().await?;
+
+    // Calls were resolved per crate; resolve again across the workspace so cross-crate calls count
+    let mut symbols = workspace_analyzer::parser::ParsedSymbols::new();
+    for crate_meta in &snapshot.crates {
+        if let Some(crate_symbols) = snapshot.symbols.get(&crate_meta.name) {
+            symbols.merge(crate_symbols.clone());
+        }
+    }
+    workspace_analyzer::parser::references::resolve_all_references(&mut symbols)?;
+
+    Ok((snapshot, symbols))
+}
+
+async fn find_dead_code(config_path: PathBuf, options: DeadCodeOptions) -> Result<()> {
+    let config = Config::from_file(config_path.to_str().unwrap())?;
+    let (snapshot, symbols) = workspace_symbols(&config).await?;
+
+    let mut dead_code_config = config.dead_code.clone();
+    dead_code_config.tests &= !options.no_tests;
+    dead_code_config.public_api &= !options.no_public_api;
+    dead_code_config.entry_points.extend(options.entry_points);
+
+    let patterns = workspace_analyzer::analyzer::FrameworkPatterns::with_default_patterns();
+    let reachability = workspace_analyzer::analyzer::Reachability::analyze(
+        &symbols,
+        &snapshot.crates,
+        &dead_code_config,
+        &patterns,
+    );
+
+    if let Some(name) = options.explain {
+        match reachability.why_live(&name) {
+            Some(chain) => {
+                println!("✅ '{}' is reachable:", name);
+                for step in chain {
+                    println!("  {}", step);
+                }
+            }
+            None => println!("🪦 '{}' is not reachable from any entry point (or does not exist)", name),
+        }
+        return Ok(());
+    }
+
+    let report = reachability.report(options.crate_name.as_deref());
+
+    println!("Dead Code Report");
+    println!("================");
+    println!("Entry points:");
+    for (kind, count) in &report.entry_points {
+        println!("  {:>5}  {}", count, kind);
+    }
+    println!("Reachable functions: {}/{}", report.reachable_functions, report.total_functions);
+    println!("Reachable types: {}/{}", report.reachable_types, report.total_types);
+
+    for (title, items) in [("Dead functions", &report.dead_functions), ("Dead types", &report.dead_types)] {
+        println!("\n{} ({}):", title, items.len());
+        for item in items {
+            println!("  🪦 {} ({}:{})", item.qualified_name, item.file_path, item.line);
+            for step in &item.reason {
+                println!("      ↳ {}", step);
+            }
+        }
+    }
+
+    if let Some(output_path) = options.output_json {
+        std::fs::write(&output_path, serde_json::to_string_pretty(&report)?)?;
+        eprintln!("💾 Results written to {:?}", output_path);
+    }
+
+    Ok(())
+}
+
+async fn audit_messages(config_path: PathBuf, crate_name: Option<String>, output_json: Option<PathBuf>) -> Result<()> {
+    let config = Config::from_file(config_path.to_str().unwrap())?;
+    let (_, symbols) = workspace_symbols(&config).await?;
+    let audit = workspace_analyzer::analyzer::MessageAudit::analyze(&symbols, crate_name.as_deref());
+
+    println!("Actor Message Report");
+    println!("====================");
+
+    println!("\nUnhandled sends ({}):", audit.unhandled_sends.len());
+    for send in &audit.unhandled_sends {
+        let receiver = send.receiver.as_deref().unwrap_or("any actor");
+        println!("  ❌ {} {}s {} to {} ({}:{})", send.sender, send.method, send.message_type, receiver, send.file_path, send.line);
+        if !send.handled_by.is_empty() {
+            println!("      ↳ handled only by {}", send.handled_by.join(", "));
+        }
+    }
+
+    println!("\nHandlers never sent to ({}):", audit.unused_handlers.len());
+    for handler in &audit.unused_handlers {
+        println!("  💤 impl Message<{}> for {} ({}:{})", handler.message_type, handler.actor, handler.file_path, handler.line);
+    }
+
+    println!("\nMessage types neither sent nor handled ({}):", audit.orphaned_messages.len());
+    for message in &audit.orphaned_messages {
+        println!("  👻 {} ({}:{})", message.qualified_name, message.file_path, message.line);
+    }
+
+    if let Some(output_path) = output_json {
+        std::fs::write(&output_path, serde_json::to_string_pretty(&audit)?)?;
+        eprintln!("💾 Results written to {:?}", output_path);
+    }
+
+    Ok(())
+}
+
+async fn analyze_changes(
+    config_path: PathBuf,
+    spec: workspace_analyzer::ChangeSpec,
+    repo: Option<PathBuf>,
+    depth: usize,
+    output_json: Option<PathBuf>,
+) -> Result<()> {
+    let config = Config::from_file(config_path.to_str().unwrap())?;
+    let repo = repo.unwrap_or_else(|| config.workspace.root.clone());
+    let changes = workspace_analyzer::ChangeSet::from_git(&repo, &spec)?;
+    println!("Change Impact Report");
+    println!("====================");
+    println!("Compared {}: {} files changed", changes.description, changes.files.len());
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let (_, symbols) = workspace_symbols(&config).await?;
+    let targets = changes.changed_targets(&symbols, &config);
+    let impact = workspace_analyzer::analyzer::BatchImpact::analyze(&symbols, &config, targets, depth);
+
+    let layer = |layer: &Option<String>| layer.as_deref().map(|l| format!(", layer {}", l)).unwrap_or_default();
+    println!("\nChanged items ({}):", impact.reports.len());
+    for report in &impact.reports {
+        let target = &report.target;
+        println!(
+            "  ✏️  {} {} ({}:{}, {}{}): {} direct uses",
+            target.kind.as_str(), target.qualified_name, target.file_path, target.line + 1,
+            target.crate_name, layer(&target.layer), report.direct_count()
+        );
+    }
+
+    println!("\nAffected within {} hops ({}):", impact.depth, impact.affected.len());
+    for item in impact.affected.iter().filter(|item| !item.is_test) {
+        println!("  {} {} {} ({}{})", item.distance, item.kind, item.name, item.crate_name, layer(&item.layer));
+    }
+
+    let tests: Vec<_> = impact.affected_tests().collect();
+    println!("\nTests to run ({}):", tests.len());
+    for test in tests {
+        println!("  🧪 {} ({})", test.name, test.crate_name);
+    }
+    println!("\nAffected crates: {}", impact.affected_crates().join(", "));
+
+    if let Some(output_path) = output_json {
+        let report = serde_json::json!({ "changes": changes, "impact": impact });
+        std::fs::write(&output_path, serde_json::to_string_pretty(&report)?)?;
+        eprintln!("💾 Results written to {:?}", output_path);
+    }
+
+    Ok(())
+}
+
 async fn health_check(config_path: PathBuf) -> Result<()> {
     let config = Config::from_file(config_path.to_str().unwrap())?;
     
-    eprintln!("🔗 Connecting to Memgraph at {}...", config.memgraph.uri);
-    let graph = workspace_analyzer::MemgraphClient::new(&config).await?;
+    if config.graph.backend == workspace_analyzer::config::GraphBackendKind::Memgraph {
+        eprintln!("🔗 Connecting to Memgraph at {}...", config.memgraph.uri);
+    }
+    let graph = workspace_analyzer::graph::connect_backend(&config).await?;
     
     let is_healthy = graph.health_check().await?;
     
     if is_healthy {
-        println!("✅ Memgraph 3.0 connection is healthy");
+        println!("✅ {:?} graph backend is healthy", config.graph.backend);
     } else {
         println!("⚠️  Memgraph connection is slow (>50ms response time)");
     }
@@ -257,124 +460,66 @@ async fn health_check(config_path: PathBuf) -> Result<()> {
 }
 
 
-async fn analyze_symbol_impact(config_path: PathBuf, symbol: String, symbol_type: Option<String>) -> Result<()> {
+async fn analyze_symbol_impact(config_path: PathBuf, symbol: String, symbol_type: Option<String>, depth: usize, output_json: Option<PathBuf>) -> Result<()> {
     println!("🎯 Symbol Impact Analysis");
     println!("═══════════════════════════");
     
-    // Load configuration
     let config = Config::from_file(config_path.to_str().unwrap())?;
-    
-    // Create analyzer
-    let mut analyzer = workspace_analyzer::WorkspaceAnalyzer::new_with_config(config.clone())?;
-    let graph_client = workspace_analyzer::graph::MemgraphClient::new(&config).await?;
-    
-    // Create workspace snapshot
-    println!("🔍 Creating workspace snapshot...");
-    let snapshot = analyzer.create_snapshot().await?;
+    let (snapshot, symbols) = workspace_symbols(&config).await?;
     println!("  Found {} crates ({} workspace members)", 
         snapshot.crates.len(),
         snapshot.crates.iter().filter(|c| c.is_workspace_member).count()
     );
     
-    // Search for the symbol across all crates
     println!("🔎 Searching for symbol '{}' in workspace...", symbol);
+    let reports = workspace_analyzer::analyzer::SymbolImpact::analyze(&symbols, &config, &symbol, symbol_type.as_deref(), depth);
     
-    let mut found_symbols = Vec::new();
-    
-    // Search for the symbol in functions
-    for func in &snapshot.functions {
-        if func.name == symbol || func.qualified_name.contains(&symbol) {
-            found_symbols.push(format!("Function: {} in {}:{}", 
-                func.qualified_name, func.file_path, func.line_start));
-        }
-    }
-    
-    // Search for the symbol in types
-    for typ in &snapshot.types {
-        if typ.name == symbol || typ.qualified_name.contains(&symbol) {
-            found_symbols.push(format!("{:?}: {} in {}:{}", 
-                typ.kind, typ.qualified_name, typ.file_path, typ.line_start));
-        }
-    }
-    
-    if found_symbols.is_empty() {
+    if reports.is_empty() {
         println!("❌ Symbol '{}' not found in the workspace", symbol);
         println!("💡 Try searching for:");
         println!("   - Function names (e.g., 'calculate_price')");
         println!("   - Struct names (e.g., 'Order')");  
         println!("   - Trait names (e.g., 'Serialize')");
-        println!("   - Enum names (e.g., 'OrderStatus')");
+        println!("   - Enum names (e.g., 'OrderStatus') or variants (e.g., 'OrderStatus::Filled')");
         return Ok(());
     }
     
-    println!("✅ Found {} matches for symbol '{}':", found_symbols.len(), symbol);
-    for (i, found_symbol) in found_symbols.iter().enumerate() {
-        println!("  {}. {}", i + 1, found_symbol);
+    println!("✅ Found {} matches for symbol '{}':", reports.len(), symbol);
+    for (i, report) in reports.iter().enumerate() {
+        let target = &report.target;
+        println!("  {}. {} {} in {}:{}", i + 1, target.kind.as_str(), target.qualified_name, target.file_path, target.line + 1);
     }
     
-    // Analyze impact by looking at function references
-    println!("\n📊 Impact Analysis:");
-    println!("──────────────────");
-    
-    // Count direct usages using function references
+    let layer = |layer: &Option<String>| layer.as_deref().map(|l| format!(", layer {}", l)).unwrap_or_default();
     let mut direct_usages = 0;
-    let mut calling_functions = Vec::new();
-    
-    // Analyze function references to find usages
-    for (target, callers) in &snapshot.function_references {
-        if target.contains(&symbol) {
-            direct_usages += callers.len();
-            calling_functions.extend(callers.clone());
-        }
-    }
-    
-    if direct_usages > 0 {
-        println!("📞 Direct function calls: {}", direct_usages);
-        println!("🔗 Called by {} different functions", calling_functions.len());
-        
-        // Show sample callers
-        let sample_size = std::cmp::min(5, calling_functions.len());
-        if sample_size > 0 {
-            println!("📋 Sample callers:");
-            for (i, caller) in calling_functions.iter().take(sample_size).enumerate() {
-                println!("  {}. {}", i + 1, caller);
+    for report in &reports {
+        let target = &report.target;
+        println!("\n📊 Impact of {} {} ({}{}):", target.kind.as_str(), target.qualified_name, target.crate_name, layer(&target.layer));
+        println!("──────────────────");
+        for section in &report.sections {
+            println!("{} ({}):", section.name.replace('_', " "), section.sites.len());
+            for site in section.sites.iter().take(10) {
+                let member = site.member.as_deref().map(|m| format!(" via {}", m)).unwrap_or_default();
+                println!("  • {}{} at {}:{} ({}{})", site.user, member, site.file_path, site.line + 1, site.crate_name, layer(&site.layer));
             }
-            if calling_functions.len() > sample_size {
-                println!("  ... and {} more", calling_functions.len() - sample_size);
+            if section.sites.len() > 10 {
+                println!("  ... and {} more", section.sites.len() - 10);
             }
         }
-    } else {
-        println!("📞 No direct function calls found");
+        
+        println!("💥 Blast radius within {} hops: {} items", report.depth, report.blast_radius.len());
+        for item in report.blast_radius.iter().take(20) {
+            println!("  {} {} {} ({}{})", item.distance, item.kind, item.name, item.crate_name, layer(&item.layer));
+        }
+        if report.blast_radius.len() > 20 {
+            println!("  ... and {} more", report.blast_radius.len() - 20);
+        }
+        direct_usages += report.direct_count();
     }
     
-    // Analyze by symbol type
-    if let Some(sym_type) = symbol_type {
-        println!("\n🔍 Type-specific analysis for '{}':", sym_type);
-        match sym_type.to_lowercase().as_str() {
-            "struct" => {
-                println!("  • Look for field access patterns");
-                println!("  • Check for trait implementations");
-                println!("  • Verify constructor usage");
-            },
-            "trait" => {
-                println!("  • Check for implementations across crates");
-                println!("  • Look for trait bounds in generics");
-                println!("  • Verify method calls on trait objects");
-            },
-            "enum" => {
-                println!("  • Check variant usage patterns");
-                println!("  • Look for match expressions");
-                println!("  • Verify serialization/deserialization");
-            },
-            "function" => {
-                println!("  • Direct function calls (shown above)");
-                println!("  • Function pointer usage");
-                println!("  • Higher-order function usage");
-            },
-            _ => {
-                println!("  • General symbol analysis performed");
-            }
-        }
+    if let Some(output_path) = &output_json {
+        std::fs::write(output_path, serde_json::to_string_pretty(&reports)?)?;
+        eprintln!("💾 Results written to {:?}", output_path);
     }
     
     // Provide change impact guidance
@@ -1,16 +1,26 @@
 use anyhow::Result;
 use serde_json::{json, Value};
 use tokio::sync::RwLock;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::config::Config;
-use crate::graph::MemgraphClient;
-use crate::parser::ParsedSymbols;
-use crate::analyzer::WorkspaceAnalyzer;
+use crate::graph::{connect_backend, incoming_call_counts, FunctionNode, GraphBackend};
+use crate::parser::{expand_declarative_macros, ParsedSymbols};
+use crate::parser::symbols::FunctionContext;
+use crate::analyzer::{
+    ActorRegistry, AskGraph, BatchImpact, FailureOutcome, FrameworkPatterns, MessageAudit, Reachability, SequenceDiagram,
+    SequenceFormat, SupervisionTree, SymbolImpact, TargetKind, WorkspaceAnalyzer,
+};
+use crate::changes::{repository_root, ChangeSet, ChangeSpec};
 use crate::workspace::{WorkspaceDiscovery, CrateMetadata};
 use crate::architecture::ArchitectureAnalyzer;
 use crate::embeddings::{EmbeddingGenerator, SemanticSearch};
 use crate::incremental::IncrementalUpdater;
+use crate::mcp::tools::tool_registry;
+
+/// Most results a semantic search returns, whatever limit the client asks for
+const MAX_SEARCH_RESULTS: u64 = 1000;
 
 #[derive(Debug, Clone)]
 pub struct McpRequest {
@@ -33,9 +43,61 @@ pub struct McpError {
     pub data: Option<Value>,
 }
 
+impl McpResponse {
+    /// JSON-RPC 2.0 response object; carries exactly one of `result` or `error`
+    pub fn to_json(&self) -> Value {
+        match &self.error {
+            Some(error) => json!({
+                "jsonrpc": "2.0",
+                "id": self.id,
+                "error": error,
+            }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": self.id,
+                "result": self.result.clone().unwrap_or_else(|| json!({})),
+            }),
+        }
+    }
+}
+
+/// `path`, relative to `root` unless absolute, with symlinks resolved, if it exists and lies inside `root`
+fn within(root: &Path, path: &Path) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let path = root.join(path).canonicalize().ok()?;
+    path.starts_with(&root).then_some(path)
+}
+
+/// Convert a handler response into an MCP `tools/call` result.
+///
+/// Handlers that already produce `content` blocks are passed through; other JSON results are
+/// rendered as a text block. Handler errors become `isError` results rather than JSON-RPC errors,
+/// as the MCP spec asks for tool execution failures.
+fn tool_call_result(response: McpResponse) -> Value {
+    if let Some(error) = response.error {
+        return json!({
+            "content": [{"type": "text", "text": error.message}],
+            "isError": true
+        });
+    }
+
+    let result = response.result.unwrap_or(Value::Null);
+    if result.get("content").is_some_and(|c| c.is_array()) {
+        let mut result = result;
+        result["isError"] = json!(false);
+        return result;
+    }
+
+    let text = serde_json::to_string_pretty(&result).unwrap_or_else(|_| result.to_string());
+    json!({
+        "content": [{"type": "text", "text": text}],
+        "isError": false
+    })
+}
+
 pub struct EnhancedMcpServer {
     config: Config,
-    graph: Arc<MemgraphClient>,
+    graph: Arc<dyn GraphBackend>,
     analyzer: Arc<RwLock<WorkspaceAnalyzer>>,
     workspace_discovery: Arc<RwLock<WorkspaceDiscovery>>,
     architecture_analyzer: Arc<ArchitectureAnalyzer>,
@@ -49,13 +111,18 @@ pub struct EnhancedMcpServer {
 impl EnhancedMcpServer {
     pub async fn new(config_path: &str) -> Result<Self> {
         let config = Config::from_file(config_path)?;
-        let graph = Arc::new(MemgraphClient::new(&config).await?);
+        let graph = connect_backend(&config).await?;
+        Self::with_graph(config, graph)
+    }
+
+    /// Build a server around an already connected graph backend
+    pub fn with_graph(config: Config, graph: Arc<dyn GraphBackend>) -> Result<Self> {
         let analyzer = Arc::new(RwLock::new(WorkspaceAnalyzer::new_with_config(config.clone())?));
         
         let workspace_discovery = Arc::new(RwLock::new(WorkspaceDiscovery::new(config.clone())));
         let architecture_analyzer = Arc::new(ArchitectureAnalyzer::new(graph.clone(), config.clone()));
         let embedding_generator = Arc::new(EmbeddingGenerator::new(config.clone()));
-        let semantic_search = Arc::new(RwLock::new(SemanticSearch::new()));
+        let semantic_search = Arc::new(RwLock::new(SemanticSearch::with_provider(embedding_generator.provider())));
         let incremental_updater = Arc::new(RwLock::new(IncrementalUpdater::new(config.clone(), graph.clone())?));
 
         Ok(Self {
@@ -161,23 +228,166 @@ impl EnhancedMcpServer {
         Ok(())
     }
 
+    /// Handle one line of JSON-RPC input from the transport.
+    ///
+    /// Returns the serialized response, or `None` for notifications, which never get one.
+    pub async fn handle_message(&self, line: &str) -> Option<Value> {
+        let message = match serde_json::from_str::<Value>(line) {
+            Ok(message) => message,
+            Err(e) => {
+                let response = self.error_response(None, -32700, &format!("Parse error: {}", e));
+                return Some(response.to_json());
+            }
+        };
+
+        let id = message.get("id").cloned();
+        let method = match message.get("method").and_then(|m| m.as_str()) {
+            Some(method) if message.is_object() => method.to_string(),
+            _ => {
+                let response = self.error_response(id, -32600, "Invalid Request: expected an object with a 'method' string");
+                return Some(response.to_json());
+            }
+        };
+
+        let request = McpRequest {
+            id,
+            method,
+            params: message.get("params").cloned(),
+        };
+
+        if request.id.is_none() {
+            self.handle_notification(request).await;
+            return None;
+        }
+
+        Some(self.handle_request(request).await.to_json())
+    }
+
+    async fn handle_notification(&self, request: McpRequest) {
+        match request.method.as_str() {
+            "notifications/initialized" => eprintln!("🤝 Client initialized"),
+            "notifications/cancelled" => {}
+            method if method.starts_with("notifications/") => {
+                eprintln!("⚠️ Ignoring unsupported notification: {}", method);
+            }
+            _ => {
+                // Requests without an id are still executed, but JSON-RPC forbids replying to them
+                self.handle_request(request).await;
+            }
+        }
+    }
+
+    /// Serve newline-delimited JSON-RPC over stdin/stdout until stdin closes
+    pub async fn serve_stdio(&self) -> Result<()> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+        let stdin = tokio::io::stdin();
+        let mut reader = tokio::io::BufReader::new(stdin);
+        let stdout = tokio::io::stdout();
+        let mut writer = tokio::io::BufWriter::new(stdout);
+
+        loop {
+            let mut line = String::new();
+            match reader.read_line(&mut line).await {
+                Ok(0) => break,
+                Ok(_) => {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    if let Some(response) = self.handle_message(&line).await {
+                        writer.write_all(response.to_string().as_bytes()).await?;
+                        writer.write_all(b"\n").await?;
+                        writer.flush().await?;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error reading from stdin: {}", e);
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn handle_request(&self, request: McpRequest) -> McpResponse {
         match request.method.as_str() {
             "initialize" => self.handle_initialize(request).await,
+            "ping" => McpResponse {
+                id: request.id,
+                result: Some(json!({})),
+                error: None,
+            },
+            "tools/list" => self.handle_tools_list(request),
+            "tools/call" => self.handle_tools_call(request).await,
+            // Bare tool names are still accepted for clients that predate tools/call
+            _ => {
+                let method = request.method.clone();
+                self.call_tool(&method, request).await
+            }
+        }
+    }
+
+    fn handle_tools_list(&self, request: McpRequest) -> McpResponse {
+        let tools: Vec<Value> = tool_registry().iter().map(|tool| tool.to_json()).collect();
+        McpResponse {
+            id: request.id,
+            result: Some(json!({ "tools": tools })),
+            error: None,
+        }
+    }
+
+    async fn handle_tools_call(&self, request: McpRequest) -> McpResponse {
+        let params = request.params.as_ref();
+        let name = match params.and_then(|p| p.get("name")).and_then(|n| n.as_str()) {
+            Some(name) => name.to_string(),
+            None => return self.error_response(request.id, -32602, "Missing 'name' parameter"),
+        };
+        if !tool_registry().iter().any(|tool| tool.name == name) {
+            return self.error_response(request.id, -32602, &format!("Unknown tool: {}", name));
+        }
+
+        let arguments = params
+            .and_then(|p| p.get("arguments"))
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        let tool_request = McpRequest {
+            id: request.id.clone(),
+            method: name.clone(),
+            params: Some(arguments),
+        };
+
+        let response = self.call_tool(&name, tool_request).await;
+        McpResponse {
+            id: request.id,
+            result: Some(tool_call_result(response)),
+            error: None,
+        }
+    }
+
+    /// Dispatch a tool by name. Every entry in [`tool_registry`] must have an arm here.
+    async fn call_tool(&self, name: &str, request: McpRequest) -> McpResponse {
+        match name {
             "workspace_context" => self.handle_workspace_context(request).await,
             "analyze_change_impact" => self.handle_analyze_change_impact(request).await,
             "analyze_batch_change_impact" => self.handle_analyze_batch_change_impact(request).await,
             "analyze_file_changes" => self.handle_analyze_file_changes(request).await,
             "discover_functions_from_diff" => self.handle_discover_functions_from_diff(request).await,
+            "analyze_git_changes" => self.handle_analyze_git_changes(request).await,
             "incremental_file_analysis" => self.handle_incremental_file_analysis(request).await,
             "find_unreferenced_functions" => self.handle_find_unreferenced_functions(request).await,
+            "find_dead_code" => self.handle_find_dead_code(request).await,
             "find_test_only_functions" => self.handle_find_test_only_functions(request).await,
             "find_functions_without_tests" => self.handle_find_functions_without_tests(request).await,
             "find_functions_with_tests" => self.handle_find_functions_with_tests(request).await,
             "find_most_referenced_functions" => self.handle_find_most_referenced_functions(request).await,
             "find_most_referenced_without_tests" => self.handle_find_most_referenced_without_tests(request).await,
             "generate_actor_spawn_diagram" => self.handle_generate_actor_spawn_diagram(request).await,
+            "get_supervision_tree" => self.handle_get_supervision_tree(request).await,
+            "find_ask_cycles" => self.handle_find_ask_cycles(request).await,
+            "find_unhandled_messages" => self.handle_find_unhandled_messages(request).await,
             "generate_actor_message_diagram" => self.handle_generate_actor_message_diagram(request).await,
+            "generate_message_sequence_diagram" => self.handle_generate_message_sequence_diagram(request).await,
             "get_actor_details" => self.handle_get_actor_details(request).await,
             "get_distributed_actors" => self.handle_get_distributed_actors(request).await,
             "generate_distributed_actor_message_flow" => self.handle_generate_distributed_actor_message_flow(request).await,
@@ -197,7 +407,7 @@ impl EnhancedMcpServer {
                 result: None,
                 error: Some(McpError {
                     code: -32601,
-                    message: format!("Method not found: {}", request.method),
+                    message: format!("Method not found: {}", name),
                     data: None,
                 }),
             },
@@ -235,6 +445,9 @@ impl EnhancedMcpServer {
             }
         }
 
+        // Invocations may use macros defined in other crates, so expand once everything is merged
+        expand_declarative_macros(&mut all_symbols);
+        self.incremental_updater.write().await.set_macro_definitions(all_symbols.macro_definitions.clone());
         crate::parser::references::resolve_all_references(&mut all_symbols).unwrap_or_else(|e| {
             eprintln!("⚠️ Failed to resolve references: {}", e);
         });
@@ -276,123 +489,7 @@ impl EnhancedMcpServer {
                 "serverInfo": {
                     "name": "enhanced-rust-workspace-analyzer",
                     "version": "0.1.0"
-                },
-                "tools": [
-                    {
-                        "name": "workspace_context",
-                        "description": "Get comprehensive context about the workspace with Memgraph 3.0 and tree-sitter",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "include_stats": {"type": "boolean", "description": "Include detailed statistics"},
-                                "crate_filter": {"type": "string", "description": "Filter by crate name"}
-                            }
-                        }
-                    },
-                    {
-                        "name": "analyze_change_impact",
-                        "description": "Analyze impact of changes using graph traversal",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "target": {"type": "string", "description": "Function or type to analyze"},
-                                "depth": {"type": "number", "description": "Analysis depth (default: 3)"}
-                            },
-                            "required": ["target"]
-                        }
-                    },
-                    {
-                        "name": "analyze_batch_change_impact",
-                        "description": "Analyze impact of multiple changed functions/types in batch",
-                        "inputSchema": {
-                            "type": "object", 
-                            "properties": {
-                                "targets": {
-                                    "type": "array",
-                                    "items": {"type": "string"},
-                                    "description": "List of functions/types to analyze"
-                                },
-                                "depth": {"type": "number", "description": "Analysis depth (default: 3)"},
-                                "aggregate": {"type": "boolean", "description": "Combine results into single report (default: true)"}
-                            },
-                            "required": ["targets"]
-                        }
-                    },
-                    {
-                        "name": "check_architecture_violations",
-                        "description": "Check for architecture violations with layer analysis",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "severity": {"type": "string", "enum": ["all", "error", "warning"], "description": "Filter by severity"}
-                            }
-                        }
-                    },
-                    {
-                        "name": "semantic_search", 
-                        "description": "Search functions and types semantically using embeddings",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "query": {"type": "string", "description": "Search query"},
-                                "limit": {"type": "number", "description": "Number of results (default: 10)"},
-                                "crate_filter": {"type": "string", "description": "Limit search to specific crate"}
-                            },
-                            "required": ["query"]
-                        }
-                    },
-                    {
-                        "name": "get_function_details",
-                        "description": "Get detailed information about a specific function",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "qualified_name": {"type": "string", "description": "Fully qualified function name"}
-                            },
-                            "required": ["qualified_name"]
-                        }
-                    },
-                    {
-                        "name": "get_type_details",
-                        "description": "Get detailed information about a specific type",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "qualified_name": {"type": "string", "description": "Fully qualified type name"}
-                            },
-                            "required": ["qualified_name"]
-                        }
-                    },
-                    {
-                        "name": "get_crate_overview",
-                        "description": "Get overview of a specific crate",
-                        "inputSchema": {
-                            "type": "object", 
-                            "properties": {
-                                "crate_name": {"type": "string", "description": "Name of the crate"}
-                            },
-                            "required": ["crate_name"]
-                        }
-                    },
-                    {
-                        "name": "get_layer_health",
-                        "description": "Get architecture layer health report",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {}
-                        }
-                    },
-                    {
-                        "name": "incremental_update",
-                        "description": "Perform incremental update of changed files",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "files": {"type": "array", "items": {"type": "string"}, "description": "List of changed file paths"}
-                            }
-                        }
-                    }
-                ]
+                }
             })),
             error: None,
         }
@@ -495,96 +592,47 @@ impl EnhancedMcpServer {
             .and_then(|v| v.as_u64())
             .unwrap_or(3) as usize;
 
-        // Query 1: Find all functions that call the target (existing functionality)
-        let impact_query = format!(
-            "MATCH (f:Function {{qualified_name: '{}'}})
-             MATCH path = (caller:Function)-[:CALLS*1..{}]->(f)
-             RETURN DISTINCT caller.qualified_name as caller,
-                    caller.crate as caller_crate,
-                    caller.file as file,
-                    caller.line_start as line,
-                    caller.is_test as is_test,
-                    size(path) as distance
-             ORDER BY distance, caller_crate
-             LIMIT 50",
-            target, depth
-        );
-
-        let query = neo4rs::Query::new(impact_query);
-        let result = match self.graph.execute_query(query).await {
-            Ok(result) => result,
+        // Find all functions that call the target, then the tests that call it directly
+        let (impacted_functions, direct_tests) = match self.collect_change_impact(&target, depth).await {
+            Ok(impact) => impact,
             Err(e) => return self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
         };
 
-        let mut impacted_functions = Vec::new();
-        let mut test_functions = Vec::new();
-        let mut regular_functions = Vec::new();
-
-        for row in result {
-            if let (Ok(caller), Ok(caller_crate), Ok(file), Ok(line), Ok(is_test), Ok(distance)) = (
-                row.get::<String>("caller"),
-                row.get::<String>("caller_crate"), 
-                row.get::<String>("file"),
-                row.get::<i64>("line"),
-                row.get::<bool>("is_test"),
-                row.get::<i64>("distance"),
-            ) {
-                let function_info = json!({
-                    "function": caller,
-                    "crate": caller_crate,
-                    "file": file,
-                    "line": line,
-                    "distance": distance,
-                    "is_test": is_test
-                });
-                
-                impacted_functions.push(function_info.clone());
-                
-                if is_test {
-                    test_functions.push(function_info);
-                } else {
-                    regular_functions.push(function_info);
-                }
-            }
-        }
+        let (test_functions, regular_functions): (Vec<Value>, Vec<Value>) = impacted_functions.iter()
+            .cloned()
+            .partition(|f| f["is_test"].as_bool().unwrap_or(false));
 
-        // Query 2: Find test functions that directly test the target function
-        let direct_test_query = format!(
-            "MATCH (test:Function {{is_test: true}})-[:CALLS]->(f:Function {{qualified_name: '{}'}})
-             RETURN DISTINCT test.qualified_name as test_function,
-                    test.crate as test_crate,
-                    test.file as test_file,
-                    test.line_start as test_line",
-            target
-        );
+        self.create_impact_report(&target, depth, &impacted_functions, &test_functions, &regular_functions, &direct_tests, request.id)
+    }
 
-        let test_query = neo4rs::Query::new(direct_test_query);
-        let test_result = match self.graph.execute_query(test_query).await {
-            Ok(result) => result,
-            Err(_) => {
-                // If query fails, continue without direct test analysis
-                return self.create_impact_report(&target, depth, &impacted_functions, &test_functions, &regular_functions, &Vec::new(), request.id);
-            }
-        };
+    /// Transitive callers of `target` (up to `depth` hops, at most 50) and the tests that call it directly
+    async fn collect_change_impact(&self, target: &str, depth: usize) -> Result<(Vec<Value>, Vec<Value>)> {
+        let mut impacted_functions = Vec::new();
+        for (caller, distance) in self.graph.transitive_callers(target, depth).await?.into_iter().take(50) {
+            impacted_functions.push(json!({
+                "function": caller.qualified_name,
+                "crate": caller.crate_name,
+                "file": caller.file,
+                "line": caller.line_start,
+                "distance": distance,
+                "is_test": caller.is_test
+            }));
+        }
 
         let mut direct_tests = Vec::new();
-        for row in test_result {
-            if let (Ok(test_func), Ok(test_crate), Ok(test_file), Ok(test_line)) = (
-                row.get::<String>("test_function"),
-                row.get::<String>("test_crate"),
-                row.get::<String>("test_file"), 
-                row.get::<i64>("test_line"),
-            ) {
+        let mut seen_tests = std::collections::HashSet::new();
+        for edge in self.graph.find_callers(target).await? {
+            if edge.caller.is_test && seen_tests.insert(edge.caller.id.clone()) {
                 direct_tests.push(json!({
-                    "function": test_func,
-                    "crate": test_crate,
-                    "file": test_file,
-                    "line": test_line
+                    "function": edge.caller.qualified_name,
+                    "crate": edge.caller.crate_name,
+                    "file": edge.caller.file,
+                    "line": edge.caller.line_start
                 }));
             }
         }
 
-        self.create_impact_report(&target, depth, &impacted_functions, &test_functions, &regular_functions, &direct_tests, request.id)
+        Ok((impacted_functions, direct_tests))
     }
 
     async fn handle_analyze_batch_change_impact(&self, request: McpRequest) -> McpResponse {
@@ -628,24 +676,8 @@ impl EnhancedMcpServer {
         let mut analysis_results = Vec::new();
 
         for target in targets {
-            // Query for each target
-            let impact_query = format!(
-                "MATCH (f:Function {{qualified_name: '{}'}})
-                 MATCH path = (caller:Function)-[:CALLS*1..{}]->(f)
-                 RETURN DISTINCT caller.qualified_name as caller,
-                        caller.crate as caller_crate,
-                        caller.file as file,
-                        caller.line_start as line,
-                        caller.is_test as is_test,
-                        size(path) as distance
-                 ORDER BY distance, caller_crate
-                 LIMIT 50",
-                target, depth
-            );
-
-            let query = neo4rs::Query::new(impact_query);
-            let result = match self.graph.execute_query(query).await {
-                Ok(result) => result,
+            let (target_impacted, direct_tests) = match self.collect_change_impact(target, depth).await {
+                Ok(impact) => impact,
                 Err(e) => {
                     analysis_results.push(json!({
                         "target": target,
@@ -656,66 +688,20 @@ impl EnhancedMcpServer {
                 }
             };
 
-            let mut target_impacted = Vec::new();
-            for row in result {
-                if let (Ok(caller), Ok(caller_crate), Ok(file), Ok(line), Ok(is_test), Ok(distance)) = (
-                    row.get::<String>("caller"),
-                    row.get::<String>("caller_crate"), 
-                    row.get::<String>("file"),
-                    row.get::<i64>("line"),
-                    row.get::<bool>("is_test"),
-                    row.get::<i64>("distance"),
-                ) {
-                    let function_info = json!({
-                        "function": caller,
-                        "crate": caller_crate,
-                        "file": file,
-                        "line": line,
-                        "distance": distance,
-                        "is_test": is_test,
-                        "affects_target": target
-                    });
-                    
-                    target_impacted.push(function_info.clone());
-                    all_impacted.push(function_info.clone());
-                    
-                    if is_test {
-                        all_test_functions.push(function_info.clone());
-                    } else {
-                        all_regular_functions.push(function_info.clone());
-                    }
+            for mut function_info in target_impacted.iter().cloned() {
+                function_info["affects_target"] = json!(target);
+                all_impacted.push(function_info.clone());
+
+                if function_info["is_test"].as_bool().unwrap_or(false) {
+                    all_test_functions.push(function_info);
+                } else {
+                    all_regular_functions.push(function_info);
                 }
             }
 
-            // Query for direct tests for this target
-            let direct_test_query = format!(
-                "MATCH (test:Function {{is_test: true}})-[:CALLS]->(f:Function {{qualified_name: '{}'}})
-                 RETURN DISTINCT test.qualified_name as test_function,
-                        test.crate as test_crate,
-                        test.file as test_file,
-                        test.line_start as test_line",
-                target
-            );
-
-            let test_query = neo4rs::Query::new(direct_test_query);
-            if let Ok(test_result) = self.graph.execute_query(test_query).await {
-                for row in test_result {
-                    if let (Ok(test_func), Ok(test_crate), Ok(test_file), Ok(test_line)) = (
-                        row.get::<String>("test_function"),
-                        row.get::<String>("test_crate"),
-                        row.get::<String>("test_file"), 
-                        row.get::<i64>("test_line"),
-                    ) {
-                        let direct_test_info = json!({
-                            "function": test_func,
-                            "crate": test_crate,
-                            "file": test_file,
-                            "line": test_line,
-                            "tests_target": target
-                        });
-                        all_direct_tests.push(direct_test_info);
-                    }
-                }
+            for mut direct_test_info in direct_tests {
+                direct_test_info["tests_target"] = json!(target);
+                all_direct_tests.push(direct_test_info);
             }
 
             analysis_results.push(json!({
@@ -895,6 +881,11 @@ impl EnhancedMcpServer {
             .and_then(|s| s.as_str())
             .map(|s| s.to_string());
 
+        let depth = request.params.as_ref()
+            .and_then(|p| p.get("depth"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(3) as usize;
+
         // Get current workspace symbols
         let symbols_guard = self.current_symbols.read().await;
         let symbols = match symbols_guard.as_ref() {
@@ -912,40 +903,11 @@ impl EnhancedMcpServer {
             }
         };
 
-        // Search for the symbol
-        let mut found_symbols = Vec::new();
-
-        // Search in functions
-        for func in &symbols.functions {
-            if func.name == symbol || func.qualified_name.contains(&symbol) {
-                found_symbols.push(json!({
-                    "type": "function",
-                    "name": func.qualified_name,
-                    "file": func.file_path,
-                    "line": func.line_start,
-                    "crate": func.crate_name,
-                    "visibility": func.visibility
-                }));
-            }
-        }
-
-        // Search in types
-        for typ in &symbols.types {
-            if typ.name == symbol || typ.qualified_name.contains(&symbol) {
-                found_symbols.push(json!({
-                    "type": typ.kind,
-                    "name": typ.qualified_name,
-                    "file": typ.file_path,
-                    "line": typ.line_start,
-                    "crate": typ.crate_name,
-                    "visibility": typ.visibility
-                }));
-            }
-        }
+        let reports = SymbolImpact::analyze(symbols, &self.config, &symbol, symbol_type.as_deref(), depth);
 
-        if found_symbols.is_empty() {
+        if reports.is_empty() {
             let report = format!(
-                "# Symbol Impact Analysis: '{}'\n\n❌ **Symbol not found**\n\nThe symbol '{}' was not found in the current workspace.\n\n## Suggestions:\n- Check spelling of the symbol name\n- Ensure the symbol is in a workspace member crate\n- Try searching for partial matches\n- Available symbol types: function, struct, trait, enum, type",
+                "# Symbol Impact Analysis: '{}'\n\n❌ **Symbol not found**\n\nThe symbol '{}' was not found in the current workspace.\n\n## Suggestions:\n- Check spelling of the symbol name\n- Ensure the symbol is in a workspace member crate\n- Use `Enum::Variant` for enum variants\n- Available symbol types: function, struct, trait, enum, variant, type",
                 symbol, symbol
             );
 
@@ -960,18 +922,7 @@ impl EnhancedMcpServer {
             };
         }
 
-        // Analyze impact
-        let mut direct_usages = 0;
-        let mut calling_functions = Vec::new();
-
-        // Analyze function calls to find usages
-        for call in &symbols.calls {
-            let target = call.qualified_callee.as_ref().unwrap_or(&call.callee_name);
-            if target.contains(&symbol) {
-                direct_usages += 1;
-                calling_functions.push(call.caller_id.clone());
-            }
-        }
+        let direct_usages: usize = reports.iter().map(SymbolImpact::direct_count).sum();
 
         // Generate impact level
         let (impact_level, impact_emoji) = if direct_usages == 0 {
@@ -985,72 +936,55 @@ impl EnhancedMcpServer {
         // Build detailed report
         let mut report = format!(
             "# Symbol Impact Analysis: '{}'\n\n✅ **Found {} matches**\n\n## Symbol Definitions:\n",
-            symbol, found_symbols.len()
+            symbol, reports.len()
         );
 
-        for (i, found_symbol) in found_symbols.iter().enumerate() {
+        for (i, impact) in reports.iter().enumerate() {
+            let target = &impact.target;
             report.push_str(&format!(
                 "{}. **{}**: `{}` in `{}:{}`\n",
-                i + 1,
-                found_symbol["type"].as_str().unwrap_or("unknown"),
-                found_symbol["name"].as_str().unwrap_or("unknown"),
-                found_symbol["file"].as_str().unwrap_or("unknown"),
-                found_symbol["line"].as_i64().unwrap_or(0)
+                i + 1, target.kind.as_str(), target.qualified_name, target.file_path, target.line + 1
             ));
         }
 
         report.push_str(&format!(
-            "\n## 📊 Impact Analysis\n\n{} **{} IMPACT**\n\n",
-            impact_emoji, impact_level
+            "\n## 📊 Impact Analysis\n\n{} **{} IMPACT**: {} direct usages\n",
+            impact_emoji, impact_level, direct_usages
         ));
 
-        if direct_usages > 0 {
+        let layer = |layer: &Option<String>| layer.as_deref().map(|l| format!(", layer `{}`", l)).unwrap_or_default();
+        for impact in &reports {
+            let target = &impact.target;
             report.push_str(&format!(
-                "- **Direct function calls**: {}\n- **Called by {} different functions**\n\n",
-                direct_usages, calling_functions.len()
+                "\n### {} `{}` (crate `{}`{})\n\n",
+                target.kind.as_str(), target.qualified_name, target.crate_name, layer(&target.layer)
             ));
-
-            let sample_size = std::cmp::min(5, calling_functions.len());
-            if sample_size > 0 {
-                report.push_str("### Sample Callers:\n");
-                for (i, caller) in calling_functions.iter().take(sample_size).enumerate() {
-                    report.push_str(&format!("{}. `{}`\n", i + 1, caller));
+            for section in &impact.sections {
+                report.push_str(&format!("- **{}**: {}\n", section.name.replace('_', " "), section.sites.len()));
+                for site in section.sites.iter().take(10) {
+                    let member = site.member.as_deref().map(|m| format!(" via `{}`", m)).unwrap_or_default();
+                    report.push_str(&format!(
+                        "  - `{}`{} at `{}:{}` (crate `{}`{})\n",
+                        site.user, member, site.file_path, site.line + 1, site.crate_name, layer(&site.layer)
+                    ));
                 }
-                if calling_functions.len() > sample_size {
-                    report.push_str(&format!("... and {} more\n", calling_functions.len() - sample_size));
+                if section.sites.len() > 10 {
+                    report.push_str(&format!("  - ... and {} more\n", section.sites.len() - 10));
                 }
             }
-        } else {
-            report.push_str("- **No direct function calls found**\n");
-        }
 
-        // Add type-specific analysis
-        if let Some(sym_type) = symbol_type {
-            report.push_str(&format!("\n## 🔍 Type-Specific Analysis ('{}')\n\n", sym_type));
-            match sym_type.to_lowercase().as_str() {
-                "struct" => {
-                    report.push_str("- Check for field access patterns\n");
-                    report.push_str("- Verify trait implementations\n");
-                    report.push_str("- Review constructor usage\n");
-                },
-                "trait" => {
-                    report.push_str("- Check implementations across crates\n");
-                    report.push_str("- Look for trait bounds in generics\n");
-                    report.push_str("- Verify trait object usage\n");
-                },
-                "enum" => {
-                    report.push_str("- Check variant usage patterns\n");
-                    report.push_str("- Look for match expressions\n");
-                    report.push_str("- Verify serialization impact\n");
-                },
-                "function" => {
-                    report.push_str("- Direct calls analyzed above\n");
-                    report.push_str("- Check function pointer usage\n");
-                    report.push_str("- Review higher-order usage\n");
-                },
-                _ => {
-                    report.push_str("- General symbol analysis performed\n");
-                }
+            report.push_str(&format!(
+                "\n**Blast radius** within {} hops: {} items\n",
+                impact.depth, impact.blast_radius.len()
+            ));
+            for item in impact.blast_radius.iter().take(20) {
+                report.push_str(&format!(
+                    "- {} hop(s): {} `{}` (crate `{}`{})\n",
+                    item.distance, item.kind, item.name, item.crate_name, layer(&item.layer)
+                ));
+            }
+            if impact.blast_radius.len() > 20 {
+                report.push_str(&format!("- ... and {} more\n", impact.blast_radius.len() - 20));
             }
         }
 
@@ -1092,11 +1026,11 @@ impl EnhancedMcpServer {
                 "content": [{"type": "text", "text": report}],
                 "symbol": symbol,
                 "found": true,
-                "matches": found_symbols.len(),
+                "matches": reports.len(),
                 "impact_level": impact_level.to_lowercase(),
                 "direct_usages": direct_usages,
-                "calling_functions_count": calling_functions.len(),
-                "found_symbols": found_symbols
+                "depth": depth,
+                "reports": reports
             })),
             error: None,
         }
@@ -1186,12 +1120,55 @@ impl EnhancedMcpServer {
             None => return self.error_response(request.id, -32602, "Missing 'query' parameter"),
         };
 
+        // Client supplied, so bounded before it sizes any search
         let limit = request.params.as_ref()
             .and_then(|p| p.get("limit"))
             .and_then(|v| v.as_u64())
-            .unwrap_or(10) as usize;
+            .unwrap_or(10)
+            .clamp(1, MAX_SEARCH_RESULTS) as usize;
+
+        let caller_of = self.extract_required_param(&request, "caller_of");
 
         let semantic_search = self.semantic_search.read().await;
+        // Structural filters need the graph; so does a server whose process-local index is empty
+        if caller_of.is_some() || semantic_search.is_empty() {
+            let vector = match semantic_search.embed_query(&query).await {
+                Ok(vector) => vector,
+                Err(e) => return self.error_response(request.id, -32603, &format!("Search failed: {}", e)),
+            };
+            let results = match self.graph.similar_functions(&vector, limit, caller_of.as_deref()).await {
+                Ok(results) => results,
+                Err(e) => return self.error_response(request.id, -32603, &format!("Search failed: {}", e)),
+            };
+
+            let report = format!(
+                "# Semantic Search Results\n\n\
+                **Query**: {}\n\
+                {}\
+                **Results**: {}\n\n\
+                ## 🔍 Matches\n{}\n",
+                query,
+                caller_of.as_ref().map(|target| format!("**Callers of**: `{}`\n", target)).unwrap_or_default(),
+                results.len(),
+                results.iter()
+                    .map(|(function, similarity)| format!("- **{}** (similarity: {:.3}) - Function in `{}` ({}:{})",
+                        function.qualified_name,
+                        similarity,
+                        function.crate_name,
+                        function.file,
+                        function.line_start))
+                    .collect::<Vec<_>>().join("\n")
+            );
+
+            return McpResponse {
+                id: request.id,
+                result: Some(json!({
+                    "content": [{"type": "text", "text": report}]
+                })),
+                error: None,
+            };
+        }
+
         let results = match semantic_search.search(&query, limit).await {
             Ok(results) => results,
             Err(e) => return self.error_response(request.id, -32603, &format!("Search failed: {}", e)),
@@ -1640,24 +1617,14 @@ impl EnhancedMcpServer {
     }
 
     async fn discover_functions_in_files(&self, file_paths: &[String]) -> Result<Vec<String>> {
+        let functions = self.graph.functions().await?;
         let mut discovered_functions = Vec::new();
-        
-        // Query the database for functions in the specified files
-        for file_path in file_paths {
-            let query = neo4rs::Query::new(format!(
-                "MATCH (f:Function)
-                 WHERE f.file ENDS WITH '{}'
-                 RETURN f.qualified_name as qualified_name",
-                file_path.trim_start_matches('/').replace('\'', "\\'")
-            ));
 
-            let result = self.graph.execute_query(query).await?;
-            
-            for row in result {
-                if let Ok(qualified_name) = row.get::<String>("qualified_name") {
-                    discovered_functions.push(qualified_name);
-                }
-            }
+        for file_path in file_paths {
+            let suffix = file_path.trim_start_matches('/');
+            discovered_functions.extend(functions.iter()
+                .filter(|f| f.file.ends_with(suffix))
+                .map(|f| f.qualified_name.clone()));
         }
 
         discovered_functions.dedup();
@@ -1683,49 +1650,27 @@ impl EnhancedMcpServer {
             .and_then(|v| v.as_u64())
             .unwrap_or(3) as usize;
 
-        // Extract file names from the diff
-        let changed_files = self.extract_changed_files_from_diff(diff_text);
-        
-        // Extract function signatures from the diff
-        let function_signatures = self.extract_function_signatures_from_diff(diff_text);
-        
-        // Query database to find qualified names for the discovered functions
-        let mut qualified_functions = Vec::new();
-        
-        for signature in &function_signatures {
-            let query = neo4rs::Query::new(format!(
-                "MATCH (f:Function)
-                 WHERE f.name = '{}'
-                 RETURN f.qualified_name as qualified_name, f.file as file",
-                signature.replace('\'', "\\'")
-            ));
-
-            if let Ok(result) = self.graph.execute_query(query).await {
-                for row in result {
-                    if let (Ok(qualified_name), Ok(file)) = (
-                        row.get::<String>("qualified_name"),
-                        row.get::<String>("file")
-                    ) {
-                        // Prefer matches from changed files
-                        let is_in_changed_file = changed_files.iter()
-                            .any(|changed_file| file.ends_with(changed_file));
-                        
-                        if is_in_changed_file {
-                            qualified_functions.insert(0, qualified_name); // Put changed file matches first
-                        } else {
-                            qualified_functions.push(qualified_name);
-                        }
-                    }
-                }
-            }
-        }
-
-        qualified_functions.dedup();
+        // Hunks of the diff against the spans of the functions and types they fall in. Diff paths
+        // are taken relative to the repository of the workspace, or the workspace root outside one.
+        let workspace_root = self.config.workspace.root.clone();
+        let root = tokio::task::spawn_blocking(move || repository_root(&workspace_root).unwrap_or(workspace_root))
+            .await
+            .unwrap_or_else(|_| self.config.workspace.root.clone());
+        let changes = ChangeSet::from_diff("pasted diff", root, diff_text);
+        let changed_files: Vec<&str> = changes.files.iter().map(|file| file.path.as_str()).collect();
+        let changed_items = match self.current_symbols.read().await.as_ref() {
+            Some(symbols) => changes.changed_targets(symbols, &self.config),
+            None => return self.error_response(request.id, -32603, "Workspace not initialized"),
+        };
+        let qualified_functions: Vec<String> = changed_items.iter()
+            .filter(|item| item.kind == TargetKind::Function)
+            .map(|item| item.qualified_name.clone())
+            .collect();
 
         let response_data = json!({
             "analysis_type": "git_diff_function_discovery",
             "changed_files": changed_files,
-            "function_signatures": function_signatures,
+            "changed_items": changed_items,
             "qualified_functions": qualified_functions,
             "discovery_count": qualified_functions.len()
         });
@@ -1774,66 +1719,62 @@ impl EnhancedMcpServer {
         }
     }
 
-    fn extract_changed_files_from_diff(&self, diff_text: &str) -> Vec<String> {
-        let mut files = Vec::new();
-        
-        for line in diff_text.lines() {
-            if line.starts_with("diff --git") {
-                // Extract file path from: diff --git a/path/to/file.rs b/path/to/file.rs
-                if let Some(captures) = line.split_whitespace().nth(2) {
-                    if let Some(file_path) = captures.strip_prefix("a/") {
-                        files.push(file_path.to_string());
-                    }
-                }
-            } else if line.starts_with("+++") {
-                // Extract from: +++ b/path/to/file.rs
-                if let Some(file_path) = line.strip_prefix("+++ b/") {
-                    files.push(file_path.to_string());
-                }
-            }
-        }
-        
-        files.dedup();
-        files
-    }
+    async fn handle_analyze_git_changes(&self, request: McpRequest) -> McpResponse {
+        let params = request.params.as_ref();
+        let param = |name: &str| params.and_then(|p| p.get(name)).and_then(|v| v.as_str()).map(String::from);
 
-    fn extract_function_signatures_from_diff(&self, diff_text: &str) -> Vec<String> {
-        let mut functions = Vec::new();
-        
-        for line in diff_text.lines() {
-            if line.starts_with("+") || line.starts_with("-") {
-                let content = &line[1..].trim(); // Remove +/- prefix
-                
-                // Look for function definitions
-                if let Some(func_name) = self.extract_rust_function_name(content) {
-                    functions.push(func_name);
-                }
-            }
-        }
-        
-        functions.dedup();
-        functions
-    }
+        let spec = match (param("since"), param("range")) {
+            (Some(rev), _) => ChangeSpec::Since(rev),
+            (None, Some(range)) => ChangeSpec::Range(range),
+            (None, None) => ChangeSpec::WorkingTree,
+        };
+        let depth = params
+            .and_then(|p| p.get("depth"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(3) as usize;
+        // Repository config can run commands, so only repositories inside the workspace are read
+        let repo = match param("repo_path") {
+            Some(path) => match self.config.all_workspace_roots().find_map(|root| within(root, Path::new(&path))) {
+                Some(repo) => repo,
+                None => return self.error_response(request.id, -32602, &format!("'{}' is not inside the workspace", path)),
+            },
+            None => self.config.workspace.root.clone(),
+        };
 
-    fn extract_rust_function_name(&self, line: &str) -> Option<String> {
-        // Simple regex-like patterns for Rust function definitions
-        let line = line.trim();
-        
-        // Match: pub fn function_name, fn function_name, async fn function_name, etc.
-        if line.contains("fn ") {
-            // Find "fn " and extract the next word
-            if let Some(fn_pos) = line.find("fn ") {
-                let after_fn = &line[fn_pos + 3..];
-                if let Some(name_end) = after_fn.find(&['(', '<', ' '][..]) {
-                    let function_name = after_fn[..name_end].trim();
-                    if !function_name.is_empty() && function_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                        return Some(function_name.to_string());
-                    }
-                }
-            }
+        // `git` runs to completion; keep it off the async workers
+        let read = tokio::task::spawn_blocking(move || ChangeSet::from_git(&repo, &spec)).await;
+        let changes = match read.map_err(anyhow::Error::from).and_then(|changes| changes) {
+            Ok(changes) => changes,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Failed to read changes: {}", e)),
+        };
+
+        let symbols_guard = self.current_symbols.read().await;
+        let symbols = match symbols_guard.as_ref() {
+            Some(symbols) => symbols,
+            None => return self.error_response(request.id, -32603, "Workspace not initialized"),
+        };
+        let targets = changes.changed_targets(symbols, &self.config);
+        let impact = BatchImpact::analyze(symbols, &self.config, targets, depth);
+        let affected_tests: Vec<&str> = impact.affected_tests().map(|test| test.name.as_str()).collect();
+
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "analysis_type": "git_change_impact",
+                "compared": changes.description,
+                "changed_files": changes.files.iter().map(|file| &file.path).collect::<Vec<_>>(),
+                "summary": {
+                    "changed_files": changes.files.len(),
+                    "changed_items": impact.reports.len(),
+                    "affected": impact.affected.len(),
+                    "affected_tests": affected_tests.len(),
+                    "affected_crates": impact.affected_crates()
+                },
+                "affected_tests": affected_tests,
+                "impact": impact
+            })),
+            error: None,
         }
-        
-        None
     }
 
     async fn handle_incremental_file_analysis(&self, request: McpRequest) -> McpResponse {
@@ -2016,64 +1957,25 @@ impl EnhancedMcpServer {
             .and_then(|p| p.get("crate"))
             .and_then(|v| v.as_str());
 
-        // Build the query to find functions with no incoming CALLS relationships
-        // Go back to the working COUNT approach but keep it simple
-        let mut query_parts = vec![
-            "MATCH (f:Function)".to_string(),
-            "OPTIONAL MATCH (caller:Function)-[:CALLS]->(f)".to_string(),
-            "WITH f, COUNT(caller) AS caller_count".to_string(),
-            "WHERE caller_count = 0".to_string(),
-        ];
-
-        // Add filters
-        if exclude_public {
-            query_parts.push("AND NOT f.visibility = 'pub'".to_string());
-        }
-        
-        if exclude_tests {
-            query_parts.push("AND NOT f.is_test = true".to_string());
-        }
-        
-        if let Some(crate_name) = crate_filter {
-            query_parts.push(format!("AND f.crate = '{}'", crate_name.replace('\'', "\\'")));
-        }
+        let candidates = match self.graph.find_unreferenced_functions().await {
+            Ok(functions) => functions,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        };
 
-        query_parts.push(
-            "RETURN f.qualified_name as qualified_name, f.name as name, f.crate as crate, f.file as file, f.line_start as line, f.visibility as visibility, f.is_test as is_test ORDER BY f.crate, f.name".to_string()
-        );
-
-        let query_str = query_parts.join(" ");
-        let query = neo4rs::Query::new(query_str);
-
-        let mut unreferenced_functions = Vec::new();
-        match self.graph.execute_query(query).await {
-            Ok(result) => {
-                for row in result {
-                    if let (Ok(qualified_name), Ok(name), Ok(crate_name), Ok(file), Ok(line), Ok(visibility), Ok(is_test)) = (
-                        row.get::<String>("qualified_name"),
-                        row.get::<String>("name"),
-                        row.get::<String>("crate"),
-                        row.get::<String>("file"),
-                        row.get::<i64>("line"),
-                        row.get::<String>("visibility"),
-                        row.get::<bool>("is_test"),
-                    ) {
-                        unreferenced_functions.push(json!({
-                            "qualified_name": qualified_name,
-                            "name": name,
-                            "crate": crate_name,
-                            "file": file,
-                            "line": line,
-                            "visibility": visibility,
-                            "is_test": is_test
-                        }));
-                    }
-                }
-            }
-            Err(e) => {
-                return self.error_response(request.id, -32603, &format!("Query failed: {}", e));
-            }
-        }
+        let unreferenced_functions: Vec<Value> = candidates.into_iter()
+            .filter(|f| !exclude_public || f.visibility != "pub")
+            .filter(|f| !exclude_tests || !f.is_test)
+            .filter(|f| crate_filter.is_none_or(|c| f.crate_name == c))
+            .map(|f| json!({
+                "qualified_name": f.qualified_name,
+                "name": f.name,
+                "crate": f.crate_name,
+                "file": f.file,
+                "line": f.line_start,
+                "visibility": f.visibility,
+                "is_test": f.is_test
+            }))
+            .collect();
 
         McpResponse {
             id: request.id,
@@ -2091,6 +1993,84 @@ impl EnhancedMcpServer {
         }
     }
 
+    async fn handle_find_dead_code(&self, request: McpRequest) -> McpResponse {
+        let params = request.params.as_ref();
+        let flag = |name: &str| params.and_then(|p| p.get(name)).and_then(|v| v.as_bool());
+
+        let mut config = self.config.dead_code.clone();
+        config.tests = flag("include_tests").unwrap_or(config.tests);
+        config.public_api = flag("include_public_api").unwrap_or(config.public_api);
+        if let Some(entry_points) = params.and_then(|p| p.get("entry_points")).and_then(|v| v.as_array()) {
+            config.entry_points.extend(entry_points.iter().filter_map(|v| v.as_str()).map(str::to_string));
+        }
+        let crate_filter = params.and_then(|p| p.get("crate")).and_then(|v| v.as_str());
+        let explain = params.and_then(|p| p.get("explain")).and_then(|v| v.as_str());
+        let limit = params
+            .and_then(|p| p.get("limit"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(100) as usize;
+
+        let symbols = self.current_symbols.read().await;
+        let Some(symbols) = symbols.as_ref() else {
+            return self.error_response(request.id, -32603, "Workspace not initialized");
+        };
+        let crates = self.current_crates.read().await;
+        let patterns = FrameworkPatterns::with_default_patterns();
+        let reachability = Reachability::analyze(symbols, &crates, &config, &patterns);
+
+        if let Some(name) = explain {
+            let chain = reachability.why_live(name);
+            return McpResponse {
+                id: request.id,
+                result: Some(json!({
+                    "analysis_type": "dead_code_explain",
+                    "symbol": name,
+                    "reachable": chain.is_some(),
+                    "reason": chain.unwrap_or_default()
+                })),
+                error: None,
+            };
+        }
+
+        let mut report = reachability.report(crate_filter);
+        let dead_function_count = report.dead_functions.len();
+        let dead_type_count = report.dead_types.len();
+        report.dead_functions.truncate(limit);
+        report.dead_types.truncate(limit);
+
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "analysis_type": "dead_code",
+                "roots": {
+                    "tests": config.tests,
+                    "public_api": config.public_api,
+                    "entry_points": config.entry_points
+                },
+                "crate_filter": crate_filter,
+                "dead_function_count": dead_function_count,
+                "dead_type_count": dead_type_count,
+                "report": report
+            })),
+            error: None,
+        }
+    }
+
+    /// Every function of the crate, if any, with its number of incoming CALLS edges and how
+    /// many of them come from tests
+    async fn call_coverage(&self, crate_filter: Option<&str>) -> Result<Vec<(FunctionNode, usize, usize)>> {
+        let calls = self.graph.calls().await?;
+        let counts = incoming_call_counts(&calls);
+        Ok(self.graph.functions().await?
+            .into_iter()
+            .filter(|f| crate_filter.is_none_or(|crate_name| f.crate_name == crate_name))
+            .map(|f| {
+                let (calls, test_calls) = counts.get(f.id.as_str()).copied().unwrap_or_default();
+                (f, calls, test_calls)
+            })
+            .collect())
+    }
+
     async fn handle_find_test_only_functions(&self, request: McpRequest) -> McpResponse {
         let params = request.params.as_ref();
         
@@ -2103,60 +2083,23 @@ impl EnhancedMcpServer {
             .and_then(|v| v.as_bool())
             .unwrap_or(true);
 
-        // Query to find functions that are only called by test functions
-        let mut query_parts = vec![
-            "MATCH (f:Function)".to_string(),
-            "OPTIONAL MATCH (caller:Function)-[:CALLS]->(f)".to_string(),
-            "WITH f, COLLECT(caller) as callers".to_string(),
-            "WHERE SIZE(callers) > 0 AND ALL(c IN callers WHERE c.is_test = true)".to_string(),
-        ];
-
-        if !include_public {
-            query_parts.push("AND NOT f.visibility = 'pub'".to_string());
-        }
-
-        if let Some(crate_name) = crate_filter {
-            query_parts.push(format!("AND f.crate = '{}'", crate_name.replace('\'', "\\'")));
-        }
-
-        query_parts.push(
-            "RETURN f.qualified_name as qualified_name, f.name as name, f.crate as crate, f.file as file, 
-                    f.line_start as line, f.visibility as visibility, f.is_test as is_test
-             ORDER BY f.crate, f.name".to_string()
-        );
-
-        let query_str = query_parts.join(" ");
-        let query = neo4rs::Query::new(query_str);
-
-        let mut test_only_functions = Vec::new();
-        match self.graph.execute_query(query).await {
-            Ok(result) => {
-                for row in result {
-                    if let (Ok(qualified_name), Ok(name), Ok(crate_name), Ok(file), Ok(line), Ok(visibility), Ok(is_test)) = (
-                        row.get::<String>("qualified_name"),
-                        row.get::<String>("name"),
-                        row.get::<String>("crate"),
-                        row.get::<String>("file"),
-                        row.get::<i64>("line"),
-                        row.get::<String>("visibility"),
-                        row.get::<bool>("is_test"),
-                    ) {
-                        test_only_functions.push(json!({
-                            "qualified_name": qualified_name,
-                            "name": name,
-                            "crate": crate_name,
-                            "file": file,
-                            "line": line,
-                            "visibility": visibility,
-                            "is_test": is_test
-                        }));
-                    }
-                }
-            }
-            Err(e) => {
-                return self.error_response(request.id, -32603, &format!("Query failed: {}", e));
-            }
-        }
+        // Functions that are called, but only by test functions
+        let functions = match self.call_coverage(crate_filter).await {
+            Ok(functions) => functions,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        };
+        let test_only_functions: Vec<Value> = functions.into_iter()
+            .filter(|(f, calls, test_calls)| *calls > 0 && test_calls == calls && (include_public || f.visibility != "pub"))
+            .map(|(f, _, _)| json!({
+                "qualified_name": f.qualified_name,
+                "name": f.name,
+                "crate": f.crate_name,
+                "file": f.file,
+                "line": f.line_start,
+                "visibility": f.visibility,
+                "is_test": f.is_test
+            }))
+            .collect();
 
         McpResponse {
             id: request.id,
@@ -2174,24 +2117,24 @@ impl EnhancedMcpServer {
     }
 
     async fn handle_debug_call_relationships(&self, request: McpRequest) -> McpResponse {
-        // First check if any relationships exist at all
-        let count_query = neo4rs::Query::new("MATCH ()-[r:CALLS]->() RETURN COUNT(r) as count".to_string());
-        
-        let mut total_calls = 0;
-        if let Ok(result) = self.graph.execute_query(count_query).await {
-            if let Some(row) = result.first() {
-                if let Ok(count) = row.get::<i64>("count") {
-                    total_calls = count;
-                }
-            }
-        }
+        let mut calls = match self.graph.calls().await {
+            Ok(calls) => calls,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Debug query failed: {}", e)),
+        };
+        calls.sort_by(|a, b| {
+            (&a.caller.qualified_name, &a.callee.qualified_name).cmp(&(&b.caller.qualified_name, &b.callee.qualified_name))
+        });
 
-        // Check all relationship types that exist
-        let rel_types_query = neo4rs::Query::new("MATCH ()-[r]->() RETURN DISTINCT TYPE(r) as rel_type, COUNT(r) as count".to_string());
+        // Relationship types the backend keeps counts for
         let mut rel_types = Vec::new();
-        if let Ok(result) = self.graph.execute_query(rel_types_query).await {
-            for row in result {
-                if let (Ok(rel_type), Ok(count)) = (row.get::<String>("rel_type"), row.get::<i64>("count")) {
+        if let Ok(stats) = self.graph.get_statistics().await {
+            for (rel_type, count) in [
+                ("CALLS", stats.call_edges),
+                ("IMPLEMENTS", stats.implements_edges),
+                ("SPAWNS", stats.spawn_edges),
+                ("DEPENDS_ON", stats.depends_on_edges),
+            ] {
+                if count > 0 {
                     rel_types.push(json!({
                         "relationship_type": rel_type,
                         "count": count
@@ -2200,46 +2143,20 @@ impl EnhancedMcpServer {
             }
         }
 
-        // Query all CALLS relationships if they exist
-        let query = neo4rs::Query::new(
-            "MATCH (caller)-[r:CALLS]->(callee)
-             RETURN caller.qualified_name as caller_name, callee.qualified_name as callee_name, 
-                    labels(caller) as caller_labels, labels(callee) as callee_labels,
-                    r.file as call_file, r.line as call_line
-             ORDER BY caller_name, callee_name".to_string()
-        );
-
-        let mut call_relationships = Vec::new();
-        match self.graph.execute_query(query).await {
-            Ok(result) => {
-                for row in result {
-                    let caller_name = row.get::<String>("caller_name").unwrap_or_else(|_| "unknown".to_string());
-                    let callee_name = row.get::<String>("callee_name").unwrap_or_else(|_| "unknown".to_string());
-                    let caller_labels = row.get::<Vec<String>>("caller_labels").unwrap_or_else(|_| vec![]);
-                    let callee_labels = row.get::<Vec<String>>("callee_labels").unwrap_or_else(|_| vec![]);
-                    let call_file = row.get::<String>("call_file").unwrap_or_else(|_| "unknown".to_string());
-                    let call_line = row.get::<i64>("call_line").unwrap_or(0);
-
-                    call_relationships.push(json!({
-                        "caller": caller_name,
-                        "callee": callee_name,
-                        "caller_labels": caller_labels,
-                        "callee_labels": callee_labels,
-                        "file": call_file,
-                        "line": call_line
-                    }));
-                }
-            }
-            Err(e) => {
-                return self.error_response(request.id, -32603, &format!("Debug query failed: {}", e));
-            }
-        }
+        let call_relationships: Vec<Value> = calls.iter()
+            .map(|call| json!({
+                "caller": call.caller.qualified_name,
+                "callee": call.callee.qualified_name,
+                "file": call.caller.file,
+                "line": call.line
+            }))
+            .collect();
 
         McpResponse {
             id: request.id,
             result: Some(json!({
                 "analysis_type": "debug_call_relationships",
-                "total_calls_relationships": total_calls,
+                "total_calls_relationships": calls.len(),
                 "all_relationship_types": rel_types,
                 "calls_relationships_count": call_relationships.len(),
                 "calls_relationships": call_relationships
@@ -2282,55 +2199,31 @@ impl EnhancedMcpServer {
         let limit = params
             .and_then(|p| p.get("limit"))
             .and_then(|v| v.as_i64())
-            .unwrap_or(100) as i32;
+            .unwrap_or(100);
 
         // Find functions that have no test coverage
-        let mut query_parts = vec![
-            "MATCH (f:Function)".to_string(),
-            "WHERE (f.is_test = false OR f.is_test IS NULL)".to_string(),
-            "OPTIONAL MATCH (test:Function)-[:CALLS]->(f)".to_string(),
-            "WHERE test.is_test = true".to_string(),
-            "WITH f, COUNT(test) AS test_count".to_string(),
-            "WHERE test_count = 0".to_string(),
-        ];
-
-        if let Some(crate_name) = crate_filter {
-            query_parts.push(format!("AND f.crate = '{}'", crate_name));
-        }
-
-        query_parts.push("RETURN f.qualified_name, f.crate, f.visibility".to_string());
-        query_parts.push("ORDER BY f.qualified_name".to_string());
-        query_parts.push(format!("LIMIT {}", limit));
-
-        let query = query_parts.join(" ");
-        
-        match self.graph.execute_query(neo4rs::Query::new(query)).await {
-            Ok(result) => {
-                let mut functions = Vec::new();
-                for row in result {
-                    if let (Ok(qualified_name), Ok(crate_name), Ok(visibility)) = (
-                        row.get::<String>("f.qualified_name"),
-                        row.get::<String>("f.crate"),
-                        row.get::<String>("f.visibility")
-                    ) {
-                        functions.push(json!({
-                            "qualified_name": qualified_name,
-                            "crate": crate_name,
-                            "visibility": visibility
-                        }));
-                    }
-                }
+        let mut functions = match self.call_coverage(crate_filter).await {
+            Ok(functions) => functions,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        };
+        functions.retain(|(f, _, test_calls)| !f.is_test && *test_calls == 0);
+        functions.sort_by(|a, b| a.0.qualified_name.cmp(&b.0.qualified_name));
+        let functions: Vec<Value> = functions.into_iter()
+            .take(limit.max(0) as usize)
+            .map(|(f, _, _)| json!({
+                "qualified_name": f.qualified_name,
+                "crate": f.crate_name,
+                "visibility": f.visibility
+            }))
+            .collect();
 
-                McpResponse {
-                    id: request.id,
-                    result: Some(json!({
-                        "functions_without_tests": functions,
-                        "count": functions.len()
-                    })),
-                    error: None,
-                }
-            }
-            Err(e) => self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "functions_without_tests": functions,
+                "count": functions.len()
+            })),
+            error: None,
         }
     }
 
@@ -2344,186 +2237,110 @@ impl EnhancedMcpServer {
         let limit = params
             .and_then(|p| p.get("limit"))
             .and_then(|v| v.as_i64())
-            .unwrap_or(100) as i32;
+            .unwrap_or(100);
 
         // Find functions that have test coverage
-        let mut query_parts = vec![
-            "MATCH (f:Function)".to_string(),
-            "WHERE (f.is_test = false OR f.is_test IS NULL)".to_string(),
-            "OPTIONAL MATCH (test:Function)-[:CALLS]->(f)".to_string(),
-            "WHERE test.is_test = true".to_string(),
-            "WITH f, COUNT(test) AS test_count".to_string(),
-            "WHERE test_count > 0".to_string(),
-        ];
-
-        if let Some(crate_name) = crate_filter {
-            query_parts.push(format!("AND f.crate = '{}'", crate_name));
-        }
-
-        query_parts.push("RETURN f.qualified_name, f.crate, f.visibility, test_count".to_string());
-        query_parts.push("ORDER BY test_count DESC, f.qualified_name".to_string());
-        query_parts.push(format!("LIMIT {}", limit));
-
-        let query = query_parts.join(" ");
-        
-        match self.graph.execute_query(neo4rs::Query::new(query)).await {
-            Ok(result) => {
-                let mut functions = Vec::new();
-                for row in result {
-                    if let (Ok(qualified_name), Ok(crate_name), Ok(visibility), Ok(test_count)) = (
-                        row.get::<String>("f.qualified_name"),
-                        row.get::<String>("f.crate"),
-                        row.get::<String>("f.visibility"),
-                        row.get::<i64>("test_count")
-                    ) {
-                        functions.push(json!({
-                            "qualified_name": qualified_name,
-                            "crate": crate_name,
-                            "visibility": visibility,
-                            "test_count": test_count
-                        }));
-                    }
-                }
+        let mut functions = match self.call_coverage(crate_filter).await {
+            Ok(functions) => functions,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        };
+        functions.retain(|(f, _, test_calls)| !f.is_test && *test_calls > 0);
+        functions.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.qualified_name.cmp(&b.0.qualified_name)));
+        let functions: Vec<Value> = functions.into_iter()
+            .take(limit.max(0) as usize)
+            .map(|(f, _, test_calls)| json!({
+                "qualified_name": f.qualified_name,
+                "crate": f.crate_name,
+                "visibility": f.visibility,
+                "test_count": test_calls
+            }))
+            .collect();
 
-                McpResponse {
-                    id: request.id,
-                    result: Some(json!({
-                        "functions_with_tests": functions,
-                        "count": functions.len()
-                    })),
-                    error: None,
-                }
-            }
-            Err(e) => self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "functions_with_tests": functions,
+                "count": functions.len()
+            })),
+            error: None,
         }
     }
 
     async fn handle_find_most_referenced_functions(&self, request: McpRequest) -> McpResponse {
         let params = request.params.as_ref();
         
-        let limit = params
-            .and_then(|p| p.get("limit"))
-            .and_then(|v| v.as_i64())
-            .unwrap_or(10) as i32;
-        
         let crate_filter = params
             .and_then(|p| p.get("crate"))
             .and_then(|v| v.as_str());
+        
+        let limit = params
+            .and_then(|p| p.get("limit"))
+            .and_then(|v| v.as_i64())
+            .unwrap_or(10);
 
         // Find functions with the most incoming CALLS relationships
-        let mut query_parts = vec![
-            "MATCH (f:Function)".to_string(),
-            "OPTIONAL MATCH (caller:Function)-[:CALLS]->(f)".to_string(),
-            "WITH f, COUNT(caller) AS reference_count".to_string(),
-            "WHERE reference_count > 0".to_string(),
-        ];
-
-        if let Some(crate_name) = crate_filter {
-            query_parts.push(format!("AND f.crate = '{}'", crate_name));
-        }
-
-        query_parts.push("RETURN f.qualified_name, f.crate, f.visibility, reference_count".to_string());
-        query_parts.push("ORDER BY reference_count DESC".to_string());
-        query_parts.push(format!("LIMIT {}", limit));
-
-        let query = query_parts.join(" ");
-        
-        match self.graph.execute_query(neo4rs::Query::new(query)).await {
-            Ok(result) => {
-                let mut functions = Vec::new();
-                for row in result {
-                    if let (Ok(qualified_name), Ok(crate_name), Ok(visibility), Ok(reference_count)) = (
-                        row.get::<String>("f.qualified_name"),
-                        row.get::<String>("f.crate"),
-                        row.get::<String>("f.visibility"),
-                        row.get::<i64>("reference_count")
-                    ) {
-                        functions.push(json!({
-                            "qualified_name": qualified_name,
-                            "crate": crate_name,
-                            "visibility": visibility,
-                            "reference_count": reference_count
-                        }));
-                    }
-                }
+        let mut functions = match self.call_coverage(crate_filter).await {
+            Ok(functions) => functions,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        };
+        functions.retain(|(_, calls, _)| *calls > 0);
+        functions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.qualified_name.cmp(&b.0.qualified_name)));
+        let functions: Vec<Value> = functions.into_iter()
+            .take(limit.max(0) as usize)
+            .map(|(f, calls, _)| json!({
+                "qualified_name": f.qualified_name,
+                "crate": f.crate_name,
+                "visibility": f.visibility,
+                "reference_count": calls
+            }))
+            .collect();
 
-                McpResponse {
-                    id: request.id,
-                    result: Some(json!({
-                        "most_referenced_functions": functions,
-                        "count": functions.len()
-                    })),
-                    error: None,
-                }
-            }
-            Err(e) => self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "most_referenced_functions": functions,
+                "count": functions.len()
+            })),
+            error: None,
         }
     }
 
     async fn handle_find_most_referenced_without_tests(&self, request: McpRequest) -> McpResponse {
         let params = request.params.as_ref();
         
-        let limit = params
-            .and_then(|p| p.get("limit"))
-            .and_then(|v| v.as_i64())
-            .unwrap_or(10) as i32;
-        
         let crate_filter = params
             .and_then(|p| p.get("crate"))
             .and_then(|v| v.as_str());
+        
+        let limit = params
+            .and_then(|p| p.get("limit"))
+            .and_then(|v| v.as_i64())
+            .unwrap_or(10);
 
         // Find heavily referenced functions without test coverage
-        let mut query_parts = vec![
-            "MATCH (f:Function)".to_string(),
-            "WHERE (f.is_test = false OR f.is_test IS NULL)".to_string(),
-            "OPTIONAL MATCH (caller:Function)-[:CALLS]->(f)".to_string(),
-            "WITH f, COUNT(caller) AS reference_count".to_string(),
-            "WHERE reference_count > 0".to_string(),
-            "OPTIONAL MATCH (test:Function)-[:CALLS]->(f)".to_string(),
-            "WHERE test.is_test = true".to_string(),
-            "WITH f, reference_count, COUNT(test) AS test_count".to_string(),
-            "WHERE test_count = 0".to_string(),
-        ];
-
-        if let Some(crate_name) = crate_filter {
-            query_parts.push(format!("AND f.crate = '{}'", crate_name));
-        }
-
-        query_parts.push("RETURN f.qualified_name, f.crate, f.visibility, reference_count".to_string());
-        query_parts.push("ORDER BY reference_count DESC".to_string());
-        query_parts.push(format!("LIMIT {}", limit));
-
-        let query = query_parts.join(" ");
-        
-        match self.graph.execute_query(neo4rs::Query::new(query)).await {
-            Ok(result) => {
-                let mut functions = Vec::new();
-                for row in result {
-                    if let (Ok(qualified_name), Ok(crate_name), Ok(visibility), Ok(reference_count)) = (
-                        row.get::<String>("f.qualified_name"),
-                        row.get::<String>("f.crate"),
-                        row.get::<String>("f.visibility"),
-                        row.get::<i64>("reference_count")
-                    ) {
-                        functions.push(json!({
-                            "qualified_name": qualified_name,
-                            "crate": crate_name,
-                            "visibility": visibility,
-                            "reference_count": reference_count
-                        }));
-                    }
-                }
+        let mut functions = match self.call_coverage(crate_filter).await {
+            Ok(functions) => functions,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        };
+        functions.retain(|(f, calls, test_calls)| !f.is_test && *calls > 0 && *test_calls == 0);
+        functions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.qualified_name.cmp(&b.0.qualified_name)));
+        let functions: Vec<Value> = functions.into_iter()
+            .take(limit.max(0) as usize)
+            .map(|(f, calls, _)| json!({
+                "qualified_name": f.qualified_name,
+                "crate": f.crate_name,
+                "visibility": f.visibility,
+                "reference_count": calls
+            }))
+            .collect();
 
-                McpResponse {
-                    id: request.id,
-                    result: Some(json!({
-                        "most_referenced_without_tests": functions,
-                        "count": functions.len()
-                    })),
-                    error: None,
-                }
-            }
-            Err(e) => self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "most_referenced_without_tests": functions,
+                "count": functions.len()
+            })),
+            error: None,
         }
     }
 
@@ -2534,47 +2351,31 @@ impl EnhancedMcpServer {
             .and_then(|p| p.get("crate"))
             .and_then(|v| v.as_str());
 
-        // Query for all actor spawn relationships using Type:Actor nodes
-        let mut query_parts = vec![
-            "MATCH (parent:Type:Actor)-[r:SPAWNS]->(child:Type:Actor)".to_string(),
-        ];
-
-        if let Some(crate_name) = crate_filter {
-            query_parts.push(format!("WHERE parent.crate = '{}' OR child.crate = '{}'", crate_name, crate_name));
-        }
-
-        query_parts.push("RETURN parent.name, parent.crate, child.name, child.crate, r.context".to_string());
-        query_parts.push("ORDER BY parent.name, child.name".to_string());
-
-        let query = query_parts.join(" ");
-        
-        match self.graph.execute_query(neo4rs::Query::new(query)).await {
-            Ok(result) => {
+        match self.graph.actor_spawns().await {
+            Ok(spawns) => {
                 let mut relationships = Vec::new();
                 let mut mermaid_lines = vec!["graph TD".to_string()];
                 
-                for row in result {
-                    if let (Ok(parent_name), Ok(parent_crate), Ok(child_name), Ok(child_crate), context_opt) = (
-                        row.get::<String>("parent.name"),
-                        row.get::<String>("parent.crate"),
-                        row.get::<String>("child.name"),
-                        row.get::<String>("child.crate"),
-                        row.get::<String>("r.context").ok()
-                    ) {
-                        let parent_id = format!("{}_{}", parent_crate.replace("-", "_"), parent_name);
-                        let child_id = format!("{}_{}", child_crate.replace("-", "_"), child_name);
-                        
-                        // Create mermaid diagram line
-                        mermaid_lines.push(format!("  {} -->|spawns| {}", parent_id, child_id));
-                        
-                        relationships.push(json!({
-                            "parent": parent_name,
-                            "parent_crate": parent_crate,
-                            "child": child_name,
-                            "child_crate": child_crate,
-                            "context": context_opt
-                        }));
+                for spawn in spawns {
+                    if let Some(crate_name) = crate_filter {
+                        if spawn.parent_crate != crate_name && spawn.child_crate != crate_name {
+                            continue;
+                        }
                     }
+
+                    let parent_id = format!("{}_{}", spawn.parent_crate.replace("-", "_"), spawn.parent);
+                    let child_id = format!("{}_{}", spawn.child_crate.replace("-", "_"), spawn.child);
+                    
+                    // Create mermaid diagram line
+                    mermaid_lines.push(format!("  {} -->|spawns| {}", parent_id, child_id));
+                    
+                    relationships.push(json!({
+                        "parent": spawn.parent,
+                        "parent_crate": spawn.parent_crate,
+                        "child": spawn.child,
+                        "child_crate": spawn.child_crate,
+                        "context": spawn.context
+                    }));
                 }
 
                 let mermaid_diagram = mermaid_lines.join("\n");
@@ -2608,6 +2409,112 @@ impl EnhancedMcpServer {
         }
     }
 
+    async fn handle_get_supervision_tree(&self, request: McpRequest) -> McpResponse {
+        let params = request.params.as_ref();
+        let crate_filter = params.and_then(|p| p.get("crate")).and_then(|v| v.as_str());
+        let actor_filter = params.and_then(|p| p.get("actor_name")).and_then(|v| v.as_str());
+
+        let links = match self.graph.supervision_links().await {
+            Ok(links) => links,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Supervision query failed: {}", e)),
+        };
+
+        let mut tree = SupervisionTree::from_edges(&links);
+        if let Some(actor) = actor_filter {
+            tree = tree.focus(actor);
+        }
+        if let Some(crate_name) = crate_filter {
+            tree = tree.retain_crate(crate_name);
+        }
+
+        let mut restarted = Vec::new();
+        let mut cascading = Vec::new();
+        let mut stack: Vec<_> = tree.roots.iter().collect();
+        while let Some(node) = stack.pop() {
+            for child in &node.children {
+                match child.on_failure {
+                    Some(FailureOutcome::Restart) => restarted.push(json!({
+                        "supervisor": node.actor, "child": child.actor
+                    })),
+                    Some(FailureOutcome::CascadingStop) => cascading.push(json!({
+                        "child": child.actor, "stops": child.cascades_to
+                    })),
+                    _ => {}
+                }
+            }
+            stack.extend(node.children.iter());
+        }
+
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "analysis_type": "supervision_tree",
+                "tree": if tree.roots.is_empty() { "No supervision links found".to_string() } else { tree.render() },
+                "roots": tree.roots,
+                "actor_count": tree.actor_count,
+                "link_count": tree.link_count,
+                "restarted_on_failure": restarted,
+                "cascading_stops": cascading,
+                "note": "Hooks that are not overridden use kameo's defaults: on_link_died and on_panic stop the actor on abnormal exit"
+            })),
+            error: None,
+        }
+    }
+
+    async fn handle_find_ask_cycles(&self, request: McpRequest) -> McpResponse {
+        let params = request.params.as_ref();
+        let crate_filter = params.and_then(|p| p.get("crate")).and_then(|v| v.as_str());
+        let limit = params.and_then(|p| p.get("limit")).and_then(|v| v.as_u64()).unwrap_or(50) as usize;
+
+        let symbols = self.current_symbols.read().await;
+        let Some(symbols) = symbols.as_ref() else {
+            return self.error_response(request.id, -32603, "Workspace not initialized");
+        };
+
+        let graph = AskGraph::from_symbols(symbols);
+        let cycles = graph.cycles(limit, |cycle| crate_filter.is_none_or(|name| {
+            cycle.hops.iter().flat_map(|h| &h.sends).any(|s| s.crate_name == name)
+        }));
+
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "analysis_type": "ask_cycles",
+                "ask_edges": graph.edge_count(),
+                "cycle_count": cycles.len(),
+                "summary": cycles.iter().map(|c| c.describe()).collect::<Vec<_>>(),
+                "cycles": cycles,
+            })),
+            error: None,
+        }
+    }
+
+    async fn handle_find_unhandled_messages(&self, request: McpRequest) -> McpResponse {
+        let crate_filter = request.params.as_ref()
+            .and_then(|p| p.get("crate"))
+            .and_then(|v| v.as_str());
+
+        let symbols = self.current_symbols.read().await;
+        let Some(symbols) = symbols.as_ref() else {
+            return self.error_response(request.id, -32603, "Workspace not initialized");
+        };
+        let audit = MessageAudit::analyze(symbols, crate_filter);
+
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "analysis_type": "message_audit",
+                "unhandled_send_count": audit.unhandled_sends.len(),
+                "unused_handler_count": audit.unused_handlers.len(),
+                "orphaned_message_count": audit.orphaned_messages.len(),
+                "unhandled_sends": audit.unhandled_sends,
+                "unused_handlers": audit.unused_handlers,
+                "orphaned_messages": audit.orphaned_messages,
+            })),
+            error: None,
+        }
+    }
+
     async fn handle_generate_actor_message_diagram(&self, request: McpRequest) -> McpResponse {
         let params = request.params.as_ref();
         
@@ -2619,70 +2526,49 @@ impl EnhancedMcpServer {
             .and_then(|p| p.get("method"))
             .and_then(|v| v.as_str()); // "tell", "ask", or None for both
 
-        // Query for all message send relationships using Type:Actor nodes
-        let mut query_parts = vec![
-            "MATCH (sender:Type:Actor)-[r:SENDS]->(receiver:Type:Actor)".to_string(),
-        ];
-
-        let mut where_clauses = Vec::new();
-        
-        if let Some(crate_name) = crate_filter {
-            where_clauses.push(format!("(sender.crate = '{}' OR receiver.crate = '{}')", crate_name, crate_name));
-        }
-        
-        if let Some(method) = method_filter {
-            where_clauses.push(format!("r.method = '{}'", method));
-        }
-        
-        if !where_clauses.is_empty() {
-            query_parts.push(format!("WHERE {}", where_clauses.join(" AND ")));
-        }
-
-        query_parts.push("RETURN sender.name, sender.crate, receiver.name, receiver.crate, r.message_type, r.method".to_string());
-        query_parts.push("ORDER BY sender.name, receiver.name, r.message_type".to_string());
-
-        let query = query_parts.join(" ");
-        
-        match self.graph.execute_query(neo4rs::Query::new(query)).await {
-            Ok(result) => {
+        match self.graph.message_sends().await {
+            Ok(sends) => {
                 let mut relationships = Vec::new();
                 let mut mermaid_lines = vec!["graph LR".to_string()]; // Use LR for messaging diagrams
                 let mut tell_count = 0;
                 let mut ask_count = 0;
                 
-                for row in result {
-                    if let (Ok(sender_name), Ok(sender_crate), Ok(receiver_name), Ok(receiver_crate), Ok(message_type), Ok(method)) = (
-                        row.get::<String>("sender.name"),
-                        row.get::<String>("sender.crate"),
-                        row.get::<String>("receiver.name"),
-                        row.get::<String>("receiver.crate"),
-                        row.get::<String>("r.message_type"),
-                        row.get::<String>("r.method")
-                    ) {
-                        let sender_id = format!("{}_{}", sender_crate.replace("-", "_"), sender_name);
-                        let receiver_id = format!("{}_{}", receiver_crate.replace("-", "_"), receiver_name);
-                        
-                        // Create mermaid diagram line with method and message type
-                        let method_label = if method == "Tell" {
-                            tell_count += 1;
-                            "tell"
-                        } else {
-                            ask_count += 1;
-                            "ask"
-                        };
-                        
-                        mermaid_lines.push(format!("  {} -->|{}: {}| {}", 
-                            sender_id, method_label, message_type, receiver_id));
-                        
-                        relationships.push(json!({
-                            "sender": sender_name,
-                            "sender_crate": sender_crate,
-                            "receiver": receiver_name,
-                            "receiver_crate": receiver_crate,
-                            "message_type": message_type,
-                            "method": method
-                        }));
+                for send in sends {
+                    let receiver_crate = send.receiver_crate.clone().unwrap_or_else(|| send.sender_crate.clone());
+                    if let Some(crate_name) = crate_filter {
+                        if send.sender_crate != crate_name && receiver_crate != crate_name {
+                            continue;
+                        }
                     }
+                    if let Some(method) = method_filter {
+                        if !send.method.eq_ignore_ascii_case(method) {
+                            continue;
+                        }
+                    }
+
+                    let sender_id = format!("{}_{}", send.sender_crate.replace("-", "_"), send.sender);
+                    let receiver_id = format!("{}_{}", receiver_crate.replace("-", "_"), send.receiver);
+                    
+                    // Create mermaid diagram line with method and message type
+                    let method_label = if send.method == "Tell" {
+                        tell_count += 1;
+                        "tell"
+                    } else {
+                        ask_count += 1;
+                        "ask"
+                    };
+                    
+                    mermaid_lines.push(format!("  {} -->|{}: {}| {}", 
+                        sender_id, method_label, send.message_type, receiver_id));
+                    
+                    relationships.push(json!({
+                        "sender": send.sender,
+                        "sender_crate": send.sender_crate,
+                        "receiver": send.receiver,
+                        "receiver_crate": receiver_crate,
+                        "message_type": send.message_type,
+                        "method": send.method
+                    }));
                 }
 
                 let mermaid_diagram = mermaid_lines.join("\n");
@@ -2718,6 +2604,43 @@ impl EnhancedMcpServer {
         }
     }
 
+    async fn handle_generate_message_sequence_diagram(&self, request: McpRequest) -> McpResponse {
+        let entry = match self.extract_required_param(&request, "entry") {
+            Some(entry) => entry,
+            None => return self.error_response(request.id, -32602, "Missing 'entry' parameter"),
+        };
+        let params = request.params.as_ref();
+        let format_name = params.and_then(|p| p.get("format")).and_then(|v| v.as_str()).unwrap_or("mermaid");
+        let Some(format) = SequenceFormat::from_name(format_name) else {
+            return self.error_response(request.id, -32602, &format!("Unknown diagram format: {}", format_name));
+        };
+        let max_depth = params.and_then(|p| p.get("max_depth")).and_then(|v| v.as_u64()).unwrap_or(8) as usize;
+
+        let symbols = self.current_symbols.read().await;
+        let Some(symbols) = symbols.as_ref() else {
+            return self.error_response(request.id, -32603, "Workspace not initialized");
+        };
+        let Some(diagram) = SequenceDiagram::trace(symbols, &entry, max_depth) else {
+            return self.error_response(request.id, -32602, &format!("No function or message handler named '{}'", entry));
+        };
+
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "analysis_type": "message_sequence",
+                "entry": entry,
+                "format": format_name,
+                "diagram": diagram.render(format),
+                "participant_count": diagram.participants.len(),
+                "step_count": diagram.steps.len(),
+                "truncated": diagram.truncated,
+                "participants": diagram.participants,
+                "steps": diagram.steps,
+            })),
+            error: None,
+        }
+    }
+
     async fn handle_get_actor_details(&self, request: McpRequest) -> McpResponse {
         let params = request.params.as_ref()
             .ok_or_else(|| "Missing parameters")
@@ -2732,94 +2655,87 @@ impl EnhancedMcpServer {
             .and_then(|p| p.get("crate"))
             .and_then(|v| v.as_str());
 
-        // Build comprehensive query for actor with all its relationships
-        let mut query = String::from("MATCH (a:Type:Actor {name: $actor_name})");
-        
-        if let Some(crate_name) = crate_filter {
-            query.push_str(&format!(" WHERE a.crate = '{}'", crate_name));
-        }
-
-        query.push_str("
-            OPTIONAL MATCH (a)-[:HAS_METHOD]->(m:Function)
-            OPTIONAL MATCH (a)-[:HAS_FIELD]->(f:Field)
-            OPTIONAL MATCH (a)-[:SPAWNS]->(spawned:Type:Actor)
-            OPTIONAL MATCH (spawner:Type:Actor)-[:SPAWNS]->(a)
-            OPTIONAL MATCH (a)-[:HANDLES]->(msg:MessageType)
-            OPTIONAL MATCH (a)-[:SENDS]->(receiver:Type:Actor)
-            OPTIONAL MATCH (sender:Type:Actor)-[:SENDS]->(a)
-            OPTIONAL MATCH (a)-[:IMPLEMENTS]->(trait:Type)
-            RETURN a,
-                   collect(DISTINCT m) as methods,
-                   collect(DISTINCT f) as fields,
-                   collect(DISTINCT spawned.name) as spawns_actors,
-                   collect(DISTINCT spawner.name) as spawned_by,
-                   collect(DISTINCT msg.name) as handles_messages,
-                   collect(DISTINCT receiver.name) as sends_to,
-                   collect(DISTINCT sender.name) as receives_from,
-                   collect(DISTINCT trait.name) as implements_traits");
-
-        let neo_query = neo4rs::Query::new(query)
-            .param("actor_name", actor_name);
-
-        match self.graph.execute_query(neo_query).await {
-            Ok(result) => {
-                if let Some(row) = result.first() {
-                    // Extract actor node properties
-                    let actor_node = row.get::<neo4rs::Node>("a").ok();
-                    
-                    let actor_props = actor_node.as_ref().map(|node| {
-                        json!({
-                            "name": node.get::<String>("name").unwrap_or_default(),
-                            "crate": node.get::<String>("crate").unwrap_or_default(),
-                            "module_path": node.get::<String>("module_path").unwrap_or_default(),
-                            "file_path": node.get::<String>("file_path").unwrap_or_default(),
-                            "visibility": node.get::<String>("visibility").unwrap_or_default(),
-                            "is_distributed": node.get::<bool>("is_distributed").unwrap_or(false),
-                            "actor_type": node.get::<String>("actor_type").unwrap_or_default(),
-                        })
-                    });
-
-                    // Extract related data
-                    let methods: Vec<neo4rs::Node> = row.get("methods").unwrap_or_default();
-                    let method_names: Vec<String> = methods.iter()
-                        .filter_map(|m| m.get::<String>("name").ok())
-                        .collect();
-
-                    let fields: Vec<neo4rs::Node> = row.get("fields").unwrap_or_default();
-                    let field_info: Vec<serde_json::Value> = fields.iter()
-                        .map(|f| json!({
-                            "name": f.get::<String>("name").unwrap_or_default(),
-                            "type": f.get::<String>("type_name").unwrap_or_default(),
-                        }))
-                        .collect();
+        let relationships = async {
+            Ok::<_, anyhow::Error>((
+                self.graph.actor_spawns().await?,
+                self.graph.message_handlers().await?,
+                self.graph.message_sends().await?,
+            ))
+        };
+        let (spawns, handlers, sends) = match relationships.await {
+            Ok(relationships) => relationships,
+            Err(e) => return self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        };
 
-                    McpResponse {
-                        id: request.id,
-                        result: Some(json!({
-                            "actor": actor_props,
-                            "methods": method_names,
-                            "fields": field_info,
-                            "spawns_actors": row.get::<Vec<String>>("spawns_actors").unwrap_or_default(),
-                            "spawned_by": row.get::<Vec<String>>("spawned_by").unwrap_or_default(),
-                            "handles_messages": row.get::<Vec<String>>("handles_messages").unwrap_or_default(),
-                            "sends_to": row.get::<Vec<String>>("sends_to").unwrap_or_default(),
-                            "receives_from": row.get::<Vec<String>>("receives_from").unwrap_or_default(),
-                            "implements_traits": row.get::<Vec<String>>("implements_traits").unwrap_or_default(),
-                        })),
-                        error: None,
-                    }
-                } else {
-                    McpResponse {
-                        id: request.id,
-                        result: Some(json!({
-                            "error": format!("Actor '{}' not found", actor_name),
-                            "note": "Make sure the actor has been analyzed and exists in the graph"
-                        })),
-                        error: None,
-                    }
+        let symbols = self.current_symbols.read().await;
+        let Some(symbols) = symbols.as_ref() else {
+            return self.error_response(request.id, -32603, "Workspace not initialized");
+        };
+        let Some(actor) = symbols.actors.iter()
+            .find(|a| a.name == actor_name && crate_filter.is_none_or(|crate_name| a.crate_name == crate_name))
+        else {
+            return McpResponse {
+                id: request.id,
+                result: Some(json!({
+                    "error": format!("Actor '{}' not found", actor_name),
+                    "note": "Make sure the actor has been analyzed and exists in the graph"
+                })),
+                error: None,
+            };
+        };
+        let is_actor = |name: &str, crate_name: &str| name == actor.name && crate_name == actor.crate_name;
+
+        let mut methods = std::collections::BTreeSet::new();
+        let mut traits = std::collections::BTreeSet::new();
+        for function in symbols.functions.iter().filter(|f| f.crate_name == actor.crate_name) {
+            match &function.function_context {
+                FunctionContext::RegularImpl { type_name } if *type_name == actor.name => {
+                    methods.insert(function.name.clone());
+                }
+                FunctionContext::TraitImpl { trait_name, type_name } if *type_name == actor.name => {
+                    methods.insert(function.name.clone());
+                    traits.insert(trait_name.clone());
                 }
+                _ => {}
             }
-            Err(e) => self.error_response(request.id, -32603, &format!("Query failed: {}", e)),
+        }
+        traits.extend(symbols.impls.iter()
+            .filter(|i| i.type_name == actor.name)
+            .filter_map(|i| i.trait_name.clone()));
+
+        let fields: Vec<Value> = symbols.types.iter()
+            .find(|t| t.qualified_name == actor.qualified_name && t.crate_name == actor.crate_name)
+            .map(|t| t.fields.iter().map(|f| json!({ "name": f.name, "type": f.field_type })).collect())
+            .unwrap_or_default();
+
+        fn names<'a>(names: impl Iterator<Item = &'a String>) -> std::collections::BTreeSet<&'a String> {
+            names.collect()
+        }
+
+        McpResponse {
+            id: request.id,
+            result: Some(json!({
+                "actor": {
+                    "name": actor.name,
+                    "crate": actor.crate_name,
+                    "module_path": actor.module_path,
+                    "file_path": actor.file_path,
+                    "visibility": actor.visibility,
+                    "is_distributed": actor.is_distributed,
+                    "actor_type": format!("{:?}", actor.actor_type),
+                },
+                "methods": methods,
+                "fields": fields,
+                "spawns_actors": names(spawns.iter().filter(|s| is_actor(&s.parent, &s.parent_crate)).map(|s| &s.child)),
+                "spawned_by": names(spawns.iter().filter(|s| is_actor(&s.child, &s.child_crate)).map(|s| &s.parent)),
+                "handles_messages": names(handlers.iter().filter(|h| is_actor(&h.actor, &h.actor_crate)).map(|h| &h.message_type)),
+                "sends_to": names(sends.iter().filter(|s| is_actor(&s.sender, &s.sender_crate)).map(|s| &s.receiver)),
+                "receives_from": names(sends.iter()
+                    .filter(|s| s.receiver == actor.name && s.receiver_crate.as_ref().is_none_or(|c| *c == actor.crate_name))
+                    .map(|s| &s.sender)),
+                "implements_traits": traits,
+            })),
+            error: None,
         }
     }
 
@@ -2938,6 +2854,16 @@ impl EnhancedMcpServer {
             let mut mermaid_diagram = String::from("graph LR\n");
             mermaid_diagram.push_str("    %% Distributed Actor Message Flow\n\n");
             
+            // Sends to a looked-up name nobody registers cannot reach any actor
+            let registry = ActorRegistry::from_symbols(symbols);
+            let is_unregistered = |flow: &crate::parser::symbols::DistributedMessageFlow| {
+                flow.registered_name.as_deref().is_some_and(|name| !registry.is_registered(name))
+            };
+            let unregistered_targets: std::collections::HashSet<_> = flows.iter()
+                .filter(|flow| is_unregistered(flow))
+                .map(|flow| (flow.target_actor.clone(), flow.target_crate.clone()))
+                .collect();
+
             // Add all actors involved in flows
             let mut involved_actors = std::collections::HashSet::new();
             for flow in &flows {
@@ -2948,11 +2874,17 @@ impl EnhancedMcpServer {
             // Add actor nodes
             for (actor_name, crate_name) in &involved_actors {
                 let node_id = format!("{}_{}", actor_name.replace(" ", "_"), crate_name.replace("-", "_"));
+                let class = if unregistered_targets.contains(&(actor_name.clone(), crate_name.clone())) {
+                    "unregistered"
+                } else {
+                    "actor"
+                };
                 mermaid_diagram.push_str(&format!(
-                    "    {}[\"{}\\n({})\"]:::actor\n",
+                    "    {}[\"{}\\n({})\"]:::{}\n",
                     node_id,
                     actor_name,
-                    crate_name
+                    crate_name,
+                    class
                 ));
             }
             
@@ -2980,6 +2912,7 @@ impl EnhancedMcpServer {
             mermaid_diagram.push_str("\n    %% Styling\n");
             mermaid_diagram.push_str("    classDef actor fill:#e1f5fe,stroke:#0277bd,stroke-width:2px\n");
             mermaid_diagram.push_str("    classDef message fill:#fff3e0,stroke:#ff8f00,stroke-width:1px\n");
+            mermaid_diagram.push_str("    classDef unregistered fill:#ffebee,stroke:#c62828,stroke-width:2px,stroke-dasharray:5 5\n");
 
             McpResponse {
                 id: request.id,
@@ -2996,8 +2929,25 @@ impl EnhancedMcpServer {
                         "to": flow.target_actor,
                         "message": flow.message_type,
                         "method": format!("{:?}", flow.send_method),
-                        "location": format!("{}:{}", flow.send_location.file_path, flow.send_location.line)
-                    })).collect::<Vec<_>>()
+                        "location": format!("{}:{}", flow.send_location.file_path, flow.send_location.line),
+                        "registered_name": flow.registered_name,
+                        "unregistered": is_unregistered(flow)
+                    })).collect::<Vec<_>>(),
+                    "registry": registry.names.iter().map(|entry| json!({
+                        "name": entry.registered_name,
+                        "actors": entry.actors,
+                        "registrations": entry.registrations.len(),
+                        "lookups": entry.lookups.len()
+                    })).collect::<Vec<_>>(),
+                    "unregistered_lookups": registry.unregistered_lookups.iter()
+                        .filter(|lookup| crate_filter.is_none_or(|name| lookup.crate_name == name))
+                        .map(|lookup| json!({
+                            "name": lookup.registered_name,
+                            "actor": lookup.actor_name,
+                            "function": lookup.function,
+                            "location": format!("{}:{}", lookup.file_path, lookup.line)
+                        }))
+                        .collect::<Vec<_>>()
                 })),
                 error: None,
             }
@@ -3055,4 +3005,233 @@ incremental = true
         let result = EnhancedMcpServer::new(config_path.to_str().unwrap()).await;
         assert!(result.is_ok() || result.is_err()); // Either way, config parsing should work
     }
+
+    async fn memory_server(temp_dir: &TempDir) -> EnhancedMcpServer {
+        let mut config = Config::default();
+        config.workspace.root = temp_dir.path().to_path_buf();
+        config.graph.backend = crate::config::GraphBackendKind::Memory;
+        config.embeddings.enabled = false;
+
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+        EnhancedMcpServer::new(config_path.to_str().unwrap()).await.unwrap()
+    }
+
+    /// In-memory backend that records every raw Cypher query instead of running it
+    struct RecordingGraph {
+        inner: crate::graph::InMemoryGraph,
+        executed: std::sync::Mutex<Vec<crate::graph::CypherQuery>>,
+    }
+
+    #[async_trait::async_trait]
+    impl GraphBackend for RecordingGraph {
+        fn kind(&self) -> crate::config::GraphBackendKind { self.inner.kind() }
+        async fn clear_workspace(&self) -> Result<()> { self.inner.clear_workspace().await }
+        async fn create_crate_nodes(&self, crates: &[CrateMetadata]) -> Result<()> { self.inner.create_crate_nodes(crates).await }
+        async fn populate_from_symbols(&self, symbols: &ParsedSymbols) -> Result<()> { self.inner.populate_from_symbols(symbols).await }
+        async fn remove_file(&self, file_path: &str) -> Result<()> { self.inner.remove_file(file_path).await }
+        async fn get_statistics(&self) -> Result<crate::graph::GraphStatistics> { self.inner.get_statistics().await }
+        async fn health_check(&self) -> Result<bool> { self.inner.health_check().await }
+        async fn test_connection(&self) -> Result<()> { self.inner.test_connection().await }
+        async fn verify_population(&self) -> Result<()> { self.inner.verify_population().await }
+        async fn get_crates(&self) -> Result<Vec<crate::graph::CrateNode>> { self.inner.get_crates().await }
+        async fn functions(&self) -> Result<Vec<crate::graph::FunctionNode>> { self.inner.functions().await }
+        async fn calls(&self) -> Result<Vec<crate::graph::CallEdge>> { self.inner.calls().await }
+        async fn find_callers(&self, name: &str) -> Result<Vec<crate::graph::CallEdge>> { self.inner.find_callers(name).await }
+        async fn find_referrers(&self, name: &str) -> Result<Vec<crate::graph::CallEdge>> { self.inner.find_referrers(name).await }
+        async fn find_callees(&self, name: &str) -> Result<Vec<crate::graph::CallEdge>> { self.inner.find_callees(name).await }
+        async fn cross_crate_calls(&self) -> Result<Vec<crate::graph::CallEdge>> { self.inner.cross_crate_calls().await }
+        async fn find_unreferenced_functions(&self) -> Result<Vec<crate::graph::FunctionNode>> { self.inner.find_unreferenced_functions().await }
+        async fn actor_spawns(&self) -> Result<Vec<crate::graph::SpawnEdge>> { self.inner.actor_spawns().await }
+        async fn supervision_links(&self) -> Result<Vec<crate::graph::SupervisionEdge>> { self.inner.supervision_links().await }
+        async fn type_users(&self, name: &str) -> Result<Vec<crate::graph::TypeUsageEdge>> { self.inner.type_users(name).await }
+        async fn trait_dispatches(&self) -> Result<Vec<crate::graph::DispatchEdge>> { self.inner.trait_dispatches().await }
+        async fn message_handlers(&self) -> Result<Vec<crate::graph::HandlerEdge>> { self.inner.message_handlers().await }
+        async fn message_sends(&self) -> Result<Vec<crate::graph::SendEdge>> { self.inner.message_sends().await }
+        async fn mark_call_violation(&self, caller: &str, callee: &str, kind: &str, severity: &str) -> Result<()> {
+            self.inner.mark_call_violation(caller, callee, kind, severity).await
+        }
+
+        async fn execute_query(&self, query: crate::graph::CypherQuery) -> Result<Vec<neo4rs::Row>> {
+            self.executed.lock().unwrap().push(query);
+            Ok(Vec::new())
+        }
+    }
+
+    const HOSTILE: &str = "evil' }) MATCH (n) DETACH DELETE n //\"";
+
+    /// Arguments for `tool` with every declared string parameter set to a hostile value
+    fn hostile_arguments(tool: &crate::mcp::ToolDefinition) -> Value {
+        let mut arguments = serde_json::Map::new();
+        for (name, schema) in tool.input_schema["properties"].as_object().unwrap() {
+            let value = match schema["type"].as_str() {
+                Some("string") if name == "diff" => json!(format!(
+                    "+++ b/src/{0}.rs\n@@ -1,1 +1,2 @@\n+fn {0}() {{}}\n+pub fn evil() {{}}",
+                    HOSTILE
+                )),
+                Some("string") => json!(HOSTILE),
+                Some("array") => json!([HOSTILE, format!("src/{}.rs", HOSTILE)]),
+                Some("number") => json!(-1),
+                Some("boolean") => json!(true),
+                _ => continue,
+            };
+            arguments.insert(name.clone(), value);
+        }
+        Value::Object(arguments)
+    }
+
+    #[tokio::test]
+    async fn test_hostile_tool_arguments_never_reach_raw_cypher() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.workspace.root = temp_dir.path().to_path_buf();
+        config.embeddings.enabled = false;
+
+        let mut symbols = ParsedSymbols::new();
+        symbols.functions.push(crate::parser::symbols::RustFunction {
+            id: "app::evil:1".to_string(),
+            name: "evil".to_string(),
+            qualified_name: "app::evil".to_string(),
+            crate_name: "app".to_string(),
+            module_path: "app".to_string(),
+            file_path: format!("src/{}.rs", HOSTILE),
+            line_start: 1,
+            line_end: 1,
+            visibility: "pub".to_string(),
+            is_async: false,
+            is_unsafe: false,
+            is_generic: false,
+            is_test: false,
+            is_trait_impl: false,
+            is_method: false,
+            function_context: crate::parser::symbols::FunctionContext::Free,
+            doc_comment: None,
+            signature: "pub fn evil()".to_string(),
+            parameters: Vec::new(),
+            return_type: None,
+            embedding_text: None,
+            module: "app".to_string(),
+            cfg: None,
+            attributes: Vec::new(),
+        });
+
+        let graph = Arc::new(RecordingGraph {
+            inner: crate::graph::InMemoryGraph::new(config.clone()),
+            executed: std::sync::Mutex::new(Vec::new()),
+        });
+        let server = EnhancedMcpServer::with_graph(config, graph.clone()).unwrap();
+        *server.current_symbols.write().await = Some(symbols);
+
+        for tool in tool_registry() {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": {"name": tool.name, "arguments": hostile_arguments(&tool)}
+            });
+            let response = server.handle_message(&request.to_string()).await.unwrap();
+            assert!(response.get("result").is_some(), "{} returned a protocol error: {}", tool.name, response);
+        }
+
+        // Tools go through typed backend methods, so they also work where Cypher is unavailable
+        let executed = graph.executed.lock().unwrap();
+        assert!(executed.is_empty(), "tools ran raw Cypher: {:?}", executed.iter().map(|q| q.text()).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_git_changes_stay_inside_the_workspace() {
+        let temp_dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let server = memory_server(&temp_dir).await;
+
+        for repo_path in [outside.path().to_str().unwrap(), "..", "/"] {
+            let request = McpRequest {
+                id: Some(json!(1)),
+                method: "analyze_git_changes".to_string(),
+                params: Some(json!({"repo_path": repo_path})),
+            };
+            let error = server.call_tool("analyze_git_changes", request).await.error.unwrap();
+            assert_eq!(error.code, -32602, "{}: {}", repo_path, error.message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_matches_registry() {
+        let temp_dir = TempDir::new().unwrap();
+        let server = memory_server(&temp_dir).await;
+
+        let response = server.handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).await.unwrap();
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), tool_registry().len());
+        assert!(response.get("error").is_none());
+        for tool in tools {
+            assert!(tool["name"].is_string());
+            assert_eq!(tool["inputSchema"]["type"], "object");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_registered_tool_is_dispatched() {
+        let temp_dir = TempDir::new().unwrap();
+        let server = memory_server(&temp_dir).await;
+
+        for tool in tool_registry() {
+            let request = McpRequest { id: Some(json!(1)), method: tool.name.to_string(), params: Some(json!({})) };
+            let response = server.call_tool(tool.name, request).await;
+            if let Some(error) = response.error {
+                assert_ne!(error.code, -32601, "tool {} is advertised but not dispatched", tool.name);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_returns_content_blocks() {
+        let temp_dir = TempDir::new().unwrap();
+        let server = memory_server(&temp_dir).await;
+        *server.current_symbols.write().await = Some(ParsedSymbols::new());
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":"a","method":"tools/call","params":{"name":"list_functions","arguments":{"limit":5}}}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["result"]["isError"], false);
+        assert_eq!(response["result"]["content"][0]["type"], "text");
+
+        // Missing required argument is a tool error, not a protocol error
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"get_function_details","arguments":{}}}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"no_such_tool"}}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], -32602);
+        assert!(response.get("result").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_jsonrpc_framing_errors_and_notifications() {
+        let temp_dir = TempDir::new().unwrap();
+        let server = memory_server(&temp_dir).await;
+
+        let response = server.handle_message("{not json").await.unwrap();
+        assert_eq!(response["error"]["code"], -32700);
+        assert!(response["id"].is_null());
+
+        let response = server.handle_message(r#"{"jsonrpc":"2.0","id":7,"params":{}}"#).await.unwrap();
+        assert_eq!(response["error"]["code"], -32600);
+        assert_eq!(response["id"], 7);
+
+        let response = server.handle_message(r#"{"jsonrpc":"2.0","id":8,"method":"no/such/method"}"#).await.unwrap();
+        assert_eq!(response["error"]["code"], -32601);
+
+        assert!(server.handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).await.is_none());
+
+        let response = server.handle_message(r#"{"jsonrpc":"2.0","id":9,"method":"ping"}"#).await.unwrap();
+        assert_eq!(response["result"], json!({}));
+    }
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 pub mod server;
 pub mod enhanced_server;
+pub mod tools;
 
 pub use server::{WorkspaceMcpServer};
-pub use enhanced_server::{EnhancedMcpServer, McpRequest, McpResponse, McpError};
\ No newline at end of file
+pub use enhanced_server::{EnhancedMcpServer, McpRequest, McpResponse, McpError};
+pub use tools::{tool_registry, ToolDefinition};
\ No newline at end of file
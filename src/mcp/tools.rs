@@ -0,0 +1,391 @@
+use serde_json::{json, Value};
+
+/// A tool exposed over MCP `tools/list` / `tools/call`
+#[derive(Debug, Clone)]
+pub struct ToolDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub input_schema: Value,
+}
+
+impl ToolDefinition {
+    fn new(name: &'static str, description: &'static str, properties: Value, required: &[&str]) -> Self {
+        let mut input_schema = json!({
+            "type": "object",
+            "properties": properties,
+        });
+        if !required.is_empty() {
+            input_schema["required"] = json!(required);
+        }
+        Self { name, description, input_schema }
+    }
+
+    /// Entry for the `tools` array of a `tools/list` result
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "inputSchema": self.input_schema,
+        })
+    }
+}
+
+fn crate_filter() -> Value {
+    json!({"type": "string", "description": "Only include results from this crate"})
+}
+
+fn limit(default: usize) -> Value {
+    json!({"type": "number", "description": format!("Maximum number of results (default: {})", default)})
+}
+
+fn depth() -> Value {
+    json!({"type": "number", "description": "Analysis depth (default: 3)"})
+}
+
+fn file_list() -> Value {
+    json!({"type": "array", "items": {"type": "string"}, "description": "List of changed file paths"})
+}
+
+/// Every tool the enhanced MCP server can dispatch, in the order they are advertised.
+///
+/// This is the single source for `tools/list`; `EnhancedMcpServer::call_tool` must handle each name.
+pub fn tool_registry() -> Vec<ToolDefinition> {
+    vec![
+        ToolDefinition::new(
+            "workspace_context",
+            "Get comprehensive context about the workspace: crates, layers and graph statistics",
+            json!({
+                "include_stats": {"type": "boolean", "description": "Include detailed statistics"},
+                "crate_filter": {"type": "string", "description": "Filter by crate name"}
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "analyze_change_impact",
+            "Analyze impact of changing a function or type using graph traversal",
+            json!({
+                "target": {"type": "string", "description": "Function or type to analyze"},
+                "depth": depth()
+            }),
+            &["target"],
+        ),
+        ToolDefinition::new(
+            "analyze_batch_change_impact",
+            "Analyze impact of multiple changed functions/types in batch",
+            json!({
+                "targets": {"type": "array", "items": {"type": "string"}, "description": "List of functions/types to analyze"},
+                "depth": depth(),
+                "aggregate": {"type": "boolean", "description": "Combine results into single report (default: true)"}
+            }),
+            &["targets"],
+        ),
+        ToolDefinition::new(
+            "analyze_file_changes",
+            "Discover the functions defined in changed files and analyze their impact",
+            json!({
+                "files": file_list(),
+                "depth": depth()
+            }),
+            &["files"],
+        ),
+        ToolDefinition::new(
+            "discover_functions_from_diff",
+            "Find the functions and types whose lines a unified diff touches",
+            json!({
+                "diff": {"type": "string", "description": "Unified diff text (e.g. output of `git diff`), with paths relative to the repository root"},
+                "depth": depth(),
+                "include_impact": {"type": "boolean", "description": "Also run change impact analysis on the discovered functions"}
+            }),
+            &["diff"],
+        ),
+        ToolDefinition::new(
+            "analyze_git_changes",
+            "Read the changes of a git repository and analyze the impact of every function and type they touch",
+            json!({
+                "repo_path": {"type": "string", "description": "Repository to read; must lie inside the workspace (default: the workspace root)"},
+                "since": {"type": "string", "description": "Compare the working tree with the merge base of this revision, e.g. origin/main"},
+                "range": {"type": "string", "description": "Compare a commit range instead, e.g. v1.0..HEAD (default: uncommitted changes)"},
+                "depth": depth()
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "incremental_file_analysis",
+            "Re-parse changed files, optionally updating the graph and reporting impact",
+            json!({
+                "files": file_list(),
+                "depth": depth(),
+                "include_impact": {"type": "boolean", "description": "Include change impact for functions in the files"},
+                "update_database": {"type": "boolean", "description": "Write the re-parsed symbols to the graph"}
+            }),
+            &["files"],
+        ),
+        ToolDefinition::new(
+            "find_unreferenced_functions",
+            "Find functions that are never called",
+            json!({
+                "crate": crate_filter(),
+                "exclude_public": {"type": "boolean", "description": "Skip public functions (default: false)"},
+                "exclude_tests": {"type": "boolean", "description": "Skip test functions (default: true)"}
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "find_dead_code",
+            "Find functions and types unreachable from entry points (binaries, tests, benches, public API, framework dispatch), with the reason each is dead",
+            json!({
+                "crate": crate_filter(),
+                "include_tests": {"type": "boolean", "description": "Treat tests as entry points (default: from [dead_code] config)"},
+                "include_public_api": {"type": "boolean", "description": "Treat pub items of library crates as entry points (default: from [dead_code] config)"},
+                "entry_points": {"type": "array", "items": {"type": "string"}, "description": "Extra entry point function names or qualified names"},
+                "explain": {"type": "string", "description": "Instead of a report, explain which entry point keeps this function or type alive"},
+                "limit": limit(100)
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "find_test_only_functions",
+            "Find functions that are only called from tests",
+            json!({
+                "crate": crate_filter(),
+                "include_public": {"type": "boolean", "description": "Include public functions (default: true)"}
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "find_functions_without_tests",
+            "Find functions that no test calls",
+            json!({
+                "crate": crate_filter(),
+                "limit": limit(100)
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "find_functions_with_tests",
+            "Find functions that are called from tests",
+            json!({
+                "crate": crate_filter(),
+                "limit": limit(100)
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "find_most_referenced_functions",
+            "Rank functions by number of callers",
+            json!({
+                "crate": crate_filter(),
+                "limit": limit(10)
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "find_most_referenced_without_tests",
+            "Rank untested functions by number of callers",
+            json!({
+                "crate": crate_filter(),
+                "limit": limit(10)
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "generate_actor_spawn_diagram",
+            "Generate a Mermaid diagram of actor spawn relationships",
+            json!({
+                "crate": crate_filter()
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "get_supervision_tree",
+            "Render the actor supervision tree built from spawn_link/link, showing which children are restarted and which failures cascade into stops",
+            json!({
+                "crate": crate_filter(),
+                "actor_name": {"type": "string", "description": "Only show the subtree supervised by this actor"}
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "find_ask_cycles",
+            "Find cycles of actors asking each other while handling a message, which can deadlock, with the file and line of each ask",
+            json!({
+                "crate": crate_filter(),
+                "limit": limit(50)
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "find_unhandled_messages",
+            "Find messages sent to actors that do not handle them, handlers nothing sends to, and message types neither sent nor handled",
+            json!({
+                "crate": crate_filter()
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "generate_actor_message_diagram",
+            "Generate a Mermaid diagram of messages sent between actors",
+            json!({
+                "crate": crate_filter(),
+                "method": {"type": "string", "enum": ["tell", "ask"], "description": "Only include this send method"}
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "generate_message_sequence_diagram",
+            "Generate an ordered sequence diagram of calls, tells and asks starting from an entry function or message type, following message handler bodies",
+            json!({
+                "entry": {"type": "string", "description": "Entry function (e.g. 'Gateway::submit') or message type (e.g. 'PlaceOrder')"},
+                "format": {"type": "string", "enum": ["mermaid", "plantuml"], "description": "Diagram syntax (default: mermaid)"},
+                "max_depth": {"type": "number", "description": "Maximum nesting of followed calls and handlers (default: 8)"}
+            }),
+            &["entry"],
+        ),
+        ToolDefinition::new(
+            "get_actor_details",
+            "Get an actor's spawns, handled messages and sent messages",
+            json!({
+                "actor_name": {"type": "string", "description": "Actor type name"},
+                "crate": crate_filter()
+            }),
+            &["actor_name"],
+        ),
+        ToolDefinition::new(
+            "get_distributed_actors",
+            "List distributed actors and the messages they handle",
+            json!({
+                "crate": crate_filter(),
+                "exclude_tests": {"type": "boolean", "description": "Skip actors defined in tests (default: false)"},
+                "limit": limit(50)
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "generate_distributed_actor_message_flow",
+            "Generate a Mermaid diagram of distributed actor message flows, resolving remote lookups through registered actor names and flagging lookups of names nobody registers",
+            json!({
+                "actor": {"type": "string", "description": "Only include flows involving this actor"},
+                "crate": crate_filter()
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "debug_call_relationships",
+            "Report counts and samples of the relationships stored in the graph",
+            json!({}),
+            &[],
+        ),
+        ToolDefinition::new(
+            "analyze_symbol_impact",
+            "Analyze where a function, type, trait or enum variant is used across the workspace, and what a change to it may reach",
+            json!({
+                "symbol": {"type": "string", "description": "Symbol name, qualified name, or `Enum::Variant`"},
+                "symbol_type": {"type": "string", "enum": ["function", "struct", "trait", "enum", "variant", "type"], "description": "Only analyze symbols of this kind"},
+                "depth": depth()
+            }),
+            &["symbol"],
+        ),
+        ToolDefinition::new(
+            "check_architecture_violations",
+            "Check for architecture violations with layer analysis",
+            json!({
+                "severity": {"type": "string", "enum": ["all", "error", "warning"], "description": "Filter by severity"},
+                "limit": {"type": "number", "description": "Maximum number of violations to report"}
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "semantic_search",
+            "Search functions and types semantically using embeddings, optionally restricted to callers of a function",
+            json!({
+                "query": {"type": "string", "description": "Search query"},
+                "limit": limit(10),
+                "caller_of": {"type": "string", "description": "Only return functions that directly call this qualified function name"}
+            }),
+            &["query"],
+        ),
+        ToolDefinition::new(
+            "get_function_details",
+            "Get detailed information about a specific function",
+            json!({
+                "qualified_name": {"type": "string", "description": "Fully qualified function name"}
+            }),
+            &["qualified_name"],
+        ),
+        ToolDefinition::new(
+            "get_type_details",
+            "Get detailed information about a specific type",
+            json!({
+                "qualified_name": {"type": "string", "description": "Fully qualified type name"}
+            }),
+            &["qualified_name"],
+        ),
+        ToolDefinition::new(
+            "get_crate_overview",
+            "Get overview of a specific crate",
+            json!({
+                "crate_name": {"type": "string", "description": "Name of the crate"}
+            }),
+            &["crate_name"],
+        ),
+        ToolDefinition::new(
+            "get_layer_health",
+            "Get architecture layer health report",
+            json!({}),
+            &[],
+        ),
+        ToolDefinition::new(
+            "incremental_update",
+            "Perform incremental update of changed files",
+            json!({
+                "files": file_list()
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "list_functions",
+            "List parsed functions, optionally filtered by name",
+            json!({
+                "search": {"type": "string", "description": "Substring to match against function names (alias: pattern)"},
+                "limit": limit(20)
+            }),
+            &[],
+        ),
+        ToolDefinition::new(
+            "debug_graph",
+            "Verify that the graph was populated",
+            json!({}),
+            &[],
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_registry_names_are_unique() {
+        let registry = tool_registry();
+        let names: HashSet<_> = registry.iter().map(|tool| tool.name).collect();
+        assert_eq!(names.len(), registry.len());
+    }
+
+    #[test]
+    fn test_required_params_are_declared() {
+        for tool in tool_registry() {
+            let schema = tool.to_json()["inputSchema"].clone();
+            assert_eq!(schema["type"], "object", "{}", tool.name);
+            let properties = schema["properties"].as_object().unwrap();
+            for required in schema["required"].as_array().into_iter().flatten() {
+                assert!(
+                    properties.contains_key(required.as_str().unwrap()),
+                    "{} requires undeclared parameter {}",
+                    tool.name,
+                    required
+                );
+            }
+        }
+    }
+}
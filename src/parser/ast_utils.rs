@@ -178,6 +178,82 @@ pub fn has_attribute(node: Node, source: &[u8], attribute_name: &str) -> bool {
     false
 }
 
+/// Predicate of a `#[cfg(...)]` / `#![cfg(...)]` attribute, e.g. `feature = "postgres"`
+pub fn cfg_predicate(attribute: Node, source: &[u8]) -> Option<String> {
+    let text = safe_node_text(attribute, source)?;
+    let inner = text.trim()
+        .trim_start_matches('#')
+        .trim_start_matches('!')
+        .trim_start()
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .trim();
+    let predicate = inner.strip_prefix("cfg")?.trim_start().strip_prefix('(')?.strip_suffix(')')?;
+    Some(predicate.trim().to_string())
+}
+
+/// `cfg` predicates written on an item or statement, from its own attribute children
+/// and the attribute items directly preceding it
+pub fn extract_cfg_predicates(node: Node, source: &[u8]) -> Vec<String> {
+    let mut predicates = Vec::new();
+
+    let mut sibling = node.prev_sibling();
+    while let Some(prev) = sibling {
+        match prev.kind() {
+            "attribute_item" => predicates.extend(cfg_predicate(prev, source)),
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        sibling = prev.prev_sibling();
+    }
+    // Collected bottom-up; keep source order
+    predicates.reverse();
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "attribute_item" {
+            predicates.extend(cfg_predicate(child, source));
+        }
+    }
+
+    predicates
+}
+
+/// Outer attributes written on an item, e.g. `#[test]` or `#[no_mangle]`, in source order:
+/// the attribute items directly preceding it and its own attribute children
+pub fn extract_attributes(node: Node, source: &[u8]) -> Vec<String> {
+    let mut attributes = Vec::new();
+
+    let mut sibling = node.prev_sibling();
+    while let Some(prev) = sibling {
+        match prev.kind() {
+            "attribute_item" => attributes.extend(safe_node_text(prev, source).map(|t| t.trim().to_string())),
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        sibling = prev.prev_sibling();
+    }
+    attributes.reverse();
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "attribute_item" {
+            attributes.extend(safe_node_text(child, source).map(|t| t.trim().to_string()));
+        }
+    }
+
+    attributes
+}
+
+/// Value of a plain string literal argument, without the quotes
+pub fn string_literal(node: Node, source: &[u8]) -> Option<String> {
+    if node.kind() != "string_literal" {
+        return None;
+    }
+    let text = safe_node_text(node, source)?;
+    Some(text.strip_prefix('"')?.strip_suffix('"')?.to_string())
+}
+
 /// Safely traverse up the parent chain looking for a node of the given type
 pub fn find_ancestor_of_type<'a>(mut node: Node<'a>, node_type: &str) -> Option<Node<'a>> {
     while let Some(parent) = node.parent() {
@@ -266,6 +342,34 @@ pub fn extract_generics(node: Node, source: &[u8]) -> Vec<String> {
     generics
 }
 
+/// Bounds on the generic parameters of a node, as `(parameter, bound list)` pairs taken from
+/// `<T: Bound>` and from its `where` clause
+pub fn extract_trait_bounds(node: Node, source: &[u8]) -> Vec<(String, String)> {
+    let mut bounds = Vec::new();
+    let mut push = |left: Option<Node>, right: Option<Node>| {
+        if let (Some(left), Some(right)) = (left.and_then(|n| safe_node_text(n, source)), right.and_then(|n| safe_node_text(n, source))) {
+            bounds.push((left.to_string(), right.trim_start_matches(':').trim().to_string()));
+        }
+    };
+
+    if let Some(type_params) = node.child_by_field_name("type_parameters") {
+        let mut cursor = type_params.walk();
+        for child in type_params.children(&mut cursor).filter(|c| c.kind() == "constrained_type_parameter") {
+            push(child.child_by_field_name("left"), child.child_by_field_name("bounds"));
+        }
+    }
+
+    let mut cursor = node.walk();
+    for clause in node.children(&mut cursor).filter(|c| c.kind() == "where_clause") {
+        let mut predicates = clause.walk();
+        for predicate in clause.children(&mut predicates).filter(|c| c.kind() == "where_predicate") {
+            push(predicate.child_by_field_name("left"), predicate.child_by_field_name("bounds"));
+        }
+    }
+
+    bounds
+}
+
 /// Safely get the range (start, end) of a node
 pub fn get_node_range(node: Node) -> (usize, usize) {
     (node.start_byte(), node.end_byte())
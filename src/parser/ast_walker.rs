@@ -5,6 +5,10 @@ use tree_sitter::Node;
 use crate::parser::ast_utils::*;
 use crate::parser::symbols::*;
 use crate::parser::symbols::DistributedActor;
+use crate::parser::cfg::combine_cfg;
+use crate::parser::frameworks::{is_spawn_name, remote_lookup, spawned_actor, ActorFrameworks};
+use crate::parser::module_tree::use_paths;
+use crate::parser::type_inference::{base_type_name, named_types, LocalTypes};
 
 /// Represents different contexts during AST traversal
 #[derive(Debug, Clone)]
@@ -31,6 +35,10 @@ pub enum ContextFrame {
         name: String, 
         kind: MacroKind 
     },
+    /// A closure body: an anonymous scope of the enclosing function, whose calls belong to it
+    Closure {
+        line: usize,
+    },
 }
 
 /// Different kinds of macros we track
@@ -192,12 +200,135 @@ impl ScopeStack {
     }
 }
 
+/// Classify an actor lifecycle hook body by the `ControlFlow` it returns.
+///
+/// Older kameo hooks return `Option<ActorStopReason>`, so `Ok(None)` / `Ok(Some(..))`
+/// count as continue / stop too. Any spawn in the body is taken as a restart.
+fn classify_hook_body(body: &str) -> HookBehavior {
+    let respawns = body
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(is_spawn_name);
+    let continues = body.contains("ControlFlow::Continue") || body.contains("Ok(None)");
+    let stops = body.contains("ControlFlow::Break") || body.contains("Ok(Some(");
+
+    match (respawns, continues, stops) {
+        (true, _, _) => HookBehavior::Restart,
+        (false, true, false) => HookBehavior::Continue,
+        (false, false, true) => HookBehavior::Stop,
+        _ => HookBehavior::Conditional,
+    }
+}
+
+/// Whether `node` runs inside a closure or async block handed to a spawn function such as
+/// `tokio::spawn`, i.e. on a separate task rather than the enclosing actor's own loop
+fn is_in_detached_task(node: Node, source: &[u8]) -> bool {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "function_item" => return false,
+            "async_block" | "closure_expression" => {
+                let callee = parent.parent()
+                    .filter(|args| args.kind() == "arguments")
+                    .and_then(|args| args.parent())
+                    .and_then(|call| call.child_by_field_name("function"));
+                let callee_name = callee
+                    .map(|f| f.child_by_field_name("name").or_else(|| f.child_by_field_name("field")).unwrap_or(f))
+                    .and_then(|name| safe_node_text(name, source));
+                if callee_name.is_some_and(is_spawn_name) {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        current = parent;
+    }
+    false
+}
+
+/// Whether an expression is used as a value: a call argument, array or tuple element, field
+/// or `let` initializer, assigned value or return value
+fn is_value_position(node: Node) -> bool {
+    let Some(parent) = node.parent() else { return false };
+    match parent.kind() {
+        "arguments" | "array_expression" | "tuple_expression" | "return_expression" => true,
+        "field_initializer" | "let_declaration" => parent.child_by_field_name("value") == Some(node),
+        "assignment_expression" => parent.child_by_field_name("right") == Some(node),
+        _ => false,
+    }
+}
+
+/// Whether a path is part of a pattern: a `match` arm, `if let`/`while let`, `let` or `for`
+/// binding. Guards and other expressions inside a `match` arm are not.
+fn in_pattern(node: Node) -> bool {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "match_pattern" => return parent.child_by_field_name("condition") != Some(current),
+            "let_condition" | "let_declaration" | "for_expression" => {
+                return parent.child_by_field_name("pattern") == Some(current);
+            }
+            kind if kind.ends_with("_pattern") => current = parent,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// A path without its turbofish arguments: `Vec::<u8>::new` becomes `Vec::new`
+fn without_turbofish(path: &str) -> String {
+    let mut stripped = String::with_capacity(path.len());
+    let mut depth = 0usize;
+    let mut rest = path;
+    while let Some(ch) = rest.chars().next() {
+        if depth == 0 && rest.starts_with("::<") {
+            depth = 1;
+            rest = &rest[3..];
+            continue;
+        }
+        match ch {
+            '<' if depth > 0 => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            _ if depth == 0 => stripped.push(ch),
+            _ => {}
+        }
+        rest = &rest[ch.len_utf8()..];
+    }
+    stripped
+}
+
 /// Unified AST walker that processes nodes in a single pass
 pub struct UnifiedWalker<'a> {
     scope_stack: ScopeStack,
     source: &'a [u8],
     crate_name: String,
     file_path: PathBuf,
+    /// Local binding types for each function body currently being walked
+    local_types: Vec<LocalTypes>,
+    /// `cfg` predicates of the enclosing items, outermost first
+    cfg_stack: Vec<String>,
+    /// Actor frameworks whose conventions are recognised in this crate
+    frameworks: ActorFrameworks,
+}
+
+/// Node kinds that can carry a `#[cfg(...)]` attribute we track
+/// Message type named by a value path: `Msg::Ping` and `crate::msg::Msg::Ping` are variants of
+/// `Msg`, while `messages::Ping` and `Ping` are the unit struct `Ping`
+fn message_type_of_path(path: &str) -> Option<String> {
+    let segments: Vec<&str> = path.split("::").map(str::trim).collect();
+    let type_segments = match segments.as_slice() {
+        [.., owner, _variant] if owner.starts_with(char::is_uppercase) => &segments[..segments.len() - 1],
+        _ => &segments[..],
+    };
+    base_type_name(&type_segments.join("::"))
+}
+
+fn is_cfg_gated(kind: &str) -> bool {
+    matches!(
+        kind,
+        "function_item" | "impl_item" | "trait_item" | "struct_item" | "enum_item" | "union_item"
+            | "mod_item" | "type_item" | "const_item" | "static_item" | "macro_invocation"
+            | "expression_statement" | "let_declaration" | "match_arm"
+    )
 }
 
 impl<'a> UnifiedWalker<'a> {
@@ -207,18 +338,81 @@ impl<'a> UnifiedWalker<'a> {
             source,
             crate_name,
             file_path,
+            local_types: Vec::new(),
+            cfg_stack: Vec::new(),
+            frameworks: ActorFrameworks::default(),
         }
     }
 
+    /// Recognise actors of `frameworks` instead of the default set
+    pub fn with_frameworks(mut self, frameworks: ActorFrameworks) -> Self {
+        self.frameworks = frameworks;
+        self
+    }
+
+    /// Walk the file as module `module_path` of its crate, as placed by the crate's module tree
+    pub fn with_module_path(mut self, module_path: Vec<String>) -> Self {
+        self.scope_stack.module_path = module_path;
+        self
+    }
+
     /// Main entry point for walking the AST
     pub fn walk(&mut self, node: Node<'a>) -> ParsedSymbols {
         let mut symbols = ParsedSymbols::new();
+
+        // A file-level `#![cfg(...)]` gates everything in the file
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.kind() == "inner_attribute_item" {
+                self.cfg_stack.extend(cfg_predicate(child, self.source));
+            }
+        }
+
         self.walk_node(node, &mut symbols);
         symbols
     }
 
-    /// Recursively walk a node and its children
+    /// Walk code expanded from `invocation` as if it were written where the invocation is:
+    /// in its modules, impl block and function, under its `cfg`
+    pub(crate) fn walk_expansion(&mut self, root: Node<'a>, invocation: &MacroInvocation) -> ParsedSymbols {
+        for module in &invocation.module_path {
+            self.scope_stack.push(ContextFrame::Module { name: module.clone(), is_inline: true });
+        }
+        if let Some((type_name, trait_name)) = &invocation.impl_context {
+            self.scope_stack.push(ContextFrame::Impl {
+                type_name: type_name.clone(),
+                trait_name: trait_name.clone(),
+                generics: Vec::new(),
+            });
+        }
+        if let Some(function) = &invocation.containing_function {
+            let name = function.rsplit("::").next().unwrap_or(function);
+            self.scope_stack.push(ContextFrame::Function { name: name.to_string(), is_async: false, is_method: false });
+            self.local_types.push(LocalTypes::default());
+        }
+        self.cfg_stack.extend(invocation.cfg.clone());
+
+        let mut symbols = ParsedSymbols::new();
+        self.walk_node(root, &mut symbols);
+        symbols
+    }
+
+    /// `cfg` predicate in effect at the current position
+    fn current_cfg(&self) -> Option<String> {
+        combine_cfg(&self.cfg_stack)
+    }
+
+    /// Recursively walk a node and its children, tracking the `cfg` attributes in scope
     fn walk_node(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
+        let depth = self.cfg_stack.len();
+        if is_cfg_gated(node.kind()) {
+            self.cfg_stack.extend(extract_cfg_predicates(node, self.source));
+        }
+        self.dispatch_node(node, symbols);
+        self.cfg_stack.truncate(depth);
+    }
+
+    fn dispatch_node(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
         // Process the current node based on its type
         // These process_* methods handle their own recursion
         match node.kind() {
@@ -239,6 +433,10 @@ impl<'a> UnifiedWalker<'a> {
                 self.process_macro(node, symbols);
                 return; // Don't recurse - process_macro handles its own recursion
             },
+            "macro_definition" => {
+                self.process_macro_definition(node, symbols);
+                return; // Rules are token trees, expanded later by the macro expander
+            },
             "attribute_item" => {
                 self.process_attribute_macro(node, symbols);
                 // Continue recursion for attributes - they may contain other nodes
@@ -248,6 +446,47 @@ impl<'a> UnifiedWalker<'a> {
                 return; // Don't recurse - process_module handles it
             },
             "call_expression" => self.process_call(node, symbols),
+            "closure_expression" => {
+                self.process_closure(node, symbols);
+                return; // Don't recurse - process_closure walks the body in its own scope
+            },
+            "identifier" => self.process_value_path(node, symbols),
+            "scoped_identifier" => {
+                self.process_value_path(node, symbols);
+                // `a::B::c` is recorded once, for the whole path
+                let is_prefix = node.parent()
+                    .filter(|parent| parent.kind() == "scoped_identifier")
+                    .and_then(|parent| parent.child_by_field_name("path")) == Some(node);
+                if let (false, Some(path)) = (is_prefix, node.child_by_field_name("path")) {
+                    let kind = if in_pattern(node) { TypeUsageKind::Pattern } else { TypeUsageKind::Path };
+                    self.record_body_type_usage(node, path, kind, symbols);
+                }
+            },
+            "struct_expression" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    self.record_body_type_usage(name, name, TypeUsageKind::StructLiteral, symbols);
+                }
+            },
+            "struct_pattern" => {
+                if let Some(name) = node.child_by_field_name("type") {
+                    self.record_body_type_usage(name, name, TypeUsageKind::Pattern, symbols);
+                }
+            },
+            "field_expression" => self.record_field_access(node, symbols),
+            "for_expression" | "let_condition" | "match_pattern" => {
+                let pattern = node.child_by_field_name("pattern").unwrap_or(node);
+                if let Some(locals) = self.local_types.last_mut() {
+                    locals.declare_pattern(pattern, self.source);
+                }
+            },
+            "use_declaration" => {
+                self.process_use(node, symbols);
+                return; // Nothing to find inside a use tree
+            },
+            "let_declaration" => {
+                self.process_let(node, symbols);
+                return; // Don't recurse - process_let handles it
+            },
             "type_alias" | "type_item" => self.process_type_alias(node, symbols),
             "declaration_list" => {
                 // Process trait method declarations inside trait bodies
@@ -287,6 +526,25 @@ impl<'a> UnifiedWalker<'a> {
         let parameters = self.extract_parameters(node);
         let return_type = self.extract_return_type(node);
         let signature = safe_node_text(node, self.source).unwrap_or("").to_string();
+        let locals = self.function_locals(node, &context, &parameters);
+
+        if let FunctionContext::TraitImpl { trait_name, type_name } = &context {
+            if trait_name.rsplit("::").next() == Some("Actor") {
+                if let Some(hook) = LifecycleHook::from_method_name(&name) {
+                    let body = node.child_by_field_name("body")
+                        .and_then(|b| safe_node_text(b, self.source))
+                        .unwrap_or("");
+                    symbols.actor_hooks.push(ActorLifecycleHook {
+                        actor_name: base_type_name(type_name).unwrap_or_else(|| type_name.clone()),
+                        hook,
+                        behavior: classify_hook_body(body),
+                        line: get_line_range(node).0,
+                        file_path: self.file_path.to_string_lossy().to_string(),
+                        crate_name: self.crate_name.clone(),
+                    });
+                }
+            }
+        }
         
         let function = RustFunction {
             id: format!("{}:{}:{}", self.file_path.display(), get_line_range(node).0, name),
@@ -311,8 +569,11 @@ impl<'a> UnifiedWalker<'a> {
             return_type,
             embedding_text: None,
             module: self.scope_stack.module_path().join("::"),
+            cfg: self.current_cfg(),
+            attributes: extract_attributes(node, self.source),
         };
 
+        self.record_signature_usages(node, &function, symbols);
         symbols.functions.push(function);
 
         // Push function context for nested processing
@@ -321,6 +582,7 @@ impl<'a> UnifiedWalker<'a> {
             is_async,
             is_method,
         });
+        self.local_types.push(locals);
 
         // Process children within function body
         let mut cursor = node.walk();
@@ -329,6 +591,7 @@ impl<'a> UnifiedWalker<'a> {
         }
 
         // Pop function context when done
+        self.local_types.pop();
         self.scope_stack.pop();
     }
 
@@ -347,7 +610,7 @@ impl<'a> UnifiedWalker<'a> {
         self.scope_stack.push(impl_context);
 
         // Check if this is an Actor implementation
-        if trait_name.as_deref() == Some("Actor") {
+        if trait_name.as_deref().is_some_and(|t| self.frameworks.is_actor_trait(t)) {
             // Extract associated types to detect Kameo patterns
             let associated_types = self.extract_associated_types(node);
             let has_args_type = associated_types.contains_key("Args");
@@ -391,6 +654,7 @@ impl<'a> UnifiedWalker<'a> {
                 inferred_from_message: false,
                 visibility: "pub".to_string(), // Actors are typically public
                 doc_comment: extract_doc_comment(node, self.source),
+                cfg: self.current_cfg(),
             };
             symbols.actors.push(actor.clone());
             
@@ -410,7 +674,7 @@ impl<'a> UnifiedWalker<'a> {
             }
             
             // If it's a Kameo actor, track the message type
-            if let Some(msg_type) = associated_types.get("Msg") {
+            if let Some(msg_type) = self.frameworks.actor_message(&associated_types).as_ref() {
                 // Create a MessageType for the Kameo message
                 let message_type = MessageType {
                     id: format!("{}:{}:{}", self.file_path.display(), get_line_range(node).0, msg_type),
@@ -439,14 +703,19 @@ impl<'a> UnifiedWalker<'a> {
                     file_path: self.file_path.to_string_lossy().to_string(),
                     line: get_line_range(node).0,
                     crate_name: self.crate_name.clone(),
+                    cfg: self.current_cfg(),
                 };
                 symbols.message_handlers.push(handler);
             }
         }
 
         // Check for Message handler implementations
-        if let Some(message_type) = self.extract_message_handler_type(&trait_name) {
-            let handler = self.extract_message_handler(node, &type_name, message_type);
+        if let Some((message_type, reply_name)) = trait_name.as_deref().and_then(|t| self.frameworks.handled_message(t)) {
+            let handler = self.extract_message_handler(node, &type_name, message_type, reply_name);
+            // `#[remote_message("id")]` makes the message reachable from other nodes
+            if has_attribute(node, self.source, "remote_message") {
+                self.record_remote_message(&handler, symbols);
+            }
             symbols.message_handlers.push(handler);
         }
 
@@ -459,10 +728,33 @@ impl<'a> UnifiedWalker<'a> {
         self.scope_stack.pop();
     }
 
+    /// Add a remote message to its actor's distributed entry, creating the entry if needed
+    fn record_remote_message(&self, handler: &MessageHandler, symbols: &mut ParsedSymbols) {
+        let message = base_type_name(&handler.message_type).unwrap_or_else(|| handler.message_type.clone());
+        if let Some(actor) = symbols.distributed_actors.iter_mut().find(|a| a.actor_name == handler.actor_name) {
+            if !actor.distributed_messages.contains(&message) {
+                actor.distributed_messages.push(message);
+            }
+            return;
+        }
+        symbols.distributed_actors.push(DistributedActor {
+            id: format!("{}:{}:{}", handler.file_path, handler.line, handler.actor_name),
+            actor_name: handler.actor_name.clone(),
+            crate_name: handler.crate_name.clone(),
+            file_path: handler.file_path.clone(),
+            line: handler.line,
+            is_test: self.file_path.components().any(|c| c.as_os_str() == "tests"),
+            distributed_messages: vec![message],
+            local_messages: Vec::new(),
+        });
+    }
+
     /// Process a trait declaration
     fn process_trait(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
         let name = extract_field_text(node, "name", self.source);
         let generics = extract_generics(node, self.source);
+        // Before the trait's own frame is pushed, which would prefix its name again
+        let qualified = self.scope_stack.qualified_name(&name);
 
         let trait_context = ContextFrame::Trait {
             name: name.clone(),
@@ -474,7 +766,7 @@ impl<'a> UnifiedWalker<'a> {
         // Extract trait information
         let trait_def = RustTrait {
             name: name.clone(),
-            qualified_name: self.scope_stack.qualified_name(&name),
+            qualified_name: qualified.clone(),
             file_path: self.file_path.clone(),
             start_line: get_line_range(node).0,
             end_line: get_line_range(node).1,
@@ -486,7 +778,7 @@ impl<'a> UnifiedWalker<'a> {
         let trait_type = RustType {
             id: format!("{}:{}:{}", self.file_path.display(), get_line_range(node).0, name),
             name: name.clone(),
-            qualified_name: self.scope_stack.qualified_name(&name),
+            qualified_name: qualified.clone(),
             crate_name: self.crate_name.clone(),
             module_path: self.scope_stack.module_path().join("::"),
             file_path: self.file_path.to_string_lossy().to_string(),
@@ -503,6 +795,7 @@ impl<'a> UnifiedWalker<'a> {
             embedding_text: None,
             type_kind: "Trait".to_string(),
             module: self.scope_stack.module_path().join("::"),
+            cfg: self.current_cfg(),
         };
         
         symbols.types.push(trait_type);
@@ -562,6 +855,7 @@ impl<'a> UnifiedWalker<'a> {
                 inferred_from_message: false,
                 visibility: extract_visibility(node, self.source),
                 doc_comment: extract_doc_comment(node, self.source),
+                cfg: self.current_cfg(),
             };
             symbols.actors.push(actor);
             
@@ -620,15 +914,101 @@ impl<'a> UnifiedWalker<'a> {
                 _ => "Struct".to_string(),
             },
             module: self.scope_stack.module_path().join("::"),
+            cfg: self.current_cfg(),
         };
 
+        if let Some(message) = self.frameworks.channel_message(&rust_type.fields) {
+            self.record_channel_actor(node, &name, message, symbols);
+        }
+        if let Some(body) = node.child_by_field_name("body") {
+            self.record_field_usages(body, &rust_type.qualified_name, symbols);
+        }
+        for (_, bounds) in extract_trait_bounds(node, self.source) {
+            self.record_type_usages(symbols, &rust_type.qualified_name, true, &bounds, TypeUsageKind::Bound, rust_type.line_start);
+        }
         symbols.types.push(rust_type);
+
+        if let Some(kind) = self.message_kind_of_type(node, &name) {
+            symbols.message_types.push(MessageType {
+                id: format!("{}:{}:{}", self.file_path.display(), get_line_range(node).0, name),
+                name: name.clone(),
+                qualified_name: self.scope_stack.qualified_name(&name),
+                crate_name: self.crate_name.clone(),
+                module_path: self.scope_stack.module_path().join("::"),
+                file_path: self.file_path.to_string_lossy().to_string(),
+                line_start: get_line_range(node).0,
+                line_end: get_line_range(node).1,
+                kind,
+                visibility: extract_visibility(node, self.source),
+                doc_comment: extract_doc_comment(node, self.source),
+            });
+        }
+    }
+
+    /// A struct owning a channel receiver runs as an actor handling the channel's messages
+    fn record_channel_actor(&self, node: Node<'a>, name: &str, message: String, symbols: &mut ParsedSymbols) {
+        let (line_start, line_end) = get_line_range(node);
+        let file_path = self.file_path.to_string_lossy().to_string();
+        symbols.actors.push(RustActor {
+            id: format!("{}:{}:{}", self.file_path.display(), line_start, name),
+            name: name.to_string(),
+            qualified_name: self.scope_stack.qualified_name(name),
+            crate_name: self.crate_name.clone(),
+            module_path: self.scope_stack.module_path().join("::"),
+            file_path: file_path.clone(),
+            line_start,
+            line_end,
+            actor_type: ActorType::Local,
+            is_distributed: false,
+            is_test: has_attribute(node, self.source, "test"),
+            local_messages: vec![message.clone()],
+            inferred_from_message: false,
+            visibility: extract_visibility(node, self.source),
+            doc_comment: extract_doc_comment(node, self.source),
+            cfg: self.current_cfg(),
+        });
+        symbols.message_handlers.push(MessageHandler {
+            id: format!("{}:{}:handler", self.file_path.display(), line_start),
+            actor_name: name.to_string(),
+            actor_qualified: self.scope_stack.qualified_name(name),
+            message_qualified: self.scope_stack.qualified_name(&message),
+            message_type: message,
+            reply_type: "()".to_string(),
+            is_async: true,
+            file_path,
+            line: line_start,
+            crate_name: self.crate_name.clone(),
+            cfg: self.current_cfg(),
+        });
+    }
+
+    /// Message kind for a type declared as a message: remote kameo messages, or the
+    /// `...Tell` / `...Ask` / `...Message` / `...Query` naming convention
+    fn message_kind_of_type(&self, node: Node<'a>, name: &str) -> Option<MessageKind> {
+        if name.ends_with("Tell") {
+            Some(MessageKind::Tell)
+        } else if name.ends_with("Ask") {
+            Some(MessageKind::Ask)
+        } else if name.ends_with("Message") {
+            Some(MessageKind::Message)
+        } else if name.ends_with("Query") {
+            Some(MessageKind::Query)
+        } else if has_attribute(node, self.source, "kameo(remote)") || has_attribute(node, self.source, "remote_message") {
+            Some(MessageKind::Message)
+        } else {
+            None
+        }
     }
 
     /// Process attribute macros (e.g., #[criterion::criterion_group!(...)])
     fn process_attribute_macro(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
         // Get the attribute text
         if let Some(attr_text) = safe_node_text(node, self.source) {
+            if let Some(derives) = attr_text.strip_prefix("#[derive(").and_then(|rest| rest.strip_suffix(")]")) {
+                self.process_derives(node, attr_text, derives, symbols);
+                return;
+            }
+
             // Check if this is a macro-like attribute (contains '!')
             if attr_text.contains('!') {
                 // Extract the macro name from patterns like #[criterion::criterion_group!(...)]
@@ -666,6 +1046,34 @@ impl<'a> UnifiedWalker<'a> {
         }
     }
 
+    /// Record one `derive` expansion per derived trait. The generated impls are not visible
+    /// here; they are filled in from `cargo expand` output for crates that opt into it.
+    fn process_derives(&mut self, node: Node<'a>, attr_text: &str, derives: &str, symbols: &mut ParsedSymbols) {
+        let line_range = get_line_range(node);
+        for derive in derives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let expansion_id = format!("{}:{}:{}", self.file_path.display(), line_range.0, derive);
+            symbols.macro_expansions.push(MacroExpansion {
+                id: expansion_id.clone(),
+                crate_name: self.crate_name.clone(),
+                file_path: self.file_path.to_string_lossy().to_string(),
+                line_range: line_range.0..line_range.1,
+                macro_name: derive.to_string(),
+                macro_type: "derive".to_string(),
+                expansion_pattern: attr_text.to_string(),
+                expanded_content: None,
+                target_functions: Vec::new(),
+                containing_function: self.get_containing_function(),
+                expansion_context: MacroContext {
+                    expansion_id,
+                    macro_type: "derive".to_string(),
+                    expansion_site_line: line_range.0,
+                    name: derive.to_string(),
+                    kind: "derive".to_string(),
+                },
+            });
+        }
+    }
+
     /// Process a macro invocation
     fn process_macro(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
         if let Some(macro_name) = node.child_by_field_name("macro") {
@@ -695,8 +1103,17 @@ impl<'a> UnifiedWalker<'a> {
                     kind: "macro_invocation".to_string(),
                 }),
                 synthetic_confidence: 1.0,
+                provenance: CallProvenance::TreeSitter,
+                receiver_type: None,
+                cfg: self.current_cfg(),
             };
             symbols.calls.push(macro_call);
+
+            if let Some(send_method) = self.frameworks.send_macro(&name) {
+                if let Some(message_send) = self.detect_macro_send(node, send_method) {
+                    symbols.message_sends.push(message_send);
+                }
+            }
             
             // Special handling for distributed_actor! macro
             if name == "distributed_actor" {
@@ -740,6 +1157,7 @@ impl<'a> UnifiedWalker<'a> {
                                         inferred_from_message: false,
                                         visibility: "pub".to_string(),
                                         doc_comment: None,
+                                        cfg: self.current_cfg(),
                                     };
                                     symbols.actors.push(actor.clone());
                                     
@@ -777,6 +1195,11 @@ impl<'a> UnifiedWalker<'a> {
                 line_range: get_line_range(node),
                 containing_function: self.get_containing_function(),
                 arguments: self.extract_macro_arguments(node),
+                crate_name: self.crate_name.clone(),
+                file_path: self.file_path.to_string_lossy().to_string(),
+                module_path: self.scope_stack.module_path(),
+                impl_context: self.scope_stack.current_impl_context(),
+                cfg: self.current_cfg(),
             };
 
             symbols.macro_invocations.push(macro_inv.clone());
@@ -826,6 +1249,10 @@ impl<'a> UnifiedWalker<'a> {
                     // Track custom macro patterns from trading-backend-poc
                     self.process_custom_macro_pattern(node, &name, symbols);
                 },
+                "cfg_if" | "cfg_if::cfg_if" => {
+                    // Items inside cfg_if! are only token trees to tree-sitter
+                    self.process_cfg_if(node, symbols);
+                },
                 "tokio::join" | "join" => {
                     // Track tokio::join! macro invocations
                     // These are async runtime macros
@@ -853,6 +1280,98 @@ impl<'a> UnifiedWalker<'a> {
         }
     }
 
+    /// Record a `macro_rules!` definition for the macro expander
+    fn process_macro_definition(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
+        let name = extract_field_text(node, "name", self.source);
+        let (line_start, line_end) = get_line_range(node);
+        symbols.macro_definitions.push(MacroDefinition {
+            name,
+            crate_name: self.crate_name.clone(),
+            file_path: self.file_path.to_string_lossy().to_string(),
+            line_start,
+            line_end,
+            is_exported: has_attribute(node, self.source, "macro_export"),
+            source: safe_node_text(node, self.source).unwrap_or("").to_string(),
+        });
+    }
+
+    /// Walk the branches of `cfg_if! { if #[cfg(a)] { .. } else if #[cfg(b)] { .. } else { .. } }`
+    /// as ordinary code, each branch gated by the predicate under which it is selected
+    fn process_cfg_if(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
+        let Some(token_tree) = find_child_of_type(node, &["token_tree"]) else {
+            return;
+        };
+
+        let mut earlier: Vec<String> = Vec::new();
+        let mut condition: Option<String> = None;
+        let mut cursor = token_tree.walk();
+        for child in token_tree.children(&mut cursor) {
+            if child.kind() != "token_tree" {
+                continue;
+            }
+            match safe_node_text(child, self.source).and_then(|text| text.chars().next()) {
+                Some('[') => condition = cfg_predicate(child, self.source),
+                Some('{') => {
+                    let not_earlier = match earlier.as_slice() {
+                        [] => None,
+                        [single] => Some(format!("not({})", single)),
+                        many => Some(format!("not(any({}))", many.join(", "))),
+                    };
+                    let branch_cfg = match (condition.take(), not_earlier) {
+                        (Some(own), Some(not_earlier)) => {
+                            earlier.push(own.clone());
+                            format!("all({}, {})", not_earlier, own)
+                        }
+                        (Some(own), None) => {
+                            earlier.push(own.clone());
+                            own
+                        }
+                        (None, Some(not_earlier)) => not_earlier,
+                        (None, None) => continue,
+                    };
+                    self.walk_cfg_if_branch(child, branch_cfg, symbols);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Parse and walk one `cfg_if!` branch body in the current scope
+    fn walk_cfg_if_branch(&self, body: Node<'a>, branch_cfg: String, symbols: &mut ParsedSymbols) {
+        // Blank out everything but the branch body so that byte offsets and line numbers
+        // of the re-parsed items match the original file
+        let inside = body.start_byte() + 1..body.end_byte().saturating_sub(1);
+        let masked: Vec<u8> = self.source.iter().enumerate()
+            .map(|(idx, &byte)| if inside.contains(&idx) || byte == b'\n' { byte } else { b' ' })
+            .collect();
+
+        let mut parser = tree_sitter::Parser::new();
+        if parser.set_language(&tree_sitter_rust::language()).is_err() {
+            return;
+        }
+        let Some(tree) = parser.parse(&masked, None) else {
+            return;
+        };
+
+        // Drop the cfg_if! macro frame so branch items keep their real context
+        let mut scope_stack = self.scope_stack.clone();
+        scope_stack.pop();
+
+        let mut cfg_stack = self.cfg_stack.clone();
+        cfg_stack.push(branch_cfg);
+
+        let mut branch = UnifiedWalker {
+            scope_stack,
+            source: &masked,
+            crate_name: self.crate_name.clone(),
+            file_path: self.file_path.clone(),
+            local_types: self.local_types.clone(),
+            cfg_stack,
+            frameworks: self.frameworks.clone(),
+        };
+        symbols.merge(branch.walk(tree.root_node()));
+    }
+
     /// Process a module declaration
     fn process_module(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
         let name = extract_field_text(node, "name", self.source);
@@ -874,18 +1393,51 @@ impl<'a> UnifiedWalker<'a> {
         self.scope_stack.pop();
     }
 
+    /// Record a `use` declaration, one import per name it binds
+    fn process_use(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
+        let Some(argument) = node.child_by_field_name("argument") else { return };
+        let mut paths = Vec::new();
+        use_paths(argument, self.source, &[], &mut paths);
+
+        for (name, path) in paths {
+            let (module_path, import_type, imported_items) = if name == "*" {
+                (path.join("::"), ImportType::Glob, Vec::new())
+            } else {
+                let Some((item, parent)) = path.split_last() else { continue };
+                let alias = (item != &name).then(|| name.clone());
+                (parent.join("::"), ImportType::Simple, vec![ImportedItem { name: item.clone(), alias }])
+            };
+            symbols.imports.push(RustImport {
+                module_path,
+                imported_items,
+                import_type,
+                file_path: self.file_path.to_string_lossy().to_string(),
+                line: get_line_range(node).0,
+                crate_name: self.crate_name.clone(),
+                module: self.scope_stack.module_path().join("::"),
+                is_pub: extract_visibility(node, self.source) != "private",
+            });
+        }
+    }
+
     /// Process a function call
     fn process_call(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
         if let Some(function_node) = node.child_by_field_name("function") {
             // Parse the call to extract the actual function name and qualified path
             let (callee_name, qualified_callee) = self.parse_call_target(function_node);
             
-            // Check for Kameo message send patterns
-            if callee_name == "tell" || callee_name == "ask" || callee_name == "do_send" || callee_name == "send" {
-                if let Some(message_send) = self.detect_message_send(node, &callee_name) {
+            // Message sends as spelled by the crate's actor frameworks
+            if let Some(send_method) = self.frameworks.send_method(&callee_name) {
+                if let Some(message_send) = self.detect_message_send(node, send_method) {
+                    if let Some(flow) = self.distributed_flow(node, &message_send) {
+                        symbols.distributed_message_flows.push(flow);
+                    }
                     symbols.message_sends.push(message_send);
                 }
             }
+
+            self.detect_actor_link(node, function_node, &callee_name, symbols);
+            self.detect_registry_call(node, function_node, &callee_name, symbols);
             
             let call = FunctionCall {
                 caller_id: self.get_containing_function().unwrap_or_else(|| {
@@ -903,12 +1455,425 @@ impl<'a> UnifiedWalker<'a> {
                 is_synthetic: false,
                 macro_context: None,
                 synthetic_confidence: 0.0,
+                provenance: CallProvenance::TreeSitter,
+                receiver_type: self.infer_receiver_type(function_node),
+                cfg: self.current_cfg(),
             };
 
             symbols.calls.push(call);
         }
     }
+
+    /// Process a `let` statement, recording the binding's type once its initializer is walked
+    fn process_let(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
+        // Walk the initializer first so calls in it still see any binding being shadowed
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.walk_node(child, symbols);
+        }
+
+        if let Some(locals) = self.local_types.last_mut() {
+            locals.bind_let(node, self.source);
+        }
+    }
+
+    /// Walk a closure as a child scope of the enclosing function: its parameters shadow outer
+    /// bindings and its `let`s end with it, while its calls stay attributed to the function
+    fn process_closure(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
+        let mut locals = self.local_types.last().cloned().unwrap_or_default();
+        if let Some(params) = node.child_by_field_name("parameters") {
+            let mut cursor = params.walk();
+            for param in params.named_children(&mut cursor) {
+                match param.kind() {
+                    "identifier" => {
+                        if let Some(name) = safe_node_text(param, self.source) {
+                            locals.bind(name, None);
+                            locals.bind_actor_ref(name, None);
+                        }
+                    }
+                    "parameter" => {
+                        let pattern = param.child_by_field_name("pattern");
+                        let binding = param.child_by_field_name("type")
+                            .and_then(|t| safe_node_text(t, self.source))
+                            .and_then(|t| locals.named(t));
+                        match pattern.filter(|p| p.kind() == "identifier").and_then(|p| safe_node_text(p, self.source)) {
+                            Some(name) => {
+                                locals.bind(name, binding);
+                                locals.bind_actor_ref(name, None);
+                            }
+                            None => locals.declare_pattern(pattern.unwrap_or(param), self.source),
+                        }
+                    }
+                    _ => locals.declare_pattern(param, self.source),
+                }
+            }
+        }
+
+        self.scope_stack.push(ContextFrame::Closure { line: get_line_range(node).0 });
+        self.local_types.push(locals);
+        if let Some(body) = node.child_by_field_name("body") {
+            self.walk_node(body, symbols);
+        }
+        self.local_types.pop();
+        self.scope_stack.pop();
+    }
+
+    /// Record a function path used as a value, e.g. `map(Self::convert)` or `Box::new(handler)`.
+    /// Local bindings, capitalised paths (variants, unit structs, constants) and anything outside
+    /// a value position are skipped.
+    fn process_value_path(&mut self, node: Node<'a>, symbols: &mut ParsedSymbols) {
+        if !is_value_position(node) {
+            return;
+        }
+        let Some(text) = safe_node_text(node, self.source) else { return };
+        let name = text.rsplit("::").next().unwrap_or(text).trim();
+        if !name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_') || name == "self" || name == "_" {
+            return;
+        }
+        if node.kind() == "identifier" && self.local_types.last().is_some_and(|locals| locals.is_local(name)) {
+            return;
+        }
+        let path = match text.strip_prefix("Self::") {
+            Some(rest) => {
+                let Some((type_name, _)) = self.scope_stack.current_impl_context() else { return };
+                format!("{}::{}", base_type_name(&type_name).unwrap_or(type_name), rest)
+            }
+            None => text.to_string(),
+        };
+
+        symbols.fn_references.push(FnReference {
+            caller_id: self.get_containing_function().unwrap_or_else(|| self.scope_stack.qualified_name("global")),
+            caller_module: self.scope_stack.module_path().join("::"),
+            path,
+            qualified_target: None,
+            from_crate: self.crate_name.clone(),
+            to_crate: None,
+            line: get_line_range(node).0,
+            file_path: self.file_path.to_string_lossy().to_string(),
+            cfg: self.current_cfg(),
+        });
+    }
+
+    /// Record every type path `type_text` names as used by `user`; resolution keeps the
+    /// workspace types among them. `Self` stands for the impl's type and is dropped elsewhere.
+    fn record_type_usages(&self, symbols: &mut ParsedSymbols, user: &str, user_is_type: bool, type_text: &str, kind: TypeUsageKind, line: usize) {
+        for path in named_types(type_text) {
+            let is_dyn = type_text.contains(&format!("dyn {}", path));
+            let Some(type_path) = self.without_self(&path) else { continue };
+            symbols.type_usages.push(TypeUsage {
+                is_dyn,
+                ..self.type_usage(user, user_is_type, type_path, kind, line)
+            });
+        }
+    }
+
+    /// A usage of `type_path` by `user` in the current module, not yet resolved
+    fn type_usage(&self, user: &str, user_is_type: bool, type_path: String, kind: TypeUsageKind, line: usize) -> TypeUsage {
+        TypeUsage {
+            user: user.to_string(),
+            user_is_type,
+            user_module: self.scope_stack.module_path().join("::"),
+            type_path,
+            qualified_type: None,
+            kind,
+            member: None,
+            is_dyn: false,
+            receiver: None,
+            from_crate: self.crate_name.clone(),
+            to_crate: None,
+            line,
+            file_path: self.file_path.to_string_lossy().to_string(),
+            cfg: self.current_cfg(),
+        }
+    }
+
+    /// A path with a leading `Self` replaced by the impl's type; `None` outside an impl
+    fn without_self(&self, path: &str) -> Option<String> {
+        match path.strip_prefix("Self") {
+            Some(rest) if rest.is_empty() || rest.starts_with("::") => {
+                let (type_name, _) = self.scope_stack.current_impl_context()?;
+                Some(format!("{}{}", base_type_name(&type_name).unwrap_or(type_name), rest))
+            }
+            _ => Some(path.to_string()),
+        }
+    }
+
+    /// Types named by a function's parameters, return type and generic bounds
+    fn record_signature_usages(&self, node: Node<'a>, function: &RustFunction, symbols: &mut ParsedSymbols) {
+        let (user, line) = (function.qualified_name.as_str(), function.line_start);
+        for parameter in function.parameters.iter().filter(|p| !p.is_self) {
+            self.record_type_usages(symbols, user, false, &parameter.param_type, TypeUsageKind::Parameter, line);
+        }
+        if let Some(return_type) = &function.return_type {
+            self.record_type_usages(symbols, user, false, return_type, TypeUsageKind::Return, line);
+        }
+        for (_, bounds) in extract_trait_bounds(node, self.source) {
+            self.record_type_usages(symbols, user, false, &bounds, TypeUsageKind::Bound, line);
+        }
+    }
+
+    /// Types named by the fields of a struct, union or enum body, at each field's line
+    fn record_field_usages(&self, node: Node<'a>, user: &str, symbols: &mut ParsedSymbols) {
+        let mut cursor = node.walk();
+        match node.kind() {
+            "field_declaration" => {
+                if let Some(field_type) = node.child_by_field_name("type").and_then(|t| safe_node_text(t, self.source)) {
+                    self.record_type_usages(symbols, user, true, field_type, TypeUsageKind::Field, get_line_range(node).0);
+                }
+            }
+            "ordered_field_declaration_list" => {
+                let types = node.named_children(&mut cursor)
+                    .filter(|child| !matches!(child.kind(), "visibility_modifier" | "attribute_item"));
+                for field_type in types {
+                    if let Some(text) = safe_node_text(field_type, self.source) {
+                        self.record_type_usages(symbols, user, true, text, TypeUsageKind::Field, get_line_range(field_type).0);
+                    }
+                }
+            }
+            _ => {
+                for child in node.named_children(&mut cursor) {
+                    self.record_field_usages(child, user, symbols);
+                }
+            }
+        }
+    }
+
+    /// A type named inside a function body by a struct literal, pattern or `Type::` path.
+    ///
+    /// `type_node` is the part of `node` naming the type; the whole path is kept so resolution
+    /// can tell the variant or associated item it names through the type.
+    fn record_body_type_usage(&self, node: Node<'a>, type_node: Node<'a>, kind: TypeUsageKind, symbols: &mut ParsedSymbols) {
+        let Some(user) = self.get_containing_function() else { return };
+        let (Some(text), Some(type_text)) = (safe_node_text(node, self.source), safe_node_text(type_node, self.source)) else {
+            return;
+        };
+        // `<T as Trait>::f` names the trait through `T`
+        if text.starts_with('<') {
+            return;
+        }
+        // `Vec::<u8>::new` and `Foo::<T> { .. }`: the turbofish arguments are not the type used
+        let type_text = without_turbofish(type_text);
+        let last = type_text.rsplit("::").next().unwrap_or(&type_text);
+        if !last.starts_with(|c: char| c.is_ascii_uppercase()) {
+            return;
+        }
+        if let Some(type_path) = self.without_self(&without_turbofish(text)) {
+            symbols.type_usages.push(self.type_usage(&user, false, type_path, kind, get_line_range(node).0));
+        }
+    }
+
+    /// `value.field` in a function body, when the value's type can be inferred. The method
+    /// of `value.method()` is a call, not a field.
+    fn record_field_access(&self, node: Node<'a>, symbols: &mut ParsedSymbols) {
+        let is_callee = node.parent()
+            .filter(|parent| parent.kind() == "call_expression")
+            .and_then(|call| call.child_by_field_name("function")) == Some(node);
+        if is_callee {
+            return;
+        }
+        let Some(user) = self.get_containing_function() else { return };
+        let Some(receiver) = self.infer_receiver_type(node) else { return };
+        let Some(field) = node.child_by_field_name("field").and_then(|f| safe_node_text(f, self.source)) else {
+            return;
+        };
+        symbols.type_usages.push(TypeUsage {
+            member: Some(field.to_string()),
+            receiver: Some(receiver),
+            ..self.type_usage(&user, false, String::new(), TypeUsageKind::FieldAccess, get_line_range(node).0)
+        });
+    }
+
+    /// Local binding types for a function: its parameters, `Self` and the generics in scope
+    fn function_locals(&self, node: Node<'a>, context: &FunctionContext, parameters: &[Parameter]) -> LocalTypes {
+        let self_type = match context {
+            FunctionContext::RegularImpl { type_name } | FunctionContext::TraitImpl { type_name, .. } => {
+                base_type_name(type_name)
+            }
+            _ => None,
+        };
+
+        let mut generics = extract_generics(node, self.source);
+        for frame in &self.scope_stack.frames {
+            if let ContextFrame::Impl { generics: outer, .. } | ContextFrame::Trait { generics: outer, .. } = frame {
+                generics.extend(outer.iter().cloned());
+            }
+        }
+
+        // Bounds come from the function and from the impl or trait it belongs to
+        let mut bounds = extract_trait_bounds(node, self.source);
+        let mut ancestor = node.parent();
+        while let Some(parent) = ancestor {
+            if matches!(parent.kind(), "impl_item" | "trait_item") {
+                bounds.extend(extract_trait_bounds(parent, self.source));
+                break;
+            }
+            ancestor = parent.parent();
+        }
+
+        LocalTypes::for_function(parameters, self_type, &generics).with_bounds(&bounds, parameters)
+    }
+
+    /// Inferred type of the receiver for `recv.method(..)` calls
+    fn infer_receiver_type(&self, function_node: Node<'a>) -> Option<ReceiverType> {
+        if function_node.kind() != "field_expression" {
+            return None;
+        }
+        let receiver = function_node.child_by_field_name("value")?;
+        self.local_types.last()?.infer(receiver, self.source)
+    }
     
+    /// Record kameo links: `Child::spawn_link(&supervisor, ..)` and `actor_ref.link(&other)`
+    fn detect_actor_link(&self, node: Node<'a>, function_node: Node<'a>, callee_name: &str, symbols: &mut ParsedSymbols) {
+        let locals = self.local_types.last();
+        let actor_behind = |expr: Node<'a>| locals.and_then(|l| l.actor_behind(expr, self.source));
+        let first_arg = node.child_by_field_name("arguments").and_then(|args| args.named_child(0));
+
+        let (supervisor, child, kind) = match callee_name {
+            "spawn_link" => {
+                let Some(child) = spawned_actor(node, function_node, self.source) else {
+                    return;
+                };
+                // Inside an actor's own impl the link usually goes through `ctx.actor_ref()`
+                let enclosing = self.scope_stack.current_impl_context()
+                    .and_then(|(type_name, _)| base_type_name(&type_name));
+                let Some(supervisor) = first_arg.and_then(actor_behind).or(enclosing) else {
+                    return;
+                };
+                (supervisor, child, LinkKind::SpawnLink)
+            }
+            "link" | "link_child" if function_node.kind() == "field_expression" => {
+                let owner = function_node.child_by_field_name("value").and_then(actor_behind);
+                let other = first_arg.and_then(actor_behind);
+                let (Some(owner), Some(other)) = (owner, other) else {
+                    return;
+                };
+                (owner, other, LinkKind::Link)
+            }
+            _ => return,
+        };
+
+        let line = get_line_range(node).0;
+        let file_path = self.file_path.to_string_lossy().to_string();
+        if kind == LinkKind::SpawnLink {
+            symbols.actor_spawns.push(ActorSpawn {
+                parent_actor_id: format!("{}::{}:{}", supervisor, self.file_path.display(), line),
+                parent_actor_name: supervisor.clone(),
+                child_actor_name: child.clone(),
+                spawn_method: SpawnMethod::SpawnLink,
+                spawn_pattern: if function_node.kind() == "scoped_identifier" {
+                    SpawnPattern::DirectType
+                } else {
+                    SpawnPattern::ModuleFunction
+                },
+                context: self.get_containing_function().unwrap_or_else(|| "global".to_string()),
+                arguments: node.child_by_field_name("arguments")
+                    .and_then(|args| safe_node_text(args, self.source))
+                    .map(str::to_string),
+                line,
+                file_path: file_path.clone(),
+                from_crate: self.crate_name.clone(),
+                to_crate: self.crate_name.clone(),
+                cfg: self.current_cfg(),
+            });
+        }
+        symbols.actor_links.push(ActorLink {
+            supervisor,
+            child,
+            kind,
+            line,
+            file_path,
+            from_crate: self.crate_name.clone(),
+            cfg: self.current_cfg(),
+        });
+    }
+
+    /// Record swarm registry calls: `actor_ref.register("name")` and
+    /// `RemoteActorRef::<A>::lookup("name")`, both only with a literal name
+    fn detect_registry_call(&self, node: Node<'a>, function_node: Node<'a>, callee_name: &str, symbols: &mut ParsedSymbols) {
+        let line = get_line_range(node).0;
+        let file_path = self.file_path.to_string_lossy().to_string();
+
+        match callee_name {
+            "register" => {
+                let Some(args) = node.child_by_field_name("arguments") else {
+                    return;
+                };
+                let mut cursor = args.walk();
+                let args: Vec<Node> = args.named_children(&mut cursor).collect();
+                let Some(registered_name) = args.iter().find_map(|&arg| string_literal(arg, self.source)) else {
+                    return;
+                };
+                // `actor_ref.register("name")`, or `ActorSwarm::register(actor_ref, "name")` in older kameo
+                let locals = self.local_types.last();
+                let actor_behind = |expr: Node<'a>| locals.and_then(|l| l.actor_behind(expr, self.source));
+                let actor_name = function_node.child_by_field_name("value")
+                    .filter(|_| function_node.kind() == "field_expression")
+                    .and_then(actor_behind)
+                    .or_else(|| args.iter().find_map(|&arg| actor_behind(arg)));
+
+                symbols.actor_registrations.push(ActorRegistration {
+                    registered_name,
+                    actor_name,
+                    function: self.get_containing_function(),
+                    crate_name: self.crate_name.clone(),
+                    file_path,
+                    line,
+                });
+            }
+            "lookup" => {
+                let Some((registered_name, actor_name)) = remote_lookup(node, self.source) else {
+                    return;
+                };
+                symbols.remote_lookups.push(RemoteLookup {
+                    registered_name,
+                    actor_name,
+                    function: self.get_containing_function(),
+                    crate_name: self.crate_name.clone(),
+                    file_path,
+                    line,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// A send to a remote actor found with `lookup("name")`; the target is resolved through
+    /// the registry once every crate's registrations are known
+    fn distributed_flow(&self, call_node: Node<'a>, send: &MessageSend) -> Option<DistributedMessageFlow> {
+        let receiver = call_node.child_by_field_name("function")
+            .filter(|f| f.kind() == "field_expression")
+            .and_then(|f| f.child_by_field_name("value"))?;
+        let registered_name = self.local_types.last()?.lookup_behind(receiver, self.source)?;
+
+        let sender_context = send.sender_qualified.clone().unwrap_or_else(|| "global".to_string());
+        // Until resolved, the looked up type (or the name itself) stands in for the target
+        let target_actor = if send.receiver_qualified.as_deref() == Some(send.receiver_actor.as_str()) {
+            registered_name.clone()
+        } else {
+            send.receiver_actor.clone()
+        };
+
+        Some(DistributedMessageFlow {
+            id: format!("{}::{}::{}:{}", self.crate_name, send.sender_actor, send.message_type, send.line),
+            message_type: send.message_type.clone(),
+            sender_actor: send.sender_actor.clone(),
+            sender_context: sender_context.clone(),
+            sender_crate: self.crate_name.clone(),
+            target_actor,
+            target_crate: self.crate_name.clone(),
+            send_method: match send.send_method {
+                SendMethod::Tell => MessageSendMethod::Tell,
+                SendMethod::Ask => MessageSendMethod::Ask,
+            },
+            send_location: MessageSendLocation {
+                file_path: send.file_path.clone(),
+                line: send.line,
+                function_context: sender_context,
+            },
+            registered_name: Some(registered_name),
+        })
+    }
+
     /// Parse a call target to extract the function name and qualified path
     fn parse_call_target(&self, node: Node<'a>) -> (String, Option<String>) {
         match node.kind() {
@@ -989,63 +1954,67 @@ impl<'a> UnifiedWalker<'a> {
     }
 
     /// Detect message send patterns (tell, ask, etc.)
-    fn detect_message_send(&self, call_node: Node<'a>, method_name: &str) -> Option<MessageSend> {
+    ///
+    /// The sender is the actor whose impl contains the send, unless the send runs in a
+    /// detached task; the receiver is the actor behind the `ActorRef` when it can be inferred.
+    fn detect_message_send(&self, call_node: Node<'a>, send_method: SendMethod) -> Option<MessageSend> {
         // Get the receiver of the method call (the actor ref)
-        let receiver = if let Some(function_node) = call_node.child_by_field_name("function") {
-            if function_node.kind() == "field_expression" {
-                if let Some(value_node) = function_node.child_by_field_name("value") {
-                    safe_node_text(value_node, self.source)
-                        .unwrap_or("<unknown>")
-                        .to_string()
-                } else {
-                    "<unknown>".to_string()
-                }
-            } else {
-                "<unknown>".to_string()
-            }
-        } else {
-            "<unknown>".to_string()
-        };
-        
-        // Try to extract the message type from arguments
-        let message_type = if let Some(args_node) = call_node.child_by_field_name("arguments") {
-            // Get the first argument which should be the message
-            let mut cursor = args_node.walk();
-            let mut message_type_str = "<unknown>".to_string();
-            
-            for child in args_node.children(&mut cursor) {
-                if child.kind() != "(" && child.kind() != ")" && child.kind() != "," {
-                    // Try to extract the type or constructor name
-                    if let Some(text) = safe_node_text(child, self.source) {
-                        // If it's a struct construction like ProcessMessage::Add(42.0)
-                        if text.contains("::") {
-                            message_type_str = text.split("::").next().unwrap_or("<unknown>").to_string();
-                        } else {
-                            message_type_str = text.to_string();
-                        }
-                        break;
-                    }
-                }
-            }
-            
-            message_type_str
-        } else {
-            "<unknown>".to_string()
-        };
-        
-        // Determine send method based on method name
-        let send_method = match method_name {
-            "tell" | "do_send" => SendMethod::Tell,
-            "ask" | "send" => SendMethod::Ask,
-            _ => SendMethod::Tell,
-        };
-        
-        Some(MessageSend {
+        let receiver_node = call_node.child_by_field_name("function")
+            .filter(|f| f.kind() == "field_expression")
+            .and_then(|f| f.child_by_field_name("value"));
+
+        // The first argument is the message
+        let message_type = call_node.child_by_field_name("arguments")
+            .and_then(|args| args.named_child(0))
+            .map(|arg| self.message_type_of(arg))
+            .unwrap_or_else(|| "<unknown>".to_string());
+
+        Some(self.message_send(call_node, receiver_node, message_type, send_method))
+    }
+
+    /// Send macro such as ractor's `cast!(actor, Msg::Ping)`: the first token is the actor
+    /// handle and the message type is read from the path of the second argument
+    fn detect_macro_send(&self, node: Node<'a>, send_method: SendMethod) -> Option<MessageSend> {
+        let mut cursor = node.walk();
+        let tokens = node.children(&mut cursor).find(|c| c.kind() == "token_tree")?;
+        let mut cursor = tokens.walk();
+        let tokens: Vec<Node> = tokens.children(&mut cursor).collect();
+
+        let receiver = tokens.iter().copied().find(|t| t.kind() == "identifier" || t.kind() == "self")?;
+        let comma = tokens.iter().position(|t| t.kind() == ",")?;
+        let message: String = tokens[comma + 1..].iter()
+            .take_while(|t| !matches!(t.kind(), "," | ")" | "token_tree"))
+            .filter_map(|t| safe_node_text(*t, self.source))
+            .collect();
+        let message_type = message_type_of_path(&message).unwrap_or_else(|| "<unknown>".to_string());
+
+        Some(self.message_send(node, Some(receiver), message_type, send_method))
+    }
+
+    /// Message send record for a send at `call_node` through `receiver_node`
+    fn message_send(&self, call_node: Node<'a>, receiver_node: Option<Node<'a>>, message_type: String, send_method: SendMethod) -> MessageSend {
+        let locals = self.local_types.last();
+        let receiver_text = receiver_node
+            .and_then(|n| safe_node_text(n, self.source))
+            .unwrap_or("<unknown>")
+            .to_string();
+        let receiver_actor = receiver_node
+            .and_then(|n| locals.and_then(|l| l.actor_behind(n, self.source)))
+            .unwrap_or_else(|| receiver_text.clone());
+
+        let containing_function = self.get_containing_function();
+        let sender_actor = self.scope_stack.current_impl_context()
+            .filter(|_| !is_in_detached_task(call_node, self.source))
+            .and_then(|(type_name, _)| base_type_name(&type_name))
+            .or_else(|| containing_function.clone())
+            .unwrap_or_else(|| "global".to_string());
+
+        MessageSend {
             id: format!("{}:{}:send", self.file_path.display(), get_line_range(call_node).0),
-            sender_actor: self.get_containing_function().unwrap_or_else(|| "global".to_string()),
-            sender_qualified: Some(self.scope_stack.qualified_name("sender")),
-            receiver_actor: receiver.clone(),
-            receiver_qualified: Some(receiver),
+            sender_actor,
+            sender_qualified: containing_function,
+            receiver_actor,
+            receiver_qualified: Some(receiver_text),
             message_type,
             message_qualified: None,
             send_method,
@@ -1053,9 +2022,44 @@ impl<'a> UnifiedWalker<'a> {
             file_path: self.file_path.to_string_lossy().to_string(),
             from_crate: self.crate_name.clone(),
             to_crate: None,
+            cfg: self.current_cfg(),
+        }
+    }
+
+    /// Type name of a message expression: `Ping { .. }`, `Ping::new()`, `Cmd::Stop`, or a
+    /// local binding whose type is known, also behind `&` as remote sends take it. Falls back
+    /// to the text before the first `::`.
+    fn message_type_of(&self, arg: Node<'a>) -> String {
+        if arg.kind() == "reference_expression" {
+            if let Some(value) = arg.child_by_field_name("value") {
+                return self.message_type_of(value);
+            }
+        }
+        let inferred = match arg.kind() {
+            "struct_expression" => arg.child_by_field_name("name")
+                .and_then(|n| safe_node_text(n, self.source))
+                .and_then(base_type_name),
+            "call_expression" => arg.child_by_field_name("function")
+                .filter(|f| f.kind() == "scoped_identifier")
+                .and_then(|f| f.child_by_field_name("path"))
+                .and_then(|p| safe_node_text(p, self.source))
+                .and_then(base_type_name),
+            "scoped_identifier" => safe_node_text(arg, self.source).and_then(message_type_of_path),
+            "identifier" => self.local_types.last()
+                .and_then(|l| l.infer(arg, self.source))
+                .and_then(|t| match t {
+                    ReceiverType::Named(name) => Some(name),
+                    ReceiverType::Field { .. } | ReceiverType::Bounded { .. } => None,
+                }),
+            _ => None,
+        };
+
+        inferred.unwrap_or_else(|| {
+            let text = safe_node_text(arg, self.source).unwrap_or("<unknown>");
+            text.split("::").next().unwrap_or("<unknown>").to_string()
         })
     }
-    
+
     fn extract_associated_types(&self, node: Node<'a>) -> HashMap<String, String> {
         let mut types = HashMap::new();
         
@@ -1229,27 +2233,14 @@ impl<'a> UnifiedWalker<'a> {
             actor_type: ActorType::Local, // Default to local Kameo actor
             local_messages: Vec::new(), // Will be populated later
             inferred_from_message: false,
+            cfg: self.current_cfg(),
         }
     }
 
-    fn extract_message_handler_type(&self, trait_name: &Option<String>) -> Option<String> {
-        if let Some(name) = trait_name {
-            // Check for Kameo Message<T> pattern
-            if name.starts_with("Message<") && name.ends_with(">") {
-                let message_type = &name[8..name.len() - 1];
-                Some(message_type.to_string())
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }
-
-    fn extract_message_handler(&self, node: Node<'a>, actor_name: &str, message_type: String) -> MessageHandler {
-        // Extract associated types from the impl block to get Reply type
+    fn extract_message_handler(&self, node: Node<'a>, actor_name: &str, message_type: String, reply_name: &str) -> MessageHandler {
+        // Extract associated types from the impl block to get the reply type
         let associated_types = self.extract_associated_types(node);
-        let reply_type = associated_types.get("Reply")
+        let reply_type = associated_types.get(reply_name)
             .cloned()
             .unwrap_or_else(|| "()".to_string());
         
@@ -1264,6 +2255,7 @@ impl<'a> UnifiedWalker<'a> {
             file_path: self.file_path.to_string_lossy().to_string(),
             line: get_line_range(node).0,
             crate_name: self.crate_name.clone(),
+            cfg: self.current_cfg(),
         }
     }
 
@@ -1330,6 +2322,9 @@ impl<'a> UnifiedWalker<'a> {
                         kind: "paste".to_string(),
                     }),
                     synthetic_confidence: 0.9,
+                    provenance: CallProvenance::TreeSitter,
+                    receiver_type: None,
+                    cfg: self.current_cfg(),
                 };
                 symbols.calls.push(synthetic_call);
             }
@@ -1470,8 +2465,11 @@ impl<'a> UnifiedWalker<'a> {
             return_type,
             embedding_text: None,
             module: self.scope_stack.module_path().join("::"),
+            cfg: self.current_cfg(),
+            attributes: extract_attributes(node, self.source),
         };
         
+        self.record_signature_usages(node, &function, symbols);
         symbols.functions.push(function);
     }
     
@@ -1508,6 +2506,7 @@ impl<'a> UnifiedWalker<'a> {
             embedding_text: None,
             type_kind: "TypeAlias".to_string(),
             module: self.scope_stack.module_path().join("::"),
+            cfg: self.current_cfg(),
         };
         
         symbols.types.push(type_alias);
@@ -0,0 +1,275 @@
+use std::collections::HashSet;
+
+use crate::config::CfgConfig;
+use crate::parser::symbols::ParsedSymbols;
+use crate::workspace::CrateMetadata;
+
+/// Parsed `#[cfg(...)]` predicate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CfgPredicate {
+    /// Bare option such as `test` or `unix`
+    Flag(String),
+    /// `key = "value"` such as `feature = "postgres"`
+    KeyValue(String, String),
+    All(Vec<CfgPredicate>),
+    Any(Vec<CfgPredicate>),
+    Not(Box<CfgPredicate>),
+}
+
+impl CfgPredicate {
+    /// Parse the text between the parentheses of `cfg(...)`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parser = PredicateParser { input: text.as_bytes(), pos: 0 };
+        let predicate = parser.predicate()?;
+        parser.skip_whitespace();
+        (parser.pos == parser.input.len()).then_some(predicate)
+    }
+
+    /// Three-valued evaluation: `None` for keys we do not model (target_arch,
+    /// target_pointer_width, ...), which pass through `not`, `all` and `any` unresolved
+    pub fn evaluate(&self, active: &ActiveCfg) -> Option<bool> {
+        match self {
+            CfgPredicate::Flag(name) => Some(active.flags.contains(name)),
+            CfgPredicate::KeyValue(key, value) => match key.as_str() {
+                "feature" => Some(active.features.contains(value)),
+                "target_os" => Some(&active.target_os == value),
+                "target_family" => Some(&active.target_family == value),
+                _ => None,
+            },
+            CfgPredicate::All(predicates) => {
+                let results: Vec<_> = predicates.iter().map(|p| p.evaluate(active)).collect();
+                if results.contains(&Some(false)) {
+                    Some(false)
+                } else if results.contains(&None) {
+                    None
+                } else {
+                    Some(true)
+                }
+            }
+            CfgPredicate::Any(predicates) => {
+                let results: Vec<_> = predicates.iter().map(|p| p.evaluate(active)).collect();
+                if results.contains(&Some(true)) {
+                    Some(true)
+                } else if results.contains(&None) {
+                    None
+                } else {
+                    Some(false)
+                }
+            }
+            CfgPredicate::Not(predicate) => predicate.evaluate(active).map(|result| !result),
+        }
+    }
+}
+
+struct PredicateParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl PredicateParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.input.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.pos < self.input.len()
+            && (self.input[self.pos].is_ascii_alphanumeric() || self.input[self.pos] == b'_')
+        {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| String::from_utf8_lossy(&self.input[start..self.pos]).into_owned())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if !self.eat(b'"') {
+            return None;
+        }
+        let start = self.pos;
+        while self.pos < self.input.len() && self.input[self.pos] != b'"' {
+            self.pos += 1;
+        }
+        let value = String::from_utf8_lossy(&self.input[start..self.pos]).into_owned();
+        self.eat(b'"').then_some(value)
+    }
+
+    fn list(&mut self) -> Option<Vec<CfgPredicate>> {
+        if !self.eat(b'(') {
+            return None;
+        }
+        let mut predicates = Vec::new();
+        loop {
+            if self.eat(b')') {
+                return Some(predicates);
+            }
+            predicates.push(self.predicate()?);
+            if !self.eat(b',') {
+                return self.eat(b')').then_some(predicates);
+            }
+        }
+    }
+
+    fn predicate(&mut self) -> Option<CfgPredicate> {
+        let name = self.identifier()?;
+        match name.as_str() {
+            "all" => Some(CfgPredicate::All(self.list()?)),
+            "any" => Some(CfgPredicate::Any(self.list()?)),
+            "not" => {
+                let mut inner = self.list()?;
+                (inner.len() == 1).then(|| CfgPredicate::Not(Box::new(inner.remove(0))))
+            }
+            _ if self.eat(b'=') => Some(CfgPredicate::KeyValue(name, self.string()?)),
+            _ => Some(CfgPredicate::Flag(name)),
+        }
+    }
+}
+
+/// Combine the predicates of nested cfg scopes into the one that gates the innermost item
+pub fn combine_cfg(predicates: &[String]) -> Option<String> {
+    match predicates {
+        [] => None,
+        [single] => Some(single.clone()),
+        many => Some(format!("all({})", many.join(", "))),
+    }
+}
+
+/// The cfg configuration a crate is analysed under
+#[derive(Debug, Clone)]
+pub struct ActiveCfg {
+    pub features: HashSet<String>,
+    pub flags: HashSet<String>,
+    pub target_os: String,
+    pub target_family: String,
+}
+
+impl ActiveCfg {
+    /// Active configuration for a discovered crate, see [`ActiveCfg::for_crate_name`]
+    pub fn for_crate(config: &CfgConfig, crate_meta: &CrateMetadata) -> Self {
+        Self::for_crate_name(config, &crate_meta.name, &crate_meta.features)
+    }
+
+    /// Features from `[cfg.features]` for this crate, or `default_features` when it is not listed
+    pub fn for_crate_name(config: &CfgConfig, crate_name: &str, default_features: &[String]) -> Self {
+        let features = config
+            .features
+            .get(crate_name)
+            .map(Vec::as_slice)
+            .unwrap_or(default_features)
+            .iter()
+            .cloned()
+            .collect();
+
+        let target_os = config
+            .target_os
+            .clone()
+            .unwrap_or_else(|| std::env::consts::OS.to_string());
+        let target_family = if target_os == "windows" { "windows" } else { "unix" }.to_string();
+
+        // Test code is part of what we analyse, so `cfg(test)` items stay live
+        let mut flags: HashSet<String> = ["test", "debug_assertions"].iter().map(|s| s.to_string()).collect();
+        flags.insert(target_family.clone());
+        flags.extend(config.flags.iter().cloned());
+
+        Self { features, flags, target_os, target_family }
+    }
+
+    /// Whether code gated by `cfg` is compiled in; unparseable predicates and ones that
+    /// depend on keys we do not model count as active, so they never hide code
+    pub fn is_active(&self, cfg: Option<&str>) -> bool {
+        cfg.and_then(CfgPredicate::parse)
+            .and_then(|predicate| predicate.evaluate(self))
+            .unwrap_or(true)
+    }
+}
+
+/// Drop everything the graph is built from that is compiled out under `active`: items, impl
+/// methods, calls, function and type references, and actors with their spawns, links and messages
+pub fn filter_inactive(symbols: &mut ParsedSymbols, active: &ActiveCfg) {
+    symbols.functions.retain(|f| active.is_active(f.cfg.as_deref()));
+    symbols.types.retain(|t| active.is_active(t.cfg.as_deref()));
+    symbols.calls.retain(|c| active.is_active(c.cfg.as_deref()));
+    symbols.macro_invocations.retain(|m| active.is_active(m.cfg.as_deref()));
+    symbols.fn_references.retain(|r| active.is_active(r.cfg.as_deref()));
+    symbols.type_usages.retain(|u| active.is_active(u.cfg.as_deref()));
+    symbols.trait_dispatches.retain(|d| active.is_active(d.cfg.as_deref()));
+    symbols.actors.retain(|a| active.is_active(a.cfg.as_deref()));
+    symbols.actor_spawns.retain(|s| active.is_active(s.cfg.as_deref()));
+    symbols.actor_links.retain(|l| active.is_active(l.cfg.as_deref()));
+    symbols.message_handlers.retain(|h| active.is_active(h.cfg.as_deref()));
+    symbols.message_sends.retain(|s| active.is_active(s.cfg.as_deref()));
+    for impl_block in &mut symbols.impls {
+        impl_block.methods.retain(|m| active.is_active(m.cfg.as_deref()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active(features: &[&str]) -> ActiveCfg {
+        ActiveCfg {
+            features: features.iter().map(|s| s.to_string()).collect(),
+            flags: ["test", "unix"].iter().map(|s| s.to_string()).collect(),
+            target_os: "linux".to_string(),
+            target_family: "unix".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_predicates() {
+        assert_eq!(
+            CfgPredicate::parse(r#"feature = "postgres""#),
+            Some(CfgPredicate::KeyValue("feature".to_string(), "postgres".to_string()))
+        );
+        assert_eq!(
+            CfgPredicate::parse(r#"all(unix, not(feature = "sqlite"))"#),
+            Some(CfgPredicate::All(vec![
+                CfgPredicate::Flag("unix".to_string()),
+                CfgPredicate::Not(Box::new(CfgPredicate::KeyValue("feature".to_string(), "sqlite".to_string()))),
+            ]))
+        );
+        assert_eq!(CfgPredicate::parse("any(a, b,)").map(|_| ()), Some(()));
+        assert_eq!(CfgPredicate::parse("not(a, b)"), None);
+        assert_eq!(CfgPredicate::parse("feature = "), None);
+    }
+
+    #[test]
+    fn test_evaluate_against_active_features() {
+        let active = active(&["postgres"]);
+        assert!(active.is_active(Some(r#"feature = "postgres""#)));
+        assert!(!active.is_active(Some(r#"feature = "sqlite""#)));
+        assert!(active.is_active(Some(r#"any(feature = "sqlite", target_os = "linux")"#)));
+        assert!(!active.is_active(Some(r#"target_os = "windows""#)));
+        assert!(active.is_active(Some("test")));
+        assert!(!active.is_active(Some("miri")));
+        assert!(active.is_active(Some(r#"target_arch = "wasm32""#)));
+        assert!(active.is_active(Some(r#"not(target_arch = "wasm32")"#)));
+        assert!(active.is_active(Some(r#"all(unix, not(target_pointer_width = "16"))"#)));
+        assert!(!active.is_active(Some(r#"all(windows, not(target_arch = "wasm32"))"#)));
+        assert!(active.is_active(Some(r#"any(target_os = "linux", target_arch = "wasm32")"#)));
+        assert!(active.is_active(None));
+    }
+
+    #[test]
+    fn test_combine_cfg() {
+        assert_eq!(combine_cfg(&[]), None);
+        assert_eq!(combine_cfg(&["unix".to_string()]).as_deref(), Some("unix"));
+        assert_eq!(
+            combine_cfg(&["unix".to_string(), r#"feature = "a""#.to_string()]).as_deref(),
+            Some(r#"all(unix, feature = "a")"#)
+        );
+    }
+}
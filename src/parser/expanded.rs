@@ -0,0 +1,212 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::ExpandCommand;
+use crate::parser::symbols::{FunctionContext, MacroContext, ParsedSymbols, RustFunction, RustImpl, RustType};
+use crate::parser::type_inference::base_type_name;
+
+/// Confidence of calls taken from compiler-expanded code; the code is exact, only the line
+/// it is attributed to is approximate
+const EXPANDED_CONFIDENCE: f32 = 1.0;
+
+/// Run `command` on the crate at `crate_path` and return its macro-expanded source.
+///
+/// Library crates expand their `lib` target; other crates expand their only binary, and fail
+/// when there is more than one.
+pub fn expand_crate_source(crate_path: &Path, command: ExpandCommand) -> Result<String> {
+    let manifest = crate_path.join("Cargo.toml");
+    let mut cmd = Command::new("cargo");
+    match command {
+        ExpandCommand::CargoExpand => {
+            cmd.args(["expand", "--ugly", "--color", "never", "--manifest-path"]).arg(&manifest);
+        }
+        ExpandCommand::Rustc => {
+            cmd.args(["rustc", "--profile=check", "--color", "never", "--manifest-path"]).arg(&manifest);
+        }
+    }
+    if crate_path.join("src/lib.rs").exists() {
+        cmd.arg("--lib");
+    }
+    if command == ExpandCommand::Rustc {
+        cmd.args(["--", "-Zunpretty=expanded"]);
+    }
+
+    let output = cmd.output().with_context(|| format!("Failed to run cargo for {}", manifest.display()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+        bail!("expansion of {} failed ({}): {}", manifest.display(), output.status, reason.trim());
+    }
+    String::from_utf8(output.stdout).context("expanded source is not valid UTF-8")
+}
+
+/// `Message<Inc>` for `::kameo::message::Message<Inc>`: the trait without its path, arguments kept
+fn trait_key(trait_name: &str) -> String {
+    let trait_name = trait_name.replace(' ', "");
+    let (path, args) = match trait_name.split_once('<') {
+        Some((path, args)) => (path, Some(args)),
+        None => (trait_name.as_str(), None),
+    };
+    let name = path.rsplit("::").next().unwrap_or(path);
+    match args {
+        Some(args) => format!("{}<{}", name, args),
+        None => name.to_string(),
+    }
+}
+
+/// The type an item of the expanded source was generated for.
+///
+/// The expanded source inlines every file module, so the expanded path ends with the
+/// original type's qualified name; the longest such match wins, then any type of that name.
+fn original_type<'s>(types: &'s [RustType], expanded_path: &str) -> Option<&'s RustType> {
+    let name = base_type_name(expanded_path)?;
+    let candidates = types.iter().filter(|t| t.name == name);
+    candidates
+        .max_by_key(|t| {
+            let suffix = format!("::{}", t.qualified_name);
+            (expanded_path == t.qualified_name || expanded_path.ends_with(&suffix), std::cmp::Reverse(t.line_start))
+        })
+}
+
+/// Trait and self type of a trait impl method, keyed as `(type, trait)`
+fn impl_key(function: &RustFunction) -> Option<(String, String)> {
+    match &function.function_context {
+        FunctionContext::TraitImpl { trait_name, type_name } => Some((base_type_name(type_name)?, trait_key(trait_name))),
+        _ => None,
+    }
+}
+
+/// Fold items that only exist in the macro-expanded source of a crate into its normal parse.
+///
+/// `expanded` is the result of parsing `cargo expand` output. Trait impls it contains that
+/// the normal parse lacks were generated by derives or attribute macros: they are attributed
+/// to the type they were generated for, at the line of the matching `derive` expansion record
+/// (or the type itself), and the record lists the generated methods as its targets. Calls made
+/// by generated methods come along as synthetic calls carrying the derive's macro context, and
+/// actors or message handlers that only a macro declares are added the same way.
+///
+/// Returns the number of generated functions added.
+pub fn merge_expanded_symbols(symbols: &mut ParsedSymbols, expanded: ParsedSymbols) -> usize {
+    let known_impls: HashSet<(String, String)> = symbols.functions.iter().filter_map(impl_key)
+        .chain(symbols.impls.iter().filter_map(|imp| Some((base_type_name(&imp.type_name)?, trait_key(imp.trait_name.as_ref()?)))))
+        .collect();
+
+    // Methods of each generated impl, in the order the impls appear in the expanded source
+    let mut generated: Vec<((String, String), Vec<RustFunction>)> = Vec::new();
+    for function in expanded.functions {
+        let Some(key) = impl_key(&function) else { continue };
+        if known_impls.contains(&key) {
+            continue;
+        }
+        match generated.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, methods)) => methods.push(function),
+            None => generated.push((key, vec![function])),
+        }
+    }
+
+    let mut generated_functions = Vec::new();
+    let mut generated_calls = Vec::new();
+    let mut generated_impls = Vec::new();
+    for (_, mut methods) in generated {
+        let FunctionContext::TraitImpl { trait_name, .. } = methods[0].function_context.clone() else { continue };
+        let expanded_path = methods[0].qualified_name.rsplit_once("::").map(|(owner, _)| owner.to_string()).unwrap_or_default();
+        let Some(ty) = original_type(&symbols.types, &expanded_path) else { continue };
+
+        // The derive that generated the impl sits between the previous type and this one
+        let trait_name = trait_name.trim_start_matches("::").to_string();
+        let previous_type = symbols.types.iter()
+            .filter(|t| t.file_path == ty.file_path && t.line_start < ty.line_start)
+            .map(|t| t.line_start)
+            .max();
+        let derive = symbols.macro_expansions.iter_mut()
+            .filter(|e| e.macro_type == "derive" && e.file_path == ty.file_path && e.line_range.start <= ty.line_start)
+            .filter(|e| previous_type.is_none_or(|previous| e.line_range.start > previous))
+            .filter(|e| trait_key(&e.macro_name) == trait_key(&trait_name))
+            .max_by_key(|e| e.line_range.start);
+        let line = derive.as_ref().map(|e| e.line_range.start).unwrap_or(ty.line_start);
+        let macro_info = derive.as_ref().map(|e| e.expansion_context.clone()).unwrap_or_else(|| MacroContext {
+            expansion_id: format!("{}:{}:{}", ty.file_path, ty.line_start, trait_key(&trait_name)),
+            macro_type: "proc_macro".to_string(),
+            expansion_site_line: ty.line_start,
+            name: trait_key(&trait_name),
+            kind: "expanded".to_string(),
+        });
+
+        for method in &mut methods {
+            let expanded_name = std::mem::replace(&mut method.qualified_name, format!("{}::{}", ty.qualified_name, method.name));
+            method.id = format!("{}:{}:{}", ty.file_path, line, method.name);
+            method.crate_name = ty.crate_name.clone();
+            method.module_path = ty.module_path.clone();
+            method.module = ty.module.clone();
+            method.file_path = ty.file_path.clone();
+            method.line_start = line;
+            method.line_end = line;
+            method.function_context = FunctionContext::TraitImpl { trait_name: trait_name.clone(), type_name: ty.name.clone() };
+            method.cfg = ty.cfg.clone();
+
+            for call in expanded.calls.iter().filter(|c| c.caller_id == expanded_name) {
+                let mut call = call.clone();
+                call.caller_id = method.qualified_name.clone();
+                call.caller_module = ty.module_path.clone();
+                call.from_crate = ty.crate_name.clone();
+                call.file_path = ty.file_path.clone();
+                call.line = line;
+                call.is_synthetic = true;
+                call.macro_context = Some(macro_info.clone());
+                call.synthetic_confidence = EXPANDED_CONFIDENCE;
+                call.cfg = ty.cfg.clone();
+                generated_calls.push(call);
+            }
+        }
+        if let Some(derive) = derive {
+            derive.target_functions.extend(methods.iter().map(|m| m.qualified_name.clone()));
+        }
+
+        generated_impls.push(RustImpl {
+            type_name: ty.name.clone(),
+            trait_name: Some(trait_name),
+            methods: methods.clone(),
+            file_path: ty.file_path.clone(),
+            line_start: line,
+            line_end: line,
+            is_generic: ty.is_generic,
+        });
+        generated_functions.extend(methods);
+    }
+
+    for mut actor in expanded.actors {
+        if symbols.actors.iter().any(|a| a.name == actor.name) {
+            continue;
+        }
+        let Some(ty) = original_type(&symbols.types, &actor.qualified_name) else { continue };
+        actor.id = ty.id.clone();
+        actor.qualified_name = ty.qualified_name.clone();
+        actor.module_path = ty.module_path.clone();
+        actor.file_path = ty.file_path.clone();
+        actor.line_start = ty.line_start;
+        actor.line_end = ty.line_end;
+        symbols.actors.push(actor);
+    }
+
+    for mut handler in expanded.message_handlers {
+        let known = symbols.message_handlers.iter()
+            .any(|h| h.actor_name == handler.actor_name && h.message_type == handler.message_type);
+        if known {
+            continue;
+        }
+        let Some(ty) = original_type(&symbols.types, &handler.actor_qualified) else { continue };
+        handler.id = format!("{}:{}:handler", ty.file_path, ty.line_start);
+        handler.actor_qualified = ty.qualified_name.clone();
+        handler.file_path = ty.file_path.clone();
+        handler.line = ty.line_start;
+        symbols.message_handlers.push(handler);
+    }
+
+    let added = generated_functions.len();
+    symbols.functions.extend(generated_functions);
+    symbols.calls.extend(generated_calls);
+    symbols.impls.extend(generated_impls);
+    added
+}
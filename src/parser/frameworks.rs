@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::fmt;
+use tree_sitter::Node;
+
+use crate::parser::ast_utils::{safe_node_text, string_literal};
+use crate::parser::symbols::{Field, SendMethod};
+use crate::parser::type_inference::{base_type_name, first_type_argument};
+use crate::workspace::CrateMetadata;
+
+/// How one actor framework spells actors, message handlers and message sends.
+///
+/// The walker asks the adapters selected for a crate instead of assuming kameo, so the same
+/// `RustActor` / `MessageHandler` / `MessageSend` records come out whichever framework is used.
+pub trait ActorFrameworkAdapter: Send + Sync {
+    /// Framework name, as used by `ActorPattern::framework`
+    fn name(&self) -> &'static str;
+
+    /// Cargo dependencies that bring the framework into a crate
+    fn dependencies(&self) -> &'static [&'static str];
+
+    /// `impl <trait_name> for T` makes `T` an actor; paths such as `::kameo::actor::Actor` count too
+    fn is_actor_trait(&self, trait_name: &str) -> bool {
+        trait_name.rsplit("::").next() == Some("Actor")
+    }
+
+    /// Message handled by `impl <trait_name> for T`, e.g. `M` for kameo's `Message<M>`
+    fn handled_message(&self, _trait_name: &str) -> Option<String> {
+        None
+    }
+
+    /// Associated type of a handler impl that holds the reply
+    fn reply_type_name(&self) -> &'static str {
+        "Reply"
+    }
+
+    /// Message type declared on the actor impl itself, for frameworks with one message enum
+    fn actor_message(&self, _associated_types: &HashMap<String, String>) -> Option<String> {
+        None
+    }
+
+    /// Send method called on an actor handle: `tell`, `do_send`, ...
+    fn send_method(&self, _method: &str) -> Option<SendMethod> {
+        None
+    }
+
+    /// Send macro taking the actor and the message: `cast!(actor, msg)`
+    fn send_macro(&self, _name: &str) -> Option<SendMethod> {
+        None
+    }
+
+    /// Message type of a struct that runs as an actor by owning a channel receiver
+    fn channel_message(&self, _fields: &[Field]) -> Option<String> {
+        None
+    }
+}
+
+/// Type argument of a single-parameter generic trait such as `Message<M>` or `Handler<M>`
+fn generic_argument(trait_name: &str, expected: &str) -> Option<String> {
+    let (name, args) = trait_name.split_once('<')?;
+    if name.rsplit("::").next()?.trim() != expected {
+        return None;
+    }
+    base_type_name(args.strip_suffix('>')?)
+}
+
+/// kameo: `impl Message<M> for A { type Reply = R; }`, `actor_ref.tell(m)` / `.ask(m)`
+pub struct KameoAdapter;
+
+impl ActorFrameworkAdapter for KameoAdapter {
+    fn name(&self) -> &'static str {
+        "kameo"
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["kameo"]
+    }
+
+    fn handled_message(&self, trait_name: &str) -> Option<String> {
+        generic_argument(trait_name, "Message")
+    }
+
+    fn send_method(&self, method: &str) -> Option<SendMethod> {
+        match method {
+            "tell" => Some(SendMethod::Tell),
+            "ask" => Some(SendMethod::Ask),
+            _ => None,
+        }
+    }
+}
+
+/// Kameo handle types whose first type argument is the actor they point at
+const ACTOR_REF_TYPES: &[&str] = &["ActorRef", "WeakActorRef", "RemoteActorRef"];
+
+/// Actor a written `ActorRef<A>` / `WeakActorRef<A>` type points at, before `Self` substitution
+pub fn actor_ref_target(type_text: &str) -> Option<String> {
+    let text = type_text.trim().trim_start_matches('&').trim_start_matches("mut ").trim();
+    let open = text.find('<')?;
+    let wrapper = text[..open].rsplit("::").next()?.trim();
+    if !ACTOR_REF_TYPES.contains(&wrapper) {
+        return None;
+    }
+    base_type_name(first_type_argument(text[open + 1..].strip_suffix('>')?)?)
+}
+
+/// Kameo handle types that can be looked up from the swarm by registered name
+const LOOKUP_TYPES: &[&str] = &["RemoteActorRef", "ActorRef"];
+
+/// Registered name and actor type of a `RemoteActorRef::<A>::lookup("name")` call
+pub fn remote_lookup(call: Node, source: &[u8]) -> Option<(String, Option<String>)> {
+    let function = call.child_by_field_name("function")?;
+    if function.kind() != "scoped_identifier" || safe_node_text(function.child_by_field_name("name")?, source)? != "lookup" {
+        return None;
+    }
+    // `RemoteActorRef::<A>` is written with a turbofish; read it as `RemoteActorRef<A>`
+    let path = safe_node_text(function.child_by_field_name("path")?, source)?.replace("::<", "<");
+    let (wrapper, args) = match path.split_once('<') {
+        Some((wrapper, args)) => (wrapper, args.strip_suffix('>')),
+        None => (path.as_str(), None),
+    };
+    if !LOOKUP_TYPES.contains(&wrapper.rsplit("::").next()?.trim()) {
+        return None;
+    }
+
+    let name = string_literal(call.child_by_field_name("arguments")?.named_child(0)?, source)?;
+    Some((name, args.and_then(first_type_argument).and_then(base_type_name)))
+}
+
+/// Kameo spawn functions: `spawn`, `spawn_link`, `spawn_with_mailbox`, ...
+pub fn is_spawn_name(name: &str) -> bool {
+    name == "spawn" || name.starts_with("spawn_")
+}
+
+/// Actor started by a kameo spawn call: `Worker::spawn_link(..)` names it in the path, while
+/// `kameo::spawn(Worker { .. })` and `Actor::spawn(Worker { .. })` name it in the last argument
+pub fn spawned_actor(call: Node, function: Node, source: &[u8]) -> Option<String> {
+    let (path, name) = match function.kind() {
+        "scoped_identifier" => (
+            function.child_by_field_name("path").and_then(|p| safe_node_text(p, source)),
+            safe_node_text(function.child_by_field_name("name")?, source)?,
+        ),
+        "identifier" => (None, safe_node_text(function, source)?),
+        _ => return None,
+    };
+    if !is_spawn_name(name) {
+        return None;
+    }
+
+    let type_path = path.and_then(base_type_name).filter(|p| {
+        p != "Actor" && p.chars().next().is_some_and(|c| c.is_uppercase())
+    });
+    if let Some(actor) = type_path {
+        return Some(actor);
+    }
+
+    // Module function or trait method: the actor value is the last argument
+    let args = call.child_by_field_name("arguments")?;
+    let last = args.named_child(args.named_child_count().checked_sub(1)?)?;
+    match last.kind() {
+        "struct_expression" => base_type_name(safe_node_text(last.child_by_field_name("name")?, source)?),
+        "call_expression" => {
+            let constructor = last.child_by_field_name("function")?;
+            if constructor.kind() != "scoped_identifier" {
+                return None;
+            }
+            base_type_name(safe_node_text(constructor.child_by_field_name("path")?, source)?)
+        }
+        "identifier" => safe_node_text(last, source)
+            .filter(|text| text.chars().next().is_some_and(|c| c.is_uppercase()))
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// actix: `impl Handler<M> for A { type Result = R; }`, `addr.do_send(m)` / `addr.send(m)`
+pub struct ActixAdapter;
+
+impl ActorFrameworkAdapter for ActixAdapter {
+    fn name(&self) -> &'static str {
+        "actix"
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["actix"]
+    }
+
+    fn handled_message(&self, trait_name: &str) -> Option<String> {
+        generic_argument(trait_name, "Handler")
+    }
+
+    fn reply_type_name(&self) -> &'static str {
+        "Result"
+    }
+
+    fn send_method(&self, method: &str) -> Option<SendMethod> {
+        match method {
+            "do_send" | "try_send" => Some(SendMethod::Tell),
+            "send" => Some(SendMethod::Ask),
+            _ => None,
+        }
+    }
+}
+
+/// ractor: `impl Actor for A { type Msg = M; async fn handle(..) }`, `cast!(a, m)` / `call!(a, m)`
+pub struct RactorAdapter;
+
+impl ActorFrameworkAdapter for RactorAdapter {
+    fn name(&self) -> &'static str {
+        "ractor"
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["ractor"]
+    }
+
+    fn actor_message(&self, associated_types: &HashMap<String, String>) -> Option<String> {
+        associated_types.get("Msg").cloned()
+    }
+
+    fn send_method(&self, method: &str) -> Option<SendMethod> {
+        match method {
+            "cast" | "send_message" => Some(SendMethod::Tell),
+            _ => None,
+        }
+    }
+
+    fn send_macro(&self, name: &str) -> Option<SendMethod> {
+        match name {
+            "cast" => Some(SendMethod::Tell),
+            "call" | "call_t" => Some(SendMethod::Ask),
+            _ => None,
+        }
+    }
+}
+
+/// Plain tokio tasks used as actors: a struct owning an `mpsc::Receiver<M>`, fed by `tx.send(m)`
+pub struct TokioMpscAdapter;
+
+impl ActorFrameworkAdapter for TokioMpscAdapter {
+    fn name(&self) -> &'static str {
+        "tokio-mpsc"
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["tokio"]
+    }
+
+    fn is_actor_trait(&self, _trait_name: &str) -> bool {
+        false
+    }
+
+    fn send_method(&self, method: &str) -> Option<SendMethod> {
+        match method {
+            "send" | "try_send" => Some(SendMethod::Tell),
+            _ => None,
+        }
+    }
+
+    fn channel_message(&self, fields: &[Field]) -> Option<String> {
+        fields.iter().find_map(|field| {
+            let field_type = field.field_type.replace(' ', "");
+            let (wrapper, args) = field_type.split_once('<')?;
+            let wrapper = wrapper.rsplit("::").next()?;
+            if wrapper != "Receiver" && wrapper != "UnboundedReceiver" {
+                return None;
+            }
+            // `std::sync::mpsc` and `crossbeam` receivers are fine too, `watch` / `broadcast` are not
+            if field_type.contains("watch::") || field_type.contains("broadcast::") || field_type.contains("oneshot::") {
+                return None;
+            }
+            base_type_name(args.strip_suffix('>')?)
+        })
+    }
+}
+
+/// Every adapter, in the order they are asked; earlier adapters win a shared send method name
+const ADAPTERS: &[&'static dyn ActorFrameworkAdapter] = &[&KameoAdapter, &ActixAdapter, &RactorAdapter, &TokioMpscAdapter];
+
+/// The actor framework adapters active for one crate
+#[derive(Clone)]
+pub struct ActorFrameworks {
+    adapters: Vec<&'static dyn ActorFrameworkAdapter>,
+}
+
+impl fmt::Debug for ActorFrameworks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl Default for ActorFrameworks {
+    /// Without dependency information, recognise the trait-based frameworks. Channel actors
+    /// are left out: any struct holding a receiver would otherwise become an actor.
+    fn default() -> Self {
+        Self {
+            adapters: ADAPTERS.iter().copied().filter(|a| a.name() != TokioMpscAdapter.name()).collect(),
+        }
+    }
+}
+
+impl ActorFrameworks {
+    /// Adapters whose framework is among `dependencies`; falls back to the default set when
+    /// the crate uses none of them, since it may reach a framework through a re-exporting crate
+    pub fn for_dependencies(dependencies: &[String]) -> Self {
+        let uses = |dep: &str| dependencies.iter().any(|d| d.replace('_', "-") == dep);
+        let adapters: Vec<_> = ADAPTERS.iter().copied()
+            .filter(|adapter| adapter.dependencies().iter().any(|dep| uses(dep)))
+            .collect();
+
+        // tokio alone says nothing about actors; its `send` still wins over actix's
+        if adapters.iter().all(|a| a.name() == TokioMpscAdapter.name()) {
+            let mut frameworks = Self { adapters };
+            frameworks.adapters.extend(Self::default().adapters);
+            return frameworks;
+        }
+        Self { adapters }
+    }
+
+    pub fn for_crate(crate_meta: &CrateMetadata) -> Self {
+        Self::for_dependencies(&crate_meta.dependencies)
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.adapters.iter().map(|a| a.name()).collect()
+    }
+
+    pub fn is_actor_trait(&self, trait_name: &str) -> bool {
+        self.adapters.iter().any(|a| a.is_actor_trait(trait_name))
+    }
+
+    /// Message handled by a trait impl, with the associated type naming its reply
+    pub fn handled_message(&self, trait_name: &str) -> Option<(String, &'static str)> {
+        self.adapters.iter().find_map(|a| a.handled_message(trait_name).map(|m| (m, a.reply_type_name())))
+    }
+
+    pub fn actor_message(&self, associated_types: &HashMap<String, String>) -> Option<String> {
+        self.adapters.iter().find_map(|a| a.actor_message(associated_types))
+    }
+
+    pub fn send_method(&self, method: &str) -> Option<SendMethod> {
+        self.adapters.iter().find_map(|a| a.send_method(method))
+    }
+
+    pub fn send_macro(&self, name: &str) -> Option<SendMethod> {
+        self.adapters.iter().find_map(|a| a.send_macro(name))
+    }
+
+    pub fn channel_message(&self, fields: &[Field]) -> Option<String> {
+        self.adapters.iter().find_map(|a| a.channel_message(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names_for(dependencies: &[&str]) -> Vec<&'static str> {
+        let dependencies: Vec<String> = dependencies.iter().map(|d| d.to_string()).collect();
+        ActorFrameworks::for_dependencies(&dependencies).names()
+    }
+
+    #[test]
+    fn test_for_dependencies_selects_used_frameworks() {
+        assert_eq!(names_for(&["kameo", "serde"]), vec!["kameo"]);
+        assert_eq!(names_for(&["tokio", "actix"]), vec!["actix", "tokio-mpsc"]);
+        assert_eq!(names_for(&["ractor"]), vec!["ractor"]);
+    }
+
+    #[test]
+    fn test_for_dependencies_falls_back_to_default_set() {
+        let default = ActorFrameworks::default().names();
+        assert_eq!(default, vec!["kameo", "actix", "ractor"]);
+        assert_eq!(names_for(&["serde"]), default);
+        assert_eq!(names_for(&[]), default);
+
+        // tokio alone keeps channel actors and adds the trait-based frameworks after them
+        assert_eq!(names_for(&["tokio"]), vec!["tokio-mpsc", "kameo", "actix", "ractor"]);
+    }
+}
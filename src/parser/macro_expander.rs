@@ -0,0 +1,1036 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::parser::ast_walker::UnifiedWalker;
+use crate::parser::symbols::{FunctionContext, MacroContext, MacroDefinition, MacroInvocation, ParsedSymbols};
+
+/// Expansions followed from one invocation through the macros it expands to
+const MAX_DEPTH: usize = 8;
+
+/// Confidence of calls found in expanded code: fragments are matched greedily rather than
+/// with rustc's parser, so an expansion can occasionally differ from the real one
+const EXPANSION_CONFIDENCE: f32 = 0.9;
+
+/// Multi-character punctuation, longest first
+const PUNCTS: &[&str] = &[
+    "...", "..=", "<<=", ">>=", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=",
+    "*=", "/=", "%=", "^=", "&=", "|=", "<<", ">>", "..",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Delimiter {
+    Paren,
+    Bracket,
+    Brace,
+}
+
+impl Delimiter {
+    fn opened_by(c: char) -> Option<Self> {
+        match c {
+            '(' => Some(Delimiter::Paren),
+            '[' => Some(Delimiter::Bracket),
+            '{' => Some(Delimiter::Brace),
+            _ => None,
+        }
+    }
+
+    fn closed_by(c: char) -> Option<Self> {
+        match c {
+            ')' => Some(Delimiter::Paren),
+            ']' => Some(Delimiter::Bracket),
+            '}' => Some(Delimiter::Brace),
+            _ => None,
+        }
+    }
+
+    fn pair(self) -> (&'static str, &'static str) {
+        match self {
+            Delimiter::Paren => ("(", ")"),
+            Delimiter::Bracket => ("[", "]"),
+            Delimiter::Brace => ("{", "}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(String),
+    Lifetime(String),
+    Punct(String),
+    Group(Delimiter, Vec<Token>),
+}
+
+impl Token {
+    fn is_punct(&self, text: &str) -> bool {
+        matches!(self, Token::Punct(p) if p == text)
+    }
+
+    fn is_ident(&self, text: &str) -> bool {
+        matches!(self, Token::Ident(i) if i == text)
+    }
+}
+
+/// Split source text into token trees; `None` when the delimiters do not balance
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let at = |i: usize| chars.get(i).copied().unwrap_or('\0');
+    let is_ident_start = |c: char| c.is_alphabetic() || c == '_';
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut open: Vec<(Delimiter, Vec<Token>)> = Vec::new();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '/' && at(i + 1) == '/' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && at(i + 1) == '*' {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && at(i + 1) == '*' {
+                    depth += 1;
+                    i += 2;
+                } else if chars[i] == '*' && at(i + 1) == '/' {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            continue;
+        }
+        if let Some(delimiter) = Delimiter::opened_by(c) {
+            open.push((delimiter, std::mem::take(&mut tokens)));
+            i += 1;
+            continue;
+        }
+        if let Some(delimiter) = Delimiter::closed_by(c) {
+            let (opened, outer) = open.pop()?;
+            if opened != delimiter {
+                return None;
+            }
+            let inner = std::mem::replace(&mut tokens, outer);
+            tokens.push(Token::Group(delimiter, inner));
+            i += 1;
+            continue;
+        }
+
+        // Raw strings: r"..", r#".."#, br".."
+        let raw_prefix = match (c, at(i + 1), at(i + 2)) {
+            ('r', '"' | '#', _) if at(i + 1) == '"' || matches!(at(i + 2), '"' | '#') => Some(1),
+            ('b', 'r', '"' | '#') => Some(2),
+            _ => None,
+        };
+        if let Some(prefix) = raw_prefix {
+            i += prefix;
+            let hashes = chars[i..].iter().take_while(|&&h| h == '#').count();
+            i += hashes + 1;
+            while i < chars.len() && !(chars[i] == '"' && chars[i + 1..].iter().take(hashes).filter(|&&h| h == '#').count() == hashes) {
+                i += 1;
+            }
+            i = (i + 1 + hashes).min(chars.len());
+            tokens.push(Token::Literal(chars[start..i].iter().collect()));
+            continue;
+        }
+
+        // Strings, byte strings and (byte) chars
+        let quoted = match (c, at(i + 1)) {
+            ('"', _) => Some(0),
+            ('b' | 'c', '"') | ('b', '\'') => Some(1),
+            ('\'', '\\') => Some(0),
+            ('\'', _) if at(i + 2) == '\'' => Some(0),
+            _ => None,
+        };
+        if let Some(prefix) = quoted {
+            i += prefix;
+            let quote = chars[i];
+            i += 1;
+            while i < chars.len() && chars[i] != quote {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            tokens.push(Token::Literal(chars[start..i].iter().collect()));
+            continue;
+        }
+
+        if c == '\'' && is_ident_start(at(i + 1)) {
+            i += 1;
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push(Token::Lifetime(chars[start..i].iter().collect()));
+            continue;
+        }
+
+        if is_ident_start(c) {
+            if c == 'r' && at(i + 1) == '#' && is_ident_start(at(i + 2)) {
+                i += 2;
+            }
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            let hex = c == '0' && matches!(at(i + 1), 'x' | 'o' | 'b');
+            while i < chars.len() {
+                let d = chars[i];
+                let exponent_sign = matches!(d, '+' | '-') && matches!(chars[i - 1], 'e' | 'E') && !hex;
+                let fraction = d == '.' && at(i + 1).is_ascii_digit() && !chars[start..i].contains(&'.');
+                if is_ident_char(d) || exponent_sign || fraction {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Literal(chars[start..i].iter().collect()));
+            continue;
+        }
+
+        let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+        let punct = PUNCTS.iter().find(|p| rest.starts_with(*p)).map(|p| p.to_string()).unwrap_or_else(|| c.to_string());
+        i += punct.chars().count();
+        tokens.push(Token::Punct(punct));
+    }
+
+    open.is_empty().then_some(tokens)
+}
+
+/// Render tokens back to source on a single line, so that every node parsed from the
+/// expansion sits on the line of the invocation
+fn render(tokens: &[Token], out: &mut String) {
+    for token in tokens {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        match token {
+            Token::Ident(text) | Token::Lifetime(text) | Token::Punct(text) => out.push_str(text),
+            Token::Literal(text) => out.push_str(&text.replace('\n', "\\n").replace('\r', "\\r")),
+            Token::Group(delimiter, inner) => {
+                let (open, close) = delimiter.pair();
+                out.push_str(open);
+                render(inner, out);
+                out.push(' ');
+                out.push_str(close);
+            }
+        }
+    }
+}
+
+/// What a `$name:fragment` matcher accepts
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fragment {
+    Ident,
+    Lifetime,
+    Literal,
+    Tt,
+    Block,
+    Vis,
+    Item,
+    Expr,
+    Stmt,
+    Ty,
+    Path,
+    Pat,
+    PatParam,
+    Meta,
+}
+
+impl Fragment {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ident" => Some(Fragment::Ident),
+            "lifetime" => Some(Fragment::Lifetime),
+            "literal" => Some(Fragment::Literal),
+            "tt" => Some(Fragment::Tt),
+            "block" => Some(Fragment::Block),
+            "vis" => Some(Fragment::Vis),
+            "item" => Some(Fragment::Item),
+            "expr" | "expr_2021" => Some(Fragment::Expr),
+            "stmt" => Some(Fragment::Stmt),
+            "ty" => Some(Fragment::Ty),
+            "path" => Some(Fragment::Path),
+            "pat" => Some(Fragment::Pat),
+            "pat_param" => Some(Fragment::PatParam),
+            "meta" => Some(Fragment::Meta),
+            _ => None,
+        }
+    }
+
+    /// Tokens that end a fragment of this kind at the top level, from the follow-set rules
+    fn stops_at(self, token: &Token) -> bool {
+        let stops: &[&str] = match self {
+            Fragment::Expr | Fragment::Stmt => &[",", ";", "=>"],
+            Fragment::Ty | Fragment::Path => &[",", ";", "=>", "=", "|", ":", ">", "as", "where"],
+            Fragment::Pat => &["=>", ",", "=", "if", "in"],
+            Fragment::PatParam => &["=>", ",", "=", "|", "if", "in"],
+            Fragment::Meta => &[","],
+            _ => &[],
+        };
+        match token {
+            Token::Punct(text) | Token::Ident(text) => stops.contains(&text.as_str()),
+            Token::Group(Delimiter::Brace, _) => matches!(self, Fragment::Ty | Fragment::Path),
+            _ => false,
+        }
+    }
+
+    /// Number of leading `input` tokens this fragment matches, stopping before `follow`
+    fn match_len(self, input: &[Token], follow: Follow) -> Option<usize> {
+        let first = input.first();
+        match self {
+            Fragment::Ident => matches!(first, Some(Token::Ident(name)) if name != "_").then_some(1),
+            Fragment::Lifetime => matches!(first, Some(Token::Lifetime(_))).then_some(1),
+            Fragment::Literal => match (first?, input.get(1)) {
+                (Token::Literal(_), _) => Some(1),
+                (Token::Ident(value), _) if value == "true" || value == "false" => Some(1),
+                (Token::Punct(minus), Some(Token::Literal(_))) if minus == "-" => Some(2),
+                _ => None,
+            },
+            Fragment::Tt => first.map(|_| 1),
+            Fragment::Block => matches!(first, Some(Token::Group(Delimiter::Brace, _))).then_some(1),
+            Fragment::Vis => match (first, input.get(1)) {
+                (Some(token), Some(Token::Group(Delimiter::Paren, _))) if token.is_ident("pub") => Some(2),
+                (Some(token), _) if token.is_ident("pub") => Some(1),
+                _ => Some(0),
+            },
+            Fragment::Item => {
+                for (idx, token) in input.iter().enumerate() {
+                    match token {
+                        Token::Punct(semi) if semi == ";" => return Some(idx + 1),
+                        Token::Group(Delimiter::Brace, _) => {
+                            let trailing = input.get(idx + 1).is_some_and(|t| t.is_punct(";"));
+                            return Some(idx + 1 + trailing as usize);
+                        }
+                        _ => {}
+                    }
+                }
+                None
+            }
+            _ => self.scan_len(input, follow),
+        }
+    }
+
+    /// Greedy match for fragments with their own grammar: everything up to a stop token
+    /// outside `<..>` generics and closure parameter lists
+    fn scan_len(self, input: &[Token], follow: Follow) -> Option<usize> {
+        let tracks_generics = matches!(self, Fragment::Ty | Fragment::Path);
+        let mut angle_depth = 0usize;
+        let mut in_closure_params = false;
+        for (idx, token) in input.iter().enumerate() {
+            let at_top = angle_depth == 0 && !in_closure_params;
+            if idx > 0 && at_top && (self.stops_at(token) || follow.matches(token)) {
+                return Some(idx);
+            }
+            let previous = idx.checked_sub(1).map(|p| &input[p]);
+            match token {
+                Token::Punct(p) if p == "<" && (tracks_generics || previous.is_some_and(|t| t.is_punct("::"))) => angle_depth += 1,
+                Token::Punct(p) if p == "<<" && tracks_generics => angle_depth += 2,
+                Token::Punct(p) if p == ">" && angle_depth > 0 => angle_depth -= 1,
+                Token::Punct(p) if p == ">>" && angle_depth > 0 => angle_depth = angle_depth.saturating_sub(2),
+                Token::Punct(p) if p == "|" && matches!(self, Fragment::Expr | Fragment::Stmt) => {
+                    let opens = previous.is_none_or(|t| matches!(t, Token::Punct(_)) || t.is_ident("move"));
+                    if in_closure_params || opens {
+                        in_closure_params = !in_closure_params;
+                    }
+                }
+                _ => {}
+            }
+        }
+        (!input.is_empty()).then_some(input.len())
+    }
+}
+
+/// What comes right after a fragment in the matcher
+#[derive(Debug, Clone, Copy)]
+enum Follow<'m> {
+    Token(&'m Token),
+    Group(Delimiter),
+    Nothing,
+}
+
+impl Follow<'_> {
+    fn matches(&self, token: &Token) -> bool {
+        match (self, token) {
+            (Follow::Token(expected), token) => *expected == token,
+            (Follow::Group(expected), Token::Group(delimiter, _)) => expected == delimiter,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Repetition {
+    ZeroOrMore,
+    OneOrMore,
+    ZeroOrOne,
+}
+
+impl Repetition {
+    fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::Punct(op) if op == "*" => Some(Repetition::ZeroOrMore),
+            Token::Punct(op) if op == "+" => Some(Repetition::OneOrMore),
+            Token::Punct(op) if op == "?" => Some(Repetition::ZeroOrOne),
+            _ => None,
+        }
+    }
+}
+
+/// `$( .. ) sep op`: the separator and operator following a repetition group at `tokens[0]`
+fn repetition_suffix(tokens: &[Token]) -> Option<(Option<Token>, Repetition, usize)> {
+    if let Some(op) = tokens.first().and_then(Repetition::from_token) {
+        return Some((None, op, 1));
+    }
+    let op = tokens.get(1).and_then(Repetition::from_token)?;
+    Some((tokens.first().cloned(), op, 2))
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Token(Token),
+    Group(Delimiter, Vec<Matcher>),
+    Bind(String, Fragment),
+    Repeat { inner: Vec<Matcher>, separator: Option<Token>, op: Repetition },
+}
+
+impl Matcher {
+    fn parse(tokens: &[Token]) -> Vec<Matcher> {
+        let mut matchers = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2), tokens.get(i + 3)) {
+                (Token::Punct(dollar), Some(Token::Ident(name)), Some(colon), Some(Token::Ident(kind)))
+                    if dollar == "$" && colon.is_punct(":") && Fragment::from_name(kind).is_some() =>
+                {
+                    matchers.push(Matcher::Bind(name.clone(), Fragment::from_name(kind).unwrap_or(Fragment::Tt)));
+                    i += 4;
+                }
+                (Token::Punct(dollar), Some(Token::Group(Delimiter::Paren, inner)), _, _) if dollar == "$" => {
+                    let Some((separator, op, consumed)) = repetition_suffix(&tokens[i + 2..]) else {
+                        matchers.push(Matcher::Token(tokens[i].clone()));
+                        i += 1;
+                        continue;
+                    };
+                    matchers.push(Matcher::Repeat { inner: Matcher::parse(inner), separator, op });
+                    i += 2 + consumed;
+                }
+                (Token::Group(delimiter, inner), _, _, _) => {
+                    matchers.push(Matcher::Group(*delimiter, Matcher::parse(inner)));
+                    i += 1;
+                }
+                (token, _, _, _) => {
+                    matchers.push(Matcher::Token(token.clone()));
+                    i += 1;
+                }
+            }
+        }
+        matchers
+    }
+
+    fn as_follow(&self) -> Follow<'_> {
+        match self {
+            Matcher::Token(token) => Follow::Token(token),
+            Matcher::Group(delimiter, _) => Follow::Group(*delimiter),
+            _ => Follow::Nothing,
+        }
+    }
+
+    fn bound_names(matchers: &[Matcher], names: &mut Vec<String>) {
+        for matcher in matchers {
+            match matcher {
+                Matcher::Bind(name, _) => names.push(name.clone()),
+                Matcher::Group(_, inner) | Matcher::Repeat { inner, .. } => Matcher::bound_names(inner, names),
+                Matcher::Token(_) => {}
+            }
+        }
+    }
+}
+
+/// Fragments captured by a matcher, nested once per repetition they were matched in
+#[derive(Debug, Clone)]
+enum Binding {
+    Leaf(Vec<Token>),
+    Seq(Vec<Binding>),
+}
+
+impl Binding {
+    /// Tokens bound at repetition `indices`; a fragment bound outside a repetition can be
+    /// used at any depth, one still repeating at `indices` cannot
+    fn at(&self, indices: &[usize]) -> Option<&[Token]> {
+        let mut binding = self;
+        for &idx in indices {
+            match binding {
+                Binding::Seq(items) => binding = items.get(idx)?,
+                Binding::Leaf(_) => break,
+            }
+        }
+        match binding {
+            Binding::Leaf(tokens) => Some(tokens),
+            Binding::Seq(_) => None,
+        }
+    }
+
+    /// Repetition count of the binding at `indices`, if it repeats there
+    fn repeats_at(&self, indices: &[usize]) -> Option<usize> {
+        let mut binding = self;
+        for &idx in indices {
+            match binding {
+                Binding::Seq(items) => binding = items.get(idx)?,
+                Binding::Leaf(_) => return None,
+            }
+        }
+        match binding {
+            Binding::Seq(items) => Some(items.len()),
+            Binding::Leaf(_) => None,
+        }
+    }
+}
+
+type Bindings = HashMap<String, Binding>;
+
+/// Match `matchers` against all of `input`
+fn match_all(matchers: &[Matcher], input: &[Token], bindings: &mut Bindings) -> bool {
+    let mut pos = 0;
+    match_at(matchers, Follow::Nothing, input, &mut pos, bindings) && pos == input.len()
+}
+
+/// Match `matchers` against `input` from `pos`, leaving `pos` after the matched tokens.
+/// Fragments and repetitions are greedy and never backtrack, as in rustc.
+fn match_at(matchers: &[Matcher], follow: Follow, input: &[Token], pos: &mut usize, bindings: &mut Bindings) -> bool {
+    for (idx, matcher) in matchers.iter().enumerate() {
+        let next = matchers.get(idx + 1).map(Matcher::as_follow).unwrap_or(follow);
+        match matcher {
+            Matcher::Token(expected) => {
+                if input.get(*pos) != Some(expected) {
+                    return false;
+                }
+                *pos += 1;
+            }
+            Matcher::Group(delimiter, inner) => match input.get(*pos) {
+                Some(Token::Group(found, tokens)) if found == delimiter && match_all(inner, tokens, bindings) => *pos += 1,
+                _ => return false,
+            },
+            Matcher::Bind(name, fragment) => {
+                let Some(len) = fragment.match_len(&input[*pos..], next) else {
+                    return false;
+                };
+                bindings.insert(name.clone(), Binding::Leaf(input[*pos..*pos + len].to_vec()));
+                *pos += len;
+            }
+            Matcher::Repeat { inner, separator, op } => {
+                let inner_follow = separator.as_ref().map(Follow::Token).unwrap_or(next);
+                let mut iterations: Vec<Bindings> = Vec::new();
+                while !(*op == Repetition::ZeroOrOne && iterations.len() == 1) {
+                    let mut cursor = *pos;
+                    if let (false, Some(separator)) = (iterations.is_empty(), separator) {
+                        if input.get(cursor) != Some(separator) {
+                            break;
+                        }
+                        cursor += 1;
+                    }
+                    let mut iteration = Bindings::new();
+                    if !match_at(inner, inner_follow, input, &mut cursor, &mut iteration) || cursor == *pos {
+                        break;
+                    }
+                    *pos = cursor;
+                    iterations.push(iteration);
+                }
+                if *op == Repetition::OneOrMore && iterations.is_empty() {
+                    return false;
+                }
+
+                let mut names = Vec::new();
+                Matcher::bound_names(inner, &mut names);
+                for name in names {
+                    let repeated = iterations.iter_mut()
+                        .map(|iteration| iteration.remove(&name).unwrap_or(Binding::Seq(Vec::new())))
+                        .collect();
+                    bindings.insert(name, Binding::Seq(repeated));
+                }
+            }
+        }
+    }
+    true
+}
+
+#[derive(Debug, Clone)]
+enum Transcriber {
+    Token(Token),
+    Group(Delimiter, Vec<Transcriber>),
+    Var(String),
+    Repeat { inner: Vec<Transcriber>, separator: Option<Token> },
+}
+
+impl Transcriber {
+    fn parse(tokens: &[Token]) -> Vec<Transcriber> {
+        let mut items = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            match (&tokens[i], tokens.get(i + 1)) {
+                (Token::Punct(dollar), Some(Token::Ident(name))) if dollar == "$" => {
+                    items.push(if name == "crate" { Transcriber::Token(Token::Ident("crate".to_string())) } else { Transcriber::Var(name.clone()) });
+                    i += 2;
+                }
+                (Token::Punct(dollar), Some(Token::Group(Delimiter::Paren, inner))) if dollar == "$" => {
+                    let Some((separator, _, consumed)) = repetition_suffix(&tokens[i + 2..]) else {
+                        items.push(Transcriber::Token(tokens[i].clone()));
+                        i += 1;
+                        continue;
+                    };
+                    items.push(Transcriber::Repeat { inner: Transcriber::parse(inner), separator });
+                    i += 2 + consumed;
+                }
+                (Token::Group(delimiter, inner), _) => {
+                    items.push(Transcriber::Group(*delimiter, Transcriber::parse(inner)));
+                    i += 1;
+                }
+                (token, _) => {
+                    items.push(Transcriber::Token(token.clone()));
+                    i += 1;
+                }
+            }
+        }
+        items
+    }
+
+    fn vars(items: &[Transcriber], names: &mut Vec<String>) {
+        for item in items {
+            match item {
+                Transcriber::Var(name) => names.push(name.clone()),
+                Transcriber::Group(_, inner) | Transcriber::Repeat { inner, .. } => Transcriber::vars(inner, names),
+                Transcriber::Token(_) => {}
+            }
+        }
+    }
+}
+
+/// Substitute `bindings` into `items`; `None` when a repetition cannot be expanded
+fn transcribe(items: &[Transcriber], bindings: &Bindings, indices: &mut Vec<usize>, out: &mut Vec<Token>) -> Option<()> {
+    for item in items {
+        match item {
+            Transcriber::Token(token) => out.push(token.clone()),
+            Transcriber::Group(delimiter, inner) => {
+                let mut tokens = Vec::new();
+                transcribe(inner, bindings, indices, &mut tokens)?;
+                out.push(Token::Group(*delimiter, tokens));
+            }
+            Transcriber::Var(name) => match bindings.get(name) {
+                Some(binding) => out.extend(binding.at(indices)?.iter().cloned()),
+                None => {
+                    out.push(Token::Punct("$".to_string()));
+                    out.push(Token::Ident(name.clone()));
+                }
+            },
+            Transcriber::Repeat { inner, separator } => {
+                let mut names = Vec::new();
+                Transcriber::vars(inner, &mut names);
+                let count = names.iter().filter_map(|name| bindings.get(name)?.repeats_at(indices)).min()?;
+                for idx in 0..count {
+                    if let (true, Some(separator)) = (idx > 0, separator) {
+                        out.push(separator.clone());
+                    }
+                    indices.push(idx);
+                    transcribe(inner, bindings, indices, out)?;
+                    indices.pop();
+                }
+            }
+        }
+    }
+    Some(())
+}
+
+/// Splice `paste! { .. }` invocations into the surrounding tokens, joining their `[< .. >]`
+/// segments into identifiers
+fn expand_paste(tokens: Vec<Token>) -> Vec<Token> {
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut iter = tokens.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        match token {
+            Token::Ident(name) if name == "paste" && iter.peek().is_some_and(|t| t.is_punct("!")) => {
+                iter.next();
+                let Some(Token::Group(_, inner)) = iter.next_if(|t| matches!(t, Token::Group(..))) else {
+                    out.push(Token::Ident(name));
+                    out.push(Token::Punct("!".to_string()));
+                    continue;
+                };
+                // Drop a `paste::` / `::paste::` path prefix
+                while out.last().is_some_and(|t| t.is_punct("::")) {
+                    out.pop();
+                    if matches!(out.last(), Some(Token::Ident(_))) {
+                        out.pop();
+                    }
+                }
+                out.extend(join_segments(expand_paste(inner)));
+            }
+            Token::Group(delimiter, inner) => out.push(Token::Group(delimiter, expand_paste(inner))),
+            token => out.push(token),
+        }
+    }
+    out
+}
+
+fn join_segments(tokens: Vec<Token>) -> Vec<Token> {
+    tokens.into_iter()
+        .map(|token| match token {
+            Token::Group(Delimiter::Bracket, inner) => {
+                let is_segment = inner.len() > 2 && inner[0].is_punct("<") && inner[inner.len() - 1].is_punct(">");
+                match is_segment.then(|| join_identifier(&inner[1..inner.len() - 1])).flatten() {
+                    Some(ident) => Token::Ident(ident),
+                    None => Token::Group(Delimiter::Bracket, join_segments(inner)),
+                }
+            }
+            Token::Group(delimiter, inner) => Token::Group(delimiter, join_segments(inner)),
+            token => token,
+        })
+        .collect()
+}
+
+/// `[<get_ $field:snake>]` contents joined into one identifier
+fn join_identifier(tokens: &[Token]) -> Option<String> {
+    let mut ident = String::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let mut piece = match &tokens[i] {
+            Token::Ident(name) => name.trim_start_matches("r#").to_string(),
+            Token::Literal(text) => text.trim_matches('"').to_string(),
+            _ => return None,
+        };
+        i += 1;
+        if tokens.get(i).is_some_and(|t| t.is_punct(":")) {
+            let Some(Token::Ident(modifier)) = tokens.get(i + 1) else {
+                return None;
+            };
+            piece = change_case(&piece, modifier)?;
+            i += 2;
+        }
+        ident.push_str(&piece);
+    }
+    (!ident.is_empty()).then_some(ident)
+}
+
+/// paste's `:lower`, `:upper`, `:snake` and `:camel` case modifiers
+fn change_case(text: &str, modifier: &str) -> Option<String> {
+    match modifier {
+        "lower" => Some(text.to_lowercase()),
+        "upper" => Some(text.to_uppercase()),
+        "snake" => {
+            let mut snake = String::new();
+            let mut previous = '_';
+            for c in text.chars() {
+                if c.is_uppercase() && previous != '_' && !previous.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.extend(c.to_lowercase());
+                previous = c;
+            }
+            Some(snake)
+        }
+        "camel" => {
+            let mut camel = String::new();
+            let mut upper_next = true;
+            for c in text.chars() {
+                if c == '_' {
+                    upper_next = true;
+                } else if upper_next {
+                    camel.extend(c.to_uppercase());
+                    upper_next = false;
+                } else {
+                    camel.push(c);
+                }
+            }
+            Some(camel)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    matcher: Vec<Matcher>,
+    transcriber: Vec<Transcriber>,
+}
+
+/// One `macro_rules!` definition with its rules compiled
+#[derive(Debug, Clone)]
+struct CompiledMacro {
+    crate_name: String,
+    file_path: String,
+    is_exported: bool,
+    rules: Vec<Rule>,
+}
+
+impl CompiledMacro {
+    fn compile(definition: &MacroDefinition) -> Option<Self> {
+        let tokens = tokenize(&definition.source)?;
+        let Some(Token::Group(_, body)) = tokens.iter().find(|t| matches!(t, Token::Group(..))) else {
+            return None;
+        };
+
+        // (matcher) => { transcriber };
+        let mut rules = Vec::new();
+        let mut i = 0;
+        while let (Some(Token::Group(_, matcher)), Some(arrow), Some(Token::Group(_, transcriber))) =
+            (body.get(i), body.get(i + 1), body.get(i + 2))
+        {
+            if !arrow.is_punct("=>") {
+                break;
+            }
+            rules.push(Rule {
+                matcher: Matcher::parse(matcher),
+                transcriber: Transcriber::parse(transcriber),
+            });
+            i += 3;
+            if body.get(i).is_some_and(|t| t.is_punct(";")) {
+                i += 1;
+            }
+        }
+
+        Some(CompiledMacro {
+            crate_name: definition.crate_name.clone(),
+            file_path: definition.file_path.clone(),
+            is_exported: definition.is_exported,
+            rules,
+        })
+    }
+
+    fn expand(&self, input: &[Token]) -> Option<Vec<Token>> {
+        self.rules.iter().find_map(|rule| {
+            let mut bindings = Bindings::new();
+            if !match_all(&rule.matcher, input, &mut bindings) {
+                return None;
+            }
+            let mut out = Vec::new();
+            transcribe(&rule.transcriber, &bindings, &mut Vec::new(), &mut out)?;
+            Some(expand_paste(out))
+        })
+    }
+}
+
+/// Interprets the workspace's `macro_rules!` definitions and feeds the code their
+/// invocations expand to back into the symbols, tagged with `MacroContext` provenance
+#[derive(Debug, Clone, Default)]
+pub struct MacroExpander {
+    macros: HashMap<String, Vec<CompiledMacro>>,
+}
+
+impl MacroExpander {
+    pub fn new(definitions: &[MacroDefinition]) -> Self {
+        let mut macros: HashMap<String, Vec<CompiledMacro>> = HashMap::new();
+        for definition in definitions {
+            if let Some(compiled) = CompiledMacro::compile(definition) {
+                macros.entry(definition.name.clone()).or_default().push(compiled);
+            }
+        }
+        Self { macros }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.macros.is_empty()
+    }
+
+    /// Definitions `invocation` may refer to: the same file first, then the same crate,
+    /// then `#[macro_export]` macros of other crates
+    fn candidates<'e>(&'e self, invocation: &MacroInvocation) -> Vec<&'e CompiledMacro> {
+        let name = invocation.name.rsplit("::").next().unwrap_or(&invocation.name).trim();
+        let mut candidates: Vec<&CompiledMacro> = self.macros.get(name).into_iter().flatten()
+            .filter(|m| m.crate_name == invocation.crate_name || m.is_exported)
+            .collect();
+        candidates.sort_by_key(|m| (m.file_path != invocation.file_path, m.crate_name != invocation.crate_name));
+        candidates
+    }
+
+    /// Source the invocation expands to, on one line
+    pub fn expand(&self, invocation: &MacroInvocation) -> Option<String> {
+        let candidates = self.candidates(invocation);
+        if candidates.is_empty() {
+            return None;
+        }
+        let input = match tokenize(&invocation.arguments)?.as_slice() {
+            [Token::Group(_, inner)] => inner.clone(),
+            other => other.to_vec(),
+        };
+        let tokens = candidates.into_iter().find_map(|m| m.expand(&input))?;
+        let mut text = String::new();
+        render(&tokens, &mut text);
+        Some(text)
+    }
+
+    /// Parse the expansion of `invocation` and walk it in the invocation's scope
+    fn walk_expansion(&self, invocation: &MacroInvocation) -> Option<(String, ParsedSymbols)> {
+        let text = self.expand(invocation)?;
+
+        // Statements need a function body to parse in; padding puts them on the invocation line
+        let padding = "\n".repeat(invocation.line_range.0);
+        let in_function = invocation.containing_function.is_some();
+        let source = if in_function {
+            format!("{}fn __expansion() {{ {} }}", padding, text)
+        } else {
+            format!("{}{}", padding, text)
+        };
+
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).ok()?;
+        let tree = parser.parse(&source, None)?;
+        let root = if in_function {
+            tree.root_node().named_child(0)?.child_by_field_name("body")?
+        } else {
+            tree.root_node()
+        };
+
+        let mut walker = UnifiedWalker::new(source.as_bytes(), invocation.crate_name.clone(), PathBuf::from(&invocation.file_path));
+        let mut symbols = walker.walk_expansion(root, invocation);
+
+        let line = invocation.line_range.0;
+        let context = MacroContext {
+            expansion_id: format!("{}:{}:{}", invocation.file_path, line, invocation.name),
+            macro_type: "declarative".to_string(),
+            expansion_site_line: line,
+            name: invocation.name.clone(),
+            kind: "macro_rules".to_string(),
+        };
+        for function in &mut symbols.functions {
+            function.line_end = invocation.line_range.1;
+            if matches!(function.function_context, FunctionContext::Free) {
+                function.function_context = FunctionContext::MacroExpansion { macro_info: context.clone() };
+            }
+        }
+        for call in &mut symbols.calls {
+            call.is_synthetic = true;
+            call.synthetic_confidence = EXPANSION_CONFIDENCE;
+            call.macro_context.get_or_insert_with(|| context.clone());
+        }
+        Some((text, symbols))
+    }
+
+    /// Expand every invocation of a known `macro_rules!` macro in `symbols`, following the
+    /// macros used by expanded code too, and merge the expanded functions, calls and other
+    /// items in. The matching `MacroExpansion` records get the expanded code and the names
+    /// it calls.
+    ///
+    /// Run once, after parsing and before reference resolution.
+    pub fn expand_into(&self, symbols: &mut ParsedSymbols) {
+        if self.is_empty() {
+            return;
+        }
+
+        let mut pending: Vec<(MacroInvocation, usize)> = symbols.macro_invocations.iter()
+            .map(|invocation| (invocation.clone(), 0))
+            .collect();
+        let mut expanded = ParsedSymbols::new();
+        let mut contents: HashMap<String, (String, Vec<String>)> = HashMap::new();
+        let mut next = 0;
+        while let Some((invocation, depth)) = pending.get(next).cloned() {
+            next += 1;
+            let Some((text, generated)) = self.walk_expansion(&invocation) else {
+                continue;
+            };
+            if depth + 1 < MAX_DEPTH {
+                pending.extend(generated.macro_invocations.iter().map(|nested| (nested.clone(), depth + 1)));
+            }
+
+            let mut callees: Vec<String> = Vec::new();
+            for call in &generated.calls {
+                if !callees.contains(&call.callee_name) {
+                    callees.push(call.callee_name.clone());
+                }
+            }
+            let id = format!("{}:{}:{}", invocation.file_path, invocation.line_range.0, invocation.name);
+            contents.insert(id, (text, callees));
+            expanded.merge(generated);
+        }
+
+        symbols.merge(expanded);
+        for expansion in &mut symbols.macro_expansions {
+            if let Some((text, callees)) = contents.remove(&expansion.id) {
+                expansion.expanded_content = Some(text);
+                expansion.target_functions = callees;
+            }
+        }
+    }
+}
+
+/// Expand `symbols`' macro invocations with the `macro_rules!` definitions found among them
+pub fn expand_declarative_macros(symbols: &mut ParsedSymbols) {
+    MacroExpander::new(&symbols.macro_definitions).expand_into(symbols);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(source: &str) -> MacroDefinition {
+        MacroDefinition {
+            name: source.split(['!', '{']).nth(1).unwrap_or("").trim().to_string(),
+            crate_name: "app".to_string(),
+            file_path: "src/lib.rs".to_string(),
+            line_start: 0,
+            line_end: 0,
+            is_exported: false,
+            source: source.to_string(),
+        }
+    }
+
+    fn expand(definition_source: &str, arguments: &str) -> Option<String> {
+        let expander = MacroExpander::new(&[definition(definition_source)]);
+        let invocation = MacroInvocation {
+            name: definition(definition_source).name,
+            range: (0, 0),
+            line_range: (0, 0),
+            containing_function: None,
+            arguments: arguments.to_string(),
+            crate_name: "app".to_string(),
+            file_path: "src/lib.rs".to_string(),
+            module_path: Vec::new(),
+            impl_context: None,
+            cfg: None,
+        };
+        expander.expand(&invocation)
+    }
+
+    #[test]
+    fn test_fragments_repetitions_and_rule_order() {
+        let calls = r#"macro_rules! calls {
+            () => { nothing() };
+            ($target:path => $($arg:expr),+ $(,)?) => { $( $target($arg); )+ };
+        }"#;
+        assert_eq!(expand(calls, "()").as_deref(), Some("nothing ( )"));
+        assert_eq!(
+            expand(calls, "(Vec::<u8>::with_capacity => a + 1, f(b, c),)").as_deref(),
+            Some("Vec :: < u8 > :: with_capacity ( a + 1 ) ; Vec :: < u8 > :: with_capacity ( f ( b , c ) ) ;")
+        );
+        assert_eq!(expand(calls, "(x => )"), None, "`+` needs one expression");
+    }
+
+    #[test]
+    fn test_nested_repetitions_and_paste() {
+        let accessors = r#"macro_rules! accessors {
+            ($ty:ident { $($field:ident: $fty:ty),* }) => {
+                paste::paste! {
+                    impl $ty { $( pub fn [<get_ $field>](&self) -> &$fty { &self.$field } )* }
+                    fn [<$ty:snake _fields>]() -> usize { 0 $( + stringify!($field).len() )* }
+                }
+            };
+        }"#;
+        let expanded = expand(accessors, "{ OrderBook { bids: Vec<Level>, asks: Vec<Level> } }").unwrap();
+        assert!(expanded.contains("pub fn get_bids ( & self ) -> & Vec < Level >"), "{expanded}");
+        assert!(expanded.contains("pub fn get_asks"), "{expanded}");
+        assert!(expanded.contains("fn order_book_fields ( )"), "{expanded}");
+        assert!(!expanded.contains("paste"), "{expanded}");
+    }
+}
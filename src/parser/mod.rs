@@ -4,11 +4,23 @@ pub mod references;
 pub mod traits;
 pub mod ast_utils;
 pub mod ast_walker;
+pub mod type_inference;
+pub mod cfg;
+pub mod frameworks;
+pub mod macro_expander;
+pub mod expanded;
+pub mod module_tree;
+pub mod scopes;
 
 #[cfg(test)]
 pub mod tests;
 
 pub use rust_parser::RustParser;
+pub use frameworks::{ActorFrameworkAdapter, ActorFrameworks};
+pub use macro_expander::{expand_declarative_macros, MacroExpander};
+pub use expanded::{expand_crate_source, merge_expanded_symbols};
+pub use module_tree::ModuleTree;
+pub use scopes::ModuleScopes;
 pub use symbols::*;
 pub use references::*;
 pub use traits::*;
\ No newline at end of file
@@ -0,0 +1,390 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use tree_sitter::{Node, Parser};
+
+use crate::parser::ast_utils::{extract_visibility, get_line_range, safe_node_text};
+use crate::parser::symbols::{ReExport, RustModule};
+use crate::workspace::CrateMetadata;
+
+/// A `pub use` whose target is resolved once every module of the crate is known
+struct PendingReExport {
+    module: Vec<String>,
+    name: String,
+    target: Vec<String>,
+    file_path: PathBuf,
+    line: usize,
+}
+
+/// The modules of a crate, found by following `mod` declarations from each target root.
+///
+/// Module paths are crate-relative (`orders::book`, not `crate::orders::book`), matching the
+/// qualified names the walker produces.
+pub struct ModuleTree {
+    crate_name: String,
+    src_dir: PathBuf,
+    /// Module path of every file reached from a root
+    files: HashMap<PathBuf, Vec<String>>,
+    modules: Vec<RustModule>,
+    reexports: Vec<ReExport>,
+}
+
+/// Lexically resolve `.` and `..`, so `src/a/../b.rs` and `src/b.rs` name the same file
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Value of a `#[path = "..."]` attribute among the attributes preceding `node`
+fn path_attribute(node: Node, source: &[u8]) -> Option<String> {
+    let mut sibling = node.prev_sibling();
+    while let Some(attribute) = sibling.filter(|s| s.kind() == "attribute_item") {
+        let text: String = safe_node_text(attribute, source)?.chars().filter(|c| !c.is_whitespace()).collect();
+        if let Some(value) = text.strip_prefix("#[path=\"").and_then(|rest| rest.strip_suffix("\"]")) {
+            return Some(value.to_string());
+        }
+        sibling = attribute.prev_sibling();
+    }
+    None
+}
+
+/// Flatten a `use` tree into `(name it is bound to, path it names)` pairs; a glob import
+/// binds `*` to the module it imports from
+pub(crate) fn use_paths(node: Node, source: &[u8], prefix: &[String], out: &mut Vec<(String, Vec<String>)>) {
+    let segments = |n: Node| -> Vec<String> {
+        safe_node_text(n, source)
+            .unwrap_or("")
+            .split("::")
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    };
+    match node.kind() {
+        "identifier" | "scoped_identifier" | "self" | "crate" | "super" => {
+            let mut path = prefix.to_vec();
+            path.extend(segments(node));
+            // `use a::b::{self}` binds `b`
+            if path.last().map(String::as_str) == Some("self") && path.len() > 1 {
+                path.pop();
+            }
+            if let Some(name) = path.last().cloned() {
+                out.push((name, path));
+            }
+        }
+        "use_as_clause" => {
+            let (Some(path_node), Some(alias)) = (node.child_by_field_name("path"), node.child_by_field_name("alias")) else { return };
+            let mut path = prefix.to_vec();
+            path.extend(segments(path_node));
+            if let Some(alias) = safe_node_text(alias, source) {
+                out.push((alias.to_string(), path));
+            }
+        }
+        "scoped_use_list" => {
+            let mut nested = prefix.to_vec();
+            if let Some(path_node) = node.child_by_field_name("path") {
+                nested.extend(segments(path_node));
+            }
+            if let Some(list) = node.child_by_field_name("list") {
+                use_paths(list, source, &nested, out);
+            }
+        }
+        "use_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                use_paths(child, source, prefix, out);
+            }
+        }
+        "use_wildcard" => {
+            let mut path = prefix.to_vec();
+            let mut cursor = node.walk();
+            if let Some(module) = node.named_children(&mut cursor).next() {
+                path.extend(segments(module));
+            }
+            out.push(("*".to_string(), path));
+        }
+        _ => {}
+    }
+}
+
+impl ModuleTree {
+    /// Module tree of a crate, from the target roots cargo reports for it
+    pub fn for_crate(crate_meta: &CrateMetadata) -> Self {
+        Self::build(&crate_meta.name, &crate_meta.path, &crate_meta.targets)
+    }
+
+    /// Module tree of the crate at `crate_path`, starting at `roots`; without roots the
+    /// conventional ones are used: `src/lib.rs`, `src/main.rs` and `src/bin/*.rs`
+    pub fn build(crate_name: &str, crate_path: &Path, roots: &[PathBuf]) -> Self {
+        let src_dir = crate_path.join("src");
+        let mut roots = roots.to_vec();
+        if roots.is_empty() {
+            roots.extend([src_dir.join("lib.rs"), src_dir.join("main.rs")]);
+            if let Ok(entries) = std::fs::read_dir(src_dir.join("bin")) {
+                let mut bins: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+                bins.sort();
+                roots.extend(bins.into_iter().map(|bin| if bin.is_dir() { bin.join("main.rs") } else { bin }));
+            }
+        }
+
+        let mut tree = ModuleTree {
+            crate_name: crate_name.to_string(),
+            src_dir,
+            files: HashMap::new(),
+            modules: Vec::new(),
+            reexports: Vec::new(),
+        };
+        let mut parser = Parser::new();
+        if parser.set_language(&tree_sitter_rust::language()).is_err() {
+            return tree;
+        }
+
+        let mut pending = Vec::new();
+        for root in roots.iter().filter(|root| root.is_file()) {
+            let root = normalize(root);
+            if tree.files.contains_key(&root) {
+                continue;
+            }
+            tree.files.insert(root.clone(), Vec::new());
+            let dir = root.parent().map(Path::to_path_buf).unwrap_or_default();
+            tree.visit_file(&mut parser, &root, Vec::new(), dir, &mut pending);
+        }
+        tree.resolve_reexports(pending);
+        tree
+    }
+
+    fn visit_file(&mut self, parser: &mut Parser, file: &Path, module: Vec<String>, dir: PathBuf, pending: &mut Vec<PendingReExport>) {
+        let Ok(source) = std::fs::read_to_string(file) else { return };
+        let Some(syntax) = parser.parse(&source, None) else { return };
+        let file_dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.visit_items(parser, syntax.root_node(), source.as_bytes(), file, &file_dir, &module, &dir, pending);
+    }
+
+    /// Walk the items of one module body. `dir` holds the files of child modules; `path_dir`
+    /// is what `#[path]` is relative to: the file's directory at the top of a file, `dir`
+    /// inside inline modules.
+    #[allow(clippy::too_many_arguments)]
+    fn visit_items(
+        &mut self,
+        parser: &mut Parser,
+        body: Node,
+        source: &[u8],
+        file: &Path,
+        path_dir: &Path,
+        module: &[String],
+        dir: &Path,
+        pending: &mut Vec<PendingReExport>,
+    ) {
+        let mut cursor = body.walk();
+        for item in body.named_children(&mut cursor) {
+            match item.kind() {
+                "mod_item" => {
+                    let Some(name) = item.child_by_field_name("name").and_then(|n| safe_node_text(n, source)) else { continue };
+                    let mut child_module = module.to_vec();
+                    child_module.push(name.to_string());
+                    let path_attr = path_attribute(item, source);
+                    self.modules.push(RustModule {
+                        name: name.to_string(),
+                        path: child_module.join("::"),
+                        crate_name: self.crate_name.clone(),
+                        file_path: file.to_string_lossy().to_string(),
+                        is_public: extract_visibility(item, source) != "private",
+                        parent_module: (!module.is_empty()).then(|| module.join("::")),
+                    });
+
+                    if let Some(body) = item.child_by_field_name("body") {
+                        let child_dir = dir.join(path_attr.as_deref().unwrap_or(name));
+                        self.visit_items(parser, body, source, file, &child_dir, &child_module, &child_dir, pending);
+                        continue;
+                    }
+
+                    let candidates = match &path_attr {
+                        Some(path) => vec![path_dir.join(path)],
+                        None => vec![dir.join(format!("{}.rs", name)), dir.join(name).join("mod.rs")],
+                    };
+                    let Some(child_file) = candidates.into_iter().map(|c| normalize(&c)).find(|c| c.is_file()) else { continue };
+                    if self.files.contains_key(&child_file) {
+                        continue;
+                    }
+                    // Files named `mod.rs`, or placed by `#[path]`, keep their children next to them
+                    let child_dir = if path_attr.is_some() || child_file.file_name().is_some_and(|f| f == "mod.rs") {
+                        child_file.parent().map(Path::to_path_buf).unwrap_or_default()
+                    } else {
+                        dir.join(name)
+                    };
+                    self.files.insert(child_file.clone(), child_module.clone());
+                    self.visit_file(parser, &child_file, child_module, child_dir, pending);
+                }
+                "use_declaration" if extract_visibility(item, source) != "private" => {
+                    let Some(argument) = item.child_by_field_name("argument") else { continue };
+                    let mut paths = Vec::new();
+                    use_paths(argument, source, &[], &mut paths);
+                    // Glob re-exports are expanded by the reference resolver
+                    for (name, target) in paths.into_iter().filter(|(name, _)| name != "*") {
+                        pending.push(PendingReExport {
+                            module: module.to_vec(),
+                            name,
+                            target,
+                            file_path: file.to_path_buf(),
+                            line: get_line_range(item).0,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Turn `use` paths into crate-relative ones: `crate::`, `self::` and `super::` are
+    /// resolved against the declaring module, a leading child module name is relative to it
+    /// (as is a top-level module name, for 2015-style paths) and anything else names another crate
+    fn resolve_reexports(&mut self, pending: Vec<PendingReExport>) {
+        let known: HashSet<&str> = self.modules.iter().map(|m| m.path.as_str()).collect();
+        let mut reexports = Vec::new();
+        for reexport in pending {
+            let mut target = reexport.target.as_slice();
+            let mut resolved: Vec<String> = match target.first().map(String::as_str) {
+                Some("crate") => {
+                    target = &target[1..];
+                    Vec::new()
+                }
+                Some("self") => {
+                    target = &target[1..];
+                    reexport.module.clone()
+                }
+                Some("super") => {
+                    let mut base = reexport.module.clone();
+                    while target.first().map(String::as_str) == Some("super") {
+                        base.pop();
+                        target = &target[1..];
+                    }
+                    base
+                }
+                Some(first) => {
+                    let mut relative = reexport.module.clone();
+                    relative.push(first.to_string());
+                    if known.contains(relative.join("::").as_str()) {
+                        reexport.module.clone()
+                    } else {
+                        Vec::new()
+                    }
+                }
+                None => continue,
+            };
+            resolved.extend(target.iter().cloned());
+
+            let mut path = reexport.module.clone();
+            path.push(reexport.name);
+            if path == resolved {
+                continue;
+            }
+            reexports.push(ReExport {
+                crate_name: self.crate_name.clone(),
+                path: path.join("::"),
+                target: resolved.join("::"),
+                file_path: reexport.file_path.to_string_lossy().to_string(),
+                line: reexport.line,
+            });
+        }
+        self.reexports = reexports;
+    }
+
+    /// Module path of `file`; files no root reaches get the module their location suggests
+    pub fn module_path(&self, file: &Path) -> Vec<String> {
+        let file = normalize(file);
+        if let Some(module) = self.files.get(&file) {
+            return module.clone();
+        }
+        let Ok(relative) = file.strip_prefix(&self.src_dir) else { return Vec::new() };
+        let mut segments: Vec<String> = relative.with_extension("").iter().map(|s| s.to_string_lossy().to_string()).collect();
+        if matches!(segments.last().map(String::as_str), Some("mod" | "lib" | "main")) {
+            segments.pop();
+        }
+        segments
+    }
+
+    /// Every module declared in the crate, inline or in its own file
+    pub fn modules(&self) -> &[RustModule] {
+        &self.modules
+    }
+
+    pub fn reexports(&self) -> &[ReExport] {
+        &self.reexports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, files: &[(&str, &str)]) {
+        for (relative, source) in files {
+            let path = root.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_module_paths_follow_mod_declarations() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, &[
+            ("src/lib.rs", "mod orders;\npub mod api {\n    pub mod v1;\n}\n#[path = \"legacy_impl.rs\"]\nmod legacy;\n"),
+            ("src/orders.rs", "mod book;\n"),
+            ("src/orders/book.rs", "pub fn match_order() {}\n"),
+            ("src/api/v1.rs", "pub struct Quote;\n"),
+            ("src/legacy_impl.rs", "mod helpers;\n"),
+            ("src/helpers.rs", "fn help() {}\n"),
+            ("src/scratch/mod.rs", "fn unused() {}\n"),
+        ]);
+        let tree = ModuleTree::build("shop", root, &[]);
+        let module = |relative: &str| tree.module_path(&root.join(relative)).join("::");
+
+        assert_eq!(module("src/lib.rs"), "");
+        assert_eq!(module("src/orders.rs"), "orders");
+        assert_eq!(module("src/orders/book.rs"), "orders::book");
+        assert_eq!(module("src/api/v1.rs"), "api::v1");
+        assert_eq!(module("src/legacy_impl.rs"), "legacy");
+        // A `#[path]` file keeps its children next to it
+        assert_eq!(module("src/helpers.rs"), "legacy::helpers");
+        // Not declared anywhere, so placed by its location
+        assert_eq!(module("src/scratch/mod.rs"), "scratch");
+
+        let public: Vec<_> = tree.modules().iter().filter(|m| m.is_public).map(|m| m.path.as_str()).collect();
+        assert_eq!(public, vec!["api", "api::v1"]);
+    }
+
+    #[test]
+    fn test_reexports_resolve_to_canonical_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, &[
+            ("src/lib.rs", "mod orders;\npub mod api;\npub use orders::place;\npub use crate::api::{v1::Quote as PublicQuote, self};\nuse orders::cancel;\n"),
+            ("src/orders.rs", "pub fn place() {}\npub fn cancel() {}\n"),
+            ("src/api/mod.rs", "pub mod v1;\npub use super::orders::cancel;\npub use serde::Serialize;\npub use self::v1::*;\n"),
+            ("src/api/v1.rs", "pub struct Quote;\n"),
+        ]);
+        let tree = ModuleTree::build("shop", root, &[]);
+
+        let reexports: Vec<_> = tree.reexports().iter().map(|r| (r.path.as_str(), r.target.as_str())).collect();
+        assert_eq!(reexports, vec![
+            ("api::cancel", "orders::cancel"),
+            ("api::Serialize", "serde::Serialize"),
+            ("place", "orders::place"),
+            ("PublicQuote", "api::v1::Quote"),
+        ]);
+    }
+}
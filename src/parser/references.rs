@@ -1,11 +1,18 @@
 use anyhow::Result;
 use std::collections::HashMap;
 
+use crate::parser::scopes::{Binding, ModuleScopes};
 use crate::parser::symbols::*;
+use crate::parser::traits::TraitIndex;
+use crate::parser::type_inference::{base_type_name, trait_object_bounds};
 
 pub struct ReferenceResolver {
     symbol_table: HashMap<String, ResolvedSymbol>,
     import_table: HashMap<String, Vec<ImportedSymbol>>, // file_path -> imported symbols
+    trait_index: TraitIndex,
+    field_types: HashMap<String, HashMap<String, String>>, // type name -> field name -> field type
+    reexports: HashMap<String, String>, // re-exported path -> path of the item
+    scopes: ModuleScopes,
 }
 
 #[derive(Debug, Clone)]
@@ -35,6 +42,10 @@ impl ReferenceResolver {
         Self {
             symbol_table: HashMap::new(),
             import_table: HashMap::new(),
+            trait_index: TraitIndex::new(),
+            field_types: HashMap::new(),
+            reexports: HashMap::new(),
+            scopes: ModuleScopes::default(),
         }
     }
 
@@ -112,6 +123,21 @@ impl ReferenceResolver {
             );
         }
 
+        for reexport in &symbols.reexports {
+            let crate_path = format!("{}::{}", reexport.crate_name.replace('-', "_"), reexport.path);
+            self.reexports.insert(crate_path, reexport.target.clone());
+            self.reexports.insert(reexport.path.clone(), reexport.target.clone());
+        }
+
+        self.scopes = ModuleScopes::build(symbols);
+        self.trait_index = TraitIndex::from_symbols(symbols);
+        for rust_type in &symbols.types {
+            let fields = self.field_types.entry(rust_type.name.clone()).or_default();
+            for field in &rust_type.fields {
+                fields.insert(field.name.clone(), field.field_type.clone());
+            }
+        }
+
         Ok(())
     }
 
@@ -175,6 +201,9 @@ impl ReferenceResolver {
 
     pub fn resolve_call(&self, call_name: &str, context_module: &str, context_crate: &str, context_file: &str) -> Option<ResolvedSymbol> {
         if call_name.contains("::") {
+            if let Some(symbol) = self.resolve_in_scope(call_name, context_module, context_crate) {
+                return Some(symbol);
+            }
             return self.resolve_qualified_call(call_name);
         }
 
@@ -182,7 +211,12 @@ impl ReferenceResolver {
             return self.resolve_method_call(call_name, context_module, context_crate);
         }
 
-        // First, check imports for this file
+        // Names the calling module defines or imports, globs and re-exports included
+        if let Some(symbol) = self.resolve_in_scope(call_name, context_module, context_crate) {
+            return Some(symbol);
+        }
+
+        // Then the imports recorded for this file
         if let Some(imported) = self.resolve_from_imports(call_name, context_file) {
             return Some(imported);
         }
@@ -190,6 +224,17 @@ impl ReferenceResolver {
         self.resolve_simple_call(call_name, context_module, context_crate)
     }
 
+    fn resolve_in_scope(&self, path: &str, context_module: &str, context_crate: &str) -> Option<ResolvedSymbol> {
+        match self.scopes.resolve_path(context_crate, context_module, path)? {
+            Binding::Item { crate_name, qualified_name, is_type } => Some(ResolvedSymbol {
+                qualified_name,
+                crate_name,
+                symbol_type: if is_type { SymbolType::Type } else { SymbolType::Function },
+            }),
+            Binding::Module(_) => None,
+        }
+    }
+
     fn resolve_from_imports(&self, call_name: &str, file_path: &str) -> Option<ResolvedSymbol> {
         if let Some(imports) = self.import_table.get(file_path) {
             for imported in imports {
@@ -198,6 +243,10 @@ impl ReferenceResolver {
                     if let Some(symbol) = self.symbol_table.get(&imported.qualified_name) {
                         return Some(symbol.clone());
                     }
+                    let local_path = imported.qualified_name.strip_prefix("crate::").unwrap_or(&imported.qualified_name);
+                    if let Some(symbol) = self.follow_reexports(local_path).and_then(|target| self.symbol_table.get(&target)) {
+                        return Some(symbol.clone());
+                    }
                     
                     // If not found in symbol table, create a resolved symbol from import info
                     return Some(ResolvedSymbol {
@@ -217,6 +266,12 @@ impl ReferenceResolver {
             return Some(symbol.clone());
         }
 
+        // Then the path the item was re-exported from, e.g. `crate::place` for `orders::place`
+        let local_path = call_name.strip_prefix("crate::").unwrap_or(call_name);
+        if let Some(symbol) = self.follow_reexports(local_path).and_then(|target| self.symbol_table.get(&target)) {
+            return Some(symbol.clone());
+        }
+
         if call_name.starts_with("crate::") {
             let local_name = call_name.strip_prefix("crate::").unwrap();
             return self.symbol_table.get(local_name).cloned();
@@ -251,6 +306,27 @@ impl ReferenceResolver {
         None
     }
 
+    /// Where a path points once `pub use` re-exports are followed, or `None` when no re-export
+    /// is involved. Re-exported modules count too: `api::place` follows `pub use orders as api`.
+    pub fn follow_reexports(&self, path: &str) -> Option<String> {
+        let mut current = path.to_string();
+        // Bounded, since re-exports can form cycles
+        for _ in 0..8 {
+            let segments: Vec<&str> = current.split("::").collect();
+            let Some((len, target)) = (1..=segments.len()).rev()
+                .find_map(|len| self.reexports.get(&segments[..len].join("::")).map(|target| (len, target)))
+            else {
+                break;
+            };
+            let next = std::iter::once(target.as_str()).chain(segments[len..].iter().copied()).collect::<Vec<_>>().join("::");
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        (current != path).then_some(current)
+    }
+
     fn resolve_method_call(&self, call_name: &str, context_module: &str, context_crate: &str) -> Option<ResolvedSymbol> {
         let parts: Vec<&str> = call_name.split('.').collect();
         if parts.len() != 2 {
@@ -275,6 +351,166 @@ impl ReferenceResolver {
         None
     }
 
+    /// Resolve `recv.method()` against the methods of the receiver's inferred type.
+    ///
+    /// Returns `None` when the type (or a field along the way) is unknown to the workspace
+    /// or has no such method, rather than guessing from the method name alone.
+    pub fn resolve_receiver_method(&self, receiver: &ReceiverType, method: &str) -> Option<ResolvedSymbol> {
+        let type_name = self.receiver_type_name(receiver)?;
+        let function = self.trait_index.resolve_type_method(&type_name, method)?;
+        Some(ResolvedSymbol {
+            qualified_name: function.qualified_name.clone(),
+            crate_name: function.crate_name.clone(),
+            symbol_type: SymbolType::Function,
+        })
+    }
+
+    /// Bare type name of a receiver, or `None` when a field's type is not known
+    pub fn receiver_type_name(&self, receiver: &ReceiverType) -> Option<String> {
+        match receiver {
+            ReceiverType::Named(name) => Some(name.clone()),
+            ReceiverType::Field { owner, field } => {
+                let owner = self.receiver_type_name(owner)?;
+                let field_type = self.field_types.get(&owner)?.get(field)?;
+                base_type_name(field_type)
+            }
+            ReceiverType::Bounded { .. } => None,
+        }
+    }
+
+    /// Traits a receiver is only known by, with its type as written: `dyn`/`impl Trait`
+    /// parameters and bindings, bounded generics, and fields holding a trait object
+    fn receiver_traits(&self, receiver: &ReceiverType) -> Option<(Vec<String>, String)> {
+        match receiver {
+            ReceiverType::Bounded { traits, written } => Some((traits.clone(), written.clone())),
+            ReceiverType::Field { owner, field } => {
+                let owner = self.receiver_type_name(owner)?;
+                let field_type = self.field_types.get(&owner)?.get(field)?;
+                Some((trait_object_bounds(field_type)?, field_type.clone()))
+            }
+            ReceiverType::Named(_) => None,
+        }
+    }
+
+    /// Resolve a method call on a receiver known only by its traits.
+    ///
+    /// The call resolves to the method as declared by the first workspace trait of the
+    /// receiver that declares it, and may dispatch to every implementation of that method in
+    /// the workspace. Returns `None` when no workspace trait of the receiver declares it.
+    pub fn resolve_trait_dispatch(&self, call: &FunctionCall) -> Option<(ResolvedSymbol, Vec<TraitDispatch>)> {
+        let (traits, written) = self.receiver_traits(call.receiver_type.as_ref()?)?;
+        let declared = traits.iter().find_map(|t| self.trait_index.declared_method(t, &call.callee_name))?;
+        let FunctionContext::TraitDeclaration { trait_name } = &declared.function_context else {
+            return None;
+        };
+        let trait_qualified = declared.qualified_name.rsplit_once("::").map(|(owner, _)| owner).unwrap_or(trait_name);
+
+        let dispatches = self.trait_index.implementations_of(trait_name, &call.callee_name).into_iter()
+            .map(|target| TraitDispatch {
+                caller_id: call.caller_id.clone(),
+                from_crate: call.from_crate.clone(),
+                trait_name: trait_qualified.to_string(),
+                method_name: call.callee_name.clone(),
+                receiver: written.clone(),
+                target: target.qualified_name.clone(),
+                target_crate: target.crate_name.clone(),
+                implementing_type: match &target.function_context {
+                    FunctionContext::TraitImpl { type_name, .. } => base_type_name(type_name).unwrap_or_else(|| type_name.clone()),
+                    _ => String::new(),
+                },
+                line: call.line,
+                file_path: call.file_path.clone(),
+                cfg: call.cfg.clone(),
+            })
+            .collect();
+        let declaration = ResolvedSymbol {
+            qualified_name: declared.qualified_name.clone(),
+            crate_name: declared.crate_name.clone(),
+            symbol_type: SymbolType::Function,
+        };
+        Some((declaration, dispatches))
+    }
+
+    /// Resolve a function used as a value to a workspace function.
+    ///
+    /// Bare names only resolve through what the module defines or imports, since a bare
+    /// identifier in value position is just as likely an unrelated binding; `Type::method`
+    /// goes through the type's inherent and trait methods.
+    pub fn resolve_fn_reference(&self, reference: &FnReference) -> Option<ResolvedSymbol> {
+        let (module, crate_name) = (reference.caller_module.as_str(), reference.from_crate.as_str());
+        let segments: Vec<&str> = reference.path.split("::").collect();
+        let resolved = match segments.as_slice() {
+            [name] => self.resolve_in_scope(name, module, crate_name)
+                .or_else(|| self.resolve_from_imports(name, &reference.file_path)),
+            [owner, method] if owner.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                let function = self.trait_index.resolve_type_method(owner, method)?;
+                Some(ResolvedSymbol {
+                    qualified_name: function.qualified_name.clone(),
+                    crate_name: function.crate_name.clone(),
+                    symbol_type: SymbolType::Function,
+                })
+            }
+            _ => self.resolve_in_scope(&reference.path, module, crate_name)
+                .or_else(|| self.resolve_qualified_call(&reference.path)),
+        }?;
+        // Only functions the workspace defines; types and fabricated import paths don't count
+        match self.symbol_table.get(&resolved.qualified_name)?.symbol_type {
+            SymbolType::Function => Some(resolved),
+            _ => None,
+        }
+    }
+
+    /// Resolve a type named by a signature, field or body to a workspace type.
+    ///
+    /// Bare names resolve through what the module defines or imports; paths that do not name
+    /// a type fall back to their parent, so an enum variant path resolves to its enum. Along
+    /// with the type comes the item the path names through it: `Status::Open` resolves to
+    /// `Status` and `Open`. Field accesses resolve to the type of the value, when it has the field.
+    pub fn resolve_type_usage(&self, usage: &TypeUsage) -> Option<(ResolvedSymbol, Option<String>)> {
+        if let Some(receiver) = &usage.receiver {
+            let type_name = self.receiver_type_name(receiver)?;
+            let field = usage.member.as_ref()?;
+            self.field_types.get(&type_name)?.get(field)?;
+            // The type may be known only through a field, without being in scope
+            let resolved = self.resolve_type_path(&type_name, usage).or_else(|| self.type_named(&type_name))?;
+            return Some((resolved, Some(field.clone())));
+        }
+        let mut path = usage.type_path.as_str();
+        let mut member = None;
+        loop {
+            if let Some(resolved) = self.resolve_type_path(path, usage) {
+                return Some((resolved, member));
+            }
+            let (parent, last) = path.rsplit_once("::")?;
+            member = Some(last.to_string());
+            path = parent;
+        }
+    }
+
+    fn resolve_type_path(&self, path: &str, usage: &TypeUsage) -> Option<ResolvedSymbol> {
+        let resolved = match self.resolve_in_scope(path, &usage.user_module, &usage.from_crate) {
+            Some(resolved) => resolved,
+            None if path.contains("::") => self.resolve_qualified_call(path)?,
+            None => self.resolve_from_imports(path, &usage.file_path)?,
+        };
+        match self.symbol_table.get(&resolved.qualified_name)?.symbol_type {
+            SymbolType::Type => Some(resolved),
+            _ => None,
+        }
+    }
+
+    /// The workspace type with this bare name, if only one crate defines one
+    fn type_named(&self, name: &str) -> Option<ResolvedSymbol> {
+        let mut types = self.symbol_table.values().filter(|symbol| {
+            matches!(symbol.symbol_type, SymbolType::Type)
+                && symbol.qualified_name.rsplit("::").next() == Some(name)
+        });
+        let first = types.next()?;
+        types
+            .all(|other| other.qualified_name == first.qualified_name && other.crate_name == first.crate_name)
+            .then(|| first.clone())
+    }
+
     fn resolve_simple_call(&self, call_name: &str, context_module: &str, context_crate: &str) -> Option<ResolvedSymbol> {
         let candidates = vec![
             format!("{}::{}", context_module, call_name),
@@ -318,146 +554,87 @@ impl ReferenceResolver {
             })
             .collect()
     }
-    
-    /// Generate synthetic trait method calls for all trait implementations
-    /// This creates call edges for trait methods that can be called through dynamic dispatch
-    pub fn generate_trait_method_calls(&self, symbols: &ParsedSymbols) -> Result<Vec<FunctionCall>> {
-        let mut synthetic_calls = Vec::new();
-        
-        
-        let mut trait_impls = 0;
-        let mut simple_impls = 0;
-        
-        for impl_block in &symbols.impls {
-            // Only process trait implementations (impl Trait for Type)
-            if let Some(ref trait_name) = impl_block.trait_name {
-                trait_impls += 1;
-                
-                // Generate synthetic calls for known trait methods, even if we don't have the methods parsed
-                let trait_methods = self.get_known_trait_methods(trait_name);
-                
-                for method_name in trait_methods {
-                    // Create a synthetic call indicating this trait method can be called
-                    // We'll create a generic caller that represents "framework/runtime"
-                    let caller_id = format!("trait_caller::{}::{}", trait_name, method_name);
-                    
-                    // Build the qualified callee name for this method using full module path
-                    // Need to determine the actual qualified name of the function from the symbol table
-                    let mut qualified_callee = None;
-                    
-                    // Search for the actual function in the symbol table
-                    for (symbol_name, symbol) in &self.symbol_table {
-                        // Look for method names that end with the method name
-                        if symbol_name.ends_with(method_name) {
-                            // Try to match based on file path patterns
-                            // If the impl_block file path contains pattern and the symbol contains similar pattern
-                            let impl_file_stem = if let Some(file_name) = impl_block.file_path.split('/').last() {
-                                file_name.replace(".rs", "")
-                            } else {
-                                continue;
-                            };
-                            
-                            if symbol_name.contains(&impl_file_stem) {
-                                qualified_callee = Some(symbol_name.clone());
-                                break;
-                            }
-                        }
-                    }
-                    
-                    // If not found, use a fallback pattern
-                    if qualified_callee.is_none() {
-                        let fallback = format!("{}::{}", impl_block.type_name, method_name);
-                        qualified_callee = Some(fallback);
-                    }
-                    
-                    let synthetic_call = FunctionCall {
-                        caller_id,
-                        caller_module: "trait_framework".to_string(),
-                        callee_name: method_name.to_string(),
-                        qualified_callee: qualified_callee.clone(),
-                        call_type: CallType::Method,
-                        line: impl_block.line_start,
-                        cross_crate: false,
-                        from_crate: "trait_framework".to_string(),
-                        to_crate: None, // Will be resolved later
-                        file_path: "<trait_synthetic>".to_string(),
-                        is_synthetic: true,
-                        macro_context: Some(MacroContext {
-                            expansion_id: format!("trait_method::{}::{}", trait_name, method_name),
-                            macro_type: "trait_method".to_string(),
-                            expansion_site_line: impl_block.line_start,
-                            name: method_name.to_string(),
-                            kind: "trait_dispatch".to_string(),
-                        }),
-                        synthetic_confidence: 0.8, // Lower confidence since it's potential dispatch
-                    };
-                    
-                    synthetic_calls.push(synthetic_call);
-                }
-            } else {
-                simple_impls += 1;
-            }
-        }
-        
-        
-        Ok(synthetic_calls)
-    }
-    
-    /// Get known methods for common traits to generate synthetic calls
-    fn get_known_trait_methods(&self, trait_name: &str) -> Vec<&'static str> {
-        match trait_name {
-            "WebSocketActor" => vec!["handle_message", "event_stream", "connect", "disconnect", "subscribe", "is_connected"],
-            "Actor" => vec!["on_start", "on_stop"],
-            "Handler" => vec!["handle"],
-            "StreamHandler" => vec!["handle", "started", "finished"],
-            "Default" => vec!["default"],
-            "Clone" => vec!["clone"],
-            "Debug" => vec!["fmt"],
-            "Display" => vec!["fmt"],
-            "PartialEq" => vec!["eq"],
-            "Eq" => vec![],
-            "PartialOrd" => vec!["partial_cmp"],
-            "Ord" => vec!["cmp"],
-            "Hash" => vec!["hash"],
-            "From" => vec!["from"],
-            "Into" => vec!["into"],
-            "TryFrom" => vec!["try_from"],
-            "TryInto" => vec!["try_into"],
-            "AsRef" => vec!["as_ref"],
-            "AsMut" => vec!["as_mut"],
-            "Deref" => vec!["deref"],
-            "DerefMut" => vec!["deref_mut"],
-            "Iterator" => vec!["next"],
-            "IntoIterator" => vec!["into_iter"],
-            "Serialize" => vec!["serialize"],
-            "Deserialize" => vec!["deserialize"],
-            _ => vec![], // For unknown traits, don't generate synthetic calls
-        }
-    }
 }
 
 pub fn resolve_all_references(symbols: &mut ParsedSymbols) -> Result<()> {
     let mut resolver = ReferenceResolver::new();
     resolver.build_symbol_table(symbols)?;
 
+    let mut dispatches = Vec::new();
     for call in &mut symbols.calls {
-        if let Some(resolved) = resolver.resolve_call(
-            &call.callee_name,
-            &call.caller_module,
-            &call.from_crate,
-            &call.file_path,
-        ) {
+        // A known receiver type is authoritative; falling back to the bare method name
+        // would link e.g. `self.client.send()` to an unrelated `send`
+        let resolved = match &call.receiver_type {
+            Some(receiver) => match resolver.resolve_trait_dispatch(call) {
+                Some((declaration, targets)) => {
+                    dispatches.extend(targets);
+                    Some(declaration)
+                }
+                None => resolver.resolve_receiver_method(receiver, &call.callee_name),
+            },
+            None => resolver.resolve_call(
+                &call.callee_name,
+                &call.caller_module,
+                &call.from_crate,
+                &call.file_path,
+            ),
+        };
+        if let Some(resolved) = resolved {
             call.qualified_callee = Some(resolved.qualified_name.clone());
             call.to_crate = Some(resolved.crate_name.clone());
             call.cross_crate = call.from_crate != resolved.crate_name;
         }
     }
-    
-    // Note: Trait method call generation is now handled by framework patterns in WorkspaceAnalyzer
+    symbols.trait_dispatches = dispatches;
+
+    for reference in &mut symbols.fn_references {
+        if let Some(resolved) = resolver.resolve_fn_reference(reference) {
+            reference.qualified_target = Some(resolved.qualified_name);
+            reference.to_crate = Some(resolved.crate_name);
+        }
+    }
+
+    for usage in &mut symbols.type_usages {
+        if let Some((resolved, member)) = resolver.resolve_type_usage(usage) {
+            usage.qualified_type = Some(resolved.qualified_name);
+            usage.to_crate = Some(resolved.crate_name);
+            usage.member = member;
+        }
+    }
+
+    resolve_registered_targets(symbols);
 
     Ok(())
 }
 
+/// Point distributed sends to a looked-up name at the actor registered under that name.
+/// Names registered for several different actors are ambiguous and left alone.
+pub fn resolve_registered_targets(symbols: &mut ParsedSymbols) {
+    let mut registry: HashMap<&str, Option<(&str, &str)>> = HashMap::new();
+    for registration in &symbols.actor_registrations {
+        let Some(actor) = registration.actor_name.as_deref() else {
+            continue;
+        };
+        let target = (actor, registration.crate_name.as_str());
+        registry
+            .entry(registration.registered_name.as_str())
+            .and_modify(|existing| {
+                if existing.is_some_and(|(known, _)| known != actor) {
+                    *existing = None;
+                }
+            })
+            .or_insert(Some(target));
+    }
+
+    for flow in &mut symbols.distributed_message_flows {
+        let target = flow.registered_name.as_deref().and_then(|name| registry.get(name).copied().flatten());
+        if let Some((actor, crate_name)) = target {
+            flow.target_actor = actor.to_string();
+            flow.target_crate = crate_name.to_string();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -488,6 +665,8 @@ mod tests {
             return_type: None,
             embedding_text: None,
             module: "crate::module".to_string(),
+            cfg: None,
+            attributes: Vec::new(),
         };
         func.generate_id();
         symbols.functions.push(func);
@@ -533,6 +712,36 @@ mod tests {
         assert_eq!(symbol.qualified_name, "crate::module::test_fn");
     }
 
+    #[test]
+    fn test_resolve_through_reexports() {
+        let mut symbols = create_test_symbols();
+        for qualified_name in ["orders::place", "legacy::place"] {
+            let mut function = symbols.functions[0].clone();
+            function.name = "place".to_string();
+            function.qualified_name = qualified_name.to_string();
+            function.crate_name = "shop".to_string();
+            symbols.functions.push(function);
+        }
+        for (path, target) in [("place", "orders::place"), ("api", "orders")] {
+            symbols.reexports.push(ReExport {
+                crate_name: "shop".to_string(),
+                path: path.to_string(),
+                target: target.to_string(),
+                file_path: "src/lib.rs".to_string(),
+                line: 0,
+            });
+        }
+
+        let mut resolver = ReferenceResolver::new();
+        resolver.build_symbol_table(&symbols).unwrap();
+
+        let resolve = |path: &str| resolver.resolve_call(path, "", "shop", "src/main.rs").map(|s| s.qualified_name);
+        assert_eq!(resolve("crate::place").as_deref(), Some("orders::place"));
+        assert_eq!(resolve("shop::place").as_deref(), Some("orders::place"));
+        assert_eq!(resolve("shop::api::place").as_deref(), Some("orders::place"));
+        assert_eq!(resolver.follow_reexports("orders::place"), None);
+    }
+
     #[test]
     fn test_resolve_all_references() {
         let mut symbols = create_test_symbols();
@@ -551,6 +760,9 @@ mod tests {
             is_synthetic: false,
             macro_context: None,
             synthetic_confidence: 1.0,
+            provenance: CallProvenance::TreeSitter,
+            receiver_type: None,
+            cfg: None,
         };
         symbols.calls.push(call);
 
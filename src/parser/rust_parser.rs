@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::path::Path;
 use tree_sitter::{Language, Parser, Query, QueryCursor, Tree};
 
+use crate::parser::frameworks::ActorFrameworks;
 use crate::parser::symbols::*;
 
 // Hardcoded indicators removed - now discovered dynamically from source code patterns
@@ -236,6 +237,9 @@ impl SyntheticCallGenerator {
                         kind: "expansion".to_string(),
                     }),
                     synthetic_confidence: 0.95,
+                    provenance: CallProvenance::TreeSitter,
+                    receiver_type: None,
+                    cfg: None,
                 });
             } else if pattern.method == "nan" || pattern.method == "na" || pattern.method == "nz" {
                 // For NAN/NZ trait methods, generate calls to multiple possible output types
@@ -270,6 +274,9 @@ impl SyntheticCallGenerator {
                             kind: "expansion".to_string(),
                         }),
                         synthetic_confidence: 0.7, // Lower confidence since we're guessing types
+                        provenance: CallProvenance::TreeSitter,
+                        receiver_type: None,
+                        cfg: None,
                     });
                 }
             } else {
@@ -297,6 +304,9 @@ impl SyntheticCallGenerator {
                         kind: "expansion".to_string(),
                     }),
                     synthetic_confidence: 0.95,
+                    provenance: CallProvenance::TreeSitter,
+                    receiver_type: None,
+                    cfg: None,
                 });
             }
             
@@ -368,11 +378,51 @@ impl RustParser {
         self.parse_source(&source, file_path, crate_name)
     }
 
+    /// Parse a file recognising the actor conventions of `frameworks`
+    pub fn parse_file_with_frameworks(&mut self, file_path: &Path, crate_name: &str, frameworks: &ActorFrameworks) -> Result<ParsedSymbols> {
+        self.parse_file_in_module(file_path, crate_name, frameworks, &[])
+    }
+
+    /// Parse a file that is module `module_path` of its crate, so qualified names start with it
+    pub fn parse_file_in_module(
+        &mut self,
+        file_path: &Path,
+        crate_name: &str,
+        frameworks: &ActorFrameworks,
+        module_path: &[String],
+    ) -> Result<ParsedSymbols> {
+        let source = std::fs::read_to_string(file_path)
+            .with_context(|| format!("Failed to read file: {:?}", file_path))?;
+
+        self.parse_source_in_module(&source, file_path, crate_name, frameworks, module_path)
+    }
+
     pub fn parse_source(
         &mut self,
         source: &str,
         file_path: &Path,
         crate_name: &str,
+    ) -> Result<ParsedSymbols> {
+        self.parse_source_with_frameworks(source, file_path, crate_name, &ActorFrameworks::default())
+    }
+
+    pub fn parse_source_with_frameworks(
+        &mut self,
+        source: &str,
+        file_path: &Path,
+        crate_name: &str,
+        frameworks: &ActorFrameworks,
+    ) -> Result<ParsedSymbols> {
+        self.parse_source_in_module(source, file_path, crate_name, frameworks, &[])
+    }
+
+    pub fn parse_source_in_module(
+        &mut self,
+        source: &str,
+        file_path: &Path,
+        crate_name: &str,
+        frameworks: &ActorFrameworks,
+        module_path: &[String],
     ) -> Result<ParsedSymbols> {
         // Use the new AST walker for parsing
         use crate::parser::ast_walker::UnifiedWalker;
@@ -386,7 +436,7 @@ impl RustParser {
             source.as_bytes(),
             crate_name.to_string(),
             PathBuf::from(file_path),
-        );
+        ).with_frameworks(frameworks.clone()).with_module_path(module_path.to_vec());
         
         Ok(walker.walk(tree.root_node()))
     }
@@ -763,6 +813,7 @@ impl RustParser {
                     actor_type: ActorType::Distributed,
                     local_messages: Vec::new(),
                     inferred_from_message: false,
+                    cfg: None,
                 };
                 
                 actors.push(actor);
@@ -851,6 +902,7 @@ impl RustParser {
                         actor_type: ActorImplementationType::Local.into(),
                         local_messages: vec![message_type], // Start with the message we found
                         inferred_from_message: true, // Mark as inferred from Message impl
+                        cfg: None,
                     };
                     
                     actors.push(actor);
@@ -1117,6 +1169,7 @@ impl RustParser {
                         line,
                         function_context: sender_context,
                     },
+                    registered_name: None,
                 };
 
                 message_flows.push(message_flow);
@@ -1255,6 +1308,8 @@ impl RustParser {
                 return_type,
                 embedding_text: None,
                 module: module_path.clone(),
+                cfg: None,
+                attributes: Vec::new(),
             };
 
             function.generate_id();
@@ -1357,6 +1412,7 @@ impl RustParser {
                 embedding_text: None,
                 type_kind: format!("{:?}", kind),
                 module: module_path.clone(),
+                cfg: None,
             };
 
             rust_type.generate_id();
@@ -1623,6 +1679,8 @@ impl RustParser {
                 return_type,
                 embedding_text: None,
                 module: module_path,
+                cfg: None,
+                attributes: Vec::new(),
             };
 
             function.generate_id();
@@ -2190,6 +2248,9 @@ impl RustParser {
                                     kind: "macro_invocation".to_string(),
                                 }),
                                 synthetic_confidence: 0.95,
+                                provenance: CallProvenance::TreeSitter,
+                                receiver_type: None,
+                                cfg: None,
                             };
                             
                             synthetic_calls.push(synthetic_call);
@@ -2420,6 +2481,9 @@ impl RustParser {
             is_synthetic: false,
             macro_context: None, // Regular calls don't have macro context
             synthetic_confidence: 1.0, // Regular calls have full confidence
+            provenance: CallProvenance::TreeSitter,
+            receiver_type: None,
+            cfg: None,
         };
 
         Ok(Some(function_call))
@@ -2768,6 +2832,9 @@ impl RustParser {
             import_type,
             file_path: file_path.to_string_lossy().to_string(),
             line: use_node.start_position().row + 1,
+            crate_name: String::new(),
+            module: String::new(),
+            is_pub: false,
         };
 
         Ok(Some(rust_import))
@@ -2977,6 +3044,7 @@ impl RustParser {
             actor_type: actor_type.into(),
             local_messages: Vec::new(), // Will be populated by message handler detection
             inferred_from_message: false, // Explicitly declared with impl Actor
+            cfg: None,
         };
 
         Ok(Some(actor))
@@ -3076,6 +3144,7 @@ impl RustParser {
                 file_path: file_path.to_string_lossy().to_string(),
                 from_crate: crate_name.to_string(),
                 to_crate: crate_name.to_string(),
+                cfg: None,
             };
 
             return Ok(Some(spawn));
@@ -3111,6 +3180,7 @@ impl RustParser {
                     file_path: file_path.to_string_lossy().to_string(),
                     from_crate: crate_name.to_string(),
                     to_crate: crate_name.to_string(),
+                    cfg: None,
                 };
 
                 return Ok(Some(spawn));
@@ -3149,6 +3219,7 @@ impl RustParser {
                     file_path: file_path.to_string_lossy().to_string(),
                     from_crate: crate_name.to_string(),
                     to_crate: crate_name.to_string(),
+                    cfg: None,
                 };
 
                 return Ok(Some(spawn));
@@ -3180,6 +3251,7 @@ impl RustParser {
                         file_path: file_path.to_string_lossy().to_string(),
                         from_crate: crate_name.to_string(),
                         to_crate: crate_name.to_string(),
+                        cfg: None,
                     };
 
                     return Ok(Some(spawn));
@@ -3350,6 +3422,7 @@ impl RustParser {
                 file_path: file_path.to_string_lossy().to_string(),
                 line,
                 crate_name: crate_name.to_string(),
+                cfg: None,
             };
 
             handler.id = format!("{}:{}->{}:{}", crate_name, actor_type, message_type, line);
@@ -3675,6 +3748,7 @@ impl RustParser {
                     file_path: file_path.to_string_lossy().to_string(),
                     from_crate: crate_name.to_string(),
                     to_crate: None, // Could be enhanced
+                    cfg: None,
                 };
 
                 send.id = format!(
@@ -4088,6 +4162,7 @@ impl RustParser {
             actor_type: ActorImplementationType::Unknown.into(),
             local_messages: Vec::new(), // Inferred actors have no known local message handlers
             inferred_from_message: false, // Inferred from spawn, not Message impl
+            cfg: None,
         })
     }
 
@@ -4164,6 +4239,7 @@ impl RustParser {
                             actor_type: ActorImplementationType::Local.into(),
                             local_messages: Vec::new(), // Derived actors need separate analysis for message handlers
                             inferred_from_message: false, // Explicitly derived with #[derive(Actor)]
+                            cfg: None,
                         };
                         derive_actors.push(actor);
                     }
@@ -4378,6 +4454,7 @@ impl RustParser {
                         actor_type: ActorImplementationType::Unknown.into(),
                         local_messages: Vec::new(), // Type usage actors have no known local message handlers
                         inferred_from_message: false, // Inferred from ActorRef usage, not Message impl
+                        cfg: None,
                     };
                     type_usage_actors.push(actor);
                 }
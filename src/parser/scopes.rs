@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+
+use crate::parser::symbols::{ImportType, ParsedSymbols};
+
+/// Iterations of the import fixed point; each round settles at least one more level of
+/// `pub use` chains, so this only caps pathological cycles
+const MAX_ROUNDS: usize = 32;
+
+/// What a name bound in a module refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Binding {
+    /// A function or type, by crate and qualified name
+    Item { crate_name: String, qualified_name: String, is_type: bool },
+    /// A module, by its key (`crate_name::module::path`)
+    Module(String),
+}
+
+/// How a name came to be bound; a higher source shadows a lower one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BindingSource {
+    Glob,
+    Import,
+    Local,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    binding: Binding,
+    source: BindingSource,
+    is_pub: bool,
+}
+
+/// A `use` as the fixed point sees it
+struct Import {
+    module: String,
+    is_pub: bool,
+    path: Vec<String>,
+    /// Name bound by an explicit import; `None` for a glob import
+    name: Option<String>,
+}
+
+/// Key of a module: the crate name, underscored, followed by the crate-relative module path
+pub fn module_key(crate_name: &str, module: &str) -> String {
+    let crate_name = crate_name.replace('-', "_");
+    if module.is_empty() {
+        crate_name
+    } else {
+        format!("{}::{}", crate_name, module)
+    }
+}
+
+fn parent_module(key: &str) -> Option<String> {
+    key.rsplit_once("::").map(|(parent, _)| parent.to_string())
+}
+
+/// The names bound in every module of the workspace: items defined there, modules declared
+/// there, and what its `use` declarations import.
+///
+/// Imports are resolved to a fixed point, so glob imports see names that other imports bring
+/// into the module they import from, and `pub use` chains are followed across crates. Local
+/// items shadow imports and explicit imports shadow glob imports, as in rustc.
+#[derive(Debug, Default)]
+pub struct ModuleScopes {
+    scopes: HashMap<String, HashMap<String, Entry>>,
+}
+
+impl ModuleScopes {
+    pub fn build(symbols: &ParsedSymbols) -> Self {
+        let mut scopes = ModuleScopes::default();
+
+        for module in &symbols.modules {
+            let key = module_key(&module.crate_name, &module.path);
+            scopes.scopes.entry(key.clone()).or_default();
+            let parent = module_key(&module.crate_name, module.parent_module.as_deref().unwrap_or(""));
+            scopes.bind(&parent, &module.name, Binding::Module(key), BindingSource::Local, module.is_public);
+        }
+
+        // Only items directly in a module; methods and items nested in functions are not nameable
+        for function in &symbols.functions {
+            if function.qualified_name == qualify(&function.module_path, &function.name) {
+                let binding = Binding::Item {
+                    crate_name: function.crate_name.clone(),
+                    qualified_name: function.qualified_name.clone(),
+                    is_type: false,
+                };
+                let key = module_key(&function.crate_name, &function.module_path);
+                scopes.bind(&key, &function.name, binding, BindingSource::Local, function.visibility != "private");
+            }
+        }
+        for rust_type in &symbols.types {
+            if rust_type.qualified_name == qualify(&rust_type.module_path, &rust_type.name) {
+                let binding = Binding::Item {
+                    crate_name: rust_type.crate_name.clone(),
+                    qualified_name: rust_type.qualified_name.clone(),
+                    is_type: true,
+                };
+                let key = module_key(&rust_type.crate_name, &rust_type.module_path);
+                scopes.bind(&key, &rust_type.name, binding, BindingSource::Local, rust_type.visibility != "private");
+            }
+        }
+
+        // Imports recorded by the walker know their crate; older records carry no module
+        let imports: Vec<Import> = symbols.imports.iter()
+            .filter(|import| !import.crate_name.is_empty())
+            .flat_map(|import| {
+                let module = module_key(&import.crate_name, &import.module);
+                let base: Vec<String> = import.module_path.split("::").filter(|s| !s.is_empty()).map(str::to_string).collect();
+                let imports: Vec<Import> = match import.import_type {
+                    ImportType::Glob => vec![Import { module, is_pub: import.is_pub, path: base, name: None }],
+                    _ => import.imported_items.iter()
+                        .map(|item| Import {
+                            module: module.clone(),
+                            is_pub: import.is_pub,
+                            path: base.iter().cloned().chain(std::iter::once(item.name.clone())).collect(),
+                            name: Some(item.alias.clone().unwrap_or_else(|| item.name.clone())),
+                        })
+                        .collect(),
+                };
+                imports
+            })
+            .collect();
+        for import in &imports {
+            scopes.scopes.entry(import.module.clone()).or_default();
+        }
+
+        for _ in 0..MAX_ROUNDS {
+            let mut changed = false;
+            for import in &imports {
+                changed |= scopes.apply(import);
+            }
+            if !changed {
+                break;
+            }
+        }
+        scopes
+    }
+
+    /// Bind `name` in `module` unless a binding of higher precedence is already there.
+    /// Returns whether anything changed.
+    fn bind(&mut self, module: &str, name: &str, binding: Binding, source: BindingSource, is_pub: bool) -> bool {
+        let scope = self.scopes.entry(module.to_string()).or_default();
+        match scope.get_mut(name) {
+            Some(existing) if existing.source > source => false,
+            // Same precedence: the first binding stays; two globs naming different items are ambiguous
+            Some(existing) if existing.source == source => {
+                let widen = existing.binding == binding && is_pub && !existing.is_pub;
+                if widen {
+                    existing.is_pub = true;
+                }
+                widen
+            }
+            _ => {
+                scope.insert(name.to_string(), Entry { binding, source, is_pub });
+                true
+            }
+        }
+    }
+
+    fn apply(&mut self, import: &Import) -> bool {
+        let Some(target) = self.resolve_segments(&import.module, &import.path) else {
+            return false;
+        };
+        match &import.name {
+            Some(name) => self.bind(&import.module, name, target, BindingSource::Import, import.is_pub),
+            None => {
+                let Binding::Module(target) = target else { return false };
+                // Private names are visible to the module itself and its descendants (`use super::*`)
+                let sees_private = import.module == target || import.module.starts_with(&format!("{}::", target));
+                let names: Vec<(String, Binding)> = self.scopes.get(&target)
+                    .map(|scope| {
+                        scope.iter()
+                            .filter(|(_, entry)| sees_private || entry.is_pub)
+                            .map(|(name, entry)| (name.clone(), entry.binding.clone()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let mut changed = false;
+                for (name, binding) in names {
+                    changed |= self.bind(&import.module, &name, binding, BindingSource::Glob, import.is_pub);
+                }
+                changed
+            }
+        }
+    }
+
+    /// What `path` names when written in `module`: `crate::`, `self::` and `super::` paths,
+    /// names in scope there, and other crates of the workspace by name
+    fn resolve_segments(&self, module: &str, path: &[String]) -> Option<Binding> {
+        let (first, rest) = path.split_first()?;
+        let mut current = match first.as_str() {
+            "crate" => Binding::Module(module.split("::").next()?.to_string()),
+            "self" => Binding::Module(module.to_string()),
+            "super" => Binding::Module(parent_module(module)?),
+            name => match self.scopes.get(module).and_then(|scope| scope.get(name)) {
+                Some(entry) => entry.binding.clone(),
+                None => {
+                    let crate_root = name.replace('-', "_");
+                    if !self.scopes.contains_key(&crate_root) {
+                        return None;
+                    }
+                    Binding::Module(crate_root)
+                }
+            },
+        };
+        for segment in rest {
+            let Binding::Module(key) = &current else { return None };
+            current = if segment == "super" {
+                Binding::Module(parent_module(key)?)
+            } else {
+                self.scopes.get(key)?.get(segment)?.binding.clone()
+            };
+        }
+        Some(current)
+    }
+
+    /// What `name` refers to inside `module` of `crate_name`
+    pub fn lookup(&self, crate_name: &str, module: &str, name: &str) -> Option<&Binding> {
+        Some(&self.scopes.get(&module_key(crate_name, module))?.get(name)?.binding)
+    }
+
+    /// What a `::`-separated path refers to when written inside `module` of `crate_name`
+    pub fn resolve_path(&self, crate_name: &str, module: &str, path: &str) -> Option<Binding> {
+        let segments: Vec<String> = path.split("::").map(|s| s.trim().to_string()).collect();
+        self.resolve_segments(&module_key(crate_name, module), &segments)
+    }
+}
+
+fn qualify(module: &str, name: &str) -> String {
+    if module.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}::{}", module, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{ActorFrameworks, ModuleTree, RustParser};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// Parse crates laid out on disk as `(crate, [(path under src, source)])`
+    fn workspace(root: &Path, crates: &[(&str, &[(&str, &str)])]) -> ParsedSymbols {
+        let mut parser = RustParser::new().unwrap();
+        let mut symbols = ParsedSymbols::new();
+        for (crate_name, files) in crates {
+            let crate_path = root.join(crate_name);
+            for (relative, source) in files.iter() {
+                let path = crate_path.join("src").join(relative);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, source).unwrap();
+            }
+            let tree = ModuleTree::build(crate_name, &crate_path, &[]);
+            symbols.modules.extend_from_slice(tree.modules());
+            for (relative, _) in files.iter() {
+                let path = crate_path.join("src").join(relative);
+                let module_path = tree.module_path(&path);
+                symbols.merge(parser.parse_file_in_module(&path, crate_name, &ActorFrameworks::default(), &module_path).unwrap());
+            }
+        }
+        symbols
+    }
+
+    fn item(binding: Option<&Binding>) -> Option<(&str, &str)> {
+        match binding? {
+            Binding::Item { crate_name, qualified_name, .. } => Some((crate_name.as_str(), qualified_name.as_str())),
+            Binding::Module(_) => None,
+        }
+    }
+
+    #[test]
+    fn test_local_items_shadow_glob_imports() {
+        let temp_dir = TempDir::new().unwrap();
+        let symbols = workspace(temp_dir.path(), &[("app", &[
+            ("lib.rs", "mod util;\nmod strategy;\n"),
+            ("util.rs", "pub fn score() {}\npub fn weight() {}\npub struct Signal;\n"),
+            ("strategy.rs", "use crate::util::*;\nuse crate::util::weight as util_weight;\npub fn score() { weight(); }\nfn weight() {}\n"),
+        ])]);
+        let scopes = ModuleScopes::build(&symbols);
+
+        // Defined locally: the glob import does not override them
+        assert_eq!(item(scopes.lookup("app", "strategy", "score")), Some(("app", "strategy::score")));
+        assert_eq!(item(scopes.lookup("app", "strategy", "weight")), Some(("app", "strategy::weight")));
+        // Only reachable through the glob, or renamed
+        assert_eq!(item(scopes.lookup("app", "strategy", "Signal")), Some(("app", "util::Signal")));
+        assert_eq!(item(scopes.lookup("app", "strategy", "util_weight")), Some(("app", "util::weight")));
+    }
+
+    #[test]
+    fn test_explicit_imports_shadow_globs_and_private_items_stay_private() {
+        let temp_dir = TempDir::new().unwrap();
+        let symbols = workspace(temp_dir.path(), &[("app", &[
+            ("lib.rs", "mod a;\nmod b;\nmod c;\n"),
+            ("a.rs", "pub fn run() {}\nfn hidden() {}\n"),
+            ("b.rs", "pub fn run() {}\n"),
+            ("c.rs", "use crate::a::*;\nuse crate::b::run;\n#[cfg(test)]\nmod tests {\n    use super::*;\n}\n"),
+        ])]);
+        let scopes = ModuleScopes::build(&symbols);
+
+        assert_eq!(item(scopes.lookup("app", "c", "run")), Some(("app", "b::run")));
+        assert!(scopes.lookup("app", "c", "hidden").is_none(), "private items are not glob-imported");
+        // `use super::*` sees everything its parent imported, private imports included
+        assert_eq!(item(scopes.lookup("app", "c::tests", "run")), Some(("app", "b::run")));
+    }
+
+    #[test]
+    fn test_prelude_reexport_chains_across_crates() {
+        let temp_dir = TempDir::new().unwrap();
+        let symbols = workspace(temp_dir.path(), &[
+            ("core-types", &[
+                ("lib.rs", "pub mod prelude;\nmod order;\n"),
+                ("order.rs", "pub struct Order;\npub fn place() {}\n"),
+                // Declared before the module it re-exports from has been resolved
+                ("prelude.rs", "pub use crate::order::*;\npub use super::order::place as submit;\n"),
+            ]),
+            ("engine", &[
+                ("lib.rs", "pub use core_types::prelude::*;\n"),
+                ("main.rs", "use engine::*;\nfn main() { submit(); }\n"),
+            ]),
+        ]);
+        let scopes = ModuleScopes::build(&symbols);
+
+        assert_eq!(item(scopes.lookup("engine", "", "Order")), Some(("core-types", "order::Order")));
+        assert_eq!(item(scopes.lookup("engine", "", "submit")), Some(("core-types", "order::place")));
+        assert_eq!(
+            item(scopes.resolve_path("engine", "", "core_types::prelude::place").as_ref()),
+            Some(("core-types", "order::place"))
+        );
+    }
+}
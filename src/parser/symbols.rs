@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Context information for functions during parsing
@@ -27,6 +28,32 @@ pub struct MacroInvocation {
     pub line_range: (usize, usize),
     pub containing_function: Option<String>,
     pub arguments: String,
+    #[serde(default)]
+    pub crate_name: String,
+    #[serde(default)]
+    pub file_path: String,
+    /// Inline modules enclosing the invocation
+    #[serde(default)]
+    pub module_path: Vec<String>,
+    /// `(type, trait)` of the impl block enclosing the invocation
+    #[serde(default)]
+    pub impl_context: Option<(String, Option<String>)>,
+    #[serde(default)]
+    pub cfg: Option<String>,
+}
+
+/// A `macro_rules!` definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroDefinition {
+    pub name: String,
+    pub crate_name: String,
+    pub file_path: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    /// `#[macro_export]`, i.e. usable from other crates
+    pub is_exported: bool,
+    /// Source text of the whole definition, rules included
+    pub source: String,
 }
 
 /// Trait definition information
@@ -49,15 +76,44 @@ pub struct ParsedSymbols {
     pub calls: Vec<FunctionCall>,
     pub actors: Vec<RustActor>,
     pub actor_spawns: Vec<ActorSpawn>,
+    /// `spawn_link` / `link` relationships between actors
+    #[serde(default)]
+    pub actor_links: Vec<ActorLink>,
+    /// Lifecycle hooks overridden in `impl Actor` blocks
+    #[serde(default)]
+    pub actor_hooks: Vec<ActorLifecycleHook>,
     pub message_types: Vec<MessageType>,
     pub message_handlers: Vec<MessageHandler>,
     pub message_sends: Vec<MessageSend>,
     pub distributed_actors: Vec<DistributedActor>,
     pub distributed_message_flows: Vec<DistributedMessageFlow>,
+    /// Actors published on the swarm under a name with `register`
+    #[serde(default)]
+    pub actor_registrations: Vec<ActorRegistration>,
+    /// Remote actors looked up by registered name
+    #[serde(default)]
+    pub remote_lookups: Vec<RemoteLookup>,
     pub macro_expansions: Vec<MacroExpansion>,
     pub traits: Vec<RustTrait>,
     pub function_calls: Vec<FunctionCall>,
     pub macro_invocations: Vec<MacroInvocation>,
+    #[serde(default)]
+    pub macro_definitions: Vec<MacroDefinition>,
+    /// `pub use` re-exports, from the crate's module tree
+    #[serde(default)]
+    pub reexports: Vec<ReExport>,
+    /// Implementations a trait method call may reach, filled in by reference resolution
+    #[serde(default)]
+    pub trait_dispatches: Vec<TraitDispatch>,
+    /// Functions used as values rather than called
+    #[serde(default)]
+    pub fn_references: Vec<FnReference>,
+    /// Types named by signatures, fields, bounds and bodies
+    #[serde(default)]
+    pub type_usages: Vec<TypeUsage>,
+    /// Embedding vectors keyed by function/type id, filled in by `EmbeddingGenerator`
+    #[serde(default)]
+    pub embeddings: HashMap<String, Vec<f32>>,
 }
 
 
@@ -85,6 +141,12 @@ pub struct RustFunction {
     pub return_type: Option<String>,
     pub embedding_text: Option<String>,
     pub module: String,
+    /// `cfg` predicate gating this item, nested predicates combined with `all(..)`
+    #[serde(default)]
+    pub cfg: Option<String>,
+    /// Outer attributes as written, e.g. `#[no_mangle]` or `#[tokio::main]`
+    #[serde(default)]
+    pub attributes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,6 +178,9 @@ pub struct RustType {
     pub embedding_text: Option<String>,
     pub type_kind: String,
     pub module: String,
+    /// `cfg` predicate gating this item, nested predicates combined with `all(..)`
+    #[serde(default)]
+    pub cfg: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -159,6 +224,26 @@ pub struct RustImport {
     pub import_type: ImportType,
     pub file_path: String,
     pub line: usize,
+    #[serde(default)]
+    pub crate_name: String,
+    /// Module the `use` is declared in, crate-relative like qualified names
+    #[serde(default)]
+    pub module: String,
+    /// `pub use`: the imported names are re-exported from `module`
+    #[serde(default)]
+    pub is_pub: bool,
+}
+
+/// An item made visible under a second path with `pub use`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReExport {
+    pub crate_name: String,
+    /// Path the item is re-exported under, e.g. `api::place` for `pub use crate::orders::place;` in `api`
+    pub path: String,
+    /// Path of the item itself, e.g. `orders::place`; items of other crates keep the crate name
+    pub target: String,
+    pub file_path: String,
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -201,6 +286,129 @@ pub struct FunctionCall {
     pub is_synthetic: bool,
     pub macro_context: Option<MacroContext>, // Links to originating macro
     pub synthetic_confidence: f32,           // Confidence in synthetic call
+    #[serde(default)]
+    pub provenance: CallProvenance,          // Which analysis produced or vouched for this edge
+    #[serde(default)]
+    pub receiver_type: Option<ReceiverType>, // Inferred type of `recv` in `recv.method()`
+    #[serde(default)]
+    pub cfg: Option<String>,                 // `cfg` predicate of the enclosing item or statement
+}
+
+/// A function path used as a value: passed as an argument (`map(Self::convert)`,
+/// `Box::new(handler)`), stored in a field or binding, or returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FnReference {
+    /// Qualified name of the enclosing function, as for calls
+    pub caller_id: String,
+    pub caller_module: String,
+    /// Path as written, with `Self` replaced by the impl's type
+    pub path: String,
+    /// Qualified name of the referenced function, once resolved
+    pub qualified_target: Option<String>,
+    pub from_crate: String,
+    pub to_crate: Option<String>,
+    pub line: usize,
+    pub file_path: String,
+    #[serde(default)]
+    pub cfg: Option<String>,
+}
+
+/// Where a [`TypeUsage`] names its type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TypeUsageKind {
+    Parameter,
+    Return,
+    Field,
+    /// Generic bound or `where` predicate
+    Bound,
+    StructLiteral,
+    /// `Type::` path in a body: associated functions, constants and enum variants
+    Path,
+    /// `Enum::Variant` in a `match` arm, `if let` or other pattern
+    Pattern,
+    /// `value.field` on a value of the type
+    FieldAccess,
+}
+
+impl TypeUsageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TypeUsageKind::Parameter => "parameter",
+            TypeUsageKind::Return => "return",
+            TypeUsageKind::Field => "field",
+            TypeUsageKind::Bound => "bound",
+            TypeUsageKind::StructLiteral => "struct_literal",
+            TypeUsageKind::Path => "path",
+            TypeUsageKind::Pattern => "pattern",
+            TypeUsageKind::FieldAccess => "field_access",
+        }
+    }
+}
+
+/// A type named by a function's signature or body, or by another type's fields and bounds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeUsage {
+    /// Qualified name of the function or type naming the type
+    pub user: String,
+    pub user_is_type: bool,
+    pub user_module: String,
+    /// Path as written, with `Self` replaced by the impl's type; empty for field accesses,
+    /// whose type comes from `receiver`
+    pub type_path: String,
+    /// Qualified name of the workspace type, once resolved
+    pub qualified_type: Option<String>,
+    pub kind: TypeUsageKind,
+    /// Item named through the type: the variant, associated function or field
+    #[serde(default)]
+    pub member: Option<String>,
+    /// Named as `dyn Trait`
+    #[serde(default)]
+    pub is_dyn: bool,
+    /// Inferred type of the value a field is read from
+    #[serde(default)]
+    pub receiver: Option<ReceiverType>,
+    pub from_crate: String,
+    pub to_crate: Option<String>,
+    pub line: usize,
+    pub file_path: String,
+    #[serde(default)]
+    pub cfg: Option<String>,
+}
+
+/// Type of a method call receiver as inferred from the enclosing function body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReceiverType {
+    /// Bare type name taken from an annotation, constructor, parameter or `self`
+    Named(String),
+    /// `owner.field`; the field's type is looked up once all struct definitions are known
+    Field { owner: Box<ReceiverType>, field: String },
+    /// `dyn Trait`, `impl Trait` or a generic parameter: any type implementing `traits`
+    Bounded { traits: Vec<String>, written: String },
+}
+
+/// Where a call edge comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallProvenance {
+    /// Found by tree-sitter and not checked against an LSP server
+    #[default]
+    TreeSitter,
+    /// Found by tree-sitter; the LSP server resolved the call to the same function
+    LspConfirmed,
+    /// Found by tree-sitter; the LSP server resolved the call to a different function
+    LspCorrected,
+    /// Missed by tree-sitter; reported by the LSP server's call hierarchy
+    LspDiscovered,
+}
+
+impl CallProvenance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallProvenance::TreeSitter => "tree_sitter",
+            CallProvenance::LspConfirmed => "lsp_confirmed",
+            CallProvenance::LspCorrected => "lsp_corrected",
+            CallProvenance::LspDiscovered => "lsp_discovered",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -228,6 +436,8 @@ pub struct RustActor {
     pub actor_type: ActorType,
     pub local_messages: Vec<String>,
     pub inferred_from_message: bool,
+    #[serde(default)]
+    pub cfg: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -276,6 +486,8 @@ pub struct ActorSpawn {
     pub file_path: String,
     pub from_crate: String,
     pub to_crate: String,
+    #[serde(default)]
+    pub cfg: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -306,6 +518,108 @@ impl std::fmt::Display for SpawnPattern {
     }
 }
 
+/// A method call on a trait object, `impl Trait` or bounded generic, paired with one workspace
+/// implementation it may dispatch to at runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraitDispatch {
+    /// Qualified name of the calling function
+    pub caller_id: String,
+    pub from_crate: String,
+    /// Qualified name of the trait declaring the method
+    pub trait_name: String,
+    pub method_name: String,
+    /// Receiver type as written, e.g. `&dyn Store` or `S`
+    pub receiver: String,
+    /// Qualified name of the implementing method
+    pub target: String,
+    pub target_crate: String,
+    pub implementing_type: String,
+    pub line: usize,
+    pub file_path: String,
+    #[serde(default)]
+    pub cfg: Option<String>,
+}
+
+/// A kameo link between two actors: when one dies, the other's `on_link_died` runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorLink {
+    /// Actor the link is made from; for `spawn_link` this is the supervising actor
+    pub supervisor: String,
+    pub child: String,
+    pub kind: LinkKind,
+    pub line: usize,
+    pub file_path: String,
+    pub from_crate: String,
+    #[serde(default)]
+    pub cfg: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkKind {
+    SpawnLink,  // Child::spawn_link(&supervisor_ref, args)
+    Link,       // actor_ref.link(&other_ref)
+}
+
+impl std::fmt::Display for LinkKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkKind::SpawnLink => write!(f, "spawn_link"),
+            LinkKind::Link => write!(f, "link"),
+        }
+    }
+}
+
+/// An overridden `Actor` lifecycle method such as `on_link_died` or `on_panic`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorLifecycleHook {
+    pub actor_name: String,
+    pub hook: LifecycleHook,
+    pub behavior: HookBehavior,
+    pub line: usize,
+    pub file_path: String,
+    pub crate_name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LifecycleHook {
+    OnStart,
+    OnStop,
+    OnPanic,
+    OnLinkDied,
+}
+
+impl LifecycleHook {
+    pub fn from_method_name(name: &str) -> Option<Self> {
+        match name {
+            "on_start" => Some(LifecycleHook::OnStart),
+            "on_stop" => Some(LifecycleHook::OnStop),
+            "on_panic" => Some(LifecycleHook::OnPanic),
+            "on_link_died" => Some(LifecycleHook::OnLinkDied),
+            _ => None,
+        }
+    }
+}
+
+/// What an overridden hook does, judged from the `ControlFlow` values its body returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HookBehavior {
+    Continue,     // Only returns ControlFlow::Continue; the actor keeps running
+    Stop,         // Only returns ControlFlow::Break; the actor stops
+    Restart,      // Spawns a replacement actor before continuing
+    Conditional,  // Returns both, or something we cannot classify
+}
+
+impl std::fmt::Display for HookBehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookBehavior::Continue => write!(f, "continue"),
+            HookBehavior::Stop => write!(f, "stop"),
+            HookBehavior::Restart => write!(f, "restart"),
+            HookBehavior::Conditional => write!(f, "conditional"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageType {
     pub id: String,
@@ -341,6 +655,8 @@ pub struct MessageHandler {
     pub file_path: String,
     pub line: usize,
     pub crate_name: String,
+    #[serde(default)]
+    pub cfg: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -357,6 +673,8 @@ pub struct MessageSend {
     pub file_path: String,
     pub from_crate: String,
     pub to_crate: Option<String>,
+    #[serde(default)]
+    pub cfg: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -376,15 +694,25 @@ impl ParsedSymbols {
             calls: Vec::new(),
             actors: Vec::new(),
             actor_spawns: Vec::new(),
+            actor_links: Vec::new(),
+            actor_hooks: Vec::new(),
             message_types: Vec::new(),
             message_handlers: Vec::new(),
             message_sends: Vec::new(),
             distributed_actors: Vec::new(),
             distributed_message_flows: Vec::new(),
+            actor_registrations: Vec::new(),
+            remote_lookups: Vec::new(),
             macro_expansions: Vec::new(),
             traits: Vec::new(),
             function_calls: Vec::new(),
             macro_invocations: Vec::new(),
+            macro_definitions: Vec::new(),
+            reexports: Vec::new(),
+            trait_dispatches: Vec::new(),
+            fn_references: Vec::new(),
+            type_usages: Vec::new(),
+            embeddings: HashMap::new(),
         }
     }
 
@@ -431,12 +759,23 @@ impl ParsedSymbols {
         self.impls.extend(other.impls);
         self.calls.extend(other.calls);
         self.actors.extend(other.actors);
+        self.actor_links.extend(other.actor_links);
+        self.actor_hooks.extend(other.actor_hooks);
         self.message_types.extend(other.message_types);
         self.message_handlers.extend(other.message_handlers);
         self.message_sends.extend(other.message_sends);
         self.distributed_actors.extend(other.distributed_actors);
         self.distributed_message_flows.extend(other.distributed_message_flows);
+        self.actor_registrations.extend(other.actor_registrations);
+        self.remote_lookups.extend(other.remote_lookups);
         self.macro_expansions.extend(other.macro_expansions);
+        self.macro_invocations.extend(other.macro_invocations);
+        self.macro_definitions.extend(other.macro_definitions);
+        self.reexports.extend(other.reexports);
+        self.trait_dispatches.extend(other.trait_dispatches);
+        self.fn_references.extend(other.fn_references);
+        self.type_usages.extend(other.type_usages);
+        self.embeddings.extend(other.embeddings);
     }
 
     pub fn get_function_by_name(&self, name: &str) -> Option<&RustFunction> {
@@ -594,6 +933,32 @@ pub struct DistributedMessageFlow {
     pub target_crate: String,
     pub send_method: MessageSendMethod,  // tell or ask
     pub send_location: MessageSendLocation,
+    #[serde(default)]
+    pub registered_name: Option<String>, // Name the target was looked up by, if any
+}
+
+/// `actor_ref.register("name")`: publishes an actor on the swarm under a string name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorRegistration {
+    pub registered_name: String,
+    /// Actor behind the registered reference, when it could be inferred
+    pub actor_name: Option<String>,
+    pub function: Option<String>,
+    pub crate_name: String,
+    pub file_path: String,
+    pub line: usize,
+}
+
+/// `RemoteActorRef::<A>::lookup("name")`: finds a remote actor by its registered name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteLookup {
+    pub registered_name: String,
+    /// Actor type named in the lookup's type argument or binding annotation
+    pub actor_name: Option<String>,
+    pub function: Option<String>,
+    pub crate_name: String,
+    pub file_path: String,
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
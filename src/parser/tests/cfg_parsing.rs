@@ -0,0 +1,159 @@
+use crate::parser::cfg::{filter_inactive, ActiveCfg};
+use crate::parser::RustParser;
+use std::path::Path;
+
+const FEATURE_GATED_BACKENDS: &str = r#"
+#[cfg(feature = "postgres")]
+pub fn connect() -> Pool { postgres_pool() }
+
+#[cfg(not(feature = "postgres"))]
+pub fn connect() -> Pool { sqlite_pool() }
+
+#[cfg(unix)]
+mod platform {
+    #[cfg(target_os = "linux")]
+    pub fn watch() {}
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "metrics")] {
+        pub fn record() { metrics_backend(); }
+    } else {
+        pub fn record() {}
+    }
+}
+
+pub fn run() {
+    #[cfg(feature = "postgres")]
+    migrate();
+    connect();
+}
+"#;
+
+fn cfg_of<'a>(symbols: &'a crate::parser::ParsedSymbols, name: &str) -> Vec<Option<&'a str>> {
+    symbols.functions.iter()
+        .filter(|f| f.name == name)
+        .map(|f| f.cfg.as_deref())
+        .collect()
+}
+
+#[test]
+fn test_cfg_predicates_recorded_on_items_and_calls() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let symbols = parser
+        .parse_source(FEATURE_GATED_BACKENDS, Path::new("src/db.rs"), "db")
+        .expect("parse failed");
+
+    assert_eq!(
+        cfg_of(&symbols, "connect"),
+        vec![Some(r#"feature = "postgres""#), Some(r#"not(feature = "postgres")"#)]
+    );
+    assert_eq!(cfg_of(&symbols, "watch"), vec![Some(r#"all(unix, target_os = "linux")"#)]);
+    assert_eq!(cfg_of(&symbols, "run"), vec![None]);
+
+    let migrate = symbols.calls.iter().find(|c| c.callee_name == "migrate").expect("migrate() call");
+    assert_eq!(migrate.cfg.as_deref(), Some(r#"feature = "postgres""#));
+    let sqlite = symbols.calls.iter().find(|c| c.callee_name == "sqlite_pool").expect("sqlite_pool() call");
+    assert_eq!(sqlite.cfg.as_deref(), Some(r#"not(feature = "postgres")"#));
+}
+
+#[test]
+fn test_cfg_if_branches_parsed_as_items() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let symbols = parser
+        .parse_source(FEATURE_GATED_BACKENDS, Path::new("src/db.rs"), "db")
+        .expect("parse failed");
+
+    let record: Vec<_> = symbols.functions.iter().filter(|f| f.name == "record").collect();
+    assert_eq!(record.len(), 2, "both cfg_if! branches should be parsed");
+    assert_eq!(record[0].cfg.as_deref(), Some(r#"feature = "metrics""#));
+    let expected_row = FEATURE_GATED_BACKENDS.lines()
+        .position(|line| line.contains("pub fn record() { metrics_backend"))
+        .unwrap();
+    assert_eq!(record[0].line_start, expected_row, "line numbers should match the original file");
+    assert_eq!(record[1].cfg.as_deref(), Some(r#"not(feature = "metrics")"#));
+    assert!(symbols.calls.iter().any(|c| c.callee_name == "metrics_backend"));
+}
+
+#[test]
+fn test_filter_keeps_only_active_configuration() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let mut symbols = parser
+        .parse_source(FEATURE_GATED_BACKENDS, Path::new("src/db.rs"), "db")
+        .expect("parse failed");
+
+    let active = ActiveCfg {
+        features: ["postgres".to_string()].into_iter().collect(),
+        flags: ["unix".to_string()].into_iter().collect(),
+        target_os: "macos".to_string(),
+        target_family: "unix".to_string(),
+    };
+    filter_inactive(&mut symbols, &active);
+
+    assert_eq!(cfg_of(&symbols, "connect"), vec![Some(r#"feature = "postgres""#)]);
+    assert!(cfg_of(&symbols, "watch").is_empty());
+    assert_eq!(cfg_of(&symbols, "record"), vec![Some(r#"not(feature = "metrics")"#)]);
+    assert!(symbols.calls.iter().any(|c| c.callee_name == "postgres_pool"));
+    assert!(!symbols.calls.iter().any(|c| c.callee_name == "sqlite_pool"));
+}
+
+const GATED_BLOCK: &str = r#"
+pub fn wire(bus: &Bus) {
+    #[cfg(feature = "audit")]
+    {
+        bus.subscribe(audit_hook);
+        let log = AuditLog::open();
+    }
+    bus.subscribe(log_hook);
+}
+
+#[derive(Actor)]
+pub struct Auditor;
+
+impl Message<Flush> for Auditor {
+    type Reply = ();
+    async fn handle(&mut self, _msg: Flush, _ctx: &mut Context<Self, Self::Reply>) {
+        #[cfg(feature = "audit")]
+        self.sink.tell(Record).await;
+    }
+}
+"#;
+
+#[test]
+fn test_filter_drops_references_from_gated_code() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let mut symbols = parser
+        .parse_source(GATED_BLOCK, Path::new("src/wire.rs"), "wire")
+        .expect("parse failed");
+
+    let referenced = |symbols: &crate::parser::ParsedSymbols| -> Vec<String> {
+        symbols.fn_references.iter().map(|r| r.path.clone()).collect()
+    };
+    assert_eq!(referenced(&symbols), vec!["audit_hook", "log_hook"]);
+    assert!(symbols.type_usages.iter().any(|u| u.type_path.starts_with("AuditLog")));
+    assert!(symbols.message_sends.iter().any(|s| s.message_type == "Record"));
+
+    let active = ActiveCfg {
+        features: Default::default(),
+        flags: ["unix".to_string()].into_iter().collect(),
+        target_os: "linux".to_string(),
+        target_family: "unix".to_string(),
+    };
+    filter_inactive(&mut symbols, &active);
+
+    assert_eq!(referenced(&symbols), vec!["log_hook"]);
+    assert!(!symbols.type_usages.iter().any(|u| u.type_path.starts_with("AuditLog")));
+    assert!(symbols.message_sends.is_empty());
+    assert_eq!(symbols.message_handlers.len(), 1, "the ungated handler stays");
+}
+
+#[test]
+fn test_file_level_cfg_applies_to_every_item() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let source = "#![cfg(windows)]\n\npub fn open() {}\n";
+    let symbols = parser
+        .parse_source(source, Path::new("src/win.rs"), "db")
+        .expect("parse failed");
+
+    assert_eq!(cfg_of(&symbols, "open"), vec![Some("windows")]);
+}
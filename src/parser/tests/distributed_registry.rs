@@ -0,0 +1,92 @@
+use crate::analyzer::ActorRegistry;
+use crate::parser::references::resolve_all_references;
+use crate::parser::RustParser;
+use std::path::Path;
+
+const MARKET_NODE: &str = r#"
+use kameo::prelude::*;
+
+pub struct PriceFeed;
+
+impl Actor for PriceFeed {
+    type Args = ();
+    type Error = BoxError;
+}
+
+#[remote_message("7f3c0a")]
+impl Message<Subscribe> for PriceFeed {
+    type Reply = ();
+    async fn handle(&mut self, _: Subscribe, _: &mut Context<Self, ()>) {}
+}
+
+#[remote_message("a91e42")]
+impl Message<Snapshot> for PriceFeed {
+    type Reply = Vec<u64>;
+    async fn handle(&mut self, _: Snapshot, _: &mut Context<Self, Vec<u64>>) -> Vec<u64> { vec![] }
+}
+
+pub async fn serve() {
+    let feed = PriceFeed::spawn(());
+    feed.register("market/prices").await.unwrap();
+}
+"#;
+
+const STRATEGY_NODE: &str = r#"
+use kameo::prelude::*;
+
+pub struct Strategy { feed_ref: RemoteActorRef<PriceFeed> }
+
+pub async fn connect() {
+    let feed_ref = RemoteActorRef::lookup("market/prices").await?.unwrap();
+    feed_ref.tell(&Subscribe).await;
+    let risk = RemoteActorRef::<RiskEngine>::lookup("risk").await?.expect("risk node");
+    risk.ask(&CheckLimits { qty: 1 }).await;
+}
+"#;
+
+fn workspace() -> crate::parser::ParsedSymbols {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let mut symbols = parser
+        .parse_source(MARKET_NODE, Path::new("market/src/lib.rs"), "market")
+        .expect("parse failed");
+    symbols.merge(
+        parser
+            .parse_source(STRATEGY_NODE, Path::new("strategy/src/lib.rs"), "strategy")
+            .expect("parse failed"),
+    );
+    resolve_all_references(&mut symbols).expect("resolve failed");
+    symbols
+}
+
+#[test]
+fn test_remote_message_attribute_marks_distributed_messages() {
+    let symbols = workspace();
+    let feed = symbols.distributed_actors.iter()
+        .find(|a| a.actor_name == "PriceFeed")
+        .expect("PriceFeed is distributed");
+    assert_eq!(feed.distributed_messages, vec!["Subscribe", "Snapshot"]);
+}
+
+#[test]
+fn test_distributed_sends_resolve_through_registry() {
+    let symbols = workspace();
+
+    let registration = &symbols.actor_registrations[0];
+    assert_eq!(registration.registered_name, "market/prices");
+    assert_eq!(registration.actor_name.as_deref(), Some("PriceFeed"));
+
+    let flows: Vec<_> = symbols.distributed_message_flows.iter()
+        .map(|f| (f.message_type.as_str(), f.target_actor.as_str(), f.target_crate.as_str(), f.registered_name.as_deref()))
+        .collect();
+    assert_eq!(flows, vec![
+        // Resolved across crates although the binding name says nothing about the actor
+        ("Subscribe", "PriceFeed", "market", Some("market/prices")),
+        // Nobody registers "risk": the looked up type stands in for the target
+        ("CheckLimits", "RiskEngine", "strategy", Some("risk")),
+    ]);
+
+    let registry = ActorRegistry::from_symbols(&symbols);
+    let unregistered: Vec<_> = registry.unregistered_lookups.iter().map(|l| l.registered_name.as_str()).collect();
+    assert_eq!(unregistered, vec!["risk"]);
+    assert_eq!(registry.resolve("market/prices"), Some("PriceFeed"));
+}
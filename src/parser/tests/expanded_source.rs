@@ -0,0 +1,189 @@
+use crate::parser::symbols::{FunctionContext, ParsedSymbols};
+use crate::parser::{merge_expanded_symbols, RustParser};
+use std::path::Path;
+
+const ORDERS: &str = r#"
+use kameo::Actor;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Order {
+    pub id: u64,
+    pub lines: Vec<Line>,
+}
+
+#[derive(Clone)]
+pub struct Line {
+    pub sku: String,
+}
+
+impl std::fmt::Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.sku)
+    }
+}
+
+#[derive(Actor)]
+pub struct OrderBook;
+"#;
+
+/// What `cargo expand` prints for a `lib.rs` declaring `pub mod orders;`
+const EXPANDED: &str = r#"
+#![feature(prelude_import)]
+#[prelude_import]
+use std::prelude::rust_2021::*;
+#[macro_use]
+extern crate std;
+pub mod orders {
+    use kameo::Actor;
+    use serde::Serialize;
+    pub struct Order {
+        pub id: u64,
+        pub lines: Vec<Line>,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for Order {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::debug_struct_field2_finish(f, "Order", "id", &self.id, "lines", &&self.lines)
+        }
+    }
+    #[automatically_derived]
+    impl ::core::clone::Clone for Order {
+        #[inline]
+        fn clone(&self) -> Order {
+            Order { id: ::core::clone::Clone::clone(&self.id), lines: ::core::clone::Clone::clone(&self.lines) }
+        }
+    }
+    #[doc(hidden)]
+    #[allow(non_upper_case_globals, unused_attributes, unused_qualifications)]
+    const _: () = {
+        #[allow(unused_extern_crates, clippy::useless_attribute)]
+        extern crate serde as _serde;
+        #[automatically_derived]
+        impl _serde::Serialize for Order {
+            fn serialize<__S>(&self, __serializer: __S) -> _serde::__private::Result<__S::Ok, __S::Error>
+            where
+                __S: _serde::Serializer,
+            {
+                let mut __serde_state = _serde::Serializer::serialize_struct(__serializer, "Order", false as usize + 1 + 1)?;
+                _serde::ser::SerializeStruct::serialize_field(&mut __serde_state, "id", &self.id)?;
+                _serde::ser::SerializeStruct::end(__serde_state)
+            }
+        }
+    };
+    pub struct Line {
+        pub sku: String,
+    }
+    #[automatically_derived]
+    impl ::core::clone::Clone for Line {
+        #[inline]
+        fn clone(&self) -> Line {
+            Line { sku: ::core::clone::Clone::clone(&self.sku) }
+        }
+    }
+    impl std::fmt::Display for Line {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_fmt(format_args!("{0}", self.sku))
+        }
+    }
+    pub struct OrderBook;
+    impl ::kameo::actor::Actor for OrderBook {
+        type Args = Self;
+        type Error = ::kameo::error::Infallible;
+        fn name() -> &'static str {
+            "OrderBook"
+        }
+    }
+}
+"#;
+
+fn parse_with_expansion() -> ParsedSymbols {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let mut symbols = parser
+        .parse_source(ORDERS, Path::new("shop/src/orders.rs"), "shop")
+        .expect("parse failed");
+    let expanded = parser
+        .parse_source(EXPANDED, Path::new("shop/src/lib.rs"), "shop")
+        .expect("parse failed");
+    merge_expanded_symbols(&mut symbols, expanded);
+    symbols
+}
+
+fn row(source: &str, prefix: &str) -> usize {
+    source.lines().position(|l| l.starts_with(prefix)).unwrap()
+}
+
+#[test]
+fn test_derives_are_recorded_as_macro_expansions() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let symbols = parser
+        .parse_source(ORDERS, Path::new("shop/src/orders.rs"), "shop")
+        .expect("parse failed");
+
+    let derives: Vec<_> = symbols.macro_expansions.iter()
+        .filter(|e| e.macro_type == "derive")
+        .map(|e| (e.macro_name.as_str(), e.line()))
+        .collect();
+    let order_row = row(ORDERS, "#[derive(Debug");
+    assert_eq!(derives, vec![
+        ("Debug", order_row),
+        ("Clone", order_row),
+        ("Serialize", order_row),
+        ("Clone", row(ORDERS, "#[derive(Clone)]")),
+        ("Actor", row(ORDERS, "#[derive(Actor)]")),
+    ]);
+}
+
+#[test]
+fn test_generated_impls_are_mapped_to_the_original_derive() {
+    let symbols = parse_with_expansion();
+
+    let generated: Vec<_> = symbols.functions.iter()
+        .filter(|f| f.is_trait_impl)
+        .map(|f| (f.qualified_name.as_str(), f.file_path.as_str(), f.line_start))
+        .collect();
+    let order_row = row(ORDERS, "#[derive(Debug");
+    assert_eq!(generated, vec![
+        // Written by hand, so not taken from the expanded source a second time
+        ("Line::fmt", "shop/src/orders.rs", row(ORDERS, "    fn fmt")),
+        ("Order::fmt", "shop/src/orders.rs", order_row),
+        ("Order::clone", "shop/src/orders.rs", order_row),
+        ("Order::serialize", "shop/src/orders.rs", order_row),
+        ("Line::clone", "shop/src/orders.rs", row(ORDERS, "#[derive(Clone)]")),
+        ("OrderBook::name", "shop/src/orders.rs", row(ORDERS, "#[derive(Actor)]")),
+    ]);
+
+    let impls: Vec<_> = symbols.impls.iter()
+        .map(|i| (i.type_name.as_str(), i.trait_name.as_deref().unwrap_or("")))
+        .collect();
+    assert!(impls.contains(&("Order", "_serde::Serialize")));
+    assert!(impls.contains(&("OrderBook", "kameo::actor::Actor")));
+
+    let serialize = symbols.macro_expansions.iter()
+        .find(|e| e.macro_type == "derive" && e.macro_name == "Serialize")
+        .unwrap();
+    assert_eq!(serialize.target_functions, vec!["Order::serialize".to_string()]);
+}
+
+#[test]
+fn test_calls_and_actors_from_generated_code() {
+    let symbols = parse_with_expansion();
+
+    let serialize_calls: Vec<_> = symbols.calls.iter()
+        .filter(|c| c.caller_id == "Order::serialize")
+        .collect();
+    assert!(serialize_calls.iter().any(|c| c.callee_name == "serialize_field"));
+    assert!(serialize_calls.iter().all(|c| c.is_synthetic && c.file_path == "shop/src/orders.rs"));
+    assert_eq!(
+        serialize_calls[0].macro_context.as_ref().map(|m| (m.name.as_str(), m.kind.as_str())),
+        Some(("Serialize", "derive"))
+    );
+
+    let order_book = symbols.actors.iter().find(|a| a.name == "OrderBook").expect("derived actor");
+    assert_eq!((order_book.file_path.as_str(), order_book.line_start), ("shop/src/orders.rs", row(ORDERS, "pub struct OrderBook")));
+    let FunctionContext::TraitImpl { type_name, .. } = &symbols.functions.iter().find(|f| f.qualified_name == "OrderBook::name").unwrap().function_context else {
+        panic!("expected a trait impl");
+    };
+    assert_eq!(type_name, "OrderBook");
+}
@@ -0,0 +1,118 @@
+use crate::config::Config;
+use crate::graph::{GraphBackend, InMemoryGraph};
+use crate::parser::references::resolve_all_references;
+use crate::parser::symbols::ReceiverType;
+use crate::parser::{ParsedSymbols, RustParser};
+use std::path::Path;
+
+const ORDERS: &str = r#"
+pub struct Order { pub id: u32 }
+
+pub struct Store;
+
+impl Store {
+    pub fn new() -> Self { Store }
+    pub fn save(&self) {}
+}
+
+pub struct Converter;
+
+impl Converter {
+    fn convert(id: u32) -> Order { Order { id } }
+
+    pub fn convert_all(&self, ids: Vec<u32>) -> Vec<Order> {
+        ids.into_iter().map(Self::convert).collect()
+    }
+}
+
+fn handler_fn(order: Order) {}
+
+async fn run_loop(interval: u64) {}
+
+pub fn install() -> Box<dyn Fn(Order)> {
+    Box::new(handler_fn)
+}
+
+pub fn start() {
+    tokio::spawn(run_loop(5));
+}
+
+pub fn shadowed(orders: Vec<Order>, handler_fn: u32) {
+    let store = Store::new();
+    orders.iter().for_each(|order| validate(order));
+    run(|store| store.save());
+    store.save();
+    check(handler_fn);
+}
+
+fn validate(order: &Order) {}
+"#;
+
+fn parse() -> ParsedSymbols {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let mut symbols = parser
+        .parse_source(ORDERS, Path::new("orders/src/lib.rs"), "orders")
+        .expect("parse failed");
+    resolve_all_references(&mut symbols).expect("resolve failed");
+    symbols
+}
+
+fn references<'s>(symbols: &'s ParsedSymbols, caller: &str) -> Vec<(&'s str, Option<&'s str>)> {
+    symbols.fn_references.iter()
+        .filter(|r| r.caller_id == caller)
+        .map(|r| (r.path.as_str(), r.qualified_target.as_deref()))
+        .collect()
+}
+
+#[test]
+fn test_functions_used_as_values_are_references() {
+    let symbols = parse();
+
+    assert_eq!(references(&symbols, "Converter::convert_all"), vec![("Converter::convert", Some("Converter::convert"))]);
+    assert_eq!(references(&symbols, "install"), vec![("handler_fn", Some("handler_fn"))]);
+
+    // A call passed as an argument is still a call, not a reference
+    assert!(references(&symbols, "start").is_empty());
+    assert!(symbols.calls.iter().any(|c| c.caller_id == "start" && c.callee_name == "run_loop"));
+
+    // Parameters and closure parameters shadow functions of the same name
+    assert!(references(&symbols, "shadowed").is_empty());
+}
+
+#[test]
+fn test_closures_are_scopes_of_their_enclosing_function() {
+    let symbols = parse();
+
+    let validate = symbols.calls.iter().find(|c| c.callee_name == "validate").unwrap();
+    assert_eq!(validate.caller_id, "shadowed");
+
+    // The closure's `store` parameter hides the outer binding only inside the closure
+    let saves: Vec<_> = symbols.calls.iter()
+        .filter(|c| c.caller_id == "shadowed" && c.callee_name == "save")
+        .map(|c| c.receiver_type.clone())
+        .collect();
+    assert_eq!(saves, vec![None, Some(ReceiverType::Named("Store".to_string()))]);
+}
+
+#[tokio::test]
+async fn test_references_keep_functions_alive_in_the_graph() {
+    let graph = InMemoryGraph::new(Config::default());
+    graph.populate_from_symbols(&parse()).await.unwrap();
+
+    let referrers = graph.find_referrers("handler_fn").await.unwrap();
+    assert_eq!(referrers.len(), 1);
+    assert_eq!(referrers[0].caller.qualified_name, "install");
+    assert_eq!(referrers[0].call_type, "Reference");
+
+    let unreferenced: Vec<String> = graph.find_unreferenced_functions().await.unwrap()
+        .into_iter()
+        .map(|f| f.qualified_name)
+        .collect();
+    assert!(!unreferenced.iter().any(|name| name == "handler_fn" || name == "Converter::convert"), "{:?}", unreferenced);
+
+    let callers = graph.transitive_callers("Converter::convert", 2).await.unwrap();
+    assert!(callers.iter().any(|(f, distance)| f.qualified_name == "Converter::convert_all" && *distance == 1));
+
+    graph.remove_file("orders/src/lib.rs").await.unwrap();
+    assert!(graph.find_referrers("handler_fn").await.unwrap().is_empty());
+}
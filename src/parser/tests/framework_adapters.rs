@@ -0,0 +1,154 @@
+use crate::parser::symbols::{ParsedSymbols, SendMethod};
+use crate::parser::{ActorFrameworks, RustParser};
+use std::path::Path;
+
+const ACTIX_SOURCE: &str = r#"
+use actix::prelude::*;
+
+pub struct Ledger;
+pub struct Deposit { amount: u64 }
+pub struct Balance;
+
+impl Actor for Ledger {
+    type Context = Context<Self>;
+}
+
+impl Handler<Deposit> for Ledger {
+    type Result = ();
+    fn handle(&mut self, msg: Deposit, _ctx: &mut Context<Self>) {}
+}
+
+impl Handler<Balance> for Ledger {
+    type Result = u64;
+    fn handle(&mut self, _: Balance, _ctx: &mut Context<Self>) -> u64 { 0 }
+}
+
+pub async fn run(ledger: Addr<Ledger>) {
+    ledger.do_send(Deposit { amount: 5 });
+    let total = ledger.send(Balance).await.unwrap();
+}
+"#;
+
+const RACTOR_SOURCE: &str = r#"
+use ractor::{cast, call, Actor, ActorRef};
+
+pub struct Counter;
+pub enum CounterMsg { Increment, Get(RpcReplyPort<u64>) }
+
+impl Actor for Counter {
+    type Msg = CounterMsg;
+    type State = u64;
+    type Arguments = ();
+
+    async fn handle(&self, _me: ActorRef<Self::Msg>, msg: Self::Msg, state: &mut u64) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+}
+
+pub async fn run(counter: ActorRef<CounterMsg>) {
+    cast!(counter, CounterMsg::Increment).unwrap();
+    let value = call!(counter, CounterMsg::Get).unwrap();
+}
+"#;
+
+const MPSC_SOURCE: &str = r#"
+use tokio::sync::{mpsc, watch};
+
+pub struct Journal {
+    inbox: mpsc::Receiver<JournalEntry>,
+}
+
+pub struct Settings {
+    updates: watch::Receiver<Config>,
+}
+
+pub struct JournalEntry { line: String }
+
+pub async fn record(tx: mpsc::Sender<JournalEntry>) {
+    tx.send(JournalEntry { line: String::new() }).await.unwrap();
+}
+"#;
+
+fn parse(source: &str, dependencies: &[&str]) -> ParsedSymbols {
+    let dependencies: Vec<String> = dependencies.iter().map(|d| d.to_string()).collect();
+    let frameworks = ActorFrameworks::for_dependencies(&dependencies);
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    parser
+        .parse_source_with_frameworks(source, Path::new("src/lib.rs"), "app", &frameworks)
+        .expect("parse failed")
+}
+
+fn handlers(symbols: &ParsedSymbols) -> Vec<(&str, &str, &str)> {
+    symbols.message_handlers.iter()
+        .map(|h| (h.actor_name.as_str(), h.message_type.as_str(), h.reply_type.as_str()))
+        .collect()
+}
+
+fn sends(symbols: &ParsedSymbols) -> Vec<(&str, SendMethod)> {
+    symbols.message_sends.iter().map(|s| (s.message_type.as_str(), s.send_method.clone())).collect()
+}
+
+#[test]
+fn test_frameworks_selected_from_dependencies() {
+    let names = |deps: &[&str]| {
+        let deps: Vec<String> = deps.iter().map(|d| d.to_string()).collect();
+        ActorFrameworks::for_dependencies(&deps).names()
+    };
+    assert_eq!(names(&["serde", "actix"]), vec!["actix"]);
+    assert_eq!(names(&["kameo", "ractor"]), vec!["kameo", "ractor"]);
+    // tokio alone may still reach a framework through another crate
+    assert_eq!(names(&["tokio"]), vec!["tokio-mpsc", "kameo", "actix", "ractor"]);
+    assert_eq!(names(&[]), ActorFrameworks::default().names());
+}
+
+#[test]
+fn test_actix_handlers_and_sends() {
+    let symbols = parse(ACTIX_SOURCE, &["actix"]);
+
+    assert!(symbols.actors.iter().any(|a| a.name == "Ledger"));
+    assert_eq!(handlers(&symbols), vec![("Ledger", "Deposit", "()"), ("Ledger", "Balance", "u64")]);
+    assert_eq!(sends(&symbols), vec![("Deposit", SendMethod::Tell), ("Balance", SendMethod::Ask)]);
+}
+
+#[test]
+fn test_ractor_message_enum_and_macros() {
+    let symbols = parse(RACTOR_SOURCE, &["ractor"]);
+
+    assert!(symbols.actors.iter().any(|a| a.name == "Counter"));
+    let counter: Vec<_> = handlers(&symbols).into_iter().filter(|(actor, _, _)| *actor == "Counter").collect();
+    assert_eq!(counter.iter().map(|(_, msg, _)| *msg).collect::<Vec<_>>(), vec!["CounterMsg"]);
+
+    assert_eq!(sends(&symbols), vec![("CounterMsg", SendMethod::Tell), ("CounterMsg", SendMethod::Ask)]);
+}
+
+#[test]
+fn test_ractor_macro_message_paths() {
+    let source = r#"
+pub async fn run(counter: ActorRef<CounterMsg>, audit: ActorRef<Flush>) {
+    cast!(counter, crate::msg::CounterMsg::Increment).unwrap();
+    cast!(audit, messages::Flush).unwrap();
+    call!(counter, CounterMsg::Get).unwrap();
+}
+"#;
+    let symbols = parse(source, &["ractor"]);
+
+    assert_eq!(sends(&symbols), vec![
+        ("CounterMsg", SendMethod::Tell),
+        ("Flush", SendMethod::Tell),
+        ("CounterMsg", SendMethod::Ask),
+    ]);
+}
+
+#[test]
+fn test_mpsc_receiver_owner_is_an_actor() {
+    let symbols = parse(MPSC_SOURCE, &["tokio"]);
+
+    let actors: Vec<_> = symbols.actors.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(actors, vec!["Journal"], "watch receivers do not make actors");
+    assert_eq!(handlers(&symbols), vec![("Journal", "JournalEntry", "()")]);
+    assert_eq!(sends(&symbols), vec![("JournalEntry", SendMethod::Tell)]);
+
+    // Without tokio in the dependencies a receiver field is just a field
+    let symbols = parse(MPSC_SOURCE, &["serde"]);
+    assert!(symbols.actors.is_empty());
+}
@@ -292,3 +292,131 @@ async fn main() -> Result<()> {
     assert!(saw_tell, "Did not detect found_ref.tell(..) call");
     assert!(saw_send, "Did not detect addr.send(..) call");
 }
+
+/// Method calls resolve through the receiver's inferred type instead of the first `validate`/`send`
+/// that happens to match by name.
+#[test]
+fn test_method_calls_resolve_through_receiver_type() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+
+    let source = r#"
+pub struct Order;
+impl Order {
+    pub fn new() -> Self { Order }
+    pub fn validate(&self) -> bool { true }
+}
+
+pub struct Quote;
+impl Quote {
+    pub fn validate(&self) -> bool { false }
+}
+
+pub struct Client;
+impl Client {
+    pub fn send(&self) {}
+}
+
+pub struct Gateway {
+    client: Client,
+}
+
+impl Gateway {
+    pub fn submit(&self, quote: &Quote) {
+        let order = Order::new();
+        order.validate();
+        quote.validate();
+        let annotated: Order = make_order();
+        annotated.validate();
+        self.client.send();
+    }
+}
+
+pub fn make_order() -> Order { Order }
+pub fn send() {}
+"#;
+
+    let mut parsed = parser
+        .parse_source(source, Path::new("test.rs"), "test_crate")
+        .expect("parse failed");
+    crate::parser::references::resolve_all_references(&mut parsed).expect("resolver failed");
+
+    let resolved: Vec<Option<String>> = parsed
+        .calls
+        .iter()
+        .filter(|call| call.callee_name == "validate" || call.callee_name == "send")
+        .map(|call| call.qualified_callee.clone())
+        .collect();
+
+    let expected = [
+        ("Order::validate", "order.validate()"),
+        ("Quote::validate", "quote.validate()"),
+        ("Order::validate", "annotated.validate()"),
+        ("Client::send", "self.client.send()"),
+    ];
+    assert_eq!(resolved.len(), expected.len(), "unexpected calls: {:?}", resolved);
+    for (qualified, (suffix, call)) in resolved.iter().zip(expected.iter()) {
+        assert!(
+            qualified.as_deref().is_some_and(|q| q.ends_with(suffix)),
+            "{} resolved to {:?}",
+            call,
+            qualified
+        );
+    }
+}
+
+/// A receiver whose type is known but foreign to the workspace must not fall back to a
+/// same-named workspace function.
+#[test]
+fn test_external_receiver_type_is_not_resolved_by_name() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+
+    let source = r#"
+pub fn push() {}
+
+pub fn demo(items: &mut Vec<u32>) {
+    items.push(1);
+}
+"#;
+
+    let mut parsed = parser
+        .parse_source(source, Path::new("test.rs"), "test_crate")
+        .expect("parse failed");
+    crate::parser::references::resolve_all_references(&mut parsed).expect("resolver failed");
+
+    let call = parsed
+        .calls
+        .iter()
+        .find(|call| call.callee_name == "push")
+        .expect("Did not detect items.push(..) call");
+    assert!(call.qualified_callee.is_none(), "items.push resolved to {:?}", call.qualified_callee);
+}
+
+/// Receivers typed by a generic parameter are known by its bounds and resolve to the trait method
+#[test]
+fn test_generic_receiver_resolves_through_its_bounds() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+
+    let source = r#"
+pub trait Validate { fn validate(&self) -> bool; }
+
+pub fn check<T: Validate>(item: T) -> bool {
+    item.validate()
+}
+"#;
+
+    let mut parsed = parser
+        .parse_source(source, Path::new("test.rs"), "test_crate")
+        .expect("parse failed");
+    crate::parser::references::resolve_all_references(&mut parsed).expect("resolver failed");
+
+    let call = parsed
+        .calls
+        .iter()
+        .find(|call| call.callee_name == "validate")
+        .expect("Did not detect item.validate() call");
+    assert_eq!(
+        call.receiver_type,
+        Some(crate::parser::ReceiverType::Bounded { traits: vec!["Validate".to_string()], written: "T".to_string() })
+    );
+    assert_eq!(call.qualified_callee.as_deref(), Some("Validate::validate"));
+}
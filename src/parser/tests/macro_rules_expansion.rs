@@ -0,0 +1,133 @@
+use crate::parser::references::resolve_all_references;
+use crate::parser::symbols::{FunctionContext, ParsedSymbols};
+use crate::parser::{expand_declarative_macros, RustParser};
+use std::path::Path;
+
+const INDICATORS: &str = r#"
+#[macro_export]
+macro_rules! define_indicators {
+    ($($name:ident => $calc:path),* $(,)?) => {
+        $(
+            pub struct $name;
+            impl $name {
+                pub fn compute(&self, values: &[f64]) -> f64 { $calc(values) }
+            }
+        )*
+        paste::paste! {
+            pub fn compute_all(values: &[f64]) -> Vec<f64> {
+                vec![$( $name.compute(values) ),*]
+            }
+            $( pub fn [<new_ $name:snake>]() -> $name { $name } )*
+        }
+    };
+}
+
+macro_rules! log_call {
+    ($target:ident($($arg:expr),*)) => {{
+        record(stringify!($target));
+        $target($($arg),*)
+    }};
+}
+
+pub fn mean(values: &[f64]) -> f64 { 0.0 }
+pub fn max(values: &[f64]) -> f64 { 0.0 }
+pub fn record(name: &str) {}
+
+define_indicators! {
+    MovingAverage => mean,
+    Highest => max,
+}
+
+pub fn run(values: &[f64]) -> f64 {
+    log_call!(mean(values))
+}
+"#;
+
+const STRATEGY: &str = r#"
+use indicators::define_indicators;
+
+pub fn median(values: &[f64]) -> f64 { 0.0 }
+
+define_indicators!(Median => median);
+"#;
+
+fn workspace() -> ParsedSymbols {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let mut symbols = parser
+        .parse_source(INDICATORS, Path::new("indicators/src/lib.rs"), "indicators")
+        .expect("parse failed");
+    symbols.merge(
+        parser
+            .parse_source(STRATEGY, Path::new("strategy/src/lib.rs"), "strategy")
+            .expect("parse failed"),
+    );
+    expand_declarative_macros(&mut symbols);
+    resolve_all_references(&mut symbols).expect("resolve failed");
+    symbols
+}
+
+#[test]
+fn test_macro_rules_definitions_are_recorded() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let symbols = parser
+        .parse_source(INDICATORS, Path::new("indicators/src/lib.rs"), "indicators")
+        .expect("parse failed");
+
+    let definitions: Vec<_> = symbols.macro_definitions.iter().map(|d| (d.name.as_str(), d.is_exported)).collect();
+    assert_eq!(definitions, vec![("define_indicators", true), ("log_call", false)]);
+    assert!(symbols.functions.iter().all(|f| f.name != "compute"), "nothing is expanded while parsing");
+}
+
+#[test]
+fn test_item_expansion_generates_functions_with_provenance() {
+    let symbols = workspace();
+    let invocation_row = INDICATORS.lines().position(|l| l.starts_with("define_indicators!")).unwrap();
+
+    let compute: Vec<_> = symbols.functions.iter()
+        .filter(|f| f.name == "compute")
+        .map(|f| (f.qualified_name.as_str(), f.crate_name.as_str(), f.line_start))
+        .collect();
+    assert_eq!(compute, vec![
+        ("MovingAverage::compute", "indicators", invocation_row),
+        ("Highest::compute", "indicators", invocation_row),
+        ("Median::compute", "strategy", STRATEGY.lines().position(|l| l.starts_with("define_indicators!")).unwrap()),
+    ]);
+
+    // paste! joined `new_` with the snake-cased type name
+    let constructor = symbols.functions.iter().find(|f| f.name == "new_moving_average").expect("pasted constructor");
+    let FunctionContext::MacroExpansion { macro_info } = &constructor.function_context else {
+        panic!("expected macro provenance, got {:?}", constructor.function_context);
+    };
+    assert_eq!(macro_info.name, "define_indicators");
+    assert_eq!(macro_info.kind, "macro_rules");
+    assert_eq!(macro_info.expansion_site_line, invocation_row);
+
+    // Calls in the expansion are attributed to the generated method and resolved
+    let to_mean: Vec<_> = symbols.calls.iter()
+        .filter(|c| c.callee_name == "mean" && c.caller_id == "MovingAverage::compute")
+        .collect();
+    assert_eq!(to_mean.len(), 1);
+    assert!(to_mean[0].is_synthetic);
+    assert_eq!(to_mean[0].qualified_callee.as_deref(), Some("mean"));
+    assert_eq!(to_mean[0].macro_context.as_ref().map(|m| m.name.as_str()), Some("define_indicators"));
+
+    let expansion = symbols.macro_expansions.iter()
+        .find(|e| e.macro_name == "define_indicators" && e.crate_name == "indicators")
+        .expect("expansion record");
+    assert!(expansion.expanded_content.as_deref().is_some_and(|c| c.contains("pub struct Highest")));
+    assert!(expansion.target_functions.contains(&"max".to_string()));
+}
+
+#[test]
+fn test_statement_expansion_is_attributed_to_the_enclosing_function() {
+    let symbols = workspace();
+
+    let from_run: Vec<_> = symbols.calls.iter()
+        .filter(|c| c.caller_id == "run" && c.is_synthetic)
+        .map(|c| c.callee_name.as_str())
+        .collect();
+    assert_eq!(from_run, vec!["record", "stringify!", "mean"]);
+
+    // The function wrapped around statements to parse them is not reported
+    assert!(symbols.functions.iter().all(|f| f.name != "__expansion"));
+}
@@ -18,6 +18,15 @@ mod reference_parsing;
 mod default_pattern;
 mod instance_method_resolution;
 mod test_trait_method_field_usage;
+mod cfg_parsing;
+mod supervision_parsing;
+mod distributed_registry;
+mod framework_adapters;
+mod macro_rules_expansion;
+mod expanded_source;
+mod trait_dispatch;
+mod fn_references;
+mod type_usage;
 
 // Re-export test runner for use in integration tests
 pub use function_parsing::test_function_parsing;
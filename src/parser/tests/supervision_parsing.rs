@@ -0,0 +1,122 @@
+use crate::analyzer::{FailureOutcome, SupervisionTree};
+use crate::graph::collect_supervision_edges;
+use crate::parser::symbols::{HookBehavior, LifecycleHook, LinkKind, SpawnMethod};
+use crate::parser::RustParser;
+use std::path::Path;
+
+const SUPERVISED_POOL: &str = r#"
+use kameo::prelude::*;
+
+pub struct PoolSupervisor { worker: ActorRef<Worker> }
+pub struct Worker;
+pub struct Cache;
+pub struct Metrics;
+
+impl Actor for PoolSupervisor {
+    type Args = ();
+    type Error = BoxError;
+
+    async fn on_start(_: (), actor_ref: ActorRef<Self>) -> Result<Self, BoxError> {
+        let worker = Worker::spawn_link(&actor_ref, ()).await;
+        let cache = kameo::actor::spawn_link(&actor_ref, Cache {}).await;
+        Ok(PoolSupervisor { worker })
+    }
+
+    async fn on_link_died(&mut self, actor_ref: WeakActorRef<Self>, id: ActorID, reason: ActorStopReason)
+        -> Result<ControlFlow<ActorStopReason>, BoxError> {
+        let supervisor = actor_ref.upgrade().unwrap();
+        self.worker = Worker::spawn_link(&supervisor, ()).await;
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+impl Actor for Worker {
+    type Args = ();
+    type Error = BoxError;
+
+    async fn on_panic(&mut self, _: WeakActorRef<Self>, _: PanicError) -> Result<ControlFlow<ActorStopReason>, BoxError> {
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+impl Actor for Cache {
+    type Args = ();
+    type Error = BoxError;
+
+    async fn on_link_died(&mut self, _: WeakActorRef<Self>, _: ActorID, reason: ActorStopReason)
+        -> Result<ControlFlow<ActorStopReason>, BoxError> {
+        match reason {
+            ActorStopReason::Normal => Ok(ControlFlow::Continue(())),
+            other => Ok(ControlFlow::Break(other)),
+        }
+    }
+}
+
+impl Actor for Metrics {
+    type Args = ();
+    type Error = BoxError;
+}
+
+pub async fn start() {
+    let pool = PoolSupervisor::spawn(());
+    let metrics: ActorRef<Metrics> = Metrics::spawn(());
+    pool.link(&metrics).await;
+    let path = std::path::Path::new("a");
+    path.link(&pool);
+}
+"#;
+
+#[test]
+fn test_spawn_links_and_lifecycle_hooks_extracted() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let symbols = parser
+        .parse_source(SUPERVISED_POOL, Path::new("src/pool.rs"), "pool")
+        .expect("parse failed");
+
+    let links: Vec<_> = symbols.actor_links.iter()
+        .map(|l| (l.supervisor.as_str(), l.child.as_str(), l.kind))
+        .collect();
+    assert_eq!(links, vec![
+        ("PoolSupervisor", "Worker", LinkKind::SpawnLink),
+        ("PoolSupervisor", "Cache", LinkKind::SpawnLink),
+        ("PoolSupervisor", "Worker", LinkKind::SpawnLink),
+        ("PoolSupervisor", "Metrics", LinkKind::Link),
+    ]);
+    assert!(symbols.actor_spawns.iter().all(|s| matches!(s.spawn_method, SpawnMethod::SpawnLink)));
+    assert_eq!(symbols.actor_spawns.len(), 3);
+
+    let hook = |actor: &str, kind: LifecycleHook| {
+        symbols.actor_hooks.iter()
+            .find(|h| h.actor_name == actor && h.hook == kind)
+            .map(|h| h.behavior)
+    };
+    assert_eq!(hook("PoolSupervisor", LifecycleHook::OnLinkDied), Some(HookBehavior::Restart));
+    assert_eq!(hook("Worker", LifecycleHook::OnPanic), Some(HookBehavior::Continue));
+    assert_eq!(hook("Cache", LifecycleHook::OnLinkDied), Some(HookBehavior::Conditional));
+    assert!(hook("PoolSupervisor", LifecycleHook::OnStart).is_some());
+    assert_eq!(hook("Metrics", LifecycleHook::OnLinkDied), None);
+}
+
+#[test]
+fn test_supervision_tree_from_parsed_links() {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let symbols = parser
+        .parse_source(SUPERVISED_POOL, Path::new("src/pool.rs"), "pool")
+        .expect("parse failed");
+
+    let edges = collect_supervision_edges(&symbols);
+    assert_eq!(edges.len(), 4, "links are kept per call site");
+    let tree = SupervisionTree::from_edges(&edges);
+
+    assert_eq!(tree.roots.len(), 1);
+    let pool = &tree.roots[0];
+    assert_eq!(pool.actor, "PoolSupervisor");
+    assert_eq!(pool.restarted_children, vec!["Worker", "Cache", "Metrics"]);
+
+    let worker = pool.children.iter().find(|c| c.actor == "Worker").unwrap();
+    assert_eq!(worker.on_panic.as_deref(), Some("continue"));
+    assert!(worker.stops_with_parent, "Worker keeps kameo's default on_link_died");
+    let cache = pool.children.iter().find(|c| c.actor == "Cache").unwrap();
+    assert!(!cache.stops_with_parent);
+    assert_eq!(cache.on_failure, Some(FailureOutcome::Restart));
+}
@@ -0,0 +1,130 @@
+use crate::config::Config;
+use crate::graph::{GraphBackend, InMemoryGraph};
+use crate::parser::references::resolve_all_references;
+use crate::parser::{ParsedSymbols, RustParser};
+use std::path::Path;
+
+const STORAGE: &str = r#"
+pub trait Store {
+    fn save(&self, key: &str);
+    fn name(&self) -> &str { "store" }
+}
+
+pub struct MemoryStore;
+pub struct DiskStore;
+
+impl Store for MemoryStore {
+    fn save(&self, key: &str) {}
+}
+
+impl crate::Store for DiskStore {
+    fn save(&self, key: &str) {}
+    fn name(&self) -> &str { "disk" }
+}
+
+impl Clone for DiskStore {
+    fn clone(&self) -> Self { DiskStore }
+}
+"#;
+
+const SERVICE: &str = r#"
+use storage::Store;
+
+pub struct Service {
+    store: Box<dyn Store + Send>,
+}
+
+impl Service {
+    pub fn flush(&self) {
+        self.store.save("all");
+    }
+}
+
+pub fn persist(store: &dyn Store) {
+    store.save("one");
+}
+
+pub fn persist_generic<S>(store: &S) where S: Clone + Store {
+    store.save("two");
+    store.clone();
+}
+
+pub fn describe(store: impl Store) {
+    store.name();
+}
+"#;
+
+fn workspace() -> ParsedSymbols {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let mut symbols = parser
+        .parse_source(STORAGE, Path::new("storage/src/lib.rs"), "storage")
+        .expect("parse failed");
+    symbols.merge(
+        parser
+            .parse_source(SERVICE, Path::new("service/src/lib.rs"), "service")
+            .expect("parse failed"),
+    );
+    resolve_all_references(&mut symbols).expect("resolve failed");
+    symbols
+}
+
+fn targets<'s>(symbols: &'s ParsedSymbols, caller: &str, method: &str) -> Vec<(&'s str, &'s str)> {
+    symbols.trait_dispatches.iter()
+        .filter(|d| d.caller_id == caller && d.method_name == method)
+        .map(|d| (d.target.as_str(), d.receiver.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_dyn_impl_and_generic_receivers_dispatch_to_every_impl() {
+    let symbols = workspace();
+
+    assert_eq!(
+        targets(&symbols, "persist", "save"),
+        vec![("DiskStore::save", "&dyn Store"), ("MemoryStore::save", "&dyn Store")]
+    );
+    assert_eq!(
+        targets(&symbols, "persist_generic", "save"),
+        vec![("DiskStore::save", "S"), ("MemoryStore::save", "S")]
+    );
+    // A field holding a boxed trait object
+    assert_eq!(
+        targets(&symbols, "Service::flush", "save"),
+        vec![("DiskStore::save", "Box<dyn Store + Send>"), ("MemoryStore::save", "Box<dyn Store + Send>")]
+    );
+    // Only impls that override the method; the trait's default body is the declaration itself
+    assert_eq!(targets(&symbols, "describe", "name"), vec![("DiskStore::name", "impl Store")]);
+
+    let dispatch = symbols.trait_dispatches.iter().find(|d| d.caller_id == "persist").unwrap();
+    assert_eq!(dispatch.trait_name, "Store");
+    assert_eq!(dispatch.from_crate, "service");
+    assert_eq!(dispatch.target_crate, "storage");
+    assert_eq!(dispatch.implementing_type, "DiskStore");
+
+    // The call itself resolves to the trait's declaration of the method
+    let call = symbols.calls.iter().find(|c| c.caller_id == "persist" && c.callee_name == "save").unwrap();
+    assert_eq!(call.qualified_callee.as_deref(), Some("Store::save"));
+    assert!(call.cross_crate);
+}
+
+#[test]
+fn test_traits_outside_the_workspace_do_not_dispatch() {
+    let symbols = workspace();
+
+    // `Clone` is a bound of `S` but not a workspace trait
+    assert!(targets(&symbols, "persist_generic", "clone").is_empty());
+    assert!(symbols.trait_dispatches.iter().all(|d| d.trait_name == "Store"));
+}
+
+#[tokio::test]
+async fn test_dispatch_edges_reach_the_graph() {
+    let graph = InMemoryGraph::new(Config::default());
+    graph.populate_from_symbols(&workspace()).await.unwrap();
+
+    let edges = graph.trait_dispatches().await.unwrap();
+    assert_eq!(edges.len(), 7);
+    assert!(edges.iter().any(|e| e.caller == "describe" && e.target == "DiskStore::name" && e.receiver == "impl Store"));
+
+    graph.remove_file("storage/src/lib.rs").await.unwrap();
+    assert!(graph.trait_dispatches().await.unwrap().is_empty());
+}
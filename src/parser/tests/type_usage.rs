@@ -0,0 +1,130 @@
+use crate::config::Config;
+use crate::graph::{GraphBackend, InMemoryGraph};
+use crate::parser::references::resolve_all_references;
+use crate::parser::symbols::TypeUsageKind;
+use crate::parser::{ParsedSymbols, RustParser};
+use std::path::Path;
+
+const ORDERS: &str = r#"
+pub struct Order {
+    pub id: u32,
+    pub status: Status,
+}
+
+pub enum Status {
+    Open,
+    Filled { qty: u32 },
+}
+
+pub trait Priced {
+    fn price(&self) -> u64;
+}
+
+impl Order {
+    pub fn new(id: u32) -> Self {
+        Order { id, status: Status::Open }
+    }
+}
+"#;
+
+const SERVICE: &str = r#"
+use orders::{Order, Priced, Status};
+
+pub struct Book {
+    orders: Vec<Order>,
+}
+
+pub fn place(order: &Order) -> Result<Status, String> {
+    let fresh = Order::new(1);
+    Ok(Status::Filled { qty: 1 })
+}
+
+pub fn total<P>(items: &[P]) -> u64 where P: Priced {
+    0
+}
+"#;
+
+fn workspace() -> ParsedSymbols {
+    let mut parser = RustParser::new().expect("Failed to create parser");
+    let mut symbols = parser
+        .parse_source(ORDERS, Path::new("orders/src/lib.rs"), "orders")
+        .expect("parse failed");
+    symbols.merge(
+        parser
+            .parse_source(SERVICE, Path::new("service/src/lib.rs"), "service")
+            .expect("parse failed"),
+    );
+    resolve_all_references(&mut symbols).expect("resolve failed");
+    symbols
+}
+
+fn usages<'s>(symbols: &'s ParsedSymbols, user: &str) -> Vec<(&'s str, TypeUsageKind)> {
+    let mut usages: Vec<_> = symbols.type_usages.iter()
+        .filter(|u| u.user == user)
+        .filter_map(|u| Some((u.qualified_type.as_deref()?, u.kind)))
+        .collect();
+    usages.sort_by_key(|(name, kind)| (*name, kind.as_str()));
+    usages.dedup();
+    usages
+}
+
+#[test]
+fn test_signatures_fields_and_bodies_name_types() {
+    let symbols = workspace();
+
+    assert_eq!(
+        usages(&symbols, "place"),
+        vec![
+            ("Order", TypeUsageKind::Parameter),
+            ("Order", TypeUsageKind::Path),
+            ("Status", TypeUsageKind::Return),
+            // `Status::Filled { .. }` is a variant of `Status`
+            ("Status", TypeUsageKind::StructLiteral),
+        ]
+    );
+    assert_eq!(
+        usages(&symbols, "Order::new"),
+        vec![
+            ("Order", TypeUsageKind::Return),
+            ("Order", TypeUsageKind::StructLiteral),
+            ("Status", TypeUsageKind::Path),
+        ]
+    );
+    assert_eq!(usages(&symbols, "total"), vec![("Priced", TypeUsageKind::Bound)]);
+    assert_eq!(usages(&symbols, "Book"), vec![("Order", TypeUsageKind::Field)]);
+    assert_eq!(usages(&symbols, "Order"), vec![("Status", TypeUsageKind::Field)]);
+
+    // Types from outside the workspace stay unresolved
+    let result = symbols.type_usages.iter().find(|u| u.user == "place" && u.type_path == "Result").unwrap();
+    assert_eq!(result.qualified_type, None);
+
+    let field = symbols.type_usages.iter().find(|u| u.user == "Book" && u.type_path == "Order").unwrap();
+    assert_eq!((field.from_crate.as_str(), field.to_crate.as_deref()), ("service", Some("orders")));
+    assert_eq!(field.line, 4);
+}
+
+#[tokio::test]
+async fn test_type_usage_edges_reach_the_graph() {
+    let graph = InMemoryGraph::new(Config::default());
+    graph.populate_from_symbols(&workspace()).await.unwrap();
+
+    let users: Vec<(String, String, String)> = graph.type_users("Order").await.unwrap()
+        .into_iter()
+        .map(|u| (u.user, u.user_kind, u.usage))
+        .collect();
+    assert_eq!(
+        users,
+        vec![
+            ("Order::new".to_string(), "Function".to_string(), "return".to_string()),
+            ("Order::new".to_string(), "Function".to_string(), "struct_literal".to_string()),
+            ("Book".to_string(), "Type".to_string(), "field".to_string()),
+            ("place".to_string(), "Function".to_string(), "parameter".to_string()),
+            ("place".to_string(), "Function".to_string(), "path".to_string()),
+        ]
+    );
+
+    graph.remove_file("service/src/lib.rs").await.unwrap();
+    let remaining = graph.type_users("Order").await.unwrap();
+    assert!(remaining.iter().all(|u| u.user_crate == "orders"), "{:?}", remaining);
+    assert_eq!(remaining.len(), 2);
+}
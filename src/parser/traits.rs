@@ -1,6 +1,12 @@
-use crate::parser::symbols::{ParsedSymbols, RustFunction, RustImpl, RustType};
+use crate::parser::symbols::{FunctionContext, ParsedSymbols, RustFunction, RustImpl, RustType};
+use crate::parser::type_inference::base_type_name;
 use std::collections::HashMap;
 
+/// Key types and traits by their bare name so `Foo<T>` and `crate::Foo` share entries
+fn index_key(name: &str) -> String {
+    base_type_name(name).unwrap_or_else(|| name.to_string())
+}
+
 /// Index structure for efficient trait and type method resolution
 #[derive(Debug, Clone)]
 pub struct TraitIndex {
@@ -21,6 +27,9 @@ pub struct TraitIndex {
     
     /// Maps type names to their definitions
     pub type_registry: HashMap<String, RustType>,
+
+    /// Maps trait names to the methods declared in the trait body (including default bodies)
+    pub trait_declared_methods: HashMap<String, Vec<RustFunction>>,
 }
 
 impl TraitIndex {
@@ -32,6 +41,7 @@ impl TraitIndex {
             type_to_traits: HashMap::new(),
             function_registry: HashMap::new(),
             type_registry: HashMap::new(),
+            trait_declared_methods: HashMap::new(),
         }
     }
 
@@ -54,35 +64,63 @@ impl TraitIndex {
         
         // Process implementations
         for impl_block in &symbols.impls {
-            // Process impl block
-            
-            if let Some(trait_name) = &impl_block.trait_name {
-                // This is a trait implementation
-                let key = (impl_block.type_name.clone(), trait_name.clone());
-                index.trait_implementations.insert(key, impl_block.methods.clone());
-                
-                // Update type_to_traits mapping
-                index.type_to_traits.entry(impl_block.type_name.clone())
-                    .or_insert_with(Vec::new)
-                    .push(trait_name.clone());
-                
-                // Update trait_methods registry
-                for method in &impl_block.methods {
-                    index.trait_methods.entry(trait_name.clone())
-                        .or_insert_with(Vec::new)
-                        .push(method.name.clone());
+            for method in &impl_block.methods {
+                index.add_impl_method(&impl_block.type_name, impl_block.trait_name.as_deref(), method);
+            }
+        }
+
+        // The AST walker does not emit impl blocks; its methods carry their impl in the context
+        for function in &symbols.functions {
+            match &function.function_context {
+                FunctionContext::RegularImpl { type_name } => {
+                    index.add_impl_method(type_name, None, function);
+                }
+                FunctionContext::TraitImpl { trait_name, type_name } => {
+                    index.add_impl_method(type_name, Some(trait_name), function);
                 }
-            } else {
-                // This is an inherent implementation
-                index.type_inherent_methods.insert(impl_block.type_name.clone(), impl_block.methods.clone());
+                FunctionContext::TraitDeclaration { trait_name } => {
+                    index.trait_declared_methods.entry(index_key(trait_name))
+                        .or_default()
+                        .push(function.clone());
+                }
+                _ => {}
             }
         }
         
         index
     }
+
+    /// Register one method of an inherent (`trait_name == None`) or trait impl of `type_name`
+    fn add_impl_method(&mut self, type_name: &str, trait_name: Option<&str>, method: &RustFunction) {
+        let type_key = index_key(type_name);
+        let methods = match trait_name {
+            Some(trait_name) => {
+                let traits = self.type_to_traits.entry(type_key.clone()).or_default();
+                if !traits.iter().any(|t| t == trait_name) {
+                    traits.push(trait_name.to_string());
+                }
+
+                let trait_methods = self.trait_methods.entry(trait_name.to_string()).or_default();
+                if !trait_methods.contains(&method.name) {
+                    trait_methods.push(method.name.clone());
+                }
+
+                self.trait_implementations.entry((type_key, trait_name.to_string())).or_default()
+            }
+            None => self.type_inherent_methods.entry(type_key).or_default(),
+        };
+
+        // The same method can arrive both through `impls` and `functions`
+        if !methods.iter().any(|m| m.qualified_name == method.qualified_name && m.line_start == method.line_start) {
+            methods.push(method.clone());
+        }
+    }
     
     /// Resolve a Type::method call to the actual function
     pub fn resolve_type_method(&self, type_name: &str, method_name: &str) -> Option<&RustFunction> {
+        let type_name = index_key(type_name);
+        let type_name = type_name.as_str();
+
         // First, try inherent methods (they have higher priority)
         if let Some(inherent_methods) = self.type_inherent_methods.get(type_name) {
             for method in inherent_methods {
@@ -104,11 +142,37 @@ impl TraitIndex {
                     }
                 }
             }
+
+            // Finally, methods the impl left to the trait's default body
+            for trait_name in traits {
+                if let Some(declared) = self.trait_declared_methods.get(&index_key(trait_name)) {
+                    if let Some(method) = declared.iter().find(|m| m.name == method_name) {
+                        return Some(method);
+                    }
+                }
+            }
         }
         
         None
     }
     
+    /// Method `method_name` as declared in the body of the trait, with or without a default body
+    pub fn declared_method(&self, trait_name: &str, method_name: &str) -> Option<&RustFunction> {
+        self.trait_declared_methods.get(&index_key(trait_name))?.iter().find(|m| m.name == method_name)
+    }
+
+    /// Every impl of `method_name` in an `impl Trait for Type` block of the trait, ordered by
+    /// qualified name. Traits are compared by bare name, so `store::Store` matches `Store<K>`.
+    pub fn implementations_of(&self, trait_name: &str, method_name: &str) -> Vec<&RustFunction> {
+        let trait_key = index_key(trait_name);
+        let mut methods: Vec<&RustFunction> = self.trait_implementations.iter()
+            .filter(|((_, implemented), _)| index_key(implemented) == trait_key)
+            .flat_map(|(_, methods)| methods.iter().filter(|m| m.name == method_name))
+            .collect();
+        methods.sort_by(|a, b| (&a.qualified_name, a.line_start).cmp(&(&b.qualified_name, b.line_start)));
+        methods
+    }
+
     /// Resolve a UFCS call <Type as Trait>::method to the actual function
     pub fn resolve_ufcs_call(&self, type_name: &str, trait_name: &str, method_name: &str) -> Option<&RustFunction> {
         let key = (type_name.to_string(), trait_name.to_string());
@@ -200,6 +264,8 @@ mod tests {
             return_type: None,
             embedding_text: None,
             module: module.to_string(),
+            cfg: None,
+            attributes: Vec::new(),
         };
         func.generate_id();
         func
@@ -398,6 +464,7 @@ mod tests {
             embedding_text: None,
             type_kind: "struct".to_string(),
             module: "test".to_string(),
+            cfg: None,
         };
         symbols.types.push(rust_type);
         
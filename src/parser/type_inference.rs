@@ -0,0 +1,505 @@
+use std::collections::{HashMap, HashSet};
+use tree_sitter::Node;
+
+use crate::parser::ast_utils::safe_node_text;
+use crate::parser::frameworks::{actor_ref_target, remote_lookup, spawned_actor};
+use crate::parser::symbols::{Parameter, ReceiverType};
+
+/// Wrappers that auto-deref to their first type argument for method calls
+const DEREF_WRAPPERS: &[&str] = &["Box", "Arc", "Rc"];
+
+/// Reduce a written type to the bare name methods are looked up under.
+///
+/// `&mut crate::orders::Order<T>` becomes `Order` and `Arc<Client>` becomes `Client`.
+/// Returns `None` for types that do not name a single nominal type (`impl Trait`,
+/// `dyn Trait`, tuples, slices, function pointers, associated types).
+pub fn base_type_name(type_text: &str) -> Option<String> {
+    let mut text = type_text.trim();
+    loop {
+        if let Some(rest) = text.strip_prefix('&') {
+            text = rest.trim_start();
+        } else if let Some(rest) = text.strip_prefix("mut ") {
+            text = rest.trim_start();
+        } else if text.starts_with('\'') {
+            // Lifetime on a reference, e.g. `&'a Foo`
+            text = text.split_once(' ').map(|(_, rest)| rest.trim_start())?;
+        } else {
+            break;
+        }
+    }
+
+    if text.is_empty()
+        || text.starts_with("impl ")
+        || text.starts_with("dyn ")
+        || text.starts_with("Self::")
+        || text.starts_with(['(', '[', '*', '<'])
+        || text.starts_with("fn(")
+    {
+        return None;
+    }
+
+    let (path, args) = match text.find('<') {
+        Some(open) => (&text[..open], text[open + 1..].strip_suffix('>')),
+        None => (text, None),
+    };
+    let name = path.rsplit("::").next()?.trim();
+    if name.is_empty() || name == "_" {
+        return None;
+    }
+
+    if DEREF_WRAPPERS.contains(&name) {
+        return args.and_then(first_type_argument).and_then(base_type_name);
+    }
+
+    Some(name.to_string())
+}
+
+/// First top-level argument of a generic argument list (`K, Vec<V>` -> `K`)
+pub fn first_type_argument(args: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    for (idx, ch) in args.char_indices() {
+        match ch {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => return Some(args[..idx].trim()),
+            _ => {}
+        }
+    }
+    Some(args.trim())
+}
+
+/// Every nominal type path a written type or bound list names, in order of appearance.
+///
+/// `Result<Vec<Order>, crate::Error> + Send` names `Result`, `Vec`, `Order`, `crate::Error` and
+/// `Send`. Primitives, lifetimes and `Self::` associated types are left out; `Self` is kept.
+pub fn named_types(type_text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let tokens = type_text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':' || c == '\''));
+    for token in tokens {
+        let token = token.trim_matches(':');
+        if token.is_empty() || token.starts_with('\'') || token.starts_with("Self::") {
+            continue;
+        }
+        let name = token.rsplit("::").next().unwrap_or(token);
+        if name.starts_with(|c: char| c.is_ascii_uppercase()) && !names.iter().any(|n| n == token) {
+            names.push(token.to_string());
+        }
+    }
+    names
+}
+
+/// Auto traits and markers that say nothing about which methods a bounded type has
+const MARKER_TRAITS: &[&str] = &["Send", "Sync", "Unpin", "Sized", "Copy", "UnwindSafe", "RefUnwindSafe"];
+
+/// Bare names of the traits in a `+`-separated bound list, without markers and lifetimes.
+///
+/// `crate::store::Store<K> + Send + 'static` becomes `["Store"]`.
+pub fn bound_trait_names(bounds: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (idx, ch) in bounds.char_indices().chain(std::iter::once((bounds.len(), '+'))) {
+        match ch {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            '+' if depth == 0 => {
+                let bound = bounds[start..idx].trim().trim_start_matches('(').trim_end_matches(')').trim();
+                start = idx + 1;
+                if bound.starts_with(['\'', '?']) {
+                    continue;
+                }
+                let path = bound.split(['<', '(']).next().unwrap_or(bound);
+                let name = path.rsplit("::").next().unwrap_or(path).trim();
+                if !name.is_empty() && !MARKER_TRAITS.contains(&name) && !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Traits a `dyn Trait` or `impl Trait` type is known by, seen through references and the
+/// smart pointers methods auto-deref through. `None` when the type is not a trait type.
+pub fn trait_object_bounds(type_text: &str) -> Option<Vec<String>> {
+    let mut text = type_text.trim();
+    loop {
+        if let Some(rest) = text.strip_prefix('&') {
+            text = rest.trim_start();
+        } else if let Some(rest) = text.strip_prefix("mut ") {
+            text = rest.trim_start();
+        } else if text.starts_with('\'') {
+            text = text.split_once(' ').map(|(_, rest)| rest.trim_start())?;
+        } else {
+            break;
+        }
+    }
+
+    if let Some(bounds) = text.strip_prefix("dyn ").or_else(|| text.strip_prefix("impl ")) {
+        let names = bound_trait_names(bounds);
+        return (!names.is_empty()).then_some(names);
+    }
+
+    let (path, args) = text.split_once('<')?;
+    if !DEREF_WRAPPERS.contains(&path.rsplit("::").next()?.trim()) {
+        return None;
+    }
+    trait_object_bounds(first_type_argument(args.strip_suffix('>')?)?)
+}
+
+/// Associated functions that conventionally return `Self`
+fn is_constructor_name(name: &str) -> bool {
+    matches!(name, "new" | "default" | "from")
+        || name.starts_with("new_")
+        || name.starts_with("with_")
+        || name.starts_with("from_")
+}
+
+/// Types of the local bindings visible in one function body.
+///
+/// This is deliberately shallow: it follows `let` statements in source order and ignores
+/// block scoping, so a later `let` of the same name simply replaces the earlier binding.
+#[derive(Debug, Clone, Default)]
+pub struct LocalTypes {
+    bindings: HashMap<String, ReceiverType>,
+    /// Type of `self` (the impl's type) when inside an impl block
+    self_type: Option<String>,
+    /// Generic parameters in scope; receivers of these types dispatch through bounds
+    generics: HashSet<String>,
+    /// Trait bounds of the generic parameters, from their declaration and `where` clauses
+    bounds: HashMap<String, Vec<String>>,
+    /// Bindings holding an actor reference, mapped to the actor they point at
+    actor_refs: HashMap<String, String>,
+    /// Bindings holding a remote actor looked up by name, mapped to that name
+    remote_lookups: HashMap<String, String>,
+    /// Every local name bound so far, typed or not
+    declared: HashSet<String>,
+}
+
+impl LocalTypes {
+    pub fn for_function(parameters: &[Parameter], self_type: Option<String>, generics: &[String]) -> Self {
+        let mut locals = Self {
+            bindings: HashMap::new(),
+            self_type,
+            generics: generics.iter().cloned().collect(),
+            bounds: HashMap::new(),
+            actor_refs: HashMap::new(),
+            remote_lookups: HashMap::new(),
+            declared: HashSet::new(),
+        };
+
+        for param in parameters {
+            if param.is_self {
+                continue;
+            }
+            let binding = locals.named(&param.param_type);
+            locals.bind(&param.name, binding);
+            let actor = locals.actor_ref_type(&param.param_type);
+            locals.bind_actor_ref(&param.name, actor);
+        }
+
+        locals
+    }
+
+    /// Take `(parameter, bound)` pairs into account, rebinding parameters whose type is a
+    /// bounded generic
+    pub fn with_bounds(mut self, bounds: &[(String, String)], parameters: &[Parameter]) -> Self {
+        for (param, bound) in bounds {
+            let traits = self.bounds.entry(param.clone()).or_default();
+            for name in bound_trait_names(bound) {
+                if !traits.contains(&name) {
+                    traits.push(name);
+                }
+            }
+        }
+        for param in parameters.iter().filter(|p| !p.is_self) {
+            let binding = self.named(&param.param_type);
+            self.bind(&param.name, binding);
+        }
+        self
+    }
+
+    /// Record the type of `name`; an unknown type hides any earlier binding of the same name
+    pub fn bind(&mut self, name: &str, binding: Option<ReceiverType>) {
+        let name = name.trim_start_matches("mut ").trim();
+        self.declared.insert(name.to_string());
+        match binding {
+            Some(binding) => {
+                self.bindings.insert(name.to_string(), binding);
+            }
+            None => {
+                self.bindings.remove(name);
+            }
+        }
+    }
+
+    /// Record which actor `name` refers to; `None` hides any earlier binding of the same name
+    pub fn bind_actor_ref(&mut self, name: &str, actor: Option<String>) {
+        let name = name.trim_start_matches("mut ").trim();
+        match actor {
+            Some(actor) => {
+                self.actor_refs.insert(name.to_string(), actor);
+            }
+            None => {
+                self.actor_refs.remove(name);
+            }
+        }
+    }
+
+    /// Actor behind a written `ActorRef<A>` type, with `Self` resolved to the impl's type
+    fn actor_ref_type(&self, type_text: &str) -> Option<String> {
+        let actor = actor_ref_target(type_text)?;
+        if actor == "Self" {
+            return self.self_type.clone();
+        }
+        Some(actor)
+    }
+
+    /// Receiver type for a written type, with `Self` and generic parameters taken into account
+    pub fn named(&self, type_text: &str) -> Option<ReceiverType> {
+        let Some(name) = base_type_name(type_text) else {
+            return trait_object_bounds(type_text).map(|traits| ReceiverType::Bounded {
+                traits,
+                written: type_text.trim().to_string(),
+            });
+        };
+        if name == "Self" {
+            return self.self_type.clone().map(ReceiverType::Named);
+        }
+        if self.generics.contains(&name) {
+            return self.bounds.get(&name)
+                .filter(|traits| !traits.is_empty())
+                .map(|traits| ReceiverType::Bounded { traits: traits.clone(), written: name });
+        }
+        Some(ReceiverType::Named(name))
+    }
+
+    /// Record a `let` statement: an explicit annotation wins, otherwise the initializer is inferred
+    pub fn bind_let(&mut self, node: Node, source: &[u8]) {
+        let Some(pattern) = node.child_by_field_name("pattern") else {
+            return;
+        };
+        if pattern.kind() != "identifier" {
+            self.declare_pattern(pattern, source);
+            return;
+        }
+        let Some(name) = safe_node_text(pattern, source) else {
+            return;
+        };
+
+        let (binding, actor) = match node.child_by_field_name("type") {
+            Some(type_node) => {
+                let text = safe_node_text(type_node, source);
+                (text.and_then(|t| self.named(t)), text.and_then(|t| self.actor_ref_type(t)))
+            }
+            None => match node.child_by_field_name("value") {
+                Some(value) => (self.infer(value, source), self.actor_behind(value, source)),
+                None => (None, None),
+            },
+        };
+        let lookup = node.child_by_field_name("value").and_then(|value| self.lookup_behind(value, source));
+        self.bind(name, binding);
+        self.bind_actor_ref(name, actor);
+        match lookup {
+            Some(registered_name) => {
+                self.remote_lookups.insert(name.to_string(), registered_name);
+            }
+            None => {
+                self.remote_lookups.remove(name);
+            }
+        }
+    }
+
+    /// Record the names a pattern introduces as locals without typing them, e.g. for
+    /// destructuring `let`s, `for` loops, `if let` and match arms
+    pub fn declare_pattern(&mut self, pattern: Node, source: &[u8]) {
+        match pattern.kind() {
+            "identifier" | "shorthand_field_identifier" => {
+                if let Some(name) = safe_node_text(pattern, source) {
+                    self.declared.insert(name.to_string());
+                }
+            }
+            // `Point { x: px, .. }` binds `px`; the shorthand `Point { x }` binds `x`
+            "field_pattern" => {
+                if let Some(inner) = pattern.child_by_field_name("pattern").or_else(|| pattern.child_by_field_name("name")) {
+                    self.declare_pattern(inner, source);
+                }
+            }
+            // Paths in patterns name enum variants and constants, not bindings
+            "scoped_identifier" | "type_identifier" => {}
+            _ => {
+                let path = pattern.child_by_field_name("type");
+                let mut cursor = pattern.walk();
+                for child in pattern.named_children(&mut cursor).filter(|child| Some(*child) != path) {
+                    self.declare_pattern(child, source);
+                }
+            }
+        }
+    }
+
+    /// Whether `name` is a local binding (parameter, `let` or closure parameter) in scope
+    pub fn is_local(&self, name: &str) -> bool {
+        self.declared.contains(name)
+    }
+
+    /// Registered name of the remote actor an expression holds, for bindings of and calls to
+    /// `RemoteActorRef::lookup("name")` seen through `.await`, `?`, `unwrap` and `clone`
+    pub fn lookup_behind(&self, node: Node, source: &[u8]) -> Option<String> {
+        match node.kind() {
+            "identifier" => self.remote_lookups.get(safe_node_text(node, source)?).cloned(),
+            "reference_expression" | "try_expression" | "parenthesized_expression" | "await_expression" => {
+                let inner = node
+                    .child_by_field_name("value")
+                    .or_else(|| node.named_child(0))?;
+                self.lookup_behind(inner, source)
+            }
+            "call_expression" => {
+                let function = node.child_by_field_name("function")?;
+                if function.kind() == "field_expression" {
+                    let method = safe_node_text(function.child_by_field_name("field")?, source)?;
+                    return match method {
+                        "clone" | "unwrap" | "expect" => self.lookup_behind(function.child_by_field_name("value")?, source),
+                        _ => None,
+                    };
+                }
+                remote_lookup(node, source).map(|(name, _)| name)
+            }
+            _ => None,
+        }
+    }
+
+    /// Actor an expression holds a reference to: a known `ActorRef` binding, the result of a
+    /// kameo spawn call, or `ctx.actor_ref()` / `self.actor_ref()` for the enclosing actor
+    pub fn actor_behind(&self, node: Node, source: &[u8]) -> Option<String> {
+        match node.kind() {
+            "identifier" => self.actor_refs.get(safe_node_text(node, source)?).cloned(),
+            "reference_expression" | "try_expression" | "parenthesized_expression" | "await_expression" => {
+                let inner = node
+                    .child_by_field_name("value")
+                    .or_else(|| node.named_child(0))?;
+                self.actor_behind(inner, source)
+            }
+            "call_expression" => {
+                let mut function = node.child_by_field_name("function")?;
+                if function.kind() == "generic_function" {
+                    function = function.child_by_field_name("function")?;
+                }
+                match function.kind() {
+                    "field_expression" => {
+                        let method = safe_node_text(function.child_by_field_name("field")?, source)?;
+                        match method {
+                            "actor_ref" => self.self_type.clone(),
+                            "clone" | "downgrade" | "upgrade" | "unwrap" | "expect" => {
+                                self.actor_behind(function.child_by_field_name("value")?, source)
+                            }
+                            _ => None,
+                        }
+                    }
+                    "scoped_identifier" | "identifier" => spawned_actor(node, function, source)
+                        .or_else(|| remote_lookup(node, source).and_then(|(_, actor)| actor))
+                        .and_then(|actor| if actor == "Self" { self.self_type.clone() } else { Some(actor) }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Infer the type of an expression from the bindings seen so far
+    pub fn infer(&self, node: Node, source: &[u8]) -> Option<ReceiverType> {
+        match node.kind() {
+            "self" => self.self_type.clone().map(ReceiverType::Named),
+            "identifier" => self.bindings.get(safe_node_text(node, source)?).cloned(),
+            "reference_expression" | "try_expression" | "parenthesized_expression" => {
+                let inner = node
+                    .child_by_field_name("value")
+                    .or_else(|| node.named_child(0))?;
+                self.infer(inner, source)
+            }
+            "field_expression" => {
+                let owner = self.infer(node.child_by_field_name("value")?, source)?;
+                let field = safe_node_text(node.child_by_field_name("field")?, source)?;
+                Some(ReceiverType::Field {
+                    owner: Box::new(owner),
+                    field: field.to_string(),
+                })
+            }
+            "struct_expression" => self.named(safe_node_text(node.child_by_field_name("name")?, source)?),
+            "call_expression" => {
+                let mut function = node.child_by_field_name("function")?;
+                if function.kind() == "generic_function" {
+                    function = function.child_by_field_name("function")?;
+                }
+                if function.kind() != "scoped_identifier" {
+                    return None;
+                }
+                let name = safe_node_text(function.child_by_field_name("name")?, source)?;
+                if !is_constructor_name(name) {
+                    return None;
+                }
+                self.named(safe_node_text(function.child_by_field_name("path")?, source)?)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_type_name() {
+        assert_eq!(base_type_name("Order").as_deref(), Some("Order"));
+        assert_eq!(base_type_name("&mut Order").as_deref(), Some("Order"));
+        assert_eq!(base_type_name("&'a crate::orders::Order<T>").as_deref(), Some("Order"));
+        assert_eq!(base_type_name("Arc<RwLock<State>>").as_deref(), Some("RwLock"));
+        assert_eq!(base_type_name("Box<dyn Client>"), None);
+        assert_eq!(base_type_name("impl Client"), None);
+        assert_eq!(base_type_name("(u8, u8)"), None);
+        assert_eq!(base_type_name("Self::Item"), None);
+    }
+
+    #[test]
+    fn test_generic_parameters_are_not_receiver_types() {
+        let params = vec![Parameter {
+            name: "item".to_string(),
+            param_type: "&T".to_string(),
+            is_self: false,
+            is_mutable: false,
+        }];
+        let locals = LocalTypes::for_function(&params, Some("Queue".to_string()), &["T".to_string()]);
+        assert!(locals.bindings.is_empty());
+        assert_eq!(locals.named("Self"), Some(ReceiverType::Named("Queue".to_string())));
+    }
+
+    #[test]
+    fn test_trait_receivers_keep_their_bounds() {
+        assert_eq!(bound_trait_names("crate::store::Store<K> + Send + 'static"), vec!["Store"]);
+        assert_eq!(bound_trait_names("?Sized + Fn(u8) -> u8 + Sync"), vec!["Fn"]);
+        assert_eq!(trait_object_bounds("&mut dyn Store"), Some(vec!["Store".to_string()]));
+        assert_eq!(trait_object_bounds("Arc<dyn Store + Send + Sync>"), Some(vec!["Store".to_string()]));
+        assert_eq!(trait_object_bounds("Box<Order>"), None);
+
+        let params = vec![Parameter {
+            name: "store".to_string(),
+            param_type: "&S".to_string(),
+            is_self: false,
+            is_mutable: false,
+        }];
+        let locals = LocalTypes::for_function(&params, None, &["S".to_string()])
+            .with_bounds(&[("S".to_string(), "Store + Clone".to_string())], &params);
+        assert_eq!(
+            locals.bindings.get("store"),
+            Some(&ReceiverType::Bounded { traits: vec!["Store".to_string(), "Clone".to_string()], written: "S".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_named_types() {
+        assert_eq!(named_types("Result<Vec<Order>, crate::Error>"), vec!["Result", "Vec", "Order", "crate::Error"]);
+        assert_eq!(named_types("&'a mut [u8]"), Vec::<String>::new());
+        assert_eq!(named_types("impl Fn(Self) -> Self::Output + Send"), vec!["Fn", "Self", "Send"]);
+    }
+}
@@ -228,6 +228,8 @@ mod dummy_workspace_tests {
             dependencies: vec![],
             is_workspace_member: c.is_workspace_member,
             is_external: false,
+            features: Vec::new(),
+            targets: Vec::new(),
         }).collect();
         
         // Populate database with parsed symbols
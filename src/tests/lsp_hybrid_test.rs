@@ -0,0 +1,263 @@
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::{HybridWorkspaceAnalyzer, WorkspaceAnalyzer};
+    use crate::config::Config;
+    use crate::lsp::{CallVerifier, LspClient, LspConfig};
+    use crate::parser::symbols::{CallProvenance, FunctionCall};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    /// A scripted stand-in for rust-analyzer. It answers `definition` by looking the identifier
+    /// under the cursor up in the script's `definitions`, and `incomingCalls` from `incoming`,
+    /// locating functions by their `fn <name>` line in the opened document.
+    const FAKE_LSP_SERVER: &str = r#"
+import json, re, sys
+
+script = json.load(open(sys.argv[1]))
+docs = {}
+
+def read():
+    headers = {}
+    while True:
+        line = sys.stdin.buffer.readline()
+        if not line:
+            return None
+        line = line.decode().strip()
+        if not line:
+            break
+        name, value = line.split(":", 1)
+        headers[name.lower()] = value.strip()
+    return json.loads(sys.stdin.buffer.read(int(headers["content-length"])))
+
+def send(message):
+    message["jsonrpc"] = "2.0"
+    body = json.dumps(message).encode()
+    sys.stdout.buffer.write(b"Content-Length: %d\r\n\r\n" % len(body) + body)
+    sys.stdout.buffer.flush()
+
+def ident_at(uri, line, character):
+    text = docs[uri].split("\n")[line]
+    match = re.match(r"\w+", text[character:])
+    return match.group(0) if match else None
+
+def fn_line(uri, name):
+    for i, text in enumerate(docs[uri].split("\n")):
+        match = re.search(r"\bfn\s+(%s)\b" % re.escape(name), text)
+        if match:
+            return i, match.start(1), match.end(1)
+
+def item(uri, name):
+    line, start, end = fn_line(uri, name)
+    span = {"start": {"line": line, "character": start}, "end": {"line": line, "character": end}}
+    return {"name": name, "kind": 12, "uri": uri, "range": span, "selectionRange": span}
+
+def call_sites(uri, caller, needle):
+    lines = docs[uri].split("\n")
+    first = fn_line(uri, caller)[0]
+    sites = []
+    for i in range(first + 1, len(lines)):
+        if re.search(r"\bfn\s", lines[i]):
+            break
+        column = lines[i].find(needle)
+        if column >= 0:
+            sites.append({"start": {"line": i, "character": column}, "end": {"line": i, "character": column + len(needle)}})
+    return sites
+
+while True:
+    message = read()
+    if message is None:
+        break
+    method = message.get("method")
+    params = message.get("params") or {}
+    if method is None:
+        continue
+    if method == "initialize":
+        send({"id": message["id"], "result": {"capabilities": {"definitionProvider": True, "callHierarchyProvider": True}}})
+    elif method == "initialized":
+        send({"id": "progress", "method": "window/workDoneProgress/create", "params": {"token": "indexing"}})
+        send({"method": "window/logMessage", "params": {"type": 3, "message": "indexing"}})
+        send({"method": "experimental/serverStatus", "params": {"health": "ok", "quiescent": True}})
+    elif method == "textDocument/didOpen":
+        docs[params["textDocument"]["uri"]] = params["textDocument"]["text"]
+    elif method == "textDocument/definition":
+        uri = params["textDocument"]["uri"]
+        position = params["position"]
+        target = script["definitions"].get(ident_at(uri, position["line"], position["character"]))
+        send({"id": message["id"], "result": {"uri": uri, "range": item(uri, target)["selectionRange"]} if target else None})
+    elif method == "textDocument/prepareCallHierarchy":
+        uri = params["textDocument"]["uri"]
+        position = params["position"]
+        send({"id": message["id"], "result": [item(uri, ident_at(uri, position["line"], position["character"]))]})
+    elif method == "callHierarchy/incomingCalls":
+        target = params["item"]
+        uri = target["uri"]
+        calls = [{"from": item(uri, call["from"]), "fromRanges": call_sites(uri, call["from"], call["at"])}
+                 for call in script["incoming"].get(target["name"], [])]
+        send({"id": message["id"], "result": calls})
+    elif method == "shutdown":
+        send({"id": message["id"], "result": None})
+    elif method == "exit":
+        break
+    elif "id" in message:
+        send({"id": message["id"], "error": {"code": -32601, "message": method}})
+"#;
+
+    const LIB_RS: &str = "\
+pub fn alpha() {}
+
+pub fn beta() {}
+
+pub fn run() {
+    alpha();
+    beta();
+}
+
+pub fn dispatch() {
+    let f = alpha;
+    f();
+}
+";
+
+    const SCRIPT: &str = r#"{
+    "definitions": {"alpha": "alpha", "beta": "beta"},
+    "incoming": {
+        "alpha": [{"from": "run", "at": "alpha()"}, {"from": "dispatch", "at": "alpha;"}],
+        "beta": [{"from": "run", "at": "beta()"}]
+    }
+}"#;
+
+    fn python_available() -> bool {
+        std::process::Command::new("python3").arg("--version").output().is_ok()
+    }
+
+    /// Write the demo crate and the fake server; returns the workspace root and an LSP config
+    /// that starts the fake server
+    fn write_workspace(dir: &Path) -> (PathBuf, LspConfig) {
+        let root = dir.join("demo");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+        fs::write(root.join("src/lib.rs"), LIB_RS).unwrap();
+
+        let server = dir.join("fake_lsp_server.py");
+        let script = dir.join("script.json");
+        fs::write(&server, FAKE_LSP_SERVER).unwrap();
+        fs::write(&script, SCRIPT).unwrap();
+
+        let mut lsp_config = LspConfig::default();
+        lsp_config.server.executable_path = "python3".to_string();
+        lsp_config.server.args = vec![server.to_string_lossy().to_string(), script.to_string_lossy().to_string()];
+        lsp_config.server.init_timeout = 10;
+        (root, lsp_config)
+    }
+
+    fn workspace_config(root: &Path) -> Config {
+        let mut config = Config::default();
+        config.workspace.root = root.to_path_buf();
+        config
+    }
+
+    fn calls_from<'a>(calls: &'a [FunctionCall], caller: &str) -> Vec<&'a FunctionCall> {
+        calls.iter().filter(|call| call.caller_id.ends_with(caller)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_analysis_confirms_and_discovers_calls() {
+        if !python_available() {
+            eprintln!("skipping: python3 not available for the fake LSP server");
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let (root, lsp_config) = write_workspace(temp_dir.path());
+
+        let mut hybrid = HybridWorkspaceAnalyzer::new(&root, Some(workspace_config(&root)))
+            .await
+            .unwrap()
+            .with_lsp_config(lsp_config);
+        let result = hybrid.analyze_workspace().await.unwrap();
+        assert_eq!(result.analysis_type, "hybrid");
+
+        let snapshot = hybrid.last_snapshot().unwrap();
+        let calls = &snapshot.symbols["demo"].calls;
+
+        let run_calls = calls_from(calls, "run");
+        assert_eq!(run_calls.len(), 2, "{:?}", run_calls);
+        assert!(run_calls.iter().all(|call| call.provenance == CallProvenance::LspConfirmed), "{:?}", run_calls);
+
+        // `f()` goes through a local, so only call hierarchy knows `dispatch` reaches `alpha`
+        let dispatch_calls = calls_from(calls, "dispatch");
+        let discovered: Vec<_> = dispatch_calls
+            .iter()
+            .filter(|call| call.provenance == CallProvenance::LspDiscovered)
+            .collect();
+        assert_eq!(discovered.len(), 1, "{:?}", dispatch_calls);
+        assert_eq!(discovered[0].callee_name, "alpha");
+        assert_eq!(discovered[0].line, 10);
+        assert!(dispatch_calls
+            .iter()
+            .filter(|call| call.callee_name == "f")
+            .all(|call| call.provenance == CallProvenance::TreeSitter));
+
+        assert_eq!(result.results.len(), 1);
+        assert_eq!(result.results[0].details, "lsp_discovered");
+    }
+
+    #[tokio::test]
+    async fn test_verifier_corrects_misresolved_call() {
+        if !python_available() {
+            eprintln!("skipping: python3 not available for the fake LSP server");
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let (root, lsp_config) = write_workspace(temp_dir.path());
+
+        let mut analyzer = WorkspaceAnalyzer::new_with_config(workspace_config(&root)).unwrap();
+        let mut snapshot = analyzer.create_snapshot().await.unwrap();
+        let alpha = snapshot.functions.iter().find(|f| f.name == "alpha").unwrap().qualified_name.clone();
+        let beta = snapshot.functions.iter().find(|f| f.name == "beta").unwrap().qualified_name.clone();
+        let beta_call = snapshot
+            .symbols
+            .get_mut("demo")
+            .unwrap()
+            .calls
+            .iter_mut()
+            .find(|call| call.callee_name == "beta")
+            .unwrap();
+        beta_call.qualified_callee = Some(alpha);
+
+        let client = LspClient::start(&lsp_config.server, &root).await.unwrap();
+        let report = CallVerifier::new(&client, &lsp_config.features)
+            .verify(&mut snapshot.symbols)
+            .await
+            .unwrap();
+        client.shutdown().await.unwrap();
+
+        assert_eq!(report.corrected.len(), 1, "{:?}", report);
+        assert_eq!(report.corrected[0].qualified_callee.as_deref(), Some(beta.as_str()));
+        assert_eq!(report.corrected[0].provenance, CallProvenance::LspCorrected);
+        assert_eq!(report.confirmed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_missing_lsp_server_falls_back_to_tree_sitter() {
+        let temp_dir = TempDir::new().unwrap();
+        let (root, mut lsp_config) = write_workspace(temp_dir.path());
+        lsp_config.server.executable_path = temp_dir.path().join("no-such-lsp-server").to_string_lossy().to_string();
+
+        let mut hybrid = HybridWorkspaceAnalyzer::new(&root, Some(workspace_config(&root)))
+            .await
+            .unwrap()
+            .with_lsp_config(lsp_config.clone());
+        let result = hybrid.analyze_workspace().await.unwrap();
+        assert_eq!(result.analysis_type, "tree_sitter");
+        let calls = &hybrid.last_snapshot().unwrap().symbols["demo"].calls;
+        assert!(calls.iter().all(|call| call.provenance == CallProvenance::TreeSitter));
+
+        lsp_config.fallback.enable_graceful_fallback = false;
+        let mut strict = HybridWorkspaceAnalyzer::new(&root, Some(workspace_config(&root)))
+            .await
+            .unwrap()
+            .with_lsp_config(lsp_config);
+        assert!(strict.analyze_workspace().await.is_err());
+    }
+}
@@ -5,4 +5,4 @@ pub mod macro_expansion_test;
 pub mod simple_macro_test;
 pub mod macro_unused_detection_test;
 pub mod nz_debug_test;
-pub mod websocket_false_positive_test;
\ No newline at end of file
+pub mod websocket_false_positive_test;pub mod lsp_hybrid_test;
@@ -16,6 +16,20 @@ pub struct CrateMetadata {
     pub dependencies: Vec<String>,
     pub is_workspace_member: bool,
     pub is_external: bool,
+    /// Features enabled by a default build of this crate, transitively expanded
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Root source files of the crate's targets: lib, bins, examples, tests and benches
+    #[serde(default)]
+    pub targets: Vec<PathBuf>,
+}
+
+/// What cargo metadata says about one crate
+struct ManifestInfo {
+    dependencies: Vec<String>,
+    is_workspace_member: bool,
+    features: Vec<String>,
+    targets: Vec<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -63,7 +77,10 @@ impl WorkspaceDiscovery {
             }
         }
 
-        Ok(self.discovered.values().cloned().collect())
+        // Sorted so that everything downstream (parsing, merging) sees a stable crate order
+        let mut crates: Vec<CrateMetadata> = self.discovered.values().cloned().collect();
+        crates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(crates)
     }
 
     fn scan_workspace_root(
@@ -137,7 +154,7 @@ impl WorkspaceDiscovery {
         let path = crate_path;
         let manifest_path = path.join("Cargo.toml");
         
-        let (dependencies, is_workspace_member) = self.get_crate_dependencies(&manifest_path)?;
+        let ManifestInfo { dependencies, is_workspace_member, features, targets } = self.get_crate_dependencies(&manifest_path, &name)?;
         let layer = self.get_crate_layer(&name);
         let is_external = depth > 0 && !is_workspace_member;
 
@@ -150,6 +167,8 @@ impl WorkspaceDiscovery {
             dependencies,
             is_workspace_member,
             is_external,
+            features,
+            targets,
         }))
     }
 
@@ -190,7 +209,8 @@ impl WorkspaceDiscovery {
         Err(anyhow::anyhow!("Could not find manifest for crate: {}", crate_name))
     }
 
-    fn get_crate_dependencies(&self, manifest_path: &PathBuf) -> Result<(Vec<String>, bool)> {
+    /// Dependencies, workspace membership, default features and target roots of the crate at `manifest_path`
+    fn get_crate_dependencies(&self, manifest_path: &PathBuf, crate_name: &str) -> Result<ManifestInfo> {
         let metadata = MetadataCommand::new()
             .manifest_path(manifest_path)
             .exec()
@@ -207,7 +227,13 @@ impl WorkspaceDiscovery {
             }
         }
 
-        Ok((dependencies, is_workspace_member))
+        let package = metadata.packages.iter().find(|package| package.name == crate_name);
+        let features = package.map(|package| default_features(&package.features)).unwrap_or_default();
+        let targets = package
+            .map(|package| package.targets.iter().map(|target| target.src_path.clone().into_std_path_buf()).collect())
+            .unwrap_or_default();
+
+        Ok(ManifestInfo { dependencies, is_workspace_member, features, targets })
     }
 
     fn should_include_dependency(&self, dep: &cargo_metadata::Dependency) -> bool {
@@ -260,6 +286,33 @@ impl WorkspaceDiscovery {
     }
 }
 
+/// Expand the `default` feature into every feature it enables.
+///
+/// `dep:name` entries and `dep/feature` entries configure dependencies, not this crate,
+/// so they are not followed.
+pub fn default_features(declared: &std::collections::BTreeMap<String, Vec<String>>) -> Vec<String> {
+    let mut enabled = HashSet::new();
+    let mut pending = vec!["default".to_string()];
+
+    while let Some(feature) = pending.pop() {
+        let Some(implied) = declared.get(&feature) else {
+            continue;
+        };
+        if !enabled.insert(feature) {
+            continue;
+        }
+        for entry in implied {
+            if !entry.starts_with("dep:") && !entry.contains('/') {
+                pending.push(entry.clone());
+            }
+        }
+    }
+
+    let mut features: Vec<String> = enabled.into_iter().collect();
+    features.sort();
+    features
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,4 +365,19 @@ serde = "1.0"
         assert!(discovery.should_exclude_crate("test-utils"));
         assert!(!discovery.should_exclude_crate("my-crate"));
     }
+
+    #[test]
+    fn test_default_features_expand_transitively() {
+        let declared: std::collections::BTreeMap<String, Vec<String>> = [
+            ("default", vec!["postgres", "dep:tracing"]),
+            ("postgres", vec!["sql", "tokio-postgres/runtime"]),
+            ("sql", vec![]),
+            ("sqlite", vec!["sql"]),
+        ]
+        .into_iter()
+        .map(|(name, implied)| (name.to_string(), implied.into_iter().map(String::from).collect()))
+        .collect();
+
+        assert_eq!(default_features(&declared), vec!["default", "postgres", "sql"]);
+    }
 }
\ No newline at end of file